@@ -43,13 +43,14 @@ pub fn gen_event_handler(_args: TokenStream, input: TokenStream) -> TokenStream
                 let id = async_std::task::block_on(discrab::InteractionRouter::get_id_or_register(ctx, self.clone()));
                 // Register the handler
                 router.register_command(id, self.clone());
+                router.registry.insert(Self::NAME, Self::GUILD_ID, id);
             }
         }
 
         // Add the hook for the struct to convert the async handler to a sync one
         #impl_ #impl_generics discrab::__internal__::__InternalEventHandler<discrab::events::InteractionCtx> for #name {
             fn handler(&self, ctx: discrab::Context, val: discrab::events::InteractionCtx) {
-                async_std::task::block_on(discrab::CommandHandler::handler(
+                async_std::task::block_on(discrab::CommandHandler::validated_handler(
                     self, val,
                 ))
             }
@@ -43,7 +43,7 @@ pub fn gen_sub_handler(_args: TokenStream, input: TokenStream) -> TokenStream {
         #[async_trait::async_trait]
         #impl_ #impl_generics discrab::CommonHandler for #name {
             async fn handler(&self, ictx: discrab::events::InteractionCtx) {
-                discrab::CommandHandler::handler(self, ictx).await
+                discrab::CommandHandler::validated_handler(self, ictx).await
             }
         }
         
@@ -0,0 +1,119 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Lit, Meta, NestedMeta};
+
+pub fn gen_command_choice(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return syn::Error::new_spanned(&input, "CommandChoice can only be derived for enums")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let mut idents = Vec::new();
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return syn::Error::new_spanned(variant, "CommandChoice variants can't have fields")
+                .to_compile_error()
+                .into();
+        }
+
+        // Defaults to the variant name for the choice name, and the snake case variant name as the value
+        let mut choice_name = variant.ident.to_string();
+        let mut choice_value = to_snake_case(&choice_name);
+        for attr in variant.attrs.iter().filter(|a| a.path.is_ident("choice")) {
+            let list = match attr.parse_meta() {
+                Ok(Meta::List(list)) => list,
+                _ => {
+                    return syn::Error::new_spanned(
+                        attr,
+                        "Expected #[choice(name = \"...\", value = \"...\")]",
+                    )
+                    .to_compile_error()
+                    .into()
+                }
+            };
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(nv)) => match (&nv.lit, nv.path.get_ident()) {
+                        (Lit::Str(s), Some(key)) if key == "name" => choice_name = s.value(),
+                        (Lit::Str(s), Some(key)) if key == "value" => choice_value = s.value(),
+                        _ => {
+                            return syn::Error::new_spanned(nv, "Unknown choice attribute")
+                                .to_compile_error()
+                                .into()
+                        }
+                    },
+                    other => {
+                        return syn::Error::new_spanned(other, "Unknown choice attribute")
+                            .to_compile_error()
+                            .into()
+                    }
+                }
+            }
+        }
+
+        idents.push(&variant.ident);
+        names.push(choice_name);
+        values.push(choice_value);
+    }
+
+    let output = quote! {
+        impl discrab::CommandChoice for #name {
+            fn choices() -> Vec<discrab::api::ApplicationCommandOptionChoice> {
+                vec![
+                    #(
+                        discrab::api::ApplicationCommandOptionChoice {
+                            name: #names.to_string(),
+                            value: discrab::api::ApplicationCommandOptionValue::String(#values.to_string()),
+                        },
+                    )*
+                ]
+            }
+
+            fn value(&self) -> &'static str {
+                match self {
+                    #(
+                        #name::#idents => #values,
+                    )*
+                }
+            }
+        }
+
+        impl std::convert::TryFrom<&str> for #name {
+            type Error = String;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                match value {
+                    #(
+                        #values => Ok(#name::#idents),
+                    )*
+                    _ => Err(format!("{} is not a valid {}", value, stringify!(#name))),
+                }
+            }
+        }
+    };
+    output.into()
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
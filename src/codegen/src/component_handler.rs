@@ -0,0 +1,124 @@
+use proc_macro::TokenStream;
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, AttributeArgs, Ident, Lit, Meta, NestedMeta};
+
+/// The kinds of interaction handlers that are found by a string instead of a command id
+pub enum HandlerKind {
+    /// Found by custom id
+    Component,
+    /// Found by custom id
+    Modal,
+    /// Found by the name of the command
+    Autocomplete,
+}
+
+/// The parts of the generated code that depend on the kind of handler
+struct KindTokens {
+    handler_trait: TokenStream2,
+    reg_type: TokenStream2,
+    reg_fn: TokenStream2,
+    register: TokenStream2,
+    /// The name of the macro argument, which sets the constant with the same name in upper case
+    arg: &'static str,
+    /// The trait function that the interaction is passed to
+    handler_fn: TokenStream2,
+}
+
+impl HandlerKind {
+    fn tokens(&self) -> KindTokens {
+        match self {
+            HandlerKind::Component => KindTokens {
+                handler_trait: quote!(discrab::ComponentHandler),
+                reg_type: quote!(Component),
+                reg_fn: quote!(reg_component),
+                register: quote!(register_component),
+                arg: "custom_id",
+                handler_fn: quote!(handler),
+            },
+            HandlerKind::Modal => KindTokens {
+                handler_trait: quote!(discrab::ModalHandler),
+                reg_type: quote!(Modal),
+                reg_fn: quote!(reg_modal),
+                register: quote!(register_modal),
+                arg: "custom_id",
+                handler_fn: quote!(handler),
+            },
+            HandlerKind::Autocomplete => KindTokens {
+                handler_trait: quote!(discrab::AutocompleteHandler),
+                reg_type: quote!(Autocomplete),
+                reg_fn: quote!(reg_autocomplete),
+                register: quote!(register_autocomplete),
+                arg: "command",
+                handler_fn: quote!(suggest),
+            },
+        }
+    }
+}
+
+pub fn gen_component_handler(
+    args: TokenStream,
+    input: TokenStream,
+    kind: HandlerKind,
+) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let mut input = parse_macro_input!(input as syn::ItemImpl);
+    let KindTokens {
+        handler_trait,
+        reg_type,
+        reg_fn,
+        register,
+        arg,
+        handler_fn,
+    } = kind.tokens();
+    let const_name = Ident::new(&arg.to_uppercase(), Span::call_site());
+
+    // e.g. `custom_id = "..."` sets CUSTOM_ID, so it doesn't have to be written out in the impl
+    for nested in args {
+        match nested {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident(arg) => {
+                let value = match nv.lit {
+                    Lit::Str(s) => s,
+                    _ => panic!("{} must be a string", arg),
+                };
+                input.items.push(syn::parse_quote! {
+                    const #const_name: &'static str = #value;
+                });
+            }
+            _ => panic!("Unknown argument, expected `{} = \"...\"`", arg),
+        }
+    }
+
+    // Name of the struct that the macro is attached to
+    let name = &*input.self_ty;
+    let impl_ = &input.impl_token;
+    let impl_generics = &mut input.generics.clone();
+
+    let output = quote! {
+        #[async_trait::async_trait]
+        #input
+        // Add the hook for the struct to be registerable by the interaction handler
+        #impl_ #impl_generics discrab::Registerable for #name {
+            fn get_reg_type(&self) -> discrab::core::abstraction::traits::RegisterableType {
+                discrab::core::abstraction::traits::RegisterableType::#reg_type
+            }
+        }
+
+        #impl_ #impl_generics discrab::RegFns for #name {
+            fn #reg_fn(self: &std::sync::Arc<Self>, router: std::sync::Arc<discrab::InteractionRouter>) {
+                router.#register(<#name as #handler_trait>::#const_name, self.clone());
+            }
+        }
+
+        // Add the hook for the struct to convert the async handler to a sync one
+        #impl_ #impl_generics discrab::__internal__::__InternalEventHandler<discrab::events::InteractionCtx> for #name {
+            fn handler(&self, _ctx: discrab::Context, val: discrab::events::InteractionCtx) {
+                async_std::task::block_on(#handler_trait::#handler_fn(
+                    self, val,
+                ))
+            }
+        }
+    };
+    output.into()
+}
@@ -41,7 +41,7 @@ pub fn gen_event_handler(_args: TokenStream, input: TokenStream) -> TokenStream
         }
 
         #impl_ #impl_generics discrab::RegFns for #name {
-            fn reg_event(self: &std::sync::Arc<Self>, dispatcher: &mut discrab::EventDispatcher) {
+            fn reg_event(self: &std::sync::Arc<Self>, dispatcher: &discrab::EventDispatcher) {
                 dispatcher.get_observable(#name::EVENT_TYPE).subscribe(self.clone());
             }
         }
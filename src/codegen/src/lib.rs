@@ -5,6 +5,9 @@ mod application_command;
 mod event_handler;
 mod application_subgroup;
 mod application_subcommand;
+mod command_choice;
+mod component_handler;
+mod prefix_command;
 
 #[proc_macro_attribute]
 /// Generates additional code needed to register an EventHandler
@@ -30,6 +33,34 @@ pub fn subcommand(_args: TokenStream, input: TokenStream) -> TokenStream {
     application_subcommand::gen_sub_handler(_args, input)
 }
 
+#[proc_macro_attribute]
+/// Generates additional code needed to register a ComponentHandler.
+/// Takes the custom id (or `*` pattern) to handle, e.g. `#[component_handler(custom_id = "confirm_*")]`
+pub fn component_handler(args: TokenStream, input: TokenStream) -> TokenStream {
+    component_handler::gen_component_handler(args, input, component_handler::HandlerKind::Component)
+}
+
+#[proc_macro_attribute]
+/// Generates additional code needed to register a ModalHandler.
+/// Takes the custom id (or `*` pattern) to handle, e.g. `#[modal_handler(custom_id = "report")]`
+pub fn modal_handler(args: TokenStream, input: TokenStream) -> TokenStream {
+    component_handler::gen_component_handler(args, input, component_handler::HandlerKind::Modal)
+}
+
+#[proc_macro_attribute]
+/// Generates additional code needed to register an AutocompleteHandler.
+/// Takes the name of the command to suggest values for, e.g. `#[autocomplete_handler(command = "paint")]`
+pub fn autocomplete_handler(args: TokenStream, input: TokenStream) -> TokenStream {
+    component_handler::gen_component_handler(args, input, component_handler::HandlerKind::Autocomplete)
+}
+
+#[proc_macro_attribute]
+/// Turns an async fn into a PrefixCommand, parsing its parameters after the Context and the Message from the text of the message.
+/// Takes an optional `name`, `description`, and any number of `alias`, e.g. `#[prefix_command(description = "Bans a user", alias = "b")]`
+pub fn prefix_command(args: TokenStream, input: TokenStream) -> TokenStream {
+    prefix_command::gen_prefix_command(args, input)
+}
+
 #[proc_macro_derive(CommandArg)]
 /// Implements CommandArg for a struct
 pub fn command_arg_derive(input: TokenStream) -> TokenStream {
@@ -41,3 +72,9 @@ pub fn command_arg_derive(input: TokenStream) -> TokenStream {
     };
     gen.into()
 }
+
+#[proc_macro_derive(CommandChoice, attributes(choice))]
+/// Implements CommandChoice and TryFrom<&str> for an enum without fields
+pub fn command_choice_derive(input: TokenStream) -> TokenStream {
+    command_choice::gen_command_choice(input)
+}
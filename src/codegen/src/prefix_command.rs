@@ -0,0 +1,113 @@
+use proc_macro::TokenStream;
+
+use quote::quote;
+use syn::{parse_macro_input, AttributeArgs, FnArg, Lit, Meta, NestedMeta, Pat, ReturnType};
+
+pub fn gen_prefix_command(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let mut input = parse_macro_input!(input as syn::ItemFn);
+
+    let ident = input.sig.ident.clone();
+    let mut name = ident.to_string();
+    let mut description = String::new();
+    let mut aliases = Vec::new();
+    for nested in args {
+        let nv = match nested {
+            NestedMeta::Meta(Meta::NameValue(nv)) => nv,
+            _ => panic!("Unknown argument, expected `name`, `description` or `alias`"),
+        };
+        let value = match nv.lit {
+            Lit::Str(s) => s.value(),
+            _ => panic!("The arguments of prefix_command must be strings"),
+        };
+        if nv.path.is_ident("name") {
+            name = value;
+        } else if nv.path.is_ident("description") {
+            description = value;
+        } else if nv.path.is_ident("alias") {
+            aliases.push(value);
+        } else {
+            panic!("Unknown argument, expected `name`, `description` or `alias`");
+        }
+    }
+
+    if input.sig.asyncness.is_none() {
+        panic!("A prefix command must be an async fn");
+    }
+    if input.sig.inputs.len() < 2 {
+        panic!("A prefix command must take the Context and the Message first");
+    }
+
+    // Every parameter after the context and the message is parsed from the text of the message
+    let mut arg_names = Vec::new();
+    let mut arg_types = Vec::new();
+    for input in input.sig.inputs.iter().skip(2) {
+        let arg = match input {
+            FnArg::Typed(arg) => arg,
+            FnArg::Receiver(_) => panic!("A prefix command can't take self"),
+        };
+        let arg_name = match &*arg.pat {
+            Pat::Ident(pat) => pat.ident.clone(),
+            _ => panic!("The parameters of a prefix command must be plain names"),
+        };
+        arg_names.push(arg_name);
+        arg_types.push((*arg.ty).clone());
+    }
+    let arg_strs: Vec<_> = arg_names
+        .iter()
+        .map(|arg| arg.to_string().trim_start_matches('_').to_string())
+        .collect();
+
+    // The handler can return `Result<(), ArgError>` to reply with an error of its own
+    let call = match &input.sig.output {
+        ReturnType::Default => quote!(Self::__handler(ctx, msg, #(#arg_names),*).await; Ok(())),
+        ReturnType::Type(..) => quote!(Self::__handler(ctx, msg, #(#arg_names),*).await),
+    };
+
+    let vis = input.vis.clone();
+    input.sig.ident = syn::Ident::new("__handler", ident.span());
+    input.vis = syn::Visibility::Inherited;
+
+    let output = quote! {
+        #[allow(non_camel_case_types)]
+        #vis struct #ident;
+
+        impl #ident {
+            #input
+        }
+
+        #[async_trait::async_trait]
+        impl discrab::PrefixCommand for #ident {
+            fn name(&self) -> &'static str {
+                #name
+            }
+
+            fn aliases(&self) -> &'static [&'static str] {
+                &[#(#aliases),*]
+            }
+
+            fn description(&self) -> &'static str {
+                #description
+            }
+
+            fn usage(&self) -> String {
+                let args: Vec<String> = vec![#(<#arg_types as discrab::PrefixArg>::usage(#arg_strs)),*];
+                args.join(" ")
+            }
+
+            async fn run(
+                &self,
+                ctx: discrab::Context,
+                msg: discrab::api::Message,
+                mut args: discrab::PrefixArgs,
+            ) -> Result<(), discrab::ArgError> {
+                #(
+                    let #arg_names = args.parse::<#arg_types>(&ctx, &msg, #arg_strs).await?;
+                )*
+                args.finish()?;
+                #call
+            }
+        }
+    };
+    output.into()
+}
@@ -0,0 +1,406 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Notify;
+
+use crate::{
+    discord::{
+        ids::{params::ChannelIdParam, RawId},
+        resources::channel::message::MessageBuilder,
+        snowflake::Snowflake,
+    },
+    util::{
+        error::{Error, ErrorTypes},
+        logger::print_debug,
+    },
+    Context,
+};
+
+use super::outbox::send_message_json;
+
+/// How long to wait before trying again when an announcement couldn't be sent because of a network error or an outage
+const RETRY_AFTER: i64 = 60;
+
+/// When an announcement is posted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnouncementSchedule {
+    /// Once, at a unix timestamp in seconds
+    Once(i64),
+    /// Every time a cron expression matches, in a timezone
+    Cron { expression: String, timezone: String },
+}
+
+impl AnnouncementSchedule {
+    /// Posts the announcement once
+    /// @param time When to post it, in any timezone
+    pub fn once<T: TimeZone>(time: DateTime<T>) -> Self {
+        AnnouncementSchedule::Once(time.timestamp())
+    }
+
+    /**
+     * Posts the announcement every time a cron expression matches. The expression can have 5 fields
+     * (`minute hour day month weekday`), or 6 or 7 fields with the seconds first and the year last.
+     *
+     * ```rust,ignore
+     * // 9am on weekdays, New York time, even when the clocks change
+     * let schedule = AnnouncementSchedule::cron("0 9 * * Mon-Fri", "America/New_York")?;
+     * ```
+     * @param expression The cron expression
+     * @param timezone The IANA name of the timezone the expression is in, e.g. `Europe/Berlin` or `UTC`
+     * @return An error if the expression or the timezone are invalid
+     */
+    pub fn cron(expression: &str, timezone: &str) -> Result<Self, Error> {
+        let schedule = AnnouncementSchedule::Cron {
+            expression: expression.to_string(),
+            timezone: timezone.to_string(),
+        };
+        schedule.parse_cron()?;
+        Ok(schedule)
+    }
+
+    /// When the announcement is next posted after a time, or None if it isn't posted anymore
+    fn next_after(&self, after: i64) -> Option<i64> {
+        match self {
+            AnnouncementSchedule::Once(at) => (*at > after).then_some(*at),
+            AnnouncementSchedule::Cron { .. } => {
+                let (schedule, timezone) = self.parse_cron().ok()?;
+                let after = timezone.timestamp_opt(after, 0).single()?;
+                schedule.after(&after).next().map(|time| time.timestamp())
+            }
+        }
+    }
+
+    fn parse_cron(&self) -> Result<(cron::Schedule, Tz), Error> {
+        let AnnouncementSchedule::Cron {
+            expression,
+            timezone,
+        } = self
+        else {
+            return Err(Error::new(
+                "Not a cron schedule".to_string(),
+                ErrorTypes::PARSE,
+            ));
+        };
+        // The cron crate wants seconds, but most people write cron expressions without them
+        let expression = match expression.split_whitespace().count() {
+            5 => format!("0 {}", expression),
+            _ => expression.clone(),
+        };
+        let schedule = cron::Schedule::from_str(&expression).map_err(|e| {
+            Error::new(
+                format!("Invalid cron expression {}: {}", expression, e),
+                ErrorTypes::PARSE,
+            )
+        })?;
+        let timezone = Tz::from_str(timezone).map_err(|e| {
+            Error::new(
+                format!("Invalid timezone {}: {}", timezone, e),
+                ErrorTypes::PARSE,
+            )
+        })?;
+        Ok((schedule, timezone))
+    }
+}
+
+/// An announcement that is waiting to be posted
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduledAnnouncement {
+    /// Identifies the announcement, e.g. to cancel it
+    pub id: u64,
+    /// The channel to post in
+    pub channel_id: Snowflake,
+    /// The json body of the message
+    pub message: Value,
+    pub schedule: AnnouncementSchedule,
+    /// When the announcement is posted next, as a unix timestamp in seconds
+    pub next_run: i64,
+}
+
+/// Where the scheduled announcements are kept, so that they survive restarts.
+/// `FileAnnouncementStore` keeps them in a local file; implement this to keep them in a database instead.
+#[async_trait]
+pub trait AnnouncementStore: Send + Sync {
+    /// Saves an announcement, replacing the one with the same id
+    async fn save(&self, announcement: &ScheduledAnnouncement) -> Result<(), Error>;
+    /// Removes an announcement that is cancelled or won't be posted again
+    async fn remove(&self, id: u64) -> Result<(), Error>;
+    /// Every saved announcement
+    async fn load(&self) -> Result<Vec<ScheduledAnnouncement>, Error>;
+}
+
+/**
+ * Posts messages on a schedule: once at a given time, or repeatedly on a cron expression in a timezone.
+ * The announcements are kept in an `AnnouncementStore`, and when the scheduler is created after a restart it picks them up again.
+ * Announcements that should have been posted while the bot was down are posted once as soon as it starts.
+ *
+ * ```rust,ignore
+ * let announcements = AnnouncementScheduler::new(ctx.clone(), FileAnnouncementStore::new("announcements.json")).await?;
+ * announcements.schedule(
+ *     channel_id,
+ *     MessageBuilder::new().set_content("Weekly meeting in 10 minutes!"),
+ *     AnnouncementSchedule::cron("50 16 * * Thu", "Europe/London")?,
+ * ).await?;
+ * ```
+ */
+pub struct AnnouncementScheduler {
+    store: Arc<dyn AnnouncementStore>,
+    announcements: Arc<Mutex<BTreeMap<u64, ScheduledAnnouncement>>>,
+    /// Wakes the loop up when the announcements change
+    changed: Arc<Notify>,
+    last_id: AtomicU64,
+}
+
+impl AnnouncementScheduler {
+    /// Creates a scheduler with the announcements in the store, and starts posting them. Must be called from within a tokio runtime.
+    /// @param store Where the announcements are kept
+    pub async fn new(ctx: Context, store: impl AnnouncementStore + 'static) -> Result<Self, Error> {
+        let store: Arc<dyn AnnouncementStore> = Arc::new(store);
+        let announcements: BTreeMap<u64, ScheduledAnnouncement> = store
+            .load()
+            .await?
+            .into_iter()
+            .map(|announcement| (announcement.id, announcement))
+            .collect();
+        let last_id = announcements.keys().max().copied().unwrap_or_default();
+
+        let scheduler = Self {
+            store,
+            announcements: Arc::new(Mutex::new(announcements)),
+            changed: Arc::new(Notify::new()),
+            last_id: AtomicU64::new(last_id),
+        };
+        tokio::spawn(run_loop(
+            ctx,
+            scheduler.store.clone(),
+            scheduler.announcements.clone(),
+            scheduler.changed.clone(),
+        ));
+        Ok(scheduler)
+    }
+
+    /**
+     * Schedules a message. It is saved in the store before this returns
+     * @param channel_id The channel to post in
+     * @param message The message to post. Files can't be saved, so messages with files return an error
+     * @param schedule When to post it
+     * @return The id of the announcement, or an error if it would never be posted (like a time in the past)
+     */
+    pub async fn schedule(
+        &self,
+        channel_id: ChannelIdParam,
+        message: MessageBuilder,
+        schedule: AnnouncementSchedule,
+    ) -> Result<u64, Error> {
+        if !message.files().is_empty() {
+            return Err(Error::new(
+                "Messages with files can't be scheduled".to_string(),
+                ErrorTypes::REQUEST,
+            ));
+        }
+        message.validate()?;
+        let next_run = schedule.next_after(Utc::now().timestamp()).ok_or_else(|| {
+            Error::new(
+                "The announcement would never be posted".to_string(),
+                ErrorTypes::PARSE,
+            )
+        })?;
+        let announcement = ScheduledAnnouncement {
+            id: self.last_id.fetch_add(1, Ordering::SeqCst) + 1,
+            channel_id: channel_id.raw(),
+            message: serde_json::to_value(&message)?,
+            schedule,
+            next_run,
+        };
+        self.store.save(&announcement).await?;
+        let id = announcement.id;
+        self.announcements.lock().unwrap().insert(id, announcement);
+        self.changed.notify_one();
+        Ok(id)
+    }
+
+    /// Stops an announcement from being posted
+    /// @param id The id that `schedule` returned
+    /// @return Whether there was an announcement with the id
+    pub async fn cancel(&self, id: u64) -> Result<bool, Error> {
+        let removed = self.announcements.lock().unwrap().remove(&id).is_some();
+        if removed {
+            self.store.remove(id).await?;
+            self.changed.notify_one();
+        }
+        Ok(removed)
+    }
+
+    /// The announcements that are waiting to be posted, in the order they were scheduled
+    pub fn scheduled(&self) -> Vec<ScheduledAnnouncement> {
+        self.announcements.lock().unwrap().values().cloned().collect()
+    }
+}
+
+async fn run_loop(
+    ctx: Context,
+    store: Arc<dyn AnnouncementStore>,
+    announcements: Arc<Mutex<BTreeMap<u64, ScheduledAnnouncement>>>,
+    changed: Arc<Notify>,
+) {
+    loop {
+        let now = Utc::now().timestamp();
+        let next = announcements
+            .lock()
+            .unwrap()
+            .values()
+            .min_by_key(|announcement| announcement.next_run)
+            .cloned();
+        let Some(announcement) = next else {
+            changed.notified().await;
+            continue;
+        };
+        if announcement.next_run > now {
+            let wait = Duration::from_secs((announcement.next_run - now) as u64);
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = changed.notified() => {}
+            }
+            continue;
+        }
+
+        let next_run = match send_message_json(
+            &ctx,
+            announcement.channel_id,
+            announcement.message.clone(),
+        )
+        .await
+        {
+            Ok(_) => announcement.schedule.next_after(now),
+            // Discord rejected the message (e.g. the channel was deleted), so trying again won't help
+            Err(e) if e.status().is_some_and(|s| (400..500).contains(&s) && s != 429) => {
+                print_debug(
+                    "ANNOUNCEMENTS",
+                    format!("Unable to post announcement {}: {}", announcement.id, e),
+                );
+                announcement.schedule.next_after(now)
+            }
+            Err(e) => {
+                if ctx.settings.debug {
+                    print_debug(
+                        "ANNOUNCEMENTS",
+                        format!(
+                            "Unable to post announcement {}, retrying in {}s: {}",
+                            announcement.id, RETRY_AFTER, e
+                        ),
+                    );
+                }
+                Some(now + RETRY_AFTER)
+            }
+        };
+
+        // The announcement may have been cancelled while it was being posted
+        let updated = {
+            let mut announcements = announcements.lock().unwrap();
+            match (announcements.get_mut(&announcement.id), next_run) {
+                (Some(saved), Some(next_run)) => {
+                    saved.next_run = next_run;
+                    Some(saved.clone())
+                }
+                (Some(_), None) => {
+                    announcements.remove(&announcement.id);
+                    None
+                }
+                (None, _) => continue,
+            }
+        };
+        let saved = match updated {
+            Some(announcement) => store.save(&announcement).await,
+            None => store.remove(announcement.id).await,
+        };
+        if let Err(e) = saved {
+            print_debug(
+                "ANNOUNCEMENTS",
+                format!("Unable to save announcement {}: {}", announcement.id, e),
+            );
+        }
+    }
+}
+
+/// Keeps the announcements in a local json file, which is rewritten whenever they change
+pub struct FileAnnouncementStore {
+    path: PathBuf,
+    /// Only one write at a time, so a save can't be overwritten by an older one
+    lock: Mutex<()>,
+}
+
+impl FileAnnouncementStore {
+    /// @param path Where the announcements are kept. The file is created once the first announcement is scheduled
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> Result<BTreeMap<u64, ScheduledAnnouncement>, Error> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(self.io_error(e)),
+        }
+    }
+
+    fn write(&self, announcements: &BTreeMap<u64, ScheduledAnnouncement>) -> Result<(), Error> {
+        // Write to another file first, so a crash halfway through doesn't lose every announcement
+        let temp = self.path.with_extension("tmp");
+        fs::write(&temp, serde_json::to_vec(announcements)?)
+            .and_then(|_| fs::rename(&temp, &self.path))
+            .map_err(|e| self.io_error(e))
+    }
+
+    fn io_error(&self, e: std::io::Error) -> Error {
+        Error::with_source(
+            format!(
+                "Unable to use the announcements file {}: {}",
+                self.path.display(),
+                e
+            ),
+            ErrorTypes::REQUEST,
+            e,
+        )
+    }
+}
+
+#[async_trait]
+impl AnnouncementStore for FileAnnouncementStore {
+    async fn save(&self, announcement: &ScheduledAnnouncement) -> Result<(), Error> {
+        let _lock = self.lock.lock().unwrap();
+        let mut announcements = self.read()?;
+        announcements.insert(announcement.id, announcement.clone());
+        self.write(&announcements)
+    }
+
+    async fn remove(&self, id: u64) -> Result<(), Error> {
+        let _lock = self.lock.lock().unwrap();
+        let mut announcements = self.read()?;
+        if announcements.remove(&id).is_some() {
+            self.write(&announcements)?;
+        }
+        Ok(())
+    }
+
+    async fn load(&self) -> Result<Vec<ScheduledAnnouncement>, Error> {
+        let _lock = self.lock.lock().unwrap();
+        Ok(self.read()?.into_values().collect())
+    }
+}
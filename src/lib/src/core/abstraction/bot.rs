@@ -1,25 +1,44 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::Select;
 
 use serde_json::json;
 
 use crate::{
     core::{
-        http::{rate_limit_client::RLClient, request_queue::BasicHttpQueue},
-        interactions::handler::{websocket::WebsocketEventHandler, SocketClient},
-        settings::Settings,
+        interactions::handler::{
+            events::Intents, gateway::get_gateway, websocket::WebsocketEventHandler, SocketClient,
+        },
+        settings::{GatewayEncoding, Settings},
     },
-    discord::resources::user::User,
     util::logger::print_debug,
     Registerable,
 };
 
+#[cfg(feature = "event-forwarder")]
+use super::event_forwarder::EventForwarder;
+use crate::discord::gateway::{
+    activity::Activity,
+    presence::{Status, UpdatePresence},
+};
+#[cfg(feature = "rest")]
+use crate::discord::resources::user::User;
+#[cfg(feature = "rest")]
+use super::prefix_commands::PrefixCommands;
+
 use super::{
-    context::Context, event_dispatcher::EventDispatcher, interaction_router::InteractionRouter, traits::{RegisterableType, RegFns},
+    command_group::CommandGroup, command_registry::CommandRegistry, handler_registry::HandlerRegistry, context::Context, derived_events::RequestToSpeakTracker, guild_tracker::GuildTracker, stats_poster::{post_stats_loop, StatsPoster}, event_dispatcher::{EventDispatcher, Events}, interaction_router::InteractionRouter, traits::RegFns, user_throttle::UserThrottle, cache_backend::CacheBackend,
+    shutdown::{shutdown_pair, ShutdownHandle, ShutdownListener, CLOSE_TIMEOUT},
+    middleware::{EventMiddleware, Flow, GatewayEvent},
+    diagnostics::DebugReport,
 };
 
 /// The main bot abstraction
 ///
-/// ```rust,no_run
+/// ```rust,ignore
 ///  // Create a new bot instance with a token
 ///  let bot = Bot::new(token);
 ///  // Register Commands/Events that you want to listen for
@@ -31,98 +50,602 @@ pub struct Bot {
     /// Global context for the bot
     ctx: Context,
     /// The event dispatcher that distributes events to the registered handlers
-    event_dispatcher: EventDispatcher,
+    event_dispatcher: Arc<EventDispatcher>,
+    /// Adds and removes handlers, even while the bot is listening
+    handlers: Arc<HandlerRegistry>,
     /// The token associated with the bot
     token: String,
     /// Interaction router that distributes interactions to the respective handlers. Is registered with the event dispatcher
     interaction_router: Arc<InteractionRouter>,
+    /// Tracks the guilds the bot is in
+    guild_tracker: Arc<GuildTracker>,
+    /// Posters that are started when the bot starts listening, and how often they post
+    stats_posters: Vec<(Arc<dyn StatsPoster>, Duration)>,
+    /// The number of shards to start, or None to use the number that discord recommends
+    shards: Option<u64>,
+    /// The intents sent when identifying
+    intents: Intents,
+    /// Forwarders that are started when the bot starts listening
+    #[cfg(feature = "event-forwarder")]
+    forwarders: Vec<EventForwarder>,
+    /// Stops the bot, see `shutdown_handle`
+    shutdown: ShutdownHandle,
+    /// The listen loop's side of `shutdown`, taken while the bot is listening
+    shutdown_listener: Option<ShutdownListener>,
 }
 
 impl Bot {
     /// Create a new bot instance with a token. Your bot's token can be found in the discord developer portal
     pub fn new(token: String) -> Self {
-        let client = RLClient::new(BasicHttpQueue::new(60));
-        let ctx = Context {
-            token: token.clone(),
-            request_stream: client.get_req_sender(),
-            settings: Settings::default(),
-            cache: (),
-        };
-        let event_dispatcher = EventDispatcher::new();
+        let ctx = Context::new(token.clone());
+        let event_dispatcher = Arc::new(EventDispatcher::new());
         let interaction_router = Arc::new(InteractionRouter::new());
+        let handlers = Arc::new(HandlerRegistry::new(
+            event_dispatcher.clone(),
+            interaction_router.clone(),
+        ));
+        let (shutdown, shutdown_listener) = shutdown_pair(ctx.clone());
+
+        // Register the interaction router
+        interaction_router.set_dispatcher(&event_dispatcher);
+        event_dispatcher
+            .InteractionCtx
+            .subscribe(interaction_router.clone());
 
         Self {
             interaction_router,
             ctx,
             event_dispatcher,
+            handlers,
             token,
+            guild_tracker: Arc::new(GuildTracker::new()),
+            stats_posters: Vec::new(),
+            shards: None,
+            intents: Intents::GUILD_MESSAGES,
+            #[cfg(feature = "event-forwarder")]
+            forwarders: Vec::new(),
+            shutdown,
+            shutdown_listener: Some(shutdown_listener),
         }
     }
 
+    /// Sets the number of shards (gateway connections) to start. By default the bot uses the number that discord recommends,
+    /// which is 1 until the bot is in 1000+ guilds. Bots in more than 2500 guilds have to shard.
+    /// The shard that received an event is in `ctx.shard_id`.
+    /// @docs <https://discord.com/developers/docs/topics/gateway#sharding>
+    pub fn with_shards(mut self, count: u64) -> Self {
+        self.shards = Some(count.max(1));
+        self
+    }
+
+    /// Sets which groups of events the bot receives. Only GUILD_MESSAGES is requested by default.
+    /// Asking for only the events the bot handles saves bandwidth, and privileged intents (like MESSAGE_CONTENT)
+    /// have to be requested here after enabling them in the developer portal, otherwise the gateway closes the connection.
+    /// ```rust,ignore
+    /// bot.intents(Intents::GUILDS | Intents::GUILD_MESSAGES | Intents::MESSAGE_CONTENT);
+    /// ```
+    /// @param intents The intents to identify with
+    /// @docs <https://discord.com/developers/docs/topics/gateway#gateway-intents>
+    pub fn intents(&mut self, intents: Intents) -> &mut Self {
+        self.intents = intents;
+        self
+    }
+
+    /// Has discord compress the events it sends over the gateway, which cuts the bandwidth a lot for bots in many guilds
+    /// at the cost of some CPU to inflate them. The events are the same either way.
+    /// @param compress Whether to compress the gateway connection
+    /// @docs <https://discord.com/developers/docs/topics/gateway#transport-compression>
+    pub fn compress_gateway(&mut self, compress: bool) -> &mut Self {
+        self.settings().compress_gateway = compress;
+        self
+    }
+
+    /// Sets how the gateway encodes its payloads. ETF is smaller and faster to decode than json,
+    /// which adds up for bots that get big GUILD_CREATEs. The events are the same either way.
+    /// @param encoding The encoding of the gateway connection, json by default
+    /// @docs <https://discord.com/developers/docs/topics/gateway#etfjson>
+    pub fn gateway_encoding(&mut self, encoding: GatewayEncoding) -> &mut Self {
+        self.settings().gateway_encoding = encoding;
+        self
+    }
+
+    /// Sets the activity that the bot shows once it connects, e.g. "Playing /help".
+    /// Use `ctx.set_presence` to change it (or the status) while the bot is running
+    /// @param activity What the bot is doing
+    pub fn set_activity(&mut self, activity: Activity) -> &mut Self {
+        let status = self
+            .ctx
+            .gateway
+            .presence()
+            .map(|presence| presence.status)
+            .unwrap_or(Status::Online);
+        self.ctx
+            .gateway
+            .set_presence(UpdatePresence::new(status, Some(activity)));
+        self
+    }
+
+    /// Sets the status that the bot shows once it connects, online by default
+    /// @param status The status to show
+    pub fn set_status(&mut self, status: Status) -> &mut Self {
+        let activity = self
+            .ctx
+            .gateway
+            .presence()
+            .and_then(|presence| presence.activities.into_iter().next());
+        self.ctx
+            .gateway
+            .set_presence(UpdatePresence::new(status, activity));
+        self
+    }
+
     /// Get the settings associated with the bot's context
     pub fn settings(&mut self) -> &mut Settings {
-        &mut self.ctx.settings
+        Arc::make_mut(&mut self.ctx.settings)
     }
 
-    /// You can use this to register a command handler, or an interaction handler. The Registerable Trait is implemented for you through the `#[event_handler]` or `#[command]` macro/
-    pub async fn register(mut self, to_register: Arc<impl Registerable + RegFns>) -> Self {
-        let registerable_type= to_register.get_reg_type();
-            match registerable_type {
-                RegisterableType::Event => {
-                    to_register.reg_event(&mut self.event_dispatcher);
-                }
-                RegisterableType::Command => {
-                    to_register.reg_command(self.ctx.clone(), self.interaction_router.clone())
-                }
-                _=> ()
-            }
+    /// The number of guilds the bot is in (including unavailable ones). Needs the GUILDS intent
+    pub fn guild_count(&self) -> usize {
+        self.guild_tracker.guild_count()
+    }
+
+    /// Gets the guild tracker, which can be used to read the guild count and shard distribution while the bot is listening
+    pub fn guild_tracker(&self) -> Arc<GuildTracker> {
+        self.guild_tracker.clone()
+    }
+
+    /// A snapshot of the shards, cache and queues, for a diagnostics command. See `DebugReport`
+    pub fn debug_report(&self) -> DebugReport {
+        self.ctx.debug_report()
+    }
+
+    /// Gets the ids of the registered commands, which can be used to mention them or update their permissions.
+    /// It is filled in as commands are registered
+    pub fn command_registry(&self) -> Arc<CommandRegistry> {
+        self.interaction_router.registry.clone()
+    }
+
+    /// Posts the bot's stats (guild and shard counts) every `interval` once the bot is listening, e.g. to a bot list.
+    /// @param poster Where to post the stats, `HttpStatsPoster` covers most bot lists
+    /// @param interval How often to post. Bot lists usually ask for no more than once every few minutes
+    pub fn add_stats_poster(&mut self, poster: impl StatsPoster + 'static, interval: Duration) -> &mut Self {
+        self.stats_posters.push((Arc::new(poster), interval));
+        self
+    }
+
+    /// Forwards gateway events to an http endpoint once the bot is listening, see `EventForwarder`
+    #[cfg(feature = "event-forwarder")]
+    pub fn forward_events(&mut self, forwarder: EventForwarder) -> &mut Self {
+        self.forwarders.push(forwarder);
+        self
+    }
+
+    /// Disables an event so that it is dropped by name before its data is deserialized.
+    /// This saves CPU for bots that receive a lot of events they don't care about, like `Events::PresenceUpdate` or `Events::TypingStart`.
+    /// Handlers registered for a disabled event will never be called.
+    pub fn disable_event(&mut self, event: Events) -> &mut Self {
+        self.event_dispatcher.disable_event(event);
+        self
+    }
+
+    /**
+     * Adds middleware that sees every event before the handlers do, and can change or cancel it.
+     * Interactions are events too, so a blacklist here keeps users away from every command.
+     *
+     * ```rust,ignore
+     * bot.add_middleware(move |event, _ctx| {
+     *     let data = &event.data;
+     *     let user_id = data["author"]["id"].as_str().or(data["member"]["user"]["id"].as_str());
+     *     match user_id {
+     *         Some(id) if blacklist.contains(id) => Flow::Cancel,
+     *         _ => Flow::Continue,
+     *     }
+     * });
+     * ```
+     */
+    pub fn add_middleware<F>(&mut self, middleware: F) -> &mut Self
+    where
+        F: Fn(&mut GatewayEvent, &Context) -> Flow + Send + Sync + 'static,
+    {
+        self.event_dispatcher.add_middleware(middleware);
+        self
+    }
+
+    /// Adds middleware that can also run code after the handlers, e.g. to time them. See `EventMiddleware`
+    pub fn add_layer(&mut self, middleware: impl EventMiddleware) -> &mut Self {
+        self.event_dispatcher.add_layer(middleware);
+        self
+    }
+
+    /**
+     * Runs text commands like `!ban @user spam` from the messages the bot receives, see `PrefixCommands`.
+     * Can be called more than once, e.g. for commands with different prefixes.
+     *
+     * ```rust,ignore
+     * bot.intents(Intents::GUILD_MESSAGES | Intents::MESSAGE_CONTENT)
+     *     .prefix_commands(PrefixCommands::new("!").command(ban).command(ping));
+     * ```
+     */
+    #[cfg(feature = "rest")]
+    pub fn prefix_commands(&mut self, commands: PrefixCommands) -> &mut Self {
+        self.event_dispatcher.MessageCreate.subscribe(Arc::new(commands));
+        self
+    }
+
+    /// Limits how often each user can use the bot's commands, components and modals. Users that go over the limit get an ephemeral "slow down" message
+    /// instead of the handler being called.
+    /// @param throttle The limit for each user
+    pub fn throttle_users(&mut self, throttle: UserThrottle) -> &mut Self {
+        self.interaction_router.set_throttle(Some(throttle));
+        self
+    }
+
+    /**
+     * Stops waiting for command, component, and modal handlers that run longer than the timeout. The user is told that it is taking too long
+     * (the response is replaced if the handler already deferred it), and a `HandlerTimeout` event is sent with the name of the command and how long it ran.
+     * The handler can't be killed, so it keeps running on its own thread, but the interactions after it aren't held up.
+     *
+     * ```rust,ignore
+     * bot.handler_timeout(Duration::from_secs(10))
+     *     .command_timeout("export", Some(Duration::from_secs(600)));
+     * ```
+     * @param timeout How long a handler can run
+     */
+    pub fn handler_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.interaction_router.set_timeout(Some(timeout));
+        self
+    }
+
+    /// Replaces the handler timeout for one command, e.g. for a command that is known to be slow
+    /// @param command The name of the command
+    /// @param timeout The timeout, or None to let the command run for as long as it takes
+    pub fn command_timeout(&mut self, command: &str, timeout: Option<Duration>) -> &mut Self {
+        self.interaction_router.set_command_timeout(command, timeout);
+        self
+    }
+
+    /// Keeps the cache in another store instead of in memory, e.g. Redis so that every process of a bot can share it.
+    /// `ctx.cache()` stays empty, the resources' `fetch` methods read from the backend instead
+    /// @param backend The store to keep the cache in
+    pub fn cache_backend(&mut self, backend: impl CacheBackend + 'static) -> &mut Self {
+        self.ctx.cache_backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// Re-enables an event that was disabled with `disable_event`
+    pub fn enable_event(&mut self, event: Events) -> &mut Self {
+        self.event_dispatcher.enable_event(event);
+        self
+    }
+
+    /// You can use this to register a command handler, or an interaction handler. The Registerable Trait is implemented for you through the `#[event_handler]`, `#[command]`, `#[component_handler]`, `#[modal_handler]`, or `#[autocomplete_handler]` macro/
+    pub async fn register(self, to_register: Arc<impl Registerable + RegFns>) -> Self {
+        self.handlers.register(self.ctx.clone(), to_register);
         self
     }
 
-    /// Listen for events and commands. This will block the thread until the bot is closed (when awaited).
+    /// Registers every handler in a `CommandGroup`, see `CommandGroup` for how to split a bot's commands into modules.
+    /// The group can be replaced or removed later through `handlers()`
+    pub async fn register_group(self, group: CommandGroup) -> Self {
+        self.handlers.install(self.ctx.clone(), group);
+        self
+    }
+
+    /// Gets the handler registry, which can install, replace and uninstall groups of handlers while the bot is listening
+    pub fn handlers(&self) -> Arc<HandlerRegistry> {
+        self.handlers.clone()
+    }
+
+    /// Gets a handle that stops the bot. Get it before calling `listen`, since listening borrows the bot until it stops.
+    /// Once a listen stopped, get a new handle for the next one
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Listens like `listen`, and shuts the bot down cleanly when the process gets ctrl-c (SIGINT)
+    pub async fn listen_until_ctrl_c(&mut self) {
+        let shutdown = self.shutdown_handle();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                shutdown.request();
+            }
+        });
+        self.listen().await;
+    }
+
+    /// Listen for events and commands. This will block the thread until the bot is closed (when awaited), or shut down with a `ShutdownHandle`.
     pub async fn listen(&mut self) {
-        let event_handler = WebsocketEventHandler::create(self.ctx.clone()).await;
+        let shutdown = match self.shutdown_listener.take() {
+            Some(listener) => listener,
+            // The last listen was dropped before it stopped
+            None => {
+                let (handle, listener) = shutdown_pair(self.ctx.clone());
+                self.shutdown = handle;
+                listener
+            }
+        };
 
-        // Register the interaction router
-        self.event_dispatcher
-            .InteractionCtx
-            .subscribe(self.interaction_router.clone());
+        // The shards of the last listen closed for good
+        self.ctx.gateway.reset();
+
+        let posters: Vec<_> = self
+            .stats_posters
+            .iter()
+            .map(|(poster, interval)| {
+                tokio::spawn(post_stats_loop(
+                    self.ctx.clone(),
+                    poster.clone(),
+                    self.guild_tracker.clone(),
+                    *interval,
+                ))
+            })
+            .collect();
+
+        #[cfg(feature = "event-forwarder")]
+        let forwarders: Vec<_> = self
+            .forwarders
+            .iter()
+            .map(|forwarder| forwarder.clone().start(self.ctx.clone()))
+            .collect();
 
         if self.ctx.settings.debug {
             print_debug("BOT", "Identifying Self".to_string());
         }
 
-        // Identify object for the bot
-        let cmd = json!({
-            "op": 2,
-            "d": {
-                "token": self.token,
-                "properties": {
-                    "$os": "linux",
-                    "$browser": "discord.rs",
-                    "$device": "discord.rs",
-                },
-                "intents": 1 << 9,
+        // TODO so the gateway says that it shouldn't be cached. WHAT DOES THIS MEAN????
+        // does it mean not cached between instances, and having it get a new gateway on startup?
+        // or does it want use to periodically get a new gateway while the bot is running? plz help
+        let gateway = get_gateway(self.ctx.clone()).await.unwrap();
+        let shard_count = self.shards.unwrap_or(gateway.shards).max(1);
+        let max_concurrency = gateway.session_start_limit.max_concurrency.max(1);
+
+        self.ctx.gateway.set_intents(self.intents);
+        if self.ctx.settings.debug {
+            self.warn_missing_intents();
+        }
+        if let Some(backend) = &self.ctx.cache_backend {
+            backend.set_debug(self.ctx.settings.debug);
+        }
+        let mut shards = Vec::new();
+        for shard_id in 0..shard_count {
+            // Only max_concurrency shards can identify every 5 seconds
+            if shard_id > 0 && shard_id % max_concurrency == 0 {
+                tokio::time::sleep(Duration::from_secs(5)).await;
             }
-        });
+            if self.ctx.settings.debug {
+                print_debug("BOT", format!("Starting shard {}/{}", shard_id + 1, shard_count));
+            }
+
+            // Identify object for the shard
+            let cmd = json!({
+                "op": 2,
+                "d": {
+                    "token": self.token,
+                    "properties": {
+                        "$os": "linux",
+                        "$browser": "discord.rs",
+                        "$device": "discord.rs",
+                    },
+                    "intents": self.intents.bits(),
+                    "shard": [shard_id, shard_count],
+                }
+            });
 
-        // Send the identify object to the websocket
-        event_handler.send_command(cmd.to_string());
+            let mut shard_ctx = self.ctx.clone();
+            shard_ctx.shard_id = shard_id;
+            shard_ctx.shard_count = shard_count;
 
-        // Listen for events
-        let cmds = event_handler.get_command_channel();
+            // Connect to the websocket, which sends the identify object (and identifies again if the session is lost)
+            let event_handler = WebsocketEventHandler::create(shard_ctx.clone(), gateway.clone(), cmd);
+            self.ctx.gateway.add_shard(
+                event_handler.command_sender(),
+                event_handler.get_command_channel(),
+                event_handler.session(),
+            );
+            shards.push((shard_ctx, event_handler.get_command_channel()));
+        }
+
+        // Listen for events from every shard
         if self.ctx.settings.debug {
             print_debug("BOT", "Listening...".to_string());
         }
-        
-        while let Ok((command, data)) = cmds.recv() {
+        let mut select = Select::new();
+        for (_, cmds) in &shards {
+            select.recv(cmds);
+        }
+        let shutdown_index = select.recv(&shutdown.wake);
+        // Set once shutting down started. The shards get until then to close their connections
+        let mut close_deadline: Option<Instant> = None;
+
+        let mut live_shards = shards.len();
+        let mut request_to_speak = RequestToSpeakTracker::default();
+        while live_shards > 0 {
+            let index = match close_deadline {
+                None => select.ready(),
+                Some(deadline) => match select.ready_deadline(deadline) {
+                    Ok(index) => index,
+                    Err(_) => break,
+                },
+            };
+            if index == shutdown_index {
+                select.remove(shutdown_index);
+                if self.ctx.settings.debug {
+                    print_debug("BOT", "Shutting down".to_string());
+                }
+                close_deadline = Some(Instant::now() + CLOSE_TIMEOUT);
+                continue;
+            }
+            let (ctx, cmds) = &shards[index];
+            let (command, data) = match cmds.try_recv() {
+                Ok(event) => event,
+                Err(crossbeam_channel::TryRecvError::Empty) => continue,
+                // The shard stopped for good
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    select.remove(index);
+                    live_shards -= 1;
+                    continue;
+                }
+            };
+            let is_request_to_speak =
+                command == "VOICE_STATE_UPDATE" && request_to_speak.is_new_request(&data);
+            let derived = if is_request_to_speak { Some(data.clone()) } else { None };
+            #[cfg(feature = "event-forwarder")]
+            for forwarder in &forwarders {
+                forwarder.forward(ctx, &command, &data);
+            }
+            self.guild_tracker.handle_event(&command, &data);
+            ctx.update_identity(&command, &data);
+            match &ctx.cache_backend {
+                Some(backend) => backend.apply_event(&ctx.settings.cache, &command, &data).await,
+                None => ctx.cache.update(&ctx.settings.cache, &command, &data),
+            }
+
             self.event_dispatcher
-                .route_event(self.ctx.clone(), command, data);
+                .route_event(ctx.clone(), command, data);
+
+            if let Some(data) = derived {
+                self.event_dispatcher
+                    .route_event(ctx.clone(), "REQUEST_TO_SPEAK".to_string(), data);
+            }
+        }
+        for poster in posters {
+            poster.abort();
         }
+        shutdown.finish().await;
+
+        // So that `shutdown_handle` gives a handle that stops the next listen
+        let (handle, listener) = shutdown_pair(self.ctx.clone());
+        self.shutdown = handle;
+        self.shutdown_listener = Some(listener);
     }
 
-    /// Get the discord user associated with the bot
+    /// The features that are fed by gateway events don't work without the intents of those events.
+    /// Nothing stops a bot from running without them, so this only points out what won't be filled in
+    fn warn_missing_intents(&self) {
+        let cache = &self.ctx.settings.cache;
+        if !self.intents.contains(Intents::GUILDS) {
+            let mut missing = "the guild tracker".to_string();
+            if cache.guilds || cache.channels || cache.roles {
+                missing.push_str(" and the cached guilds, channels and roles");
+            }
+            print_debug(
+                "BOT",
+                format!("The GUILDS intent isn't requested, so {} won't be filled in", missing),
+            );
+        }
+        if !self.intents.contains(Intents::GUILD_MEMBERS) {
+            let mut missing = "the guild tracker's member counts".to_string();
+            if cache.members {
+                missing.push_str(" and the cached members");
+            }
+            print_debug(
+                "BOT",
+                format!("The GUILD_MEMBERS intent isn't requested, so {} won't be updated when members join or leave", missing),
+            );
+        }
+        if !self.intents.contains(Intents::GUILD_VOICE_STATES) && !self.event_dispatcher.RequestToSpeak.is_empty() {
+            print_debug(
+                "BOT",
+                "The GUILD_VOICE_STATES intent isn't requested, so REQUEST_TO_SPEAK handlers will never be called".to_string(),
+            );
+        }
+    }
+
+    /// Get the discord user associated with the bot. Only makes a request if the bot didn't receive READY yet
+    #[cfg(feature = "rest")]
     pub async fn get_user(&self) -> User {
-        User::get_self(self.ctx.clone()).await.unwrap()
+        match self.ctx.current_user() {
+            Some(user) => user,
+            None => User::get_self(self.ctx.clone()).await.unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+    use super::*;
+
+    /// Answers every request with the gateway, like `GET /gateway/bot` does
+    async fn serve_gateway(listener: TcpListener, gateway_url: String) {
+        let body = json!({
+            "url": gateway_url,
+            "shards": 1,
+            "session_start_limit": { "total": 1000, "remaining": 1000, "reset_after": 0, "max_concurrency": 1 },
+        })
+        .to_string();
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    }
+
+    /// Starts a session on every connection, then waits for the bot to close it
+    async fn serve_shards(listener: TcpListener) {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_shard(stream));
+        }
+    }
+
+    async fn serve_shard(stream: TcpStream) {
+        let mut socket = accept_async(stream).await.unwrap();
+        let hello = json!({ "op": 10, "d": { "heartbeat_interval": 45000 } });
+        socket.send(Message::Text(hello.to_string())).await.unwrap();
+        // The identify
+        socket.next().await;
+        let ready = json!({ "op": 0, "s": 1, "t": "READY", "d": { "session_id": "session" } });
+        socket.send(Message::Text(ready.to_string())).await.unwrap();
+        // Reading answers the close frame the bot sends when it shuts down
+        while let Some(Ok(_)) = socket.next().await {}
+    }
+
+    /// Shuts the bot down once its shard is connected
+    /// @return How many shards the bot had, and whether they were closing before the shutdown
+    async fn stop_once_connected(ctx: Context, shutdown: ShutdownHandle) -> (usize, bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let reports = loop {
+            let reports = ctx.gateway.shard_reports();
+            if reports.iter().any(|shard| shard.connected) || Instant::now() > deadline {
+                break reports;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
+        let closing = ctx.gateway.is_closing();
+        shutdown.request();
+        (reports.len(), closing)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn listens_again_after_shutting_down() {
+        let http = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let gateway = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", http.local_addr().unwrap());
+        tokio::spawn(serve_gateway(http, format!("ws://{}", gateway.local_addr().unwrap())));
+        tokio::spawn(serve_shards(gateway));
+
+        let mut bot = Bot::new("token".to_string());
+        bot.settings().set_proxy(&api_url);
+        // The fake READY has none of the fields the handlers get
+        bot.disable_event(Events::Ready);
+
+        for _ in 0..2 {
+            let stop = tokio::spawn(stop_once_connected(bot.ctx.clone(), bot.shutdown_handle()));
+            bot.listen().await;
+            let (shards, closing) = stop.await.unwrap();
+            assert_eq!(shards, 1);
+            assert!(!closing);
+        }
     }
 }
@@ -0,0 +1,107 @@
+use std::{future::Future, ops::Deref};
+
+use crate::{
+    core::abstraction::cache_backend::{get_as, CacheKey},
+    discord::{
+        resources::{
+            channel::Channel, guild::guild_member::GuildMember, guild::guild_object::Guild,
+            user::User,
+        },
+        snowflake::Snowflake,
+    },
+    util::error::Error,
+    Context,
+};
+
+/// A value that was either read from the cache, or fetched from discord because the cache didn't have it (or there is no cache).
+/// Methods that return this work the same either way, the variant only tells you how fresh the value is and whether a request was made.
+#[derive(Clone, Debug)]
+pub enum CacheOrHttp<T> {
+    /// The value was in the cache, so no request was made. It may be slightly out of date.
+    Cache(T),
+    /// The value was fetched from discord
+    Http(T),
+}
+
+impl<T> CacheOrHttp<T> {
+    /// Whether the value came from the cache
+    pub fn is_cached(&self) -> bool {
+        matches!(self, CacheOrHttp::Cache(_))
+    }
+
+    /// Returns the value, no matter where it came from
+    pub fn into_inner(self) -> T {
+        match self {
+            CacheOrHttp::Cache(value) | CacheOrHttp::Http(value) => value,
+        }
+    }
+
+    /// Transforms the value while keeping track of where it came from
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> CacheOrHttp<U> {
+        match self {
+            CacheOrHttp::Cache(value) => CacheOrHttp::Cache(f(value)),
+            CacheOrHttp::Http(value) => CacheOrHttp::Http(f(value)),
+        }
+    }
+}
+
+impl<T> Deref for CacheOrHttp<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            CacheOrHttp::Cache(value) | CacheOrHttp::Http(value) => value,
+        }
+    }
+}
+
+/// Uses the cached value if there is one, otherwise waits for the request
+pub async fn cache_or_http<T>(
+    cached: Option<T>,
+    fetch: impl Future<Output = Result<T, Error>>,
+) -> Result<CacheOrHttp<T>, Error> {
+    match cached {
+        Some(value) => Ok(CacheOrHttp::Cache(value)),
+        None => fetch.await.map(CacheOrHttp::Http),
+    }
+}
+
+// Cache lookups used by the `fetch` methods on the resources. They read the cache backend if the bot has one
+
+pub(crate) async fn cached_guild(ctx: &Context, guild_id: Snowflake) -> Option<Guild> {
+    match &ctx.cache_backend {
+        Some(backend) => get_as(backend.as_ref(), CacheKey::guild(guild_id)).await,
+        None => ctx.cache.guild(guild_id),
+    }
+}
+
+pub(crate) async fn cached_channel(ctx: &Context, channel_id: Snowflake) -> Option<Channel> {
+    match &ctx.cache_backend {
+        Some(backend) => get_as(backend.as_ref(), CacheKey::channel(channel_id)).await,
+        None => ctx.cache.channel(channel_id),
+    }
+}
+
+pub(crate) async fn cached_member(
+    ctx: &Context,
+    guild_id: Snowflake,
+    user_id: Snowflake,
+) -> Option<GuildMember> {
+    match &ctx.cache_backend {
+        Some(backend) => get_as(backend.as_ref(), CacheKey::member(guild_id, user_id)).await,
+        None => ctx.cache.member(guild_id, user_id),
+    }
+}
+
+/// The members of a guild that are in the in-memory cache. May be only some of them, see `Guild::member_count`.
+/// Cache backends can't list members, so this is empty when the bot uses one
+pub(crate) fn cached_members(ctx: &Context, guild_id: Snowflake) -> Vec<GuildMember> {
+    ctx.cache.members(guild_id)
+}
+
+pub(crate) async fn cached_user(ctx: &Context, user_id: Snowflake) -> Option<User> {
+    match &ctx.cache_backend {
+        Some(backend) => get_as(backend.as_ref(), CacheKey::user(user_id)).await,
+        None => ctx.cache.user(user_id),
+    }
+}
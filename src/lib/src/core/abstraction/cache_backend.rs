@@ -0,0 +1,433 @@
+use std::panic::RefUnwindSafe;
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+use crate::discord::{
+    resources::{
+        channel::Channel,
+        guild::{guild_member::GuildMember, guild_object::Guild},
+        user::User,
+    },
+    snowflake::Snowflake,
+};
+
+use super::memory_cache::{Cache, CacheConfig};
+
+/// The kinds of resources that are cached. Roles are kept in their guild's `roles`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CacheResource {
+    Guild,
+    Channel,
+    Member,
+    User,
+}
+
+impl CacheResource {
+    /// A short name for the kind, e.g. for prefixing keys in a key-value store
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheResource::Guild => "guild",
+            CacheResource::Channel => "channel",
+            CacheResource::Member => "member",
+            CacheResource::User => "user",
+        }
+    }
+}
+
+/// Where a cached resource is stored
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CacheKey {
+    pub resource: CacheResource,
+    /// The id of the resource. For members, this is the id of the user
+    pub id: Snowflake,
+    /// The guild that the resource belongs to. Only set for members, since user ids are only unique per guild for them
+    pub guild_id: Option<Snowflake>,
+}
+
+impl CacheKey {
+    pub fn guild(guild_id: Snowflake) -> Self {
+        Self {
+            resource: CacheResource::Guild,
+            id: guild_id,
+            guild_id: None,
+        }
+    }
+
+    pub fn channel(channel_id: Snowflake) -> Self {
+        Self {
+            resource: CacheResource::Channel,
+            id: channel_id,
+            guild_id: None,
+        }
+    }
+
+    pub fn member(guild_id: Snowflake, user_id: Snowflake) -> Self {
+        Self {
+            resource: CacheResource::Member,
+            id: user_id,
+            guild_id: Some(guild_id),
+        }
+    }
+
+    pub fn user(user_id: Snowflake) -> Self {
+        Self {
+            resource: CacheResource::User,
+            id: user_id,
+            guild_id: None,
+        }
+    }
+}
+
+impl std::fmt::Display for CacheKey {
+    /// `guild:ID`, `channel:ID`, `member:GUILD_ID:USER_ID` or `user:ID`
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.guild_id {
+            Some(guild_id) => write!(f, "{}:{}:{}", self.resource.as_str(), guild_id, self.id),
+            None => write!(f, "{}:{}", self.resource.as_str(), self.id),
+        }
+    }
+}
+
+/// A store for the cache, set with `bot.cache_backend(...)`. The in-memory `Cache` is used when none is set.
+/// Resources are stored as the json that their types serialize to, so a key-value store like Redis
+/// can be shared by every process of a bot.
+///
+/// The bot passes every gateway event to `apply_event` before it is dispatched. The default implementation
+/// keeps the resources up to date with `get`, `insert` and `evict`, so a backend usually only implements those.
+/// It doesn't remove the channels and members of a guild that the bot leaves, since they can't be listed,
+/// so backends should expire their entries. `CacheConfig::max_members_per_guild` is also only applied by the in-memory cache.
+///
+/// Backends have to be `RefUnwindSafe` since they are part of the `Context` that handlers get. Clients that aren't
+/// can be wrapped in `std::panic::AssertUnwindSafe`.
+///
+/// ```rust,ignore
+/// struct RedisCache { connection: redis::aio::ConnectionManager }
+///
+/// #[async_trait]
+/// impl CacheBackend for RedisCache {
+///     async fn get(&self, key: &CacheKey) -> Option<Value> {
+///         let json: Option<String> = self.connection.clone().get(key.to_string()).await.ok()?;
+///         serde_json::from_str(&json?).ok()
+///     }
+///     async fn insert(&self, key: CacheKey, value: Value) {
+///         let _: Result<(), _> = self.connection.clone().set_ex(key.to_string(), value.to_string(), 3600).await;
+///     }
+///     async fn evict(&self, key: &CacheKey) {
+///         let _: Result<(), _> = self.connection.clone().del(key.to_string()).await;
+///     }
+/// }
+///
+/// bot.cache_backend(RedisCache { connection });
+/// ```
+#[async_trait]
+pub trait CacheBackend: Send + Sync + RefUnwindSafe {
+    /// Gets a cached resource
+    async fn get(&self, key: &CacheKey) -> Option<Value>;
+
+    /// Caches a resource, replacing the one with the same key
+    async fn insert(&self, key: CacheKey, value: Value);
+
+    /// Removes a resource from the cache
+    async fn evict(&self, key: &CacheKey);
+
+    /**
+     * Updates the cache from a gateway event.
+     * @param config What to cache
+     * @param event The name of the event, e.g. `GUILD_CREATE`
+     * @param data The data of the event
+     */
+    async fn apply_event(&self, config: &CacheConfig, event: &str, data: &Value) {
+        update_backend(self, config, event, data).await;
+    }
+
+    /// Called with `Settings::debug` when the bot starts listening, so that the backend can log the errors it has no way to return
+    fn set_debug(&self, _debug: bool) {}
+}
+
+/// Gets a cached resource as its type. Entries that don't deserialize are treated as missing
+#[cfg(feature = "rest")]
+pub(crate) async fn get_as<T: DeserializeOwned>(
+    backend: &dyn CacheBackend,
+    key: CacheKey,
+) -> Option<T> {
+    serde_json::from_value(backend.get(&key).await?).ok()
+}
+
+/// The in-memory cache, so it can be wrapped or used where a backend is expected
+#[async_trait]
+impl CacheBackend for Cache {
+    async fn get(&self, key: &CacheKey) -> Option<Value> {
+        match (key.resource, key.guild_id) {
+            (CacheResource::Guild, _) => to_value(self.guild(key.id)),
+            (CacheResource::Channel, _) => to_value(self.channel(key.id)),
+            (CacheResource::Member, Some(guild_id)) => to_value(self.member(guild_id, key.id)),
+            (CacheResource::Member, None) => None,
+            (CacheResource::User, _) => to_value(self.user(key.id)),
+        }
+    }
+
+    async fn insert(&self, key: CacheKey, value: Value) {
+        match (key.resource, key.guild_id) {
+            (CacheResource::Guild, _) => {
+                if let Ok(guild) = serde_json::from_value::<Guild>(value) {
+                    self.store_guild(guild);
+                }
+            }
+            (CacheResource::Channel, _) => {
+                if let Ok(channel) = serde_json::from_value::<Channel>(value) {
+                    self.store_channel(channel);
+                }
+            }
+            (CacheResource::Member, Some(guild_id)) => {
+                if let Ok(member) = serde_json::from_value::<GuildMember>(value) {
+                    self.store_member(guild_id, key.id, member);
+                }
+            }
+            (CacheResource::Member, None) => {}
+            (CacheResource::User, _) => {
+                if let Ok(user) = serde_json::from_value::<User>(value) {
+                    self.store_user(user);
+                }
+            }
+        }
+    }
+
+    async fn evict(&self, key: &CacheKey) {
+        self.remove(key);
+    }
+
+    async fn apply_event(&self, config: &CacheConfig, event: &str, data: &Value) {
+        self.update(config, event, data);
+    }
+}
+
+fn to_value<T: Serialize>(value: Option<T>) -> Option<Value> {
+    serde_json::to_value(value?).ok()
+}
+
+/// Checks that the json is a valid `T`, and normalizes it to what `T` serializes to
+fn normalize<T: Serialize + DeserializeOwned>(value: &Value) -> Option<Value> {
+    let parsed: T = serde_json::from_value(value.clone()).ok()?;
+    serde_json::to_value(parsed).ok()
+}
+
+fn id_of(value: &Value) -> Option<Snowflake> {
+    serde_json::from_value(value.clone()).ok()
+}
+
+/// The default `apply_event`, which only uses `get`, `insert` and `evict`
+pub(crate) async fn update_backend<B: CacheBackend + ?Sized>(
+    backend: &B,
+    config: &CacheConfig,
+    event: &str,
+    data: &Value,
+) {
+    match event {
+        "GUILD_CREATE" => {
+            // An unavailable guild only has an id
+            if data["unavailable"].as_bool() == Some(true) {
+                return;
+            }
+            let Some(guild_id) = id_of(&data["id"]) else {
+                return;
+            };
+            if config.channels {
+                let channels = data["channels"].as_array().into_iter().flatten();
+                let threads = data["threads"].as_array().into_iter().flatten();
+                for channel in channels.chain(threads) {
+                    let mut channel = channel.clone();
+                    // The channels in GUILD_CREATE don't have a guild id
+                    channel["guild_id"] = json!(guild_id);
+                    insert_channel(backend, &channel).await;
+                }
+            }
+            if let Some(members) = data["members"].as_array() {
+                insert_members(backend, config, guild_id, members).await;
+            }
+            if config.guilds {
+                let mut guild = data.clone();
+                if let Some(guild) = guild.as_object_mut() {
+                    for field in [
+                        "channels",
+                        "threads",
+                        "members",
+                        "presences",
+                        "voice_states",
+                    ] {
+                        guild.remove(field);
+                    }
+                    if !config.roles {
+                        guild.insert("roles".to_string(), json!([]));
+                    }
+                }
+                if let Some(guild) = normalize::<Guild>(&guild) {
+                    backend.insert(CacheKey::guild(guild_id), guild).await;
+                }
+            }
+        }
+        "GUILD_UPDATE" if config.guilds => {
+            let Some(key) = id_of(&data["id"]).map(CacheKey::guild) else {
+                return;
+            };
+            if let Some(mut guild) = backend.get(&key).await {
+                merge(&mut guild, data);
+                if !config.roles {
+                    guild["roles"] = json!([]);
+                }
+                if let Some(guild) = normalize::<Guild>(&guild) {
+                    backend.insert(key, guild).await;
+                }
+            }
+        }
+        "GUILD_DELETE" => {
+            if let Some(guild_id) = id_of(&data["id"]) {
+                backend.evict(&CacheKey::guild(guild_id)).await;
+            }
+        }
+        "CHANNEL_CREATE" | "CHANNEL_UPDATE" | "THREAD_CREATE" | "THREAD_UPDATE"
+            if config.channels =>
+        {
+            insert_channel(backend, data).await;
+        }
+        "CHANNEL_DELETE" | "THREAD_DELETE" => {
+            if let Some(channel_id) = id_of(&data["id"]) {
+                backend.evict(&CacheKey::channel(channel_id)).await;
+            }
+        }
+        "GUILD_MEMBER_ADD" => {
+            if let Some(guild_id) = id_of(&data["guild_id"]) {
+                insert_members(backend, config, guild_id, std::slice::from_ref(data)).await;
+                change_member_count(backend, guild_id, 1).await;
+            }
+        }
+        "GUILD_MEMBER_UPDATE" => {
+            let (Some(guild_id), Some(user_id)) =
+                (id_of(&data["guild_id"]), id_of(&data["user"]["id"]))
+            else {
+                return;
+            };
+            insert_user(backend, config, &data["user"]).await;
+            if !config.members {
+                return;
+            }
+            let key = CacheKey::member(guild_id, user_id);
+            let member = match backend.get(&key).await {
+                Some(mut member) => {
+                    merge(&mut member, data);
+                    member
+                }
+                None => data.clone(),
+            };
+            if let Some(member) = normalize::<GuildMember>(&member) {
+                backend.insert(key, member).await;
+            }
+        }
+        "GUILD_MEMBER_REMOVE" => {
+            let (Some(guild_id), Some(user_id)) =
+                (id_of(&data["guild_id"]), id_of(&data["user"]["id"]))
+            else {
+                return;
+            };
+            backend.evict(&CacheKey::member(guild_id, user_id)).await;
+            change_member_count(backend, guild_id, -1).await;
+        }
+        "GUILD_MEMBERS_CHUNK" => {
+            if let (Some(guild_id), Some(members)) =
+                (id_of(&data["guild_id"]), data["members"].as_array())
+            {
+                insert_members(backend, config, guild_id, members).await;
+            }
+        }
+        "GUILD_ROLE_CREATE" | "GUILD_ROLE_UPDATE" | "GUILD_ROLE_DELETE" => {
+            if !config.roles {
+                return;
+            }
+            let Some(key) = id_of(&data["guild_id"]).map(CacheKey::guild) else {
+                return;
+            };
+            let Some(mut guild) = backend.get(&key).await else {
+                return;
+            };
+            let role_id = match event {
+                "GUILD_ROLE_DELETE" => id_of(&data["role_id"]),
+                _ => id_of(&data["role"]["id"]),
+            };
+            if let Some(roles) = guild["roles"].as_array_mut() {
+                roles.retain(|role| id_of(&role["id"]) != role_id);
+                if event != "GUILD_ROLE_DELETE" {
+                    roles.push(data["role"].clone());
+                }
+            }
+            if let Some(guild) = normalize::<Guild>(&guild) {
+                backend.insert(key, guild).await;
+            }
+        }
+        "USER_UPDATE" => insert_user(backend, config, data).await,
+        _ => {}
+    }
+}
+
+async fn insert_channel<B: CacheBackend + ?Sized>(backend: &B, channel: &Value) {
+    if let (Some(channel_id), Some(channel)) =
+        (id_of(&channel["id"]), normalize::<Channel>(channel))
+    {
+        backend.insert(CacheKey::channel(channel_id), channel).await;
+    }
+}
+
+async fn insert_members<B: CacheBackend + ?Sized>(
+    backend: &B,
+    config: &CacheConfig,
+    guild_id: Snowflake,
+    members: &[Value],
+) {
+    for member in members {
+        insert_user(backend, config, &member["user"]).await;
+        if !config.members {
+            continue;
+        }
+        if let (Some(user_id), Some(member)) = (
+            id_of(&member["user"]["id"]),
+            normalize::<GuildMember>(member),
+        ) {
+            backend
+                .insert(CacheKey::member(guild_id, user_id), member)
+                .await;
+        }
+    }
+}
+
+async fn insert_user<B: CacheBackend + ?Sized>(backend: &B, config: &CacheConfig, user: &Value) {
+    if !config.users || user.is_null() {
+        return;
+    }
+    if let (Some(user_id), Some(user)) = (id_of(&user["id"]), normalize::<User>(user)) {
+        backend.insert(CacheKey::user(user_id), user).await;
+    }
+}
+
+async fn change_member_count<B: CacheBackend + ?Sized>(
+    backend: &B,
+    guild_id: Snowflake,
+    change: i64,
+) {
+    let key = CacheKey::guild(guild_id);
+    let Some(mut guild) = backend.get(&key).await else {
+        return;
+    };
+    if let Some(count) = guild["member_count"].as_i64() {
+        guild["member_count"] = json!((count + change).max(0));
+        backend.insert(key, guild).await;
+    }
+}
+
+/// Applies the fields of a partial update to a cached object, keeping the fields the update doesn't have
+pub(crate) fn merge(cached: &mut Value, update: &Value) {
+    if let (Some(object), Some(fields)) = (cached.as_object_mut(), update.as_object()) {
+        for (key, field) in fields {
+            object.insert(key.clone(), field.clone());
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{any::Any, sync::Arc};
+
+use super::{
+    context::Context,
+    event_dispatcher::EventDispatcher,
+    interaction_router::InteractionRouter,
+    traits::{RegFns, Registerable, RegisterableType},
+};
+
+type Registrar = Box<dyn FnOnce(&EventDispatcher, Context, Arc<InteractionRouter>) + Send>;
+
+/// A handler of a group, kept so that it can be found again when the group is removed
+pub(crate) type GroupHandler = Arc<dyn Any + Send + Sync>;
+
+/**
+ * A set of commands, subcommands and their component/modal/autocomplete handlers that are registered together.
+ * Each module of a bot can expose its own group, so that the bot only has to register the modules instead of every handler.
+ *
+ * ```rust,ignore
+ * // moderation.rs
+ * pub fn commands() -> CommandGroup {
+ *     CommandGroup::new("moderation")
+ *         .handler(Ban)
+ *         .handler(Kick)
+ *         .handler(BanReasonAutocomplete)
+ *         .handler(AppealButton)
+ * }
+ *
+ * // main.rs
+ * let bot = Bot::new(token)
+ *     .register_group(moderation::commands()).await
+ *     .register_group(fun::commands()).await;
+ * ```
+ */
+pub struct CommandGroup {
+    name: String,
+    /// The handler names, for debugging
+    names: Vec<&'static str>,
+    registrars: Vec<(GroupHandler, Registrar)>,
+}
+
+impl CommandGroup {
+    /// Creates an empty group
+    /// @param name The name of the group, only used in debug logs
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            names: Vec::new(),
+            registrars: Vec::new(),
+        }
+    }
+
+    /// Adds a handler to the group. Anything that can be passed to `Bot::register` can be added
+    pub fn handler<T>(self, handler: T) -> Self
+    where
+        T: Registerable + RegFns + Send + Sync + 'static,
+    {
+        self.shared_handler(Arc::new(handler))
+    }
+
+    /// Adds a handler that is shared with something else
+    pub fn shared_handler<T>(mut self, handler: Arc<T>) -> Self
+    where
+        T: Registerable + RegFns + Send + Sync + 'static,
+    {
+        self.names.push(handler.get_name().unwrap_or("<event>"));
+        let kept: GroupHandler = handler.clone();
+        self.registrars.push((
+            kept,
+            Box::new(move |dispatcher, ctx, router| {
+                register_handler(&handler, dispatcher, ctx, router)
+            }),
+        ));
+        self
+    }
+
+    /// Adds every handler of another group to this one, so that groups can be nested
+    pub fn merge(mut self, other: CommandGroup) -> Self {
+        self.names.extend(other.names);
+        self.registrars.extend(other.registrars);
+        self
+    }
+
+    /// The name of the group
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of the commands in the group, with `<event>` for event handlers
+    pub fn handler_names(&self) -> &[&'static str] {
+        &self.names
+    }
+
+    /// The number of handlers in the group
+    pub fn len(&self) -> usize {
+        self.registrars.len()
+    }
+
+    /// Whether the group has no handlers
+    pub fn is_empty(&self) -> bool {
+        self.registrars.is_empty()
+    }
+
+    /// Registers every handler in the group. Called by `HandlerRegistry::install`
+    /// @return The handlers that were registered
+    pub(crate) fn register_all(
+        self,
+        dispatcher: &EventDispatcher,
+        ctx: Context,
+        router: Arc<InteractionRouter>,
+    ) -> Vec<GroupHandler> {
+        self.registrars
+            .into_iter()
+            .map(|(handler, registrar)| {
+                registrar(dispatcher, ctx.clone(), router.clone());
+                handler
+            })
+            .collect()
+    }
+}
+
+/// Registers a handler where its kind of handler goes
+pub(crate) fn register_handler<T: Registerable + RegFns>(
+    handler: &Arc<T>,
+    dispatcher: &EventDispatcher,
+    ctx: Context,
+    router: Arc<InteractionRouter>,
+) {
+    match handler.get_reg_type() {
+        RegisterableType::Event => handler.reg_event(dispatcher),
+        RegisterableType::Command => handler.reg_command(ctx, router),
+        RegisterableType::Component => handler.reg_component(router),
+        RegisterableType::Modal => handler.reg_modal(router),
+        RegisterableType::Autocomplete => handler.reg_autocomplete(router),
+        _ => (),
+    }
+}
@@ -0,0 +1,80 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use crate::discord::snowflake::Snowflake;
+
+/**
+ * The ids of the bot's application commands, filled in as the commands are registered.
+ * It saves listing the commands from the API again to mention a command or update its permissions.
+ *
+ * ```rust,ignore
+ * let commands = bot.command_registry();
+ * // Later, in a handler
+ * let mention = commands.mention("ban", None).unwrap(); // </ban:1234567890>
+ * ```
+ */
+#[derive(Default)]
+pub struct CommandRegistry {
+    /// (guild id, command name) to command id. The guild id is None for global commands
+    ids: RwLock<HashMap<(Option<Snowflake>, String), Snowflake>>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Records the id of a registered command
+     * @param name The name of the command
+     * @param guild_id The guild the command belongs to, or None for a global command
+     * @param id The id of the command
+     */
+    pub fn insert(&self, name: &str, guild_id: Option<Snowflake>, id: Snowflake) {
+        self.ids
+            .write()
+            .unwrap()
+            .insert((guild_id, name.to_string()), id);
+    }
+
+    /// The id of a global command
+    pub fn id(&self, name: &str) -> Option<Snowflake> {
+        self.ids
+            .read()
+            .unwrap()
+            .get(&(None, name.to_string()))
+            .copied()
+    }
+
+    /// The id of a command that belongs to a guild
+    pub fn guild_id(&self, guild_id: Snowflake, name: &str) -> Option<Snowflake> {
+        self.ids
+            .read()
+            .unwrap()
+            .get(&(Some(guild_id), name.to_string()))
+            .copied()
+    }
+
+    /**
+     * Creates a mention of a global command, which shows up as a clickable command in a message
+     * @param name The name of the command
+     * @param subcommand The subcommand (or "group subcommand") to mention, if any
+     * @return None if the command isn't registered
+     */
+    pub fn mention(&self, name: &str, subcommand: Option<&str>) -> Option<String> {
+        let id = self.id(name)?;
+        Some(match subcommand {
+            Some(subcommand) => format!("</{} {}:{}>", name, subcommand, id),
+            None => format!("</{}:{}>", name, id),
+        })
+    }
+
+    /// All of the registered commands as (guild id, name, id)
+    pub fn all(&self) -> Vec<(Option<Snowflake>, String, Snowflake)> {
+        self.ids
+            .read()
+            .unwrap()
+            .iter()
+            .map(|((guild_id, name), id)| (*guild_id, name.clone(), *id))
+            .collect()
+    }
+}
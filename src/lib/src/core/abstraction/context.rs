@@ -1,17 +1,417 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, OnceLock,
+};
+
+#[cfg(feature = "gateway")]
+use std::sync::atomic::AtomicBool;
+
+#[cfg(feature = "gateway")]
+use crossbeam_channel::Receiver;
 use crossbeam_channel::Sender;
+#[cfg(feature = "gateway")]
+use serde_json::Value;
+use static_assertions::assert_impl_all;
+#[cfg(feature = "gateway")]
+use tokio_tungstenite::tungstenite::{
+    protocol::{frame::coding::CloseCode, CloseFrame},
+    Message,
+};
+
+#[cfg(any(feature = "rest", feature = "gateway"))]
+use crate::{discord::resources::application::Application, util::error::Error};
+#[cfg(feature = "gateway")]
+use crate::{
+    core::{
+        abstraction::diagnostics::{DebugReport, QueueDepths, ShardReport},
+        interactions::handler::{events::Intents, websocket::Session},
+    },
+    discord::gateway::{
+        activity::Activity,
+        presence::{Status, UpdatePresence},
+    },
+};
+#[cfg(feature = "rest")]
+use crate::discord::{
+    ids::params::{self, ChannelIdParam},
+    permissions::{compute_permissions, Permissions},
+    resources::{
+        channel::{typing::ChannelType, Channel},
+        guild::{guild_member::GuildMember, guild_object::Guild},
+    },
+};
+use crate::{
+    core::{
+        abstraction::{cache_backend::CacheBackend, memory_cache::Cache},
+        http::{
+            global_limiter::RateLimitInfo,
+            rate_limit_client::{RLClient, RequestObject},
+            request_queue::BasicHttpQueue,
+        },
+        settings::Settings,
+    },
+    discord::{resources::user::User, snowflake::Snowflake},
+};
+
+/// What the bot knows about itself. It is filled in from the READY event, or the first request that needs it
+#[derive(Default)]
+pub struct Identity {
+    /// The id of the bot's application. It never changes, so it is only set once
+    application_id: OnceLock<Snowflake>,
+    /// The bot's user, kept up to date by USER_UPDATE
+    user: Mutex<Option<User>>,
+}
+
+/// The connections to the gateway, so that commands (like presence updates) can be sent from any context
+#[cfg(feature = "gateway")]
+#[derive(Default)]
+pub struct GatewayConnections {
+    /// The connections of the shards, added as the shards start
+    shards: Mutex<Vec<ShardConnection>>,
+    /// The intents the shards identified with
+    intents: Mutex<Option<Intents>>,
+    /// The last presence that was set, which the shards identify with so that it survives reconnects
+    presence: Mutex<Option<UpdatePresence>>,
+    /// Set when the bot is shutting down, so that the shards don't reconnect after closing
+    closing: AtomicBool,
+}
+
+/// What is kept of a shard's connection
+#[cfg(feature = "gateway")]
+struct ShardConnection {
+    /// Where the shard's commands are sent
+    commands: Sender<Message>,
+    /// The events the shard received that weren't dispatched yet
+    events: Receiver<(String, Value)>,
+    session: Arc<Mutex<Session>>,
+}
+
+#[cfg(feature = "gateway")]
+impl GatewayConnections {
+    pub(crate) fn add_shard(
+        &self,
+        commands: Sender<Message>,
+        events: Receiver<(String, Value)>,
+        session: Arc<Mutex<Session>>,
+    ) {
+        self.shards.lock().unwrap().push(ShardConnection {
+            commands,
+            events,
+            session,
+        });
+    }
+
+    pub(crate) fn set_intents(&self, intents: Intents) {
+        *self.intents.lock().unwrap() = Some(intents);
+    }
+
+    pub(crate) fn intents(&self) -> Option<Intents> {
+        *self.intents.lock().unwrap()
+    }
+
+    /// The state of every shard, in order of shard id
+    pub(crate) fn shard_reports(&self) -> Vec<ShardReport> {
+        self.shards
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(id, shard)| {
+                let session = shard.session.lock().unwrap();
+                ShardReport {
+                    id: id as u64,
+                    connected: session.connected,
+                    session_id: session.session_id.clone(),
+                    resume_gateway_url: session.resume_gateway_url.clone(),
+                    sequence: session.sequence_num,
+                    reconnects: session.reconnects,
+                    queued_events: shard.events.len(),
+                    queued_commands: shard.commands.len(),
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn presence(&self) -> Option<UpdatePresence> {
+        self.presence.lock().unwrap().clone()
+    }
+
+    pub(crate) fn set_presence(&self, presence: UpdatePresence) {
+        *self.presence.lock().unwrap() = Some(presence);
+    }
+
+    /// Forgets the shards of the last `listen`, which closed for good, so that the bot can listen again
+    pub(crate) fn reset(&self) {
+        self.shards.lock().unwrap().clear();
+        self.closing.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns true once the connections were closed with `close`
+    pub(crate) fn is_closing(&self) -> bool {
+        self.closing.load(Ordering::SeqCst)
+    }
+
+    /**
+     * Closes every shard's connection for good. A normal close code ends the session,
+     * so the bot goes offline straight away instead of when discord notices the connection is gone
+     * @docs <https://discord.com/developers/docs/topics/gateway#disconnections>
+     */
+    pub(crate) fn close(&self) {
+        self.closing.store(true, Ordering::SeqCst);
+        for shard in self.shards.lock().unwrap().iter() {
+            let _ = shard.commands.send(Message::Close(Some(CloseFrame {
+                code: CloseCode::Normal,
+                reason: "Shutting down".into(),
+            })));
+        }
+    }
 
-use crate::core::{http::rate_limit_client::RequestObject, settings::Settings};
+    /// Sends a command to every shard that is running
+    fn send(&self, command: String) {
+        for shard in self.shards.lock().unwrap().iter() {
+            // A shard that stopped for good has nothing to update
+            let _ = shard.commands.send(Message::Text(command.clone()));
+        }
+    }
+}
 
 /// Context object that is passed to all parts of the bot
 /// It contains key information so that methods can create requests to discord, and also contains settings for those functions
+///
+/// Cloning a context is cheap, since everything in it is behind an `Arc` (or is a handle to the request thread), and it is `Send + Sync`,
+/// so it can be cloned into as many spawned tasks as needed.
+/// The token and settings never change, but the rest is shared state that every clone sees the changes to:
+/// the cache, the rate limits, the bot's identity, the count of requests in flight, and the gateway connections
+/// (the shards, their intents and the last presence that was set).
 #[derive(Clone)]
 pub struct Context {
     /// The token for the bot's instance
-    pub token: String,
+    pub token: Arc<str>,
     /// The request sender for the instance's bot. Allows the user to make http requests
     pub request_stream: Sender<RequestObject>,
-    /// The settings for the bot's instance
-    pub settings: Settings,
-    /// The cache for the bot's instance
-    pub cache: (), // TODO
-}
\ No newline at end of file
+    /// The settings for the bot's instance. They are shared by every clone of the context, and can't be changed once the bot is listening
+    pub settings: Arc<Settings>,
+    /// The guilds, channels, members, roles and users the bot has seen. Read it with `cache()`
+    pub cache: Arc<Cache>,
+    /// The store that the cache is kept in instead of `cache`, if one was set with `Bot::cache_backend`
+    pub cache_backend: Option<Arc<dyn CacheBackend>>,
+    /// The shard that received the event this context was made for. Always 0 when the bot isn't sharded
+    pub shard_id: u64,
+    /// The number of shards the bot is using
+    pub shard_count: u64,
+    /// The global rate limits, kept up to date by the request thread. Read them with `rate_limit_info()`
+    pub rate_limits: Arc<Mutex<RateLimitInfo>>,
+    /// The bot's application id and user. Read them with `application_id()` and `current_user()`
+    pub identity: Arc<Identity>,
+    /// The number of requests that were sent and haven't been answered yet. Read it with `pending_requests()`
+    pub in_flight: Arc<AtomicUsize>,
+    /// The bot's gateway connections. Send commands to them with methods like `set_presence()`
+    #[cfg(feature = "gateway")]
+    pub gateway: Arc<GatewayConnections>,
+}
+
+// Handlers move the context into spawned tasks, so losing any of these would be a breaking change
+assert_impl_all!(Context: Send, Sync, Clone);
+
+impl Context {
+    /// Creates a new context with its own request thread.
+    /// `Bot::new` does this for you, so this is only needed when using the http layer on its own (e.g. with the `webhook-client` feature).
+    /// @param token The bot's token. Can be empty if only token-less endpoints (like executing a webhook) will be used.
+    pub fn new(token: String) -> Self {
+        let client = RLClient::new(BasicHttpQueue::new(60));
+        Context {
+            rate_limits: client.get_rate_limit_info(),
+            ..Context::with_request_stream(token, client.get_req_sender())
+        }
+    }
+
+    /// Creates a context that sends its requests to the given request stream instead of starting a request thread
+    /// @param token The bot's token
+    /// @param request_stream Where the requests made with this context are sent to
+    pub fn with_request_stream(token: String, request_stream: Sender<RequestObject>) -> Self {
+        Context {
+            token: token.into(),
+            request_stream,
+            settings: Arc::new(Settings::default()),
+            cache: Arc::new(Cache::new()),
+            cache_backend: None,
+            shard_id: 0,
+            shard_count: 1,
+            rate_limits: Arc::new(Mutex::new(RateLimitInfo::default())),
+            identity: Arc::new(Identity::default()),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            #[cfg(feature = "gateway")]
+            gateway: Arc::new(GatewayConnections::default()),
+        }
+    }
+
+    /// The global rate limits that apply to every request made with this context, and how close the bot is to them.
+    /// Useful for backing off before discord starts rejecting requests
+    pub fn rate_limit_info(&self) -> RateLimitInfo {
+        self.rate_limits.lock().unwrap().clone()
+    }
+
+    /// The number of requests made with this context (or its clones) that are waiting in the rate limit queue or for discord to answer
+    pub fn pending_requests(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /**
+     * A snapshot of the bot's connections, cache and queues, see `DebugReport`.
+     * Its `Display` is meant to be sent as is, e.g. in a code block from an owner-only command
+     */
+    #[cfg(feature = "gateway")]
+    pub fn debug_report(&self) -> DebugReport {
+        let shards = self.gateway.shard_reports();
+        DebugReport {
+            queues: QueueDepths {
+                pending_requests: self.pending_requests(),
+                request_queue: self.request_stream.len(),
+                events: shards.iter().map(|shard| shard.queued_events).sum(),
+            },
+            shards,
+            intents: self.gateway.intents(),
+            // The sizes of another store aren't known
+            cache: match self.cache_backend {
+                Some(_) => None,
+                None => Some(self.cache.sizes()),
+            },
+        }
+    }
+
+    /**
+     * The id of the bot's application. It is known once the bot received READY, otherwise
+     * it is fetched the first time and remembered, so calling this repeatedly only makes one request.
+     */
+    #[cfg(any(feature = "rest", feature = "gateway"))]
+    pub async fn application_id(&self) -> Result<Snowflake, Error> {
+        if let Some(id) = self.cached_application_id() {
+            return Ok(id);
+        }
+        let application = Application::get_self(self.clone()).await?;
+        Ok(*self.identity.application_id.get_or_init(|| application.id))
+    }
+
+    /**
+     * Checks that the bot has permissions in a channel, so a command can stop with a clear message
+     * instead of getting a 403 from discord halfway through. The channel, guild and the bot's member are
+     * read from the cache when they are there, otherwise they are fetched. DM channels always pass.
+     *
+     * ```rust,ignore
+     * if let Err(e) = ctx.assert_permissions(channel_id, Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS).await {
+     *     if let Some(missing) = e.missing_permissions() {
+     *         ictx.respond(format!("I need these permissions here: {:?}", missing)).await?;
+     *     }
+     * }
+     * ```
+     * @param channel_id The channel to check
+     * @param required The permissions that the bot needs
+     * @return An error with the permissions that the bot doesn't have in `missing_permissions()`
+     */
+    #[cfg(feature = "rest")]
+    pub async fn assert_permissions(
+        &self,
+        channel_id: ChannelIdParam,
+        required: Permissions,
+    ) -> Result<(), Error> {
+        let channel = Channel::fetch(self.clone(), channel_id).await?.into_inner();
+        let Some(guild_id) = channel.guild_id else {
+            return Ok(());
+        };
+        // Threads use the overwrites of the channel they are in
+        let is_thread = matches!(
+            channel.channel_type,
+            ChannelType::GuildNewsThread
+                | ChannelType::GuildPublicThread
+                | ChannelType::GuildPrivateThread
+        );
+        let channel = match channel.parent_id {
+            Some(parent_id) if is_thread => {
+                Channel::fetch(self.clone(), params::channel(parent_id))
+                    .await?
+                    .into_inner()
+            }
+            _ => channel,
+        };
+
+        let mut guild = Guild::fetch(self.clone(), params::guild(guild_id))
+            .await?
+            .into_inner();
+        // The cache may be set to not keep roles
+        if guild.roles.is_empty() {
+            guild = Guild::get(self.clone(), params::guild(guild_id)).await?;
+        }
+        let user_id = match self.current_user() {
+            Some(user) => user.id,
+            None => User::get_self(self.clone()).await?.id,
+        };
+        let member = GuildMember::fetch(self.clone(), params::guild(guild_id), params::user(user_id))
+            .await?;
+
+        let permissions = compute_permissions(
+            &guild,
+            user_id,
+            &member.roles,
+            channel.permission_overwrites.as_deref().unwrap_or_default(),
+            member.is_timed_out(),
+        );
+        let missing = permissions.missing(required);
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::from_missing_permissions(missing))
+        }
+    }
+
+    /**
+     * Changes the bot's status and activity on every shard. It is kept when the bot reconnects.
+     *
+     * ```rust,ignore
+     * ctx.set_presence(Status::Idle, Some(Activity::watching("the logs")));
+     * ```
+     * @param status The status to show
+     * @param activity What the bot is doing, or None to clear it
+     * @docs <https://discord.com/developers/docs/topics/gateway#update-presence>
+     */
+    #[cfg(feature = "gateway")]
+    pub fn set_presence(&self, status: Status, activity: Option<Activity>) {
+        let presence = UpdatePresence::new(status, activity);
+        let command = serde_json::json!({ "op": 3, "d": presence }).to_string();
+        self.gateway.set_presence(presence);
+        self.gateway.send(command);
+    }
+
+    /// The guilds, channels, members, roles and users the bot has seen, kept up to date from the gateway events.
+    /// Empty when the bot uses another `CacheBackend`
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    /// The id of the bot's application, if it is already known
+    pub fn cached_application_id(&self) -> Option<Snowflake> {
+        self.identity.application_id.get().copied()
+    }
+
+    /// The bot's user, once the bot received READY
+    pub fn current_user(&self) -> Option<User> {
+        self.identity.user.lock().unwrap().clone()
+    }
+
+    /// Remembers what READY and USER_UPDATE say about the bot
+    #[cfg(feature = "gateway")]
+    pub(crate) fn update_identity(&self, event: &str, data: &serde_json::Value) {
+        let user = match event {
+            "READY" => {
+                if let Ok(id) = serde_json::from_value(data["application"]["id"].clone()) {
+                    let _ = self.identity.application_id.set(id);
+                }
+                &data["user"]
+            }
+            "USER_UPDATE" => data,
+            _ => return,
+        };
+        if let Ok(user) = serde_json::from_value::<User>(user.clone()) {
+            *self.identity.user.lock().unwrap() = Some(user);
+        }
+    }
+}
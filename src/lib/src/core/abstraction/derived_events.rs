@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+/// Keeps track of the last request_to_speak_timestamp seen for each user, so that a REQUEST_TO_SPEAK event
+/// can be derived from the stream of VOICE_STATE_UPDATE events.
+#[derive(Default)]
+pub struct RequestToSpeakTracker {
+    /// (guild id, user id) -> the last request_to_speak_timestamp that was seen
+    requests: HashMap<(String, String), String>,
+}
+
+impl RequestToSpeakTracker {
+    /// Records a VOICE_STATE_UPDATE payload, and returns whether it contains a new request to speak
+    pub fn is_new_request(&mut self, voice_state: &Value) -> bool {
+        let key = (
+            voice_state["guild_id"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            voice_state["user_id"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        );
+
+        // The user left the voice channel, or lowered their hand
+        let timestamp = match (
+            voice_state["channel_id"].as_str(),
+            voice_state["request_to_speak_timestamp"].as_str(),
+        ) {
+            (Some(_), Some(timestamp)) => timestamp,
+            _ => {
+                self.requests.remove(&key);
+                return false;
+            }
+        };
+
+        match self.requests.insert(key, timestamp.to_string()) {
+            Some(previous) => previous != timestamp,
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::core::interactions::handler::events::Intents;
+
+use super::memory_cache::CacheSizes;
+
+/**
+ * What the bot is doing right now, from `ctx.debug_report()` or `bot.debug_report()`.
+ * It holds session ids, so only show it to the bot's owners.
+ *
+ * ```rust,ignore
+ * #[prefix_command(description = "Shows the state of the bot")]
+ * async fn debug(ctx: Context, msg: Message) {
+ *     if msg.author.as_ref().map(|author| author.id) != Some(OWNER_ID) {
+ *         return;
+ *     }
+ *     let report = format!("```\n{}\n```", ctx.debug_report());
+ *     let _ = Message::send(ctx, params::channel(msg.channel_id), Message::builder().set_content(&report)).await;
+ * }
+ * ```
+ */
+#[derive(Serialize, Clone, Debug)]
+pub struct DebugReport {
+    /// Every shard the bot started, in order of shard id
+    pub shards: Vec<ShardReport>,
+    /// The intents the shards identified with, None before the bot starts listening
+    pub intents: Option<Intents>,
+    /// How much is cached, None when the cache is kept in a `CacheBackend`
+    pub cache: Option<CacheSizes>,
+    pub queues: QueueDepths,
+}
+
+/// The state of a shard's connection to the gateway
+#[derive(Serialize, Clone, Debug)]
+pub struct ShardReport {
+    pub id: u64,
+    /// Whether the shard is connected and its session started (or resumed)
+    pub connected: bool,
+    /// The id of the shard's session, from READY
+    pub session_id: Option<String>,
+    /// Where the shard reconnects to when it resumes, from READY
+    pub resume_gateway_url: Option<String>,
+    /// The sequence number of the last event the shard received
+    pub sequence: Option<u64>,
+    /// The number of times the shard lost its connection
+    pub reconnects: u32,
+    /// The events the shard received that the bot didn't dispatch yet
+    pub queued_events: usize,
+    /// The commands (e.g. presence updates) waiting to be sent to the gateway
+    pub queued_commands: usize,
+}
+
+/// How much work is waiting. Numbers that keep growing mean the bot can't keep up
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct QueueDepths {
+    /// Requests that are waiting in the rate limit queue or for discord to answer, see `ctx.pending_requests()`
+    pub pending_requests: usize,
+    /// Requests that the request thread didn't pick up yet
+    pub request_queue: usize,
+    /// Events that every shard received and the bot didn't dispatch yet
+    pub events: usize,
+}
+
+impl Display for DebugReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let connected = self.shards.iter().filter(|shard| shard.connected).count();
+        writeln!(f, "Shards: {}/{} connected", connected, self.shards.len())?;
+        for shard in &self.shards {
+            writeln!(
+                f,
+                "  #{} {} session={} seq={} reconnects={} events={} commands={}",
+                shard.id,
+                if shard.connected { "up" } else { "down" },
+                shard.session_id.as_deref().unwrap_or("-"),
+                shard.sequence.map_or("-".to_string(), |seq| seq.to_string()),
+                shard.reconnects,
+                shard.queued_events,
+                shard.queued_commands,
+            )?;
+            if let Some(url) = &shard.resume_gateway_url {
+                writeln!(f, "     resume={}", url)?;
+            }
+        }
+        match self.intents {
+            Some(intents) => writeln!(f, "Intents: {:?} ({})", intents, intents.bits())?,
+            None => writeln!(f, "Intents: -")?,
+        }
+        match self.cache {
+            Some(cache) => writeln!(
+                f,
+                "Cache: {} guilds, {} channels, {} members, {} users",
+                cache.guilds, cache.channels, cache.members, cache.users
+            )?,
+            None => writeln!(f, "Cache: kept in a cache backend")?,
+        }
+        write!(
+            f,
+            "Queues: {} pending requests, {} queued requests, {} queued events",
+            self.queues.pending_requests, self.queues.request_queue, self.queues.events
+        )
+    }
+}
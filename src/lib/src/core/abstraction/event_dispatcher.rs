@@ -6,7 +6,7 @@ use crate::core::interactions::handler::events::dispatch_payloads::{
     ChannelPinsUpdate, GuildBanAddRemove, GuildEmojisUpdate, GuildIntegrationsUpdate,
     GuildMemberAdd, GuildMemberRemove, GuildMemberUpdate, GuildMembersChunk,
     GuildRoleCreateUpdateDelete, GuildScheduledEventUserAddRemove, GuildStickersUpdate,
-    IntegrationCreateUpdate, IntegrationDelete, InviteCreate, InviteDelete, MessageDelete,
+    HandlerTimeout, IntegrationCreateUpdate, IntegrationDelete, InviteCreate, InviteDelete, MessageDelete,
     MessageDeleteBulk, MessageReactionAdd, MessageReactionRemove, MessageReactionRemoveAll,
     MessageReactionRemoveEmoji, ThreadListSync, ThreadMemberUpdate, ThreadMembersUpdate,
     TypingStart, VoiceServerUpdate, WebhooksUpdate,
@@ -22,15 +22,19 @@ use crate::discord::resources::user::User;
 use crate::discord::resources::voice::VoiceState;
 use crate::util::logger::print_debug;
 use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use std::mem;
 use std::panic::{RefUnwindSafe, UnwindSafe};
 
+use super::middleware::{EventMiddleware, Flow, GatewayEvent};
 use super::observer::Observable;
 
 /**
 This code will generate an `observable` for each event.
 To see exactly what it does, here is a minimal example:
-```rust
+```rust,ignore
 event_subscriptions! {
    pub struct EventDispatcher {
        const ChannelCreate: Channel = "CHANNEL_CREATE";
@@ -42,8 +46,8 @@ It will generate:
 * Impl EventDispatcher with functions:
     * `fn new() -> Self // A default constructor for creating a new EventDispatcher`
     * `fn route_event(ctx: Context, event: String, data: serde::Value) // given ctx, the event name, and the event data, it will parse the data and then dispatch the event correctly`
-    * `fn get_observable(event: Events, type_str: &str) -> &mut Observable<EventDispatcher> // returns a mutable reference to the `Observable` for this event type, and also checks to make sure that the event type matches the event via string comparison`
-    * `fn get_observable_by_name(event: &str) -> &mut Observable<EventDispatcher> // returns a mutable reference to the `Observable` for this event type without checking that`
+    * `fn get_observable(event: Events) -> &Observable<T> // returns the `Observable` for this event type`
+    * `fn unsubscribe(handler: *const ()) -> bool // removes a handler from every `Observable``
 * A Default impl of EventDispatcher that just calls `EventDispatcher::new()`
 * An enum containing all of the events that can be dispatched
 
@@ -66,6 +70,10 @@ macro_rules! event_subscriptions {
                 $(#[$inner])*
                 pub $Flag: Observable<$x>,
             )+
+            /// Names of the events that are dropped before their data is deserialized
+            disabled_events: RwLock<HashSet<&'static str>>,
+            /// Runs around every routed event, in the order it was added
+            middleware: RwLock<Vec<Arc<dyn EventMiddleware>>>,
         }
         impl $EventSubs {
             #[doc="Creates a new EventDispatcher with empty Observables"]
@@ -74,22 +82,67 @@ macro_rules! event_subscriptions {
                     $(
                         $Flag: Observable::new(),
                     )+
+                    disabled_events: RwLock::new(HashSet::new()),
+                    middleware: RwLock::new(Vec::new()),
                 }
             }
 
 
             #[doc="Given a Context, the event name, and the event data, it will parse the data and then dispatch the event correctly"]
             pub fn route_event(&self, ctx: Context, event: String, data: Value) {
-                match event.as_str() {
+                // Disabled events are dropped by name, so their (potentially large) payloads are never parsed
+                if self.is_event_disabled(event.as_str()) {
+                    return;
+                }
+                // The lock is released before running the middleware, so that it can add more
+                let middleware = self.middleware.read().unwrap().clone();
+                let mut event = GatewayEvent { name: event, data };
+                for layer in &middleware {
+                    if layer.before(&mut event, &ctx) == Flow::Cancel {
+                        return;
+                    }
+                }
+                // Middleware can rename the event to one that is disabled
+                if self.is_event_disabled(event.name.as_str()) {
+                    return;
+                }
+                let start = Instant::now();
+                self.dispatch(ctx.clone(), &event.name, &event.data);
+                let elapsed = start.elapsed();
+                for layer in &middleware {
+                    layer.after(&event, &ctx, elapsed);
+                }
+            }
+
+            #[doc="Adds a closure that sees every routed event before the handlers do, and can change or cancel it"]
+            pub fn add_middleware<F>(&self, middleware: F)
+            where
+                F: Fn(&mut GatewayEvent, &Context) -> Flow + Send + Sync + 'static,
+            {
+                self.add_layer(middleware);
+            }
+
+            #[doc="Adds middleware that can also run code after the handlers, see `EventMiddleware`"]
+            pub fn add_layer(&self, middleware: impl EventMiddleware) {
+                self.middleware.write().unwrap().push(Arc::new(middleware));
+            }
+
+            #[doc="Parses the data of the event, and passes it to the handlers of the event"]
+            fn dispatch(&self, ctx: Context, event: &str, data: &Value) {
+                match event {
                     $(
                         // Match the event name
                         $EventName => {
-                            let data = serde_json::from_value::<$x>(data.clone()).unwrap_or_else(|e| {
-                                if ctx.settings.debug {
-                                    print_debug("EVENT_HANDLER", format!("Error parsing event data: {}", data));
+                            // Middleware can change the data, so a bad payload drops the event instead of taking down the listen loop
+                            let data = match serde_json::from_value::<$x>(data.clone()) {
+                                Ok(data) => data,
+                                Err(e) => {
+                                    if ctx.settings.debug {
+                                        print_debug("EVENT_HANDLER", format!("Dropping {}, unable to parse its data ({}): {}", event, e, data));
+                                    }
+                                    return;
                                 }
-                                panic!("Unable to deserialize event data! {}", e)
-                            });
+                            };
                             self.$Flag.notify(ctx, data);
                         }
                     )+
@@ -101,16 +154,40 @@ macro_rules! event_subscriptions {
                 }
             }
 
-            #[doc="Returns a mutable reference to the `Observable` for this event type"]
-            pub fn get_observable<T: Clone + CommandArg + UnwindSafe + RefUnwindSafe>(&mut self, event: Events) -> &mut Observable<T> {
+            #[doc="Stops the event from being deserialized and dispatched. Useful for high volume events (e.g. `PresenceUpdate` or `TypingStart`) that the bot doesn't care about"]
+            pub fn disable_event(&self, event: Events) {
+                self.disabled_events.write().unwrap().insert(event.name());
+            }
+
+            #[doc="Re-enables an event that was disabled with `disable_event`"]
+            pub fn enable_event(&self, event: Events) {
+                self.disabled_events.write().unwrap().remove(event.name());
+            }
+
+            #[doc="Whether the event with the given gateway name (e.g. `PRESENCE_UPDATE`) is disabled"]
+            pub fn is_event_disabled(&self, event: &str) -> bool {
+                self.disabled_events.read().unwrap().contains(event)
+            }
+
+            #[doc="Returns the `Observable` for this event type. Handlers can be subscribed to it at any time, even while the bot is listening"]
+            pub fn get_observable<T: Clone + CommandArg + UnwindSafe + RefUnwindSafe>(&self, event: Events) -> &Observable<T> {
                 match event {
                     $(
                         Events::$Flag => {
-                            unsafe { mem::transmute(&mut self.$Flag) }
+                            unsafe { mem::transmute(&self.$Flag) }
                         },
                     )+
                 }
             }
+
+            #[doc="Unsubscribes a handler from every event it was subscribed to, see `handler_ptr`. Returns whether it was subscribed to any"]
+            pub fn unsubscribe(&self, handler: *const ()) -> bool {
+                let mut found = false;
+                $(
+                    found |= self.$Flag.unsubscribe(handler);
+                )+
+                found
+            }
         }
 
         impl Default for $EventSubs {
@@ -121,6 +198,7 @@ macro_rules! event_subscriptions {
         }
 
         #[doc="An enum containing all of the events that can be dispatched"]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
         pub enum Events {
             $(
                 $(#[$inner])*
@@ -129,6 +207,17 @@ macro_rules! event_subscriptions {
             )+
         }
 
+        impl Events {
+            #[doc="The name of the event as it is sent over the gateway"]
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        Events::$Flag => $EventName,
+                    )+
+                }
+            }
+        }
+
    };
 }
 
@@ -178,13 +267,12 @@ event_subscriptions! {
         /// If the current user does not have the GUILD_MEMBERS Gateway Intent, then this event
         /// will only be sent if the current user was added to or removed from the thread.
         const ThreadMembersUpdate: ThreadMembersUpdate = "THREAD_MEMBERS_UPDATE";
-        /**
-         * This event can be sent in three different scenarios:
-         * 1. When a user is initially connecting, to lazily load and backfill information for all unavailable guilds sent in the Ready event. Guilds that are unavailable due to an outage will send a Guild Delete event.
-         * 2. When a Guild becomes available again to the client.
-         * 3. When the current user joins a new Guild.
-         * The inner payload is a guild object, with all the extra fields specified.
-         */
+        /// This event can be sent in three different scenarios:
+        /// 1. When a user is initially connecting, to lazily load and backfill information for all unavailable guilds sent in the Ready event. Guilds that are unavailable due to an outage will send a Guild Delete event.
+        /// 2. When a Guild becomes available again to the client.
+        /// 3. When the current user joins a new Guild.
+        ///
+        /// The inner payload is a guild object, with all the extra fields specified.
         const GuildCreate: Guild = "GUILD_CREATE";
         /// guild was updated
         const GuildUpdate: Guild = "GUILD_UPDATE";
@@ -269,10 +357,17 @@ event_subscriptions! {
         const UserUpdate: User = "USER_UPDATE";
         /// someone joined, left, or moved a voice channel
         const VoiceStateUpdate: VoiceState = "VOICE_STATE_UPDATE";
+        /// a user in a stage channel raised their hand to speak
+        /// This isn't a gateway event. It is derived from VOICE_STATE_UPDATE, and only fires when the user's request_to_speak_timestamp changes to a new value.
+        /// Like VOICE_STATE_UPDATE, it needs the GUILD_VOICE_STATES intent.
+        const RequestToSpeak: VoiceState = "REQUEST_TO_SPEAK";
         /// guild's voice server was updated
         const VoiceServerUpdate: VoiceServerUpdate = "VOICE_SERVER_UPDATE";
         /// guild channel webhook was created, update, or deleted
         const WebhooksUpdate: WebhooksUpdate = "WEBHOOKS_UPDATE";
+        /// a command, component, or modal handler ran longer than its timeout
+        /// This isn't a gateway event. It is sent by the interaction router, see `Bot::handler_timeout`.
+        const HandlerTimeout: HandlerTimeout = "HANDLER_TIMEOUT";
         /// Triggered when the bot is fully connected to the gateway.
         const Ready: ReadyPayloadData = "READY"; 
     }
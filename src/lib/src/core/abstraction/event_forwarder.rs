@@ -0,0 +1,197 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use hmac::{Hmac, Mac};
+use hyper::{Body, Client, Method, Request, StatusCode};
+use hyper_tls::HttpsConnector;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::mpsc::{channel, error::TrySendError, Receiver, Sender};
+
+use crate::util::{
+    error::{Error, ErrorTypes},
+    logger::print_debug,
+};
+
+use super::{context::Context, event_dispatcher::Events};
+
+/// The longest time to wait between two attempts at delivering an event
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/**
+ * Forwards gateway events to an http endpoint, so that backends that aren't written in rust can handle them.
+ * Each event is POSTed as `{"t": "MESSAGE_CREATE", "d": {...}, "shard_id": 0}`, in the order the events were received.
+ *
+ * When a secret is set, every request has an `X-Discrab-Timestamp` header, and an `X-Discrab-Signature` header
+ * with `sha256=` and the hex HMAC-SHA256 of `{timestamp}.{body}`. The endpoint should check both, and reject old timestamps.
+ *
+ * Failed deliveries (network errors, 429 and 5xx responses) are retried with a backoff. Events are dropped when the endpoint
+ * rejects them with another 4xx, when they run out of attempts, or when the queue is full because the endpoint can't keep up.
+ *
+ * ```rust,ignore
+ * bot.forward_events(
+ *     EventForwarder::new("https://backend.example.com/discord/events")
+ *         .secret(FORWARDER_SECRET)
+ *         .events(&[Events::MessageCreate, Events::GuildMemberAdd]),
+ * );
+ * ```
+ */
+#[derive(Clone)]
+pub struct EventForwarder {
+    url: String,
+    secret: Option<Vec<u8>>,
+    /// The names of the events to forward, or None for every event
+    events: Option<HashSet<&'static str>>,
+    max_attempts: u32,
+    queue_size: usize,
+}
+
+impl EventForwarder {
+    /// Forwards every event to the url
+    /// @param url Where to POST the events
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            secret: None,
+            events: None,
+            max_attempts: 5,
+            queue_size: 10_000,
+        }
+    }
+
+    /// Signs the requests with a shared secret
+    #[must_use]
+    pub fn secret(mut self, secret: &str) -> Self {
+        self.secret = Some(secret.as_bytes().to_vec());
+        self
+    }
+
+    /// Only forwards these events. Events that are disabled with `Bot::disable_event` are still forwarded
+    #[must_use]
+    pub fn events(mut self, events: &[Events]) -> Self {
+        self.events = Some(events.iter().map(|event| event.name()).collect());
+        self
+    }
+
+    /// How many times an event is sent before it is dropped, 5 by default
+    #[must_use]
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// How many events can wait to be delivered before new ones are dropped, 10000 by default
+    #[must_use]
+    pub fn queue_size(mut self, size: usize) -> Self {
+        self.queue_size = size.max(1);
+        self
+    }
+
+    /// Starts delivering events in the background. Called by `Bot::listen`
+    pub(crate) fn start(self, ctx: Context) -> ForwarderQueue {
+        let (sender, receiver) = channel(self.queue_size);
+        let events = self.events.clone();
+        tokio::spawn(deliver_loop(ctx, self, receiver));
+        ForwarderQueue { events, sender }
+    }
+}
+
+/// The sending side of a started `EventForwarder`
+pub(crate) struct ForwarderQueue {
+    events: Option<HashSet<&'static str>>,
+    sender: Sender<String>,
+}
+
+impl ForwarderQueue {
+    /// Queues an event if the forwarder wants it
+    pub fn forward(&self, ctx: &Context, event: &str, data: &Value) {
+        if let Some(events) = &self.events {
+            if !events.contains(event) {
+                return;
+            }
+        }
+        let body = json!({ "t": event, "d": data, "shard_id": ctx.shard_id }).to_string();
+        match self.sender.try_send(body) {
+            Ok(_) => (),
+            Err(TrySendError::Full(_)) if ctx.settings.debug => {
+                print_debug("FORWARDER", format!("Queue is full, dropping {}", event))
+            }
+            Err(_) => (),
+        }
+    }
+}
+
+async fn deliver_loop(ctx: Context, forwarder: EventForwarder, mut receiver: Receiver<String>) {
+    let client = Client::builder().build::<_, Body>(HttpsConnector::new());
+    while let Some(body) = receiver.recv().await {
+        for attempt in 1..=forwarder.max_attempts {
+            let request = match signed_request(&forwarder, &body) {
+                Ok(request) => request,
+                Err(e) => {
+                    print_debug("FORWARDER", format!("Unable to build request: {}", e));
+                    break;
+                }
+            };
+            let status = client
+                .request(request)
+                .await
+                .map(|response| response.status());
+            let retry = match &status {
+                Ok(status) if status.is_success() => break,
+                Ok(status) => *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+                Err(_) => true,
+            };
+            if ctx.settings.debug {
+                print_debug(
+                    "FORWARDER",
+                    format!("Delivery attempt {} failed: {:?}", attempt, status),
+                );
+            }
+            if !retry || attempt == forwarder.max_attempts {
+                break;
+            }
+            let backoff = Duration::from_millis(500 << attempt.min(6)).min(MAX_BACKOFF);
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
+
+fn signed_request(forwarder: &EventForwarder, body: &str) -> Result<Request<Body>, Error> {
+    let mut request = Request::builder()
+        .method(Method::POST)
+        .uri(&forwarder.url)
+        .header("content-type", "application/json");
+    if let Some(secret) = &forwarder.secret {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+            .to_string();
+        request = request.header("X-Discrab-Timestamp", &timestamp).header(
+            "X-Discrab-Signature",
+            format!("sha256={}", sign(secret, &timestamp, body)?),
+        );
+    }
+    Ok(request.body(Body::from(body.to_string()))?)
+}
+
+/// The hex HMAC-SHA256 of `{timestamp}.{body}`
+fn sign(secret: &[u8], timestamp: &str, body: &str) -> Result<String, Error> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).map_err(|e| {
+        Error::new(
+            format!("Invalid forwarder secret: {}", e),
+            ErrorTypes::REQUEST,
+        )
+    })?;
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    Ok(mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
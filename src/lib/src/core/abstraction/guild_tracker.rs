@@ -0,0 +1,183 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use serde_json::Value;
+
+use crate::discord::snowflake::Snowflake;
+
+/// Keeps track of the guilds the bot is in, and which of them are unavailable (because of an outage), from the READY,
+/// GUILD_CREATE, and GUILD_DELETE events. This is what status pages and bot lists usually need.
+///
+/// It also keeps each guild's member count, taken from GUILD_CREATE and adjusted on GUILD_MEMBER_ADD and GUILD_MEMBER_REMOVE,
+/// so the counts stay accurate without requesting every member of every guild.
+///
+/// The guild events need the GUILDS intent, and the member events the privileged GUILD_MEMBERS intent.
+/// Neither is requested by default, see `Bot::intents`.
+///
+/// The tracker is shared, so it can be read while the bot is listening:
+/// ```rust,ignore
+/// let guilds = bot.guild_tracker();
+/// tokio::spawn(async move {
+///     println!("In {} guilds ({} unavailable)", guilds.guild_count(), guilds.unavailable_guilds().len());
+/// });
+/// bot.listen().await;
+/// ```
+pub struct GuildTracker {
+    state: RwLock<GuildTrackerState>,
+}
+
+struct GuildTrackerState {
+    /// guild id -> whether the guild is available
+    guilds: HashMap<Snowflake, bool>,
+    /// guild id -> the number of members in the guild
+    member_counts: HashMap<Snowflake, u64>,
+    /// The number of shards the bot is using, 1 when the bot isn't sharded
+    shard_count: u64,
+    /// The id of the bot's user, known after READY
+    bot_id: Option<Snowflake>,
+}
+
+impl GuildTracker {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(GuildTrackerState {
+                guilds: HashMap::new(),
+                member_counts: HashMap::new(),
+                shard_count: 1,
+                bot_id: None,
+            }),
+        }
+    }
+
+    /// The number of guilds the bot is in, including the unavailable ones
+    pub fn guild_count(&self) -> usize {
+        self.state.read().unwrap().guilds.len()
+    }
+
+    /// The guilds that are currently unavailable because of an outage. Guilds are also unavailable between READY and their GUILD_CREATE.
+    pub fn unavailable_guilds(&self) -> Vec<Snowflake> {
+        self.state
+            .read()
+            .unwrap()
+            .guilds
+            .iter()
+            .filter(|(_, available)| !**available)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// The number of members in a guild. None until the guild's GUILD_CREATE is received.
+    /// Members joining and leaving are only counted with the GUILD_MEMBERS intent, otherwise the count is as of the GUILD_CREATE
+    pub fn member_count(&self, guild_id: Snowflake) -> Option<u64> {
+        self.state
+            .read()
+            .unwrap()
+            .member_counts
+            .get(&guild_id)
+            .copied()
+    }
+
+    /// The number of members in all of the guilds together (users in several guilds are counted for each)
+    pub fn total_member_count(&self) -> u64 {
+        self.state.read().unwrap().member_counts.values().sum()
+    }
+
+    /// The number of shards the bot is using
+    pub fn shard_count(&self) -> u64 {
+        self.state.read().unwrap().shard_count
+    }
+
+    /// The id of the bot's user. None until the bot has received READY
+    pub fn bot_id(&self) -> Option<Snowflake> {
+        self.state.read().unwrap().bot_id
+    }
+
+    /// The shard that receives the events for a guild
+    /// @docs <https://discord.com/developers/docs/topics/gateway#sharding-sharding-formula>
+    pub fn shard_for(&self, guild_id: Snowflake) -> u64 {
+        guild_id.timestamp() % self.shard_count()
+    }
+
+    /// The number of guilds on each shard, indexed by the shard id
+    pub fn shard_distribution(&self) -> Vec<usize> {
+        let state = self.state.read().unwrap();
+        let mut distribution = vec![0; state.shard_count as usize];
+        for id in state.guilds.keys() {
+            distribution[(id.timestamp() % state.shard_count) as usize] += 1;
+        }
+        distribution
+    }
+
+    /// Updates the tracked guilds from a gateway event. Other events are ignored.
+    pub fn handle_event(&self, event: &str, data: &Value) {
+        match event {
+            "READY" => {
+                let mut state = self.state.write().unwrap();
+                state.bot_id = object_id(&data["user"]);
+                if let Some(count) = data["shard"][1].as_u64() {
+                    state.shard_count = count.max(1);
+                }
+                // A new session sends the full guild list of its shard again
+                let shard_count = state.shard_count;
+                let shard_id = data["shard"][0].as_u64().unwrap_or(0);
+                state
+                    .guilds
+                    .retain(|id, _| id.timestamp() % shard_count != shard_id);
+                state
+                    .member_counts
+                    .retain(|id, _| id.timestamp() % shard_count != shard_id);
+                for guild in data["guilds"].as_array().into_iter().flatten() {
+                    if let Some(id) = object_id(guild) {
+                        state.guilds.insert(id, false);
+                    }
+                }
+            }
+            "GUILD_CREATE" => {
+                if let Some(id) = object_id(data) {
+                    let mut state = self.state.write().unwrap();
+                    state.guilds.insert(id, true);
+                    if let Some(member_count) = data["member_count"].as_u64() {
+                        state.member_counts.insert(id, member_count);
+                    }
+                }
+            }
+            "GUILD_DELETE" => {
+                if let Some(id) = object_id(data) {
+                    let mut state = self.state.write().unwrap();
+                    // unavailable is only set when discord is having an outage, otherwise the bot was removed from the guild
+                    if data["unavailable"].as_bool().unwrap_or(false) {
+                        state.guilds.insert(id, false);
+                    } else {
+                        state.guilds.remove(&id);
+                        state.member_counts.remove(&id);
+                    }
+                }
+            }
+            "GUILD_MEMBER_ADD" | "GUILD_MEMBER_REMOVE" => {
+                let guild_id: Option<Snowflake> =
+                    serde_json::from_value(data["guild_id"].clone()).ok();
+                if let Some(guild_id) = guild_id {
+                    let mut state = self.state.write().unwrap();
+                    if let Some(member_count) = state.member_counts.get_mut(&guild_id) {
+                        if event == "GUILD_MEMBER_ADD" {
+                            *member_count += 1;
+                        } else {
+                            *member_count = member_count.saturating_sub(1);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Default for GuildTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads the id of an object in a payload
+fn object_id(object: &Value) -> Option<Snowflake> {
+    serde_json::from_value(object["id"].clone()).ok()
+}
@@ -0,0 +1,120 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::util::logger::print_debug;
+
+use super::{
+    command_group::{register_handler, CommandGroup, GroupHandler},
+    context::Context,
+    event_dispatcher::EventDispatcher,
+    interaction_router::InteractionRouter,
+    observer::handler_ptr,
+    traits::{RegFns, Registerable},
+};
+
+/**
+ * Where the bot's handlers are kept. Handlers can be added and removed through it at any time, even while the bot is listening,
+ * so command modules can be loaded, unloaded or swapped for a new version without restarting the bot.
+ *
+ * Handlers are installed in named `CommandGroup`s, and installing a group with the name of one that is already installed replaces it.
+ * The new handlers are registered before the old ones are removed, so no events or interactions are missed in between.
+ *
+ * ```rust,ignore
+ * let handlers = bot.handlers();
+ * // Later, e.g. in an admin command
+ * handlers.install(ctx.clone(), moderation::commands());
+ * handlers.uninstall("fun");
+ * ```
+ */
+pub struct HandlerRegistry {
+    dispatcher: Arc<EventDispatcher>,
+    router: Arc<InteractionRouter>,
+    /// The handlers of each installed group, by the name of the group
+    groups: Mutex<HashMap<String, Vec<GroupHandler>>>,
+}
+
+impl HandlerRegistry {
+    pub(crate) fn new(dispatcher: Arc<EventDispatcher>, router: Arc<InteractionRouter>) -> Self {
+        Self {
+            dispatcher,
+            router,
+            groups: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers a single handler that isn't part of a group. It can't be uninstalled
+    pub fn register(&self, ctx: Context, handler: Arc<impl Registerable + RegFns>) {
+        register_handler(&handler, &self.dispatcher, ctx, self.router.clone());
+    }
+
+    /**
+     * Registers every handler of a group, replacing the group with the same name if there is one.
+     * Commands are created or updated with discord when they are registered, the same as with `Bot::register`.
+     * @param group The handlers to install
+     */
+    pub fn install(&self, ctx: Context, group: CommandGroup) {
+        let name = group.name().to_string();
+        if ctx.settings.debug {
+            print_debug(
+                "HANDLERS",
+                format!(
+                    "Installing group {}: {}",
+                    name,
+                    group.handler_names().join(", ")
+                ),
+            );
+        }
+        let mut groups = self.groups.lock().unwrap();
+        let handlers = group.register_all(&self.dispatcher, ctx, self.router.clone());
+        if let Some(old) = groups.insert(name.clone(), handlers) {
+            // A handler can be in both versions of the group, and then it has to stay
+            let kept = &groups[&name];
+            let replaced: Vec<GroupHandler> = old
+                .into_iter()
+                .filter(|handler| {
+                    !kept
+                        .iter()
+                        .any(|new| handler_ptr(new) == handler_ptr(handler))
+                })
+                .collect();
+            self.remove_handlers(&replaced);
+        }
+    }
+
+    /**
+     * Removes every handler of a group, so that no more events or interactions are sent to them.
+     * The group's commands stay registered with discord, so install a group with the same commands again to handle them.
+     * @param name The name of the group
+     * @return Whether the group was installed
+     */
+    pub fn uninstall(&self, name: &str) -> bool {
+        let removed = self.groups.lock().unwrap().remove(name);
+        match removed {
+            Some(handlers) => {
+                self.remove_handlers(&handlers);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a group with the name is installed
+    pub fn is_installed(&self, name: &str) -> bool {
+        self.groups.lock().unwrap().contains_key(name)
+    }
+
+    /// The names of the installed groups
+    pub fn installed(&self) -> Vec<String> {
+        self.groups.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn remove_handlers(&self, handlers: &[GroupHandler]) {
+        for handler in handlers {
+            let ptr = handler_ptr(handler);
+            self.dispatcher.unsubscribe(ptr);
+            self.router.unregister(ptr);
+        }
+    }
+}
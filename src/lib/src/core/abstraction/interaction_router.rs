@@ -1,42 +1,151 @@
-use std::{collections::HashMap, sync::{Arc, Mutex}};
+use std::{collections::HashMap, sync::{Arc, Mutex, Weak}, time::{Duration, Instant}};
+
+use crossbeam_channel::RecvTimeoutError;
 
 use crate::{
     api::ApplicationCommand,
     api::{Snowflake, ApplicationCommandOption},
-    core::interactions::{interaction_event::InteractionCtx, typing::Interaction},
+    core::interactions::{handler::events::dispatch_payloads::HandlerTimeout, interaction_event::InteractionCtx, typing::{Interaction, InteractionMessageBuilder, InteractionType}},
     discord::interactions::application_command::{CreateApplicationCommand, EditApplicationCommand},
     util::{logger::print_debug, common::options_equal},
     CommandHandler, Context, Registerable,
 };
 
-use super::traits::__InternalEventHandler;
+use super::{command_registry::CommandRegistry, event_dispatcher::EventDispatcher, observer::handler_ptr, traits::__InternalEventHandler, user_throttle::UserThrottle};
+
+type InteractionHandler = Arc<dyn __InternalEventHandler<InteractionCtx>>;
+
+/// Handlers that are found by the custom id of a component or modal
+#[derive(Default)]
+struct CustomIdHandlers {
+    /// Handlers by the exact custom id they handle
+    exact: HashMap<String, InteractionHandler>,
+    /// Handlers for custom id patterns (containing `*`)
+    patterns: Vec<(String, InteractionHandler)>,
+}
+
+impl CustomIdHandlers {
+    fn register(&mut self, custom_id: &str, handler: InteractionHandler) {
+        if custom_id.contains('*') {
+            self.patterns.push((custom_id.to_string(), handler));
+        } else {
+            self.exact.insert(custom_id.to_string(), handler);
+        }
+    }
+
+    /// Removes a handler from every custom id it handles
+    fn remove(&mut self, handler: *const ()) -> bool {
+        let count = self.exact.len() + self.patterns.len();
+        self.exact.retain(|_, h| handler_ptr(h) != handler);
+        self.patterns.retain(|(_, h)| handler_ptr(h) != handler);
+        self.exact.len() + self.patterns.len() != count
+    }
+
+    /// Exact custom ids win over patterns, and the most specific pattern (the one with the most characters that aren't `*`) wins over the others
+    fn find(&self, custom_id: &str) -> Option<InteractionHandler> {
+        if let Some(handler) = self.exact.get(custom_id) {
+            return Some(handler.clone());
+        }
+        self.patterns
+            .iter()
+            .filter(|(pattern, _)| pattern_matches(pattern, custom_id))
+            .max_by_key(|(pattern, _)| pattern.chars().filter(|c| *c != '*').count())
+            .map(|(_, handler)| handler.clone())
+    }
+}
 
 /// This is used to dispatch interaction events to the correct handler
 pub struct InteractionRouter {
-    pub commands: Mutex<HashMap<Snowflake, Arc<dyn __InternalEventHandler<InteractionCtx>>>>,
+    pub commands: Mutex<HashMap<Snowflake, InteractionHandler>>,
+    /// The ids of the registered commands by name
+    pub registry: Arc<CommandRegistry>,
+    /// Message component handlers by custom id
+    components: Mutex<CustomIdHandlers>,
+    /// Modal submit handlers by custom id
+    modals: Mutex<CustomIdHandlers>,
+    /// Autocomplete handlers by the name of their command
+    autocompletes: Mutex<HashMap<String, InteractionHandler>>,
+    /// Limits how often each user can use interactions, if set
+    throttle: Mutex<Option<Arc<UserThrottle>>>,
+    /// How long a handler can run before the user is told it timed out, if set
+    timeout: Mutex<Option<Duration>>,
+    /// Timeouts by command name that replace the default one. None turns the timeout off for the command
+    command_timeouts: Mutex<HashMap<String, Option<Duration>>>,
+    /// Where the HANDLER_TIMEOUT event is sent. Weak, since the dispatcher holds the router
+    dispatcher: Mutex<Weak<EventDispatcher>>,
 }
 
 impl __InternalEventHandler<Interaction> for InteractionRouter {
     /// Handles the incomming interaction from the event dispatcher, and then forawrds it to the correct handler
     fn handler(&self, ctx: Context, interaction: Interaction) {
+        // Autocomplete interactions have the id of their command, but are routed by name to a separate handler
+        if matches!(interaction.type_, InteractionType::ApplicationCommandAutocomplete) {
+            let name = interaction
+                .data
+                .as_ref()
+                .map(|data| data.name.clone())
+                .unwrap_or_default();
+            let handler = self.autocompletes.lock().unwrap().get(&name).cloned();
+            match handler {
+                Some(handler) => handler.handler(
+                    ctx.clone(),
+                    InteractionCtx::from_interaction(ctx, interaction),
+                ),
+                None if ctx.settings.debug => print_debug(
+                    "INTERACTIONS",
+                    format!("No autocomplete handler for command {}", name),
+                ),
+                None => (),
+            }
+            return;
+        }
+
+        // Autocomplete is left out of the throttle above, since it is sent for every key the user types
+        if self.is_throttled(&ctx, &interaction) {
+            return;
+        }
+
+        // Components and modal submits are routed by their custom id
+        let custom_id_handlers = match interaction.type_ {
+            InteractionType::MessageComponent => Some(&self.components),
+            InteractionType::ModalSubmit => Some(&self.modals),
+            _ => None,
+        };
+        if let Some(handlers) = custom_id_handlers {
+            let custom_id = interaction
+                .data
+                .as_ref()
+                .and_then(|data| data.custom_id.clone())
+                .unwrap_or_default();
+            // The lock is released before calling the handler, so that the handler can register more handlers
+            let handler = handlers.lock().unwrap().find(&custom_id);
+            if let Some(handler) = handler {
+                let timeout = self.timeout_for(None);
+                self.run(handler, ctx, interaction, custom_id, timeout);
+                return;
+            }
+        }
+
         // Get the id of the interaction
         let id = interaction
             .data
             .as_ref()
             .expect("Interaction doesn't have ID!")
             .id;
-        // Get the handler and then call it
-        let _commands = self.commands.lock().unwrap();
-        let command = _commands.get(&id);
+        // Get the handler and then call it. The lock is released first, so that the handler can register or remove handlers
+        let command = self.commands.lock().unwrap().get(&id).cloned();
         if let Some(command) = command {
-            command.handler(
-                ctx.clone(),
-                InteractionCtx::from_interaction(ctx, interaction),
-            );
+            let name = interaction
+                .data
+                .as_ref()
+                .map(|data| data.name.clone())
+                .unwrap_or_default();
+            let timeout = self.timeout_for(Some(&name));
+            self.run(command, ctx, interaction, name, timeout);
         } else if ctx.settings.debug {
             print_debug(
                 "INTERACTIONS",
-                format!("Unable to route interaction {}, interactions: {:?}", id, _commands.keys()),
+                format!("Unable to route interaction {}, interactions: {:?}", id, self.commands.lock().unwrap().keys()),
             );
         }
     }
@@ -47,7 +156,187 @@ impl InteractionRouter {
     pub fn new() -> Self {
         Self {
             commands: Mutex::new(HashMap::new()),
+            registry: Arc::new(CommandRegistry::new()),
+            components: Mutex::new(CustomIdHandlers::default()),
+            modals: Mutex::new(CustomIdHandlers::default()),
+            autocompletes: Mutex::new(HashMap::new()),
+            throttle: Mutex::new(None),
+            timeout: Mutex::new(None),
+            command_timeouts: Mutex::new(HashMap::new()),
+            dispatcher: Mutex::new(Weak::new()),
+        }
+    }
+
+    /// Sets how long command, component, and modal handlers can run before the user is told they timed out. Autocomplete handlers aren't timed
+    /// @param timeout The timeout, or None to let handlers run for as long as they take
+    pub fn set_timeout(&self, timeout: Option<Duration>) {
+        *self.timeout.lock().unwrap() = timeout;
+    }
+
+    /// Replaces the timeout for one command, e.g. to give a slow command more time
+    /// @param command The name of the command
+    /// @param timeout The timeout, or None to let the command run for as long as it takes
+    pub fn set_command_timeout(&self, command: &str, timeout: Option<Duration>) {
+        self.command_timeouts
+            .lock()
+            .unwrap()
+            .insert(command.to_string(), timeout);
+    }
+
+    /// Sets the dispatcher that HANDLER_TIMEOUT events are sent to. Called by `Bot::listen`
+    pub fn set_dispatcher(&self, dispatcher: &Arc<EventDispatcher>) {
+        *self.dispatcher.lock().unwrap() = Arc::downgrade(dispatcher);
+    }
+
+    /// The timeout of a handler
+    /// @param command The name of the command, or None for components and modals
+    fn timeout_for(&self, command: Option<&str>) -> Option<Duration> {
+        if let Some(timeout) = command.and_then(|command| self.command_timeouts.lock().unwrap().get(command).copied()) {
+            return timeout;
         }
+        *self.timeout.lock().unwrap()
+    }
+
+    /**
+     * Calls a handler. With a timeout, the handler runs on its own thread, and if it doesn't finish in time the user is told
+     * and a HANDLER_TIMEOUT event is sent, so that a hung handler doesn't hold up the interactions that come after it.
+     * @param name The name of the command, or the custom id of the component or modal
+     */
+    fn run(&self, handler: InteractionHandler, ctx: Context, interaction: Interaction, name: String, timeout: Option<Duration>) {
+        let Some(timeout) = timeout else {
+            handler.handler(ctx.clone(), InteractionCtx::from_interaction(ctx, interaction));
+            return;
+        };
+
+        let started = Instant::now();
+        let (done, finished) = crossbeam_channel::bounded::<()>(1);
+        let ictx = InteractionCtx::from_interaction(ctx.clone(), interaction.clone());
+        let handler_ctx = ctx.clone();
+        // The handler makes requests, which need the tokio runtime that the bot is running on
+        let runtime = tokio::runtime::Handle::try_current().ok();
+        std::thread::spawn(move || {
+            let _guard = runtime.as_ref().map(|runtime| runtime.enter());
+            handler.handler(handler_ctx, ictx);
+            let _ = done.send(());
+        });
+        // A handler that panicked drops the sender, which counts as finished
+        if finished.recv_timeout(timeout) != Err(RecvTimeoutError::Timeout) {
+            return;
+        }
+
+        let elapsed = started.elapsed();
+        if ctx.settings.debug {
+            print_debug(
+                "INTERACTIONS",
+                format!("Handler for {} timed out after {:?}", name, elapsed),
+            );
+        }
+        self.tell_timed_out(&ctx, &interaction);
+        let dispatcher = self.dispatcher.lock().unwrap().upgrade();
+        if let Some(dispatcher) = dispatcher {
+            dispatcher.HandlerTimeout.notify(
+                ctx,
+                HandlerTimeout {
+                    name,
+                    interaction_id: interaction.id,
+                    guild_id: interaction
+                        .guild_id
+                        .as_deref()
+                        .and_then(|id| id.parse::<u64>().ok())
+                        .map(Snowflake::from),
+                    channel_id: interaction.channel_id,
+                    user_id: interaction
+                        .member
+                        .as_ref()
+                        .and_then(|member| member.user.as_ref())
+                        .or(interaction.user.as_ref())
+                        .map(|user| user.id),
+                    elapsed_ms: elapsed.as_millis() as u64,
+                    timeout_ms: timeout.as_millis() as u64,
+                },
+            );
+        }
+    }
+
+    /// Responds to an interaction whose handler timed out, or replaces the response if the handler deferred or already responded
+    fn tell_timed_out(&self, ctx: &Context, interaction: &Interaction) {
+        let notice = "⌛ This is taking too long. Please try again later.";
+        let replied = async_std::task::block_on(interaction.reply(
+            ctx.clone(),
+            InteractionMessageBuilder::new().content(notice).ephemeral(),
+        ));
+        if replied.is_err() {
+            let edited = async_std::task::block_on(
+                interaction.edit_response(ctx.clone(), InteractionMessageBuilder::new().content(notice)),
+            );
+            if let Err(e) = edited {
+                if ctx.settings.debug {
+                    print_debug("INTERACTIONS", format!("Unable to tell the user that the handler timed out: {}", e));
+                }
+            }
+        }
+    }
+
+    /// Limits how often each user can use interactions. Replaces the previous throttle
+    /// @param throttle The limit, or None to turn the throttle off
+    pub fn set_throttle(&self, throttle: Option<UserThrottle>) {
+        *self.throttle.lock().unwrap() = throttle.map(Arc::new);
+    }
+
+    /// Checks the user of the interaction against the throttle, and tells them to slow down if they are over it
+    /// @return true if the interaction shouldn't be handled
+    fn is_throttled(&self, ctx: &Context, interaction: &Interaction) -> bool {
+        let throttle = match self.throttle.lock().unwrap().clone() {
+            Some(throttle) => throttle,
+            None => return false,
+        };
+        let user_id = match interaction
+            .member
+            .as_ref()
+            .and_then(|member| member.user.as_ref())
+            .or(interaction.user.as_ref())
+        {
+            Some(user) => user.id,
+            None => return false,
+        };
+        let wait = match throttle.check(user_id) {
+            Some(wait) => wait,
+            None => return false,
+        };
+
+        let message = InteractionMessageBuilder::new()
+            .content(&format!(
+                "⏳ Slow down! You can use this again in {} seconds.",
+                wait.as_secs_f64().ceil().max(1f64)
+            ))
+            .ephemeral();
+        if let Err(e) = async_std::task::block_on(interaction.reply(ctx.clone(), message)) {
+            if ctx.settings.debug {
+                print_debug("INTERACTIONS", format!("Unable to tell user {} to slow down: {}", user_id, e));
+            }
+        }
+        true
+    }
+
+    /// Registers a handler for the autocomplete options of a command
+    /// @param command The name of the command
+    pub fn register_autocomplete(&self, command: &str, handler: InteractionHandler) {
+        self.autocompletes
+            .lock()
+            .unwrap()
+            .insert(command.to_string(), handler);
+    }
+
+    /// Registers a handler for message components
+    /// @param custom_id The exact custom id of the components, or a pattern where `*` matches anything (e.g. `"confirm_*"`)
+    pub fn register_component(&self, custom_id: &str, handler: InteractionHandler) {
+        self.components.lock().unwrap().register(custom_id, handler);
+    }
+
+    /// Registers a handler for modal submits
+    /// @param custom_id The exact custom id of the modals, or a pattern where `*` matches anything (e.g. `"report_*"`)
+    pub fn register_modal(&self, custom_id: &str, handler: InteractionHandler) {
+        self.modals.lock().unwrap().register(custom_id, handler);
     }
 
     /// Registers a new interaction handler
@@ -59,6 +348,31 @@ impl InteractionRouter {
         self.commands.lock().unwrap().insert(id, cmd);
     }
 
+    /**
+     * Removes a command, component, modal, or autocomplete handler, so that its interactions aren't routed to it anymore.
+     * The command itself stays registered with discord, and is routed to the next handler registered for it.
+     * @param handler Points to the handler, see `handler_ptr`
+     * @return Whether the handler was registered
+     */
+    pub fn unregister(&self, handler: *const ()) -> bool {
+        let mut found = false;
+        {
+            let mut commands = self.commands.lock().unwrap();
+            let count = commands.len();
+            commands.retain(|_, h| handler_ptr(h) != handler);
+            found |= commands.len() != count;
+        }
+        found |= self.components.lock().unwrap().remove(handler);
+        found |= self.modals.lock().unwrap().remove(handler);
+        {
+            let mut autocompletes = self.autocompletes.lock().unwrap();
+            let count = autocompletes.len();
+            autocompletes.retain(|_, h| handler_ptr(h) != handler);
+            found |= autocompletes.len() != count;
+        }
+        found
+    }
+
     /// Gets the id of the interaction handler if it exists. If it doesn't exist, it registers a new one and returns the id
     pub async fn get_id_or_register<T: CommandHandler + Registerable>(ctx: Context, handler: Arc<T>) -> Snowflake {
         if ctx.settings.debug {
@@ -137,7 +451,8 @@ impl InteractionRouter {
                         EditApplicationCommand {
                             name: Some(T::NAME.to_string()),
                             description: Some(T::DESCRIPTION.to_string()),
-                            options,
+                            // Unset fields aren't changed, so send an empty list to remove the options
+                            options: Some(options.unwrap_or_default()),
                             default_permission: Some(true), // TODO make this user changeable
                             default_member_permissions: None, // TODO replace default_permission with this
                             dm_permission: None, // TODO make this user changeable
@@ -186,3 +501,26 @@ impl Default for InteractionRouter {
         Self::new()
     }
 }
+
+/// Checks a custom id against a pattern, where `*` matches any number of characters
+fn pattern_matches(pattern: &str, custom_id: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // The first part has to be at the start, and the last part at the end
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match custom_id.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
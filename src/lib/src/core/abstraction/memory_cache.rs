@@ -0,0 +1,466 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use super::cache_backend::{CacheKey, CacheResource};
+use crate::discord::{
+    resources::{
+        channel::Channel,
+        guild::{guild_member::GuildMember, guild_object::Guild, role::Role},
+        user::User,
+    },
+    snowflake::Snowflake,
+};
+
+/**
+ * What the cache keeps, set with `bot.settings().set_cache(...)`.
+ * Everything is cached by default. Bots that are short on memory can turn off the resources they don't need,
+ * members especially, since there can be hundreds of thousands of them in big guilds.
+ *
+ * The cache is filled in from gateway events, so it only has what the bot's intents let through (see `Bot::intents`):
+ * guilds, channels and roles need GUILDS, and members need the privileged GUILD_MEMBERS intent.
+ *
+ * ```rust,ignore
+ * bot.settings().set_cache(CacheConfig {
+ *     members: false,
+ *     ..CacheConfig::default()
+ * });
+ * ```
+ */
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+    pub guilds: bool,
+    pub channels: bool,
+    pub members: bool,
+    pub roles: bool,
+    pub users: bool,
+    /// The most members kept for a guild. Once a guild has this many, new members aren't cached (but the cached ones are still updated)
+    pub max_members_per_guild: Option<usize>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            guilds: true,
+            channels: true,
+            members: true,
+            roles: true,
+            users: true,
+            max_members_per_guild: None,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Caches nothing
+    pub fn disabled() -> Self {
+        Self {
+            guilds: false,
+            channels: false,
+            members: false,
+            roles: false,
+            users: false,
+            max_members_per_guild: None,
+        }
+    }
+}
+
+/// The number of resources in a `Cache`, see `Cache::sizes`
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct CacheSizes {
+    pub guilds: usize,
+    pub channels: usize,
+    /// The members of every guild together
+    pub members: usize,
+    pub users: usize,
+}
+
+/**
+ * The guilds, channels, members, roles and users the bot has seen, kept up to date from the gateway events.
+ * Get it with `ctx.cache()`. The resources' `fetch` methods read from it before making a request.
+ *
+ * ```rust,ignore
+ * if let Some(guild) = ctx.cache().guild(guild_id) {
+ *     println!("{} has {} roles", guild.name, guild.roles.len());
+ * }
+ * ```
+ */
+#[derive(Default)]
+pub struct Cache {
+    /// The guilds, without their channels, members, presences or voice states (those are kept separately or not at all)
+    guilds: RwLock<HashMap<Snowflake, Guild>>,
+    /// The channels and threads of every guild
+    channels: RwLock<HashMap<Snowflake, Channel>>,
+    /// guild id to user id to member
+    members: RwLock<HashMap<Snowflake, HashMap<Snowflake, GuildMember>>>,
+    users: RwLock<HashMap<Snowflake, User>>,
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets a guild. Its `channels` and `members` aren't filled in, use `guild_channels` and `members` for them
+    pub fn guild(&self, guild_id: Snowflake) -> Option<Guild> {
+        self.guilds.read().unwrap().get(&guild_id).cloned()
+    }
+
+    /// The ids of all of the cached guilds
+    pub fn guild_ids(&self) -> Vec<Snowflake> {
+        self.guilds.read().unwrap().keys().copied().collect()
+    }
+
+    /// Gets a channel or thread
+    pub fn channel(&self, channel_id: Snowflake) -> Option<Channel> {
+        self.channels.read().unwrap().get(&channel_id).cloned()
+    }
+
+    /// The channels and threads of a guild
+    pub fn guild_channels(&self, guild_id: Snowflake) -> Vec<Channel> {
+        self.channels
+            .read()
+            .unwrap()
+            .values()
+            .filter(|channel| channel.guild_id == Some(guild_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Gets a member of a guild
+    pub fn member(&self, guild_id: Snowflake, user_id: Snowflake) -> Option<GuildMember> {
+        self.members
+            .read()
+            .unwrap()
+            .get(&guild_id)?
+            .get(&user_id)
+            .cloned()
+    }
+
+    /// The cached members of a guild. Compare with the guild's `member_count` to see if they are all there
+    pub fn members(&self, guild_id: Snowflake) -> Vec<GuildMember> {
+        self.members
+            .read()
+            .unwrap()
+            .get(&guild_id)
+            .map(|members| members.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Gets a role of a guild
+    pub fn role(&self, guild_id: Snowflake, role_id: Snowflake) -> Option<Role> {
+        self.guilds
+            .read()
+            .unwrap()
+            .get(&guild_id)?
+            .roles
+            .iter()
+            .find(|role| role.id == role_id)
+            .cloned()
+    }
+
+    /// The roles of a guild
+    pub fn roles(&self, guild_id: Snowflake) -> Vec<Role> {
+        self.guilds
+            .read()
+            .unwrap()
+            .get(&guild_id)
+            .map(|guild| guild.roles.clone())
+            .unwrap_or_default()
+    }
+
+    /// Gets a user that the bot has seen in a guild
+    pub fn user(&self, user_id: Snowflake) -> Option<User> {
+        self.users.read().unwrap().get(&user_id).cloned()
+    }
+
+    /// How many of each resource are cached
+    pub fn sizes(&self) -> CacheSizes {
+        CacheSizes {
+            guilds: self.guilds.read().unwrap().len(),
+            channels: self.channels.read().unwrap().len(),
+            members: self.members.read().unwrap().values().map(HashMap::len).sum(),
+            users: self.users.read().unwrap().len(),
+        }
+    }
+
+    /// Empties the cache
+    pub fn clear(&self) {
+        self.guilds.write().unwrap().clear();
+        self.channels.write().unwrap().clear();
+        self.members.write().unwrap().clear();
+        self.users.write().unwrap().clear();
+    }
+
+    // Writes of single resources, for the `CacheBackend` implementation
+
+    pub(super) fn store_guild(&self, guild: Guild) {
+        self.guilds.write().unwrap().insert(guild.id, guild);
+    }
+
+    pub(super) fn store_channel(&self, channel: Channel) {
+        self.channels.write().unwrap().insert(channel.id, channel);
+    }
+
+    pub(super) fn store_member(
+        &self,
+        guild_id: Snowflake,
+        user_id: Snowflake,
+        member: GuildMember,
+    ) {
+        self.members
+            .write()
+            .unwrap()
+            .entry(guild_id)
+            .or_default()
+            .insert(user_id, member);
+    }
+
+    pub(super) fn store_user(&self, user: User) {
+        self.users.write().unwrap().insert(user.id, user);
+    }
+
+    pub(super) fn remove(&self, key: &CacheKey) {
+        match (key.resource, key.guild_id) {
+            (CacheResource::Guild, _) => {
+                self.guilds.write().unwrap().remove(&key.id);
+            }
+            (CacheResource::Channel, _) => {
+                self.channels.write().unwrap().remove(&key.id);
+            }
+            (CacheResource::Member, Some(guild_id)) => {
+                if let Some(members) = self.members.write().unwrap().get_mut(&guild_id) {
+                    members.remove(&key.id);
+                }
+            }
+            (CacheResource::Member, None) => {}
+            (CacheResource::User, _) => {
+                self.users.write().unwrap().remove(&key.id);
+            }
+        }
+    }
+
+    /**
+     * Updates the cache from a gateway event. The bot calls this for every event before it is dispatched,
+     * so handlers already see the new state.
+     * @param config What to cache
+     * @param event The name of the event, e.g. `GUILD_CREATE`
+     * @param data The data of the event
+     */
+    pub fn update(&self, config: &CacheConfig, event: &str, data: &Value) {
+        match event {
+            "GUILD_CREATE" => self.guild_create(config, data),
+            "GUILD_UPDATE" if config.guilds => {
+                let mut guilds = self.guilds.write().unwrap();
+                if let Some(guild) = data["id"]
+                    .as_str()
+                    .and_then(|id| guilds.get_mut(&parse_id(id)))
+                {
+                    merge(guild, data);
+                    if !config.roles {
+                        guild.roles.clear();
+                    }
+                }
+            }
+            "GUILD_DELETE" => {
+                let Some(guild_id) = data["id"].as_str().map(parse_id) else {
+                    return;
+                };
+                self.guilds.write().unwrap().remove(&guild_id);
+                self.members.write().unwrap().remove(&guild_id);
+                self.channels
+                    .write()
+                    .unwrap()
+                    .retain(|_, channel| channel.guild_id != Some(guild_id));
+            }
+            "CHANNEL_CREATE" | "CHANNEL_UPDATE" | "THREAD_CREATE" | "THREAD_UPDATE"
+                if config.channels =>
+            {
+                if let Ok(channel) = serde_json::from_value::<Channel>(data.clone()) {
+                    self.channels.write().unwrap().insert(channel.id, channel);
+                }
+            }
+            "CHANNEL_DELETE" | "THREAD_DELETE" => {
+                if let Some(channel_id) = data["id"].as_str().map(parse_id) {
+                    self.channels.write().unwrap().remove(&channel_id);
+                }
+            }
+            "GUILD_MEMBER_ADD" => {
+                if let Some(guild_id) = data["guild_id"].as_str().map(parse_id) {
+                    self.insert_members(config, guild_id, std::slice::from_ref(data));
+                    self.change_member_count(guild_id, 1);
+                }
+            }
+            "GUILD_MEMBER_UPDATE" => self.member_update(config, data),
+            "GUILD_MEMBER_REMOVE" => {
+                let (Some(guild_id), Some(user_id)) = (
+                    data["guild_id"].as_str().map(parse_id),
+                    data["user"]["id"].as_str().map(parse_id),
+                ) else {
+                    return;
+                };
+                if let Some(members) = self.members.write().unwrap().get_mut(&guild_id) {
+                    members.remove(&user_id);
+                }
+                self.change_member_count(guild_id, -1);
+            }
+            "GUILD_MEMBERS_CHUNK" => {
+                if let (Some(guild_id), Some(members)) = (
+                    data["guild_id"].as_str().map(parse_id),
+                    data["members"].as_array(),
+                ) {
+                    self.insert_members(config, guild_id, members);
+                }
+            }
+            "GUILD_ROLE_CREATE" | "GUILD_ROLE_UPDATE" => {
+                if !config.roles {
+                    return;
+                }
+                let (Some(guild_id), Ok(role)) = (
+                    data["guild_id"].as_str().map(parse_id),
+                    serde_json::from_value::<Role>(data["role"].clone()),
+                ) else {
+                    return;
+                };
+                if let Some(guild) = self.guilds.write().unwrap().get_mut(&guild_id) {
+                    guild.roles.retain(|r| r.id != role.id);
+                    guild.roles.push(role);
+                }
+            }
+            "GUILD_ROLE_DELETE" => {
+                let (Some(guild_id), Some(role_id)) = (
+                    data["guild_id"].as_str().map(parse_id),
+                    data["role_id"].as_str().map(parse_id),
+                ) else {
+                    return;
+                };
+                if let Some(guild) = self.guilds.write().unwrap().get_mut(&guild_id) {
+                    guild.roles.retain(|role| role.id != role_id);
+                }
+            }
+            "USER_UPDATE" => self.insert_user(config, data),
+            _ => {}
+        }
+    }
+
+    fn guild_create(&self, config: &CacheConfig, data: &Value) {
+        let Ok(mut guild) = serde_json::from_value::<Guild>(data.clone()) else {
+            return;
+        };
+        // An unavailable guild only has an id, which was already parsed into the defaults
+        if guild.unavailable {
+            return;
+        }
+
+        let channels = guild.channels.take().unwrap_or_default();
+        let threads = guild.threads.take().unwrap_or_default();
+        if config.channels {
+            let mut cached = self.channels.write().unwrap();
+            for mut channel in channels.into_iter().chain(threads) {
+                // The channels in GUILD_CREATE don't have a guild id
+                channel.guild_id = Some(guild.id);
+                cached.insert(channel.id, channel);
+            }
+        }
+
+        if let Some(members) = data["members"].as_array() {
+            self.insert_members(config, guild.id, members);
+        }
+        guild.members = None;
+        guild.presences = None;
+        guild.voice_states = None;
+        if !config.roles {
+            guild.roles.clear();
+        }
+        if config.guilds {
+            self.guilds.write().unwrap().insert(guild.id, guild);
+        }
+    }
+
+    fn member_update(&self, config: &CacheConfig, data: &Value) {
+        let (Some(guild_id), Some(user_id)) = (
+            data["guild_id"].as_str().map(parse_id),
+            data["user"]["id"].as_str().map(parse_id),
+        ) else {
+            return;
+        };
+        self.insert_user(config, &data["user"]);
+        if !config.members {
+            return;
+        }
+        let mut members = self.members.write().unwrap();
+        match members
+            .get_mut(&guild_id)
+            .and_then(|members| members.get_mut(&user_id))
+        {
+            Some(member) => merge(member, data),
+            None => {
+                drop(members);
+                self.insert_members(config, guild_id, std::slice::from_ref(data));
+            }
+        }
+    }
+
+    fn insert_members(&self, config: &CacheConfig, guild_id: Snowflake, members: &[Value]) {
+        for member in members {
+            self.insert_user(config, &member["user"]);
+        }
+        if !config.members {
+            return;
+        }
+        let mut cached = self.members.write().unwrap();
+        let cached = cached.entry(guild_id).or_default();
+        for member in members {
+            let Ok(member) = serde_json::from_value::<GuildMember>(member.clone()) else {
+                continue;
+            };
+            let Some(user_id) = member.user.as_ref().map(|user| user.id) else {
+                continue;
+            };
+            let full = config
+                .max_members_per_guild
+                .is_some_and(|max| cached.len() >= max);
+            if full && !cached.contains_key(&user_id) {
+                continue;
+            }
+            cached.insert(user_id, member);
+        }
+    }
+
+    fn insert_user(&self, config: &CacheConfig, user: &Value) {
+        if !config.users || user.is_null() {
+            return;
+        }
+        if let Ok(user) = serde_json::from_value::<User>(user.clone()) {
+            self.users.write().unwrap().insert(user.id, user);
+        }
+    }
+
+    fn change_member_count(&self, guild_id: Snowflake, change: i64) {
+        if let Some(guild) = self.guilds.write().unwrap().get_mut(&guild_id) {
+            if let Some(count) = guild.member_count.as_mut() {
+                *count = (*count + change).max(0);
+            }
+        }
+    }
+}
+
+fn parse_id(id: &str) -> Snowflake {
+    Snowflake::from(id.parse::<u64>().unwrap_or_default())
+}
+
+/// Applies the fields of a partial update (like GUILD_UPDATE) to a cached object, keeping the fields the update doesn't have
+fn merge<T: Serialize + DeserializeOwned>(cached: &mut T, update: &Value) {
+    let (Ok(mut value), Some(fields)) = (serde_json::to_value(&*cached), update.as_object()) else {
+        return;
+    };
+    if let Some(object) = value.as_object_mut() {
+        for (key, field) in fields {
+            object.insert(key.clone(), field.clone());
+        }
+    }
+    if let Ok(merged) = serde_json::from_value(value) {
+        *cached = merged;
+    }
+}
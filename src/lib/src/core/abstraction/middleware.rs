@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use super::context::Context;
+
+/// An event on its way to the handlers, as it was received from the gateway
+pub struct GatewayEvent {
+    /// The name of the event, e.g. `MESSAGE_CREATE`
+    pub name: String,
+    /// The data of the event, before it is parsed into the handler's type
+    pub data: Value,
+}
+
+/// What happens to an event after a middleware saw it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    /// Pass the event on to the next middleware, and then the handlers
+    Continue,
+    /// Drop the event. No handlers are called, and neither is `after`
+    Cancel,
+}
+
+/**
+ * Runs around every event the dispatcher routes, for things that apply to all handlers: logging, metrics, blacklists, ...
+ * Middleware runs in the order it was added, on the listen loop, so it should be quick.
+ *
+ * The cache is updated before the middleware runs, so cancelling an event doesn't make the cache miss it.
+ * Add it with `Bot::add_layer`, or use `Bot::add_middleware` with a closure when only `before` is needed.
+ */
+pub trait EventMiddleware: Send + Sync + 'static {
+    /**
+     * Called before the event is parsed and passed to the handlers
+     * @param event The event. The data can be changed, and so can the name to route it as another event
+     * @return Whether the event should still be dispatched
+     */
+    fn before(&self, event: &mut GatewayEvent, ctx: &Context) -> Flow;
+
+    /**
+     * Called once the handlers of the event returned
+     * @param elapsed How long the handlers took
+     */
+    fn after(&self, _event: &GatewayEvent, _ctx: &Context, _elapsed: Duration) {}
+}
+
+impl<F> EventMiddleware for F
+where
+    F: Fn(&mut GatewayEvent, &Context) -> Flow + Send + Sync + 'static,
+{
+    fn before(&self, event: &mut GatewayEvent, ctx: &Context) -> Flow {
+        self(event, ctx)
+    }
+}
@@ -1,8 +1,52 @@
+#[cfg(feature = "announcements")]
+pub mod announcements;
+#[cfg(feature = "gateway")]
 pub mod bot;
+#[cfg(feature = "rest")]
+pub mod cache;
+pub mod cache_backend;
+#[cfg(feature = "gateway")]
+pub mod command_group;
+#[cfg(feature = "gateway")]
+pub mod command_registry;
 pub mod context;
+#[cfg(feature = "gateway")]
+pub mod derived_events;
 
 pub mod traits;
+#[cfg(feature = "gateway")]
+pub mod diagnostics;
+#[cfg(feature = "gateway")]
 pub mod event_dispatcher;
+#[cfg(feature = "event-forwarder")]
+pub mod event_forwarder;
+#[cfg(feature = "gateway")]
+pub mod guild_tracker;
+#[cfg(feature = "gateway")]
+pub mod handler_registry;
+#[cfg(feature = "gateway")]
 pub mod interaction_router;
+pub mod memory_cache;
+#[cfg(feature = "gateway")]
+pub mod middleware;
+#[cfg(feature = "gateway")]
 pub mod observer;
-pub mod option_builder;
\ No newline at end of file
+#[cfg(feature = "rest")]
+pub mod outbox;
+#[cfg(all(feature = "gateway", feature = "rest"))]
+pub mod prefix_commands;
+#[cfg(feature = "cache-sqlite")]
+pub mod sqlite_cache;
+#[cfg(feature = "gateway")]
+pub mod shutdown;
+#[cfg(all(feature = "gateway", feature = "rest"))]
+pub mod starboard;
+#[cfg(feature = "gateway")]
+pub mod stats_poster;
+pub mod option_builder;
+#[cfg(feature = "gateway")]
+pub mod option_rules;
+#[cfg(feature = "gateway")]
+pub mod user_throttle;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm_plugin;
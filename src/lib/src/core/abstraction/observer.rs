@@ -1,4 +1,4 @@
-use std::{sync::Arc, panic::{self, RefUnwindSafe, UnwindSafe}};
+use std::{sync::{Arc, RwLock}, panic::{self, RefUnwindSafe, UnwindSafe}};
 
 use crate::{Context, util::logger::print_debug};
 
@@ -6,21 +6,23 @@ use super::traits::{CommandArg, __InternalEventHandler};
 
 /// This struct can be subscribed to, and when it is notified, it will call the subscribers
 pub struct Observable<T: Clone + CommandArg + UnwindSafe + RefUnwindSafe> {
-    /// The subscribers to the observable
-    pub subscribers: Vec<Arc<dyn __InternalEventHandler<T>>>,
+    /// The subscribers to the observable. They can be changed while events are being dispatched
+    subscribers: RwLock<Vec<Arc<dyn __InternalEventHandler<T>>>>,
 }
 
 impl<T: Clone + CommandArg + UnwindSafe + RefUnwindSafe> Observable<T> {
     /// Creates a new observable
     pub fn new() -> Self {
         Observable {
-            subscribers: Vec::new(),
+            subscribers: RwLock::new(Vec::new()),
         }
     }
 
     /// Notifies all subscribers with given data
     pub fn notify(&self, ctx: Context, data: T) {
-        for listener in &self.subscribers {
+        // The lock is released before calling the subscribers, so that they can subscribe or unsubscribe handlers
+        let subscribers = self.subscribers.read().unwrap().clone();
+        for listener in &subscribers {
             panic::catch_unwind(|| {
                 listener.handler(ctx.clone(), data.clone());
             }).unwrap_or_else(|t| {
@@ -30,8 +32,28 @@ impl<T: Clone + CommandArg + UnwindSafe + RefUnwindSafe> Observable<T> {
     }
 
     /// Subscribes a listener to the observable
-    pub fn subscribe(&mut self, listener: Arc<dyn __InternalEventHandler<T>>) {
-        self.subscribers.push(listener);
+    pub fn subscribe(&self, listener: Arc<dyn __InternalEventHandler<T>>) {
+        self.subscribers.write().unwrap().push(listener);
+    }
+
+    /// Removes a listener that was subscribed
+    /// @param handler Points to the listener, see `handler_ptr`
+    /// @return Whether the listener was subscribed
+    pub fn unsubscribe(&self, handler: *const ()) -> bool {
+        let mut subscribers = self.subscribers.write().unwrap();
+        let count = subscribers.len();
+        subscribers.retain(|listener| handler_ptr(listener) != handler);
+        subscribers.len() != count
+    }
+
+    /// The number of subscribed listeners
+    pub fn len(&self) -> usize {
+        self.subscribers.read().unwrap().len()
+    }
+
+    /// Whether no listeners are subscribed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
@@ -40,3 +62,8 @@ impl<T: Clone + CommandArg + UnwindSafe + RefUnwindSafe> Default for Observable<
         Observable::new()
     }
 }
+
+/// Identifies a handler by the address of its data, so that the same handler can be found behind any `Arc<dyn ...>` it was turned into
+pub fn handler_ptr<T: ?Sized>(handler: &Arc<T>) -> *const () {
+    Arc::as_ptr(handler) as *const ()
+}
@@ -1,6 +1,8 @@
 use crate::api::{ApplicationCommandOptionType, ApplicationCommandOption, channel::typing::ChannelType, ApplicationCommandOptionChoice, ApplicationCommandOptionValue};
 use paste::paste;
 
+use super::traits::CommandChoice;
+
 pub struct OptionBuilder {}
 
 macro_rules! OptionBuilderBuilder {
@@ -141,6 +143,13 @@ impl StringOptionBuilder {
         self.option.choices = Some(choices.into_iter().map(LimitedOptionChoice::into).collect());
         self
     }
+
+    /// Uses the variants of an enum that derives `CommandChoice` as the choices
+    #[must_use]
+    pub fn choices_from<T: CommandChoice>(mut self) -> Self {
+        self.option.choices = Some(T::choices());
+        self
+    }
 }
 
 impl IntegerOptionBuilder {
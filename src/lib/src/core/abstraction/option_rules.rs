@@ -0,0 +1,174 @@
+use crate::{
+    api::ApplicationCommandOptionValue,
+    core::interactions::{
+        interaction_event::InteractionCtx,
+        typing::{InteractionCallbackData, InteractionDataOption, MessageData, MessageDataFlags},
+    },
+    util::error::Error,
+};
+
+/**
+ * A rule about how the options of a command relate to each other. Discord can only mark single options as required,
+ * so rules like "either `user` or `role`, but not both" are checked by the library before the handler is called.
+ * If a rule is broken, the user gets an ephemeral message saying why, and the handler isn't called.
+ *
+ * ```rust,ignore
+ * fn option_rules() -> Vec<OptionRule> {
+ *     vec![
+ *         OptionRule::exactly_one_of(&["user", "role"]),
+ *         OptionRule::required_if("reason", "ban"),
+ *         OptionRule::not_greater("min", "max"),
+ *     ]
+ * }
+ * ```
+ */
+pub enum OptionRule {
+    /// At most one of the options can be given
+    MutuallyExclusive(Vec<&'static str>),
+    /// At least one of the options has to be given
+    AtLeastOneOf(Vec<&'static str>),
+    /// Exactly one of the options has to be given
+    ExactlyOneOf(Vec<&'static str>),
+    /// `option` has to be given when `other` is given
+    RequiredIf {
+        option: &'static str,
+        other: &'static str,
+    },
+    /// The value of `lower` can't be greater than the value of `upper` (when both are given)
+    NotGreater {
+        lower: &'static str,
+        upper: &'static str,
+    },
+    /// Any other check. Returns the message to show the user if the options aren't valid
+    Custom(fn(&InteractionCtx) -> Result<(), String>),
+}
+
+impl OptionRule {
+    /// At most one of the options can be given
+    pub fn mutually_exclusive(options: &[&'static str]) -> Self {
+        Self::MutuallyExclusive(options.to_vec())
+    }
+
+    /// At least one of the options has to be given
+    pub fn at_least_one_of(options: &[&'static str]) -> Self {
+        Self::AtLeastOneOf(options.to_vec())
+    }
+
+    /// Exactly one of the options has to be given
+    pub fn exactly_one_of(options: &[&'static str]) -> Self {
+        Self::ExactlyOneOf(options.to_vec())
+    }
+
+    /// `option` has to be given when `other` is given
+    pub fn required_if(option: &'static str, other: &'static str) -> Self {
+        Self::RequiredIf { option, other }
+    }
+
+    /// The value of `lower` can't be greater than the value of `upper`
+    pub fn not_greater(lower: &'static str, upper: &'static str) -> Self {
+        Self::NotGreater { lower, upper }
+    }
+
+    /**
+     * Checks the rule against the options of an interaction
+     * @return The message to show the user if the rule is broken
+     */
+    pub fn check(&self, ctx: &InteractionCtx) -> Result<(), String> {
+        match self {
+            Self::MutuallyExclusive(options) => {
+                let given = given_options(ctx, options);
+                if given.len() > 1 {
+                    return Err(format!("{} can't be used together.", list(&given, "and")));
+                }
+            }
+            Self::AtLeastOneOf(options) => {
+                if given_options(ctx, options).is_empty() {
+                    return Err(format!("Give {}.", list(options, "or")));
+                }
+            }
+            Self::ExactlyOneOf(options) => {
+                if given_options(ctx, options).len() != 1 {
+                    return Err(format!("Give exactly one of {}.", list(options, "or")));
+                }
+            }
+            Self::RequiredIf { option, other } => {
+                if find_option(ctx, other).is_some() && find_option(ctx, option).is_none() {
+                    return Err(format!("`{}` is required when using `{}`.", option, other));
+                }
+            }
+            Self::NotGreater { lower, upper } => {
+                let lower_value = find_option(ctx, lower).and_then(numeric_value);
+                let upper_value = find_option(ctx, upper).and_then(numeric_value);
+                if let (Some(lower_value), Some(upper_value)) = (lower_value, upper_value) {
+                    if lower_value > upper_value {
+                        return Err(format!("`{}` can't be greater than `{}`.", lower, upper));
+                    }
+                }
+            }
+            Self::Custom(check) => check(ctx)?,
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Checks the options of an interaction against a list of rules.
+ * @return The message of the first broken rule
+ */
+pub fn validate_options(ctx: &InteractionCtx, rules: &[OptionRule]) -> Result<(), String> {
+    rules.iter().try_for_each(|rule| rule.check(ctx))
+}
+
+/// Tells the user why their options were rejected, in a message only they can see
+pub async fn respond_invalid_options(ctx: &InteractionCtx, message: &str) -> Result<(), Error> {
+    ctx.respond_message(InteractionCallbackData::Message(MessageData {
+        tts: None,
+        content: Some(format!("❌ {}", message)),
+        embeds: None,
+        allowed_mentions: None,
+        flags: Some(MessageDataFlags::EPHEMERAL.bits()),
+        components: None,
+        attachments: None,
+    }))
+    .await
+}
+
+/// Finds a top level option of the interaction by name
+fn find_option<'a>(ctx: &'a InteractionCtx, name: &str) -> Option<&'a InteractionDataOption> {
+    ctx.data
+        .as_ref()?
+        .options
+        .as_ref()?
+        .iter()
+        .find(|option| option.name == name)
+}
+
+/// The options out of the given ones that the user filled in
+fn given_options(ctx: &InteractionCtx, options: &[&'static str]) -> Vec<&'static str> {
+    options
+        .iter()
+        .copied()
+        .filter(|name| find_option(ctx, name).is_some())
+        .collect()
+}
+
+fn numeric_value(option: &InteractionDataOption) -> Option<f64> {
+    match option.value.as_ref()? {
+        ApplicationCommandOptionValue::Integer(value) => Some(*value as f64),
+        ApplicationCommandOptionValue::Number(value) => Some(*value),
+        ApplicationCommandOptionValue::String(_) | ApplicationCommandOptionValue::Boolean(_) => {
+            None
+        }
+    }
+}
+
+/// Lists option names for a message, e.g. "`a`, `b` or `c`"
+fn list(options: &[&str], conjunction: &str) -> String {
+    let names: Vec<String> = options.iter().map(|name| format!("`{}`", name)).collect();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => {
+            format!("{} {} {}", rest.join(", "), conjunction, last)
+        }
+        _ => names.join(""),
+    }
+}
@@ -0,0 +1,361 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use hyper::{Method, Request};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::{
+    core::http::rate_limit_client::{send_request, RequestRoute},
+    discord::{
+        ids::{params::ChannelIdParam, RawId},
+        resources::channel::message::MessageBuilder,
+        snowflake::Snowflake,
+    },
+    util::{
+        error::{Error, ErrorTypes},
+        logger::print_debug,
+        multipart::message_request,
+    },
+    Context, BASE_URL,
+};
+
+/// The longest time to wait between two attempts at sending a message
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A message in the outbox, saved before it is sent
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OutboxEntry {
+    /// Identifies the entry when it is acknowledged. Entries are sent in the order of their ids
+    pub id: u64,
+    /// The channel to send the message to
+    pub channel_id: Snowflake,
+    /// The json body of the message
+    pub message: Value,
+}
+
+/// Where the outbox journals its messages, so that they survive restarts.
+/// `FileOutboxStore` keeps them in a local file; implement this to keep them in a database instead.
+///
+/// ```rust,ignore
+/// #[async_trait]
+/// impl OutboxStore for PostgresOutbox {
+///     async fn journal(&self, entry: &OutboxEntry) -> Result<(), Error> { /* INSERT */ }
+///     async fn acknowledge(&self, id: u64) -> Result<(), Error> { /* DELETE */ }
+///     async fn pending(&self) -> Result<Vec<OutboxEntry>, Error> { /* SELECT ... ORDER BY id */ }
+/// }
+/// ```
+#[async_trait]
+pub trait OutboxStore: Send + Sync {
+    /// Saves a message before it is sent. It has to be durable once this returns
+    async fn journal(&self, entry: &OutboxEntry) -> Result<(), Error>;
+    /// Removes a message once discord accepted it (or rejected it for good)
+    async fn acknowledge(&self, id: u64) -> Result<(), Error>;
+    /// The messages that were journaled but never acknowledged, in the order of their ids
+    async fn pending(&self) -> Result<Vec<OutboxEntry>, Error>;
+}
+
+/**
+ * Guarantees that messages are sent, even if the bot crashes or discord is down for a while.
+ * Messages are journaled in an `OutboxStore` before they are sent, and are retried (with a backoff) until discord accepts them.
+ * When the outbox is created, the messages that were still in the store from before a restart are sent first.
+ *
+ * Messages are sent one at a time in the order they were added. Discord rejecting a message (e.g. the channel was deleted,
+ * or the bot can't post in it) can't be fixed by retrying, so those messages are dropped instead of blocking the ones after them.
+ * A crash between sending and acknowledging sends the message again, so a message can be delivered twice but is never lost.
+ *
+ * ```rust,ignore
+ * let outbox = Outbox::new(ctx.clone(), FileOutboxStore::open("outbox.jsonl")?).await?;
+ * outbox.send(channel_id, MessageBuilder::new().set_content("The server restarts in 5 minutes")).await?;
+ * ```
+ */
+pub struct Outbox {
+    sender: UnboundedSender<OutboxEntry>,
+    store: Arc<dyn OutboxStore>,
+    last_id: AtomicU64,
+}
+
+impl Outbox {
+    /// Creates an outbox and starts sending the messages that are pending in the store. Must be called from within a tokio runtime.
+    /// @param store Where the messages are journaled
+    pub async fn new(ctx: Context, store: impl OutboxStore + 'static) -> Result<Self, Error> {
+        let store: Arc<dyn OutboxStore> = Arc::new(store);
+        let pending = store.pending().await?;
+        let last_id = pending
+            .iter()
+            .map(|entry| entry.id)
+            .max()
+            .unwrap_or_default();
+
+        let (sender, receiver) = unbounded_channel();
+        for entry in pending {
+            let _ = sender.send(entry);
+        }
+        tokio::spawn(deliver_loop(ctx, receiver, store.clone()));
+
+        Ok(Self {
+            sender,
+            store,
+            last_id: AtomicU64::new(last_id),
+        })
+    }
+
+    /// Journals a message, and queues it to be sent. It is safe to restart the bot once this returns
+    /// @param channel_id The channel to send the message to
+    /// @param message The message to send. Files can't be journaled, so messages with files return an error
+    pub async fn send(&self, channel_id: ChannelIdParam, message: MessageBuilder) -> Result<(), Error> {
+        if !message.files().is_empty() {
+            return Err(Error::new(
+                "Messages with files can't be sent through the outbox".to_string(),
+                ErrorTypes::REQUEST,
+            ));
+        }
+        message.validate()?;
+        let entry = OutboxEntry {
+            id: self.next_id(),
+            channel_id: channel_id.raw(),
+            message: serde_json::to_value(&message)?,
+        };
+        self.store.journal(&entry).await?;
+        self.sender.send(entry).map_err(|_| {
+            Error::new(
+                "The outbox stopped sending messages".to_string(),
+                ErrorTypes::REQUEST,
+            )
+        })
+    }
+
+    /// Ids go up with the time, so they keep going up across restarts
+    fn next_id(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or_default();
+        let mut last = self.last_id.load(Ordering::SeqCst);
+        loop {
+            let id = now.max(last + 1);
+            match self
+                .last_id
+                .compare_exchange(last, id, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => return id,
+                Err(current) => last = current,
+            }
+        }
+    }
+}
+
+async fn deliver_loop(
+    ctx: Context,
+    mut receiver: UnboundedReceiver<OutboxEntry>,
+    store: Arc<dyn OutboxStore>,
+) {
+    while let Some(entry) = receiver.recv().await {
+        let mut failed_attempts: u32 = 0;
+        loop {
+            match send_message_json(&ctx, entry.channel_id, entry.message.clone()).await {
+                Ok(_) => break,
+                Err(e) if is_retryable(&e) => {
+                    failed_attempts += 1;
+                    let backoff = Duration::from_secs(1 << failed_attempts.min(6)).min(MAX_BACKOFF);
+                    if ctx.settings.debug {
+                        print_debug(
+                            "OUTBOX",
+                            format!(
+                                "Unable to send message {}, retrying in {:?}: {}",
+                                entry.id, backoff, e
+                            ),
+                        );
+                    }
+                    tokio::time::sleep(backoff).await;
+                }
+                // Discord rejected the message, so it won't go through no matter how often it is retried
+                Err(e) => {
+                    if ctx.settings.debug {
+                        print_debug(
+                            "OUTBOX",
+                            format!(
+                                "Dropping message {} to {}: {}",
+                                entry.id, entry.channel_id, e
+                            ),
+                        );
+                    }
+                    break;
+                }
+            }
+        }
+        if let Err(e) = store.acknowledge(entry.id).await {
+            if ctx.settings.debug {
+                print_debug(
+                    "OUTBOX",
+                    format!("Unable to acknowledge message {}: {}", entry.id, e),
+                );
+            }
+        }
+    }
+}
+
+/// Only failures that may go away are retried: the request not reaching discord, discord having problems, or rate limits.
+/// Anything else means discord either rejected the message or already posted it, and sending it again would post it twice
+fn is_retryable(error: &Error) -> bool {
+    match error.status() {
+        Some(status) => status == 429 || status >= 500,
+        None => error.kind() == ErrorTypes::REQUEST,
+    }
+}
+
+/// Sends the json of a message that was saved to be sent later.
+/// The response is only parsed as json, so that a message discord accepted is never mistaken for a failure
+pub(crate) async fn send_message_json(
+    ctx: &Context,
+    channel_id: Snowflake,
+    message: Value,
+) -> Result<Value, Error> {
+    let route = RequestRoute {
+        base_route: "/channels/{channel.id}/messages".to_string(),
+        major_param: channel_id.to_string(),
+    };
+    let request_builder = message_request(
+        Request::builder().method(Method::POST).uri(format!(
+            "{}/channels/{}/messages",
+            BASE_URL, channel_id
+        )),
+        message,
+        "",
+        &[],
+    );
+
+    send_request(ctx.clone(), route, request_builder).await
+}
+
+/// A line of the journal file
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum JournalLine {
+    Journal(OutboxEntry),
+    Ack(u64),
+}
+
+/**
+ * Keeps the outbox in a local file, with a line of json for each message that is journaled or acknowledged.
+ * The file is emptied whenever every message in it has been acknowledged, so it doesn't keep growing.
+ */
+pub struct FileOutboxStore {
+    path: PathBuf,
+    /// The ids of the messages in the file that aren't acknowledged yet
+    pending: Mutex<BTreeMap<u64, OutboxEntry>>,
+}
+
+impl FileOutboxStore {
+    /// Opens the journal file, creating it if it doesn't exist
+    /// @param path Where the journal is kept
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .read(true)
+            .open(&path)
+            .map_err(|e| io_error(&path, e))?;
+
+        let mut pending = BTreeMap::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| io_error(&path, e))?;
+            // The last line may be cut off if the bot crashed while writing it, and then it was never acknowledged either
+            match serde_json::from_str(&line) {
+                Ok(JournalLine::Journal(entry)) => {
+                    pending.insert(entry.id, entry);
+                }
+                Ok(JournalLine::Ack(id)) => {
+                    pending.remove(&id);
+                }
+                Err(_) => {}
+            }
+        }
+
+        Ok(Self {
+            path,
+            pending: Mutex::new(pending),
+        })
+    }
+
+    fn append(&self, line: &JournalLine) -> Result<(), Error> {
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| io_error(&self.path, e))?;
+        let mut text = serde_json::to_string(line)?;
+        text.push('\n');
+        file.write_all(text.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|e| io_error(&self.path, e))
+    }
+}
+
+#[async_trait]
+impl OutboxStore for FileOutboxStore {
+    async fn journal(&self, entry: &OutboxEntry) -> Result<(), Error> {
+        let mut pending = self.pending.lock().unwrap();
+        self.append(&JournalLine::Journal(entry.clone()))?;
+        pending.insert(entry.id, entry.clone());
+        Ok(())
+    }
+
+    async fn acknowledge(&self, id: u64) -> Result<(), Error> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.remove(&id);
+        if pending.is_empty() {
+            File::create(&self.path)
+                .and_then(|file| file.sync_data())
+                .map_err(|e| io_error(&self.path, e))
+        } else {
+            self.append(&JournalLine::Ack(id))
+        }
+    }
+
+    async fn pending(&self) -> Result<Vec<OutboxEntry>, Error> {
+        Ok(self.pending.lock().unwrap().values().cloned().collect())
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> Error {
+    Error::with_source(
+        format!("Unable to use the outbox file {}: {}", path.display(), e),
+        ErrorTypes::REQUEST,
+        e,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_only_failures_that_may_go_away() {
+        assert!(is_retryable(&Error::new(
+            "connection reset".to_string(),
+            ErrorTypes::REQUEST
+        )));
+        assert!(is_retryable(&Error::from_response(429, b"")));
+        assert!(is_retryable(&Error::from_response(502, b"")));
+
+        assert!(!is_retryable(&Error::from_response(403, b"")));
+        assert!(!is_retryable(&Error::from_response(404, b"")));
+        // A 2xx whose body couldn't be read was still posted
+        assert!(!is_retryable(&Error::new(
+            "expected value at line 1 column 1".to_string(),
+            ErrorTypes::PARSE
+        )));
+    }
+}
@@ -0,0 +1,504 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    panic::{RefUnwindSafe, UnwindSafe},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    discord::{
+        ids::params,
+        resources::{
+            channel::{
+                message::{Message, MessageBuilder},
+                Channel,
+            },
+            guild::{guild_object::Guild, role::Role},
+            user::User,
+        },
+        snowflake::Snowflake,
+    },
+    util::logger::print_debug,
+};
+
+use super::{context::Context, traits::__InternalEventHandler};
+
+/// A text command, like `!ban @user spam`. Write the handler as a function with `#[prefix_command]`,
+/// which implements this trait on a struct with the function's name:
+///
+/// ```rust,ignore
+/// #[prefix_command(description = "Bans a user")]
+/// async fn ban(ctx: Context, msg: Message, user: User, days: Option<u64>, reason: Rest) {
+///     // ...
+/// }
+///
+/// bot.prefix_commands(PrefixCommands::new("!").command(ban));
+/// ```
+/// The parameters after the context and the message are parsed from the text after the command's name, see `PrefixArg`.
+#[async_trait]
+pub trait PrefixCommand: Send + Sync + UnwindSafe + RefUnwindSafe {
+    /// The name that the command is used with, after the prefix
+    fn name(&self) -> &'static str;
+
+    /// Other names that the command can be used with
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// What the command does, shown by `help`
+    fn description(&self) -> &'static str {
+        ""
+    }
+
+    /// The arguments of the command, e.g. `<user> [days] <reason...>`
+    fn usage(&self) -> String;
+
+    /// Parses the arguments and runs the command
+    async fn run(&self, ctx: Context, msg: Message, args: PrefixArgs) -> Result<(), ArgError>;
+}
+
+/// An argument that couldn't be parsed. It is shown to the user, together with the usage of the command
+#[derive(Clone, Debug)]
+pub struct ArgError {
+    /// The name of the argument
+    pub argument: String,
+    pub message: String,
+}
+
+impl ArgError {
+    pub fn new(argument: &str, message: impl Into<String>) -> Self {
+        Self {
+            argument: argument.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ArgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.argument.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "`{}`: {}", self.argument, self.message)
+        }
+    }
+}
+
+/**
+ * The text after a command's name, split into arguments. Arguments are separated by whitespace,
+ * and an argument with spaces can be put in double quotes: `!note "Max Power" likes "quotes \"like this\""`.
+ */
+pub struct PrefixArgs {
+    /// The text after the command's name
+    text: String,
+    /// The arguments, and where in the text each one starts
+    args: Vec<(usize, String)>,
+    /// The index of the next argument to parse
+    next: usize,
+}
+
+impl PrefixArgs {
+    /// Splits the text into arguments
+    /// @return An error if a quote isn't closed
+    pub fn new(text: &str) -> Result<Self, ArgError> {
+        let mut args = Vec::new();
+        let mut chars = text.char_indices().peekable();
+        while let Some(&(start, c)) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+            let mut arg = String::new();
+            if c == '"' {
+                chars.next();
+                let mut closed = false;
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' if matches!(chars.peek(), Some((_, '"' | '\\'))) => {
+                            arg.push(chars.next().unwrap().1)
+                        }
+                        c => arg.push(c),
+                    }
+                }
+                if !closed {
+                    return Err(ArgError::new("", "A quote isn't closed"));
+                }
+            } else {
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    arg.push(c);
+                    chars.next();
+                }
+            }
+            args.push((start, arg));
+        }
+        Ok(Self {
+            text: text.to_string(),
+            args,
+            next: 0,
+        })
+    }
+
+    /// Takes the next argument
+    pub fn next_arg(&mut self) -> Option<String> {
+        let arg = self.args.get(self.next)?.1.clone();
+        self.next += 1;
+        Some(arg)
+    }
+
+    /// Takes everything that wasn't parsed yet as it was written, quotes included
+    pub fn rest(&mut self) -> String {
+        let rest = match self.args.get(self.next) {
+            Some((start, _)) => self.text[*start..].trim_end().to_string(),
+            None => String::new(),
+        };
+        self.next = self.args.len();
+        rest
+    }
+
+    /// Returns true if every argument was parsed
+    pub fn is_empty(&self) -> bool {
+        self.next >= self.args.len()
+    }
+
+    /// Parses the next argument, see `PrefixArg`
+    /// @param name The name of the argument, for the error message
+    pub async fn parse<T: PrefixArg>(
+        &mut self,
+        ctx: &Context,
+        msg: &Message,
+        name: &str,
+    ) -> Result<T, ArgError> {
+        T::parse(self, ctx, msg, name).await
+    }
+
+    /// Fails if there are arguments left that the command doesn't take
+    pub fn finish(&self) -> Result<(), ArgError> {
+        match self.args.get(self.next) {
+            Some((_, arg)) => Err(ArgError::new("", format!("Unexpected argument `{}`", arg))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A type that a prefix command's parameter can have.
+///
+/// * Numbers, `bool` and `String` take one argument
+/// * `Snowflake`, `User`, `Channel` and `Role` take a mention or an id. Roles can also be given by name
+/// * `Option<T>` is None when there are no arguments left
+/// * `Rest` takes the rest of the text, for things like a reason
+#[async_trait]
+pub trait PrefixArg: Sized + Send {
+    /// How the argument is shown in the usage of the command
+    fn usage(name: &str) -> String {
+        format!("<{}>", name)
+    }
+
+    async fn parse(
+        args: &mut PrefixArgs,
+        ctx: &Context,
+        msg: &Message,
+        name: &str,
+    ) -> Result<Self, ArgError>;
+}
+
+fn required(args: &mut PrefixArgs, name: &str) -> Result<String, ArgError> {
+    args.next_arg()
+        .ok_or_else(|| ArgError::new(name, "This argument is missing"))
+}
+
+macro_rules! parsed_arg {
+    ($($t:ty => $expected:expr),+ $(,)?) => {
+        $(
+            #[async_trait]
+            impl PrefixArg for $t {
+                async fn parse(args: &mut PrefixArgs, _: &Context, _: &Message, name: &str) -> Result<Self, ArgError> {
+                    let arg = required(args, name)?;
+                    arg.parse()
+                        .map_err(|_| ArgError::new(name, format!("Expected {}, got `{}`", $expected, arg)))
+                }
+            }
+        )+
+    };
+}
+
+parsed_arg! {
+    i64 => "a number",
+    i32 => "a number",
+    u64 => "a positive number",
+    u32 => "a positive number",
+    f64 => "a number",
+    String => "text",
+}
+
+#[async_trait]
+impl PrefixArg for bool {
+    async fn parse(
+        args: &mut PrefixArgs,
+        _: &Context,
+        _: &Message,
+        name: &str,
+    ) -> Result<Self, ArgError> {
+        let arg = required(args, name)?;
+        match arg.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Ok(true),
+            "false" | "no" | "off" | "0" => Ok(false),
+            _ => Err(ArgError::new(name, format!("Expected yes or no, got `{}`", arg))),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: PrefixArg> PrefixArg for Option<T> {
+    fn usage(name: &str) -> String {
+        format!("[{}]", T::usage(name).trim_matches(|c| c == '<' || c == '>'))
+    }
+
+    async fn parse(
+        args: &mut PrefixArgs,
+        ctx: &Context,
+        msg: &Message,
+        name: &str,
+    ) -> Result<Self, ArgError> {
+        if args.is_empty() {
+            return Ok(None);
+        }
+        T::parse(args, ctx, msg, name).await.map(Some)
+    }
+}
+
+/// The rest of the text after the other arguments, as it was written. It can't be empty, use `Option<Rest>` if it can be left out
+#[derive(Clone, Debug)]
+pub struct Rest(pub String);
+
+#[async_trait]
+impl PrefixArg for Rest {
+    fn usage(name: &str) -> String {
+        format!("<{}...>", name)
+    }
+
+    async fn parse(
+        args: &mut PrefixArgs,
+        _: &Context,
+        _: &Message,
+        name: &str,
+    ) -> Result<Self, ArgError> {
+        match args.rest() {
+            rest if rest.is_empty() => Err(ArgError::new(name, "This argument is missing")),
+            rest => Ok(Rest(rest)),
+        }
+    }
+}
+
+/// The id in a mention (e.g. `<@123>`, `<@!123>`, `<#123>` or `<@&123>`) or a plain id
+fn mention_id(arg: &str, prefixes: &[&str]) -> Option<Snowflake> {
+    let id = match arg.strip_prefix('<').and_then(|arg| arg.strip_suffix('>')) {
+        Some(mention) => prefixes
+            .iter()
+            .find_map(|prefix| mention.strip_prefix(prefix))?,
+        None => arg,
+    };
+    id.parse::<u64>().ok().map(Snowflake::from)
+}
+
+#[async_trait]
+impl PrefixArg for Snowflake {
+    async fn parse(
+        args: &mut PrefixArgs,
+        _: &Context,
+        _: &Message,
+        name: &str,
+    ) -> Result<Self, ArgError> {
+        let arg = required(args, name)?;
+        mention_id(&arg, &["@!", "@&", "@", "#"])
+            .ok_or_else(|| ArgError::new(name, format!("Expected an id, got `{}`", arg)))
+    }
+}
+
+#[async_trait]
+impl PrefixArg for User {
+    async fn parse(
+        args: &mut PrefixArgs,
+        ctx: &Context,
+        msg: &Message,
+        name: &str,
+    ) -> Result<Self, ArgError> {
+        let arg = required(args, name)?;
+        let id = mention_id(&arg, &["@!", "@"])
+            .ok_or_else(|| ArgError::new(name, format!("Expected a user, got `{}`", arg)))?;
+        // Mentioned users are sent with the message, so they don't have to be fetched
+        if let Some(user) = msg.mentions.iter().find(|user| user.id == id) {
+            return Ok(user.clone());
+        }
+        User::fetch(ctx.clone(), id)
+            .await
+            .map(|user| user.into_inner())
+            .map_err(|_| ArgError::new(name, format!("Unknown user `{}`", arg)))
+    }
+}
+
+#[async_trait]
+impl PrefixArg for Channel {
+    async fn parse(
+        args: &mut PrefixArgs,
+        ctx: &Context,
+        _: &Message,
+        name: &str,
+    ) -> Result<Self, ArgError> {
+        let arg = required(args, name)?;
+        let id = mention_id(&arg, &["#"])
+            .ok_or_else(|| ArgError::new(name, format!("Expected a channel, got `{}`", arg)))?;
+        Channel::fetch(ctx.clone(), params::channel(id))
+            .await
+            .map(|channel| channel.into_inner())
+            .map_err(|_| ArgError::new(name, format!("Unknown channel `{}`", arg)))
+    }
+}
+
+#[async_trait]
+impl PrefixArg for Role {
+    async fn parse(
+        args: &mut PrefixArgs,
+        ctx: &Context,
+        msg: &Message,
+        name: &str,
+    ) -> Result<Self, ArgError> {
+        let arg = required(args, name)?;
+        let guild_id = msg
+            .guild_id
+            .ok_or_else(|| ArgError::new(name, "Roles can only be used in a server"))?;
+        let guild = Guild::fetch(ctx.clone(), params::guild(guild_id))
+            .await
+            .map_err(|_| ArgError::new(name, "Unable to get the roles of the server"))?
+            .into_inner();
+        let id = mention_id(&arg, &["@&"]);
+        guild
+            .roles
+            .into_iter()
+            .find(|role| Some(role.id) == id || role.name.eq_ignore_ascii_case(&arg))
+            .ok_or_else(|| ArgError::new(name, format!("Unknown role `{}`", arg)))
+    }
+}
+
+/**
+ * Runs prefix commands from the messages the bot receives. Add it to the bot with `Bot::prefix_commands`.
+ * Messages from bots are ignored. `help` lists the commands (or shows the usage of one, with `!help ban`),
+ * unless a command named `help` was added.
+ *
+ * When the arguments can't be parsed, the bot replies with what is wrong and the usage of the command.
+ * Reading the messages needs the `MESSAGE_CONTENT` intent, and `GUILD_MESSAGES` or `DIRECT_MESSAGES`.
+ */
+pub struct PrefixCommands {
+    prefix: String,
+    /// The commands by lower case name and alias
+    commands: HashMap<String, Arc<dyn PrefixCommand>>,
+    /// The commands in the order they were added, for `help`
+    ordered: Vec<Arc<dyn PrefixCommand>>,
+    help: bool,
+}
+
+impl PrefixCommands {
+    /// @param prefix What the messages start with, e.g. `!`
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            commands: HashMap::new(),
+            ordered: Vec::new(),
+            help: true,
+        }
+    }
+
+    /// Adds a command. A command with the same name (or alias) as an earlier one replaces it
+    #[must_use]
+    pub fn command(mut self, command: impl PrefixCommand + 'static) -> Self {
+        let command: Arc<dyn PrefixCommand> = Arc::new(command);
+        for name in std::iter::once(command.name()).chain(command.aliases().iter().copied()) {
+            self.commands.insert(name.to_lowercase(), command.clone());
+        }
+        self.ordered.push(command);
+        self
+    }
+
+    /// Turns off the built-in `help` command
+    #[must_use]
+    pub fn without_help(mut self) -> Self {
+        self.help = false;
+        self
+    }
+
+    /// Runs the command that the message uses, if it uses one
+    pub async fn handle(&self, ctx: Context, msg: Message) {
+        if msg.author.as_ref().is_some_and(|author| author.bot == Some(true)) {
+            return;
+        }
+        let Some(text) = msg.content.strip_prefix(&self.prefix) else {
+            return;
+        };
+        let (name, text) = text
+            .trim_start()
+            .split_once(char::is_whitespace)
+            .unwrap_or((text.trim_start(), ""));
+        let name = name.to_lowercase();
+
+        let Some(command) = self.commands.get(&name) else {
+            if self.help && name == "help" {
+                let help = self.help_text(text.trim());
+                self.reply(&ctx, &msg, help).await;
+            }
+            return;
+        };
+        let result = match PrefixArgs::new(text) {
+            Ok(args) => command.run(ctx.clone(), msg.clone(), args).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            let usage = format!("{}{} {}", self.prefix, command.name(), command.usage());
+            self.reply(&ctx, &msg, format!("{}\nUsage: `{}`", e, usage.trim_end()))
+                .await;
+        }
+    }
+
+    /// The list of commands, or the usage of one command
+    fn help_text(&self, command: &str) -> String {
+        if let Some(command) = self.commands.get(&command.to_lowercase()) {
+            let usage = format!("{}{} {}", self.prefix, command.name(), command.usage());
+            return format!("`{}`\n{}", usage.trim_end(), command.description())
+                .trim_end()
+                .to_string();
+        }
+        self.ordered
+            .iter()
+            .map(|command| {
+                let usage = format!("{}{} {}", self.prefix, command.name(), command.usage());
+                match command.description() {
+                    "" => format!("`{}`", usage.trim_end()),
+                    description => format!("`{}` - {}", usage.trim_end(), description),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    async fn reply(&self, ctx: &Context, msg: &Message, content: String) {
+        let reply = MessageBuilder::new().set_content(&content).reply_to(msg.id);
+        if let Err(e) = Message::send(ctx.clone(), params::channel(msg.channel_id), reply).await {
+            if ctx.settings.debug {
+                print_debug("PREFIX_COMMANDS", format!("Unable to reply: {}", e));
+            }
+        }
+    }
+}
+
+impl __InternalEventHandler<Message> for PrefixCommands {
+    fn handler(&self, ctx: Context, msg: Message) {
+        async_std::task::block_on(self.handle(ctx, msg))
+    }
+}
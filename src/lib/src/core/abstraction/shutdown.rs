@@ -0,0 +1,126 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{bounded, Receiver, Sender};
+use tokio::sync::watch;
+
+use super::context::Context;
+
+/// How long the shards get to close their connections before the bot stops listening anyway
+pub(crate) const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the bot waits for requests that were already sent to be answered
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Stops a listening bot. Get one with `Bot::shutdown_handle` before calling `listen`, and clone it into wherever the bot is stopped from.
+ *
+ * Shutting down closes every gateway connection with a close code that ends the session (so the bot goes offline straight away
+ * instead of when discord notices it is gone), lets the events that were already received be handled,
+ * and waits for the http requests that were already sent to be answered. Then `listen` returns.
+ *
+ * ```rust,ignore
+ * let shutdown = bot.shutdown_handle();
+ * tokio::spawn(async move {
+ *     wait_for_deploy().await;
+ *     shutdown.shutdown().await;
+ * });
+ * bot.listen().await;
+ * ```
+ */
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    ctx: Context,
+    requested: Arc<AtomicBool>,
+    /// Wakes up the listen loop, which is blocked waiting for events
+    wake: Sender<()>,
+    /// Becomes true once `listen` is about to return
+    stopped: watch::Receiver<bool>,
+}
+
+/// The listen loop's side of a `ShutdownHandle`
+pub(crate) struct ShutdownListener {
+    ctx: Context,
+    requested: Arc<AtomicBool>,
+    pub(crate) wake: Receiver<()>,
+    stopped: watch::Sender<bool>,
+}
+
+/// Creates a handle for stopping the bot, and the listen loop's side of it
+pub(crate) fn shutdown_pair(ctx: Context) -> (ShutdownHandle, ShutdownListener) {
+    let requested = Arc::new(AtomicBool::new(false));
+    let (wake_send, wake_recv) = bounded(1);
+    let (stopped_send, stopped_recv) = watch::channel(false);
+    (
+        ShutdownHandle {
+            ctx: ctx.clone(),
+            requested: requested.clone(),
+            wake: wake_send,
+            stopped: stopped_recv,
+        },
+        ShutdownListener {
+            ctx,
+            requested,
+            wake: wake_recv,
+            stopped: stopped_send,
+        },
+    )
+}
+
+impl ShutdownHandle {
+    /**
+     * Starts shutting down, without waiting for it to finish.
+     * Use this in event and command handlers: they run on the listen loop, so waiting there for `listen` to return would never finish.
+     */
+    pub fn request(&self) {
+        if self.requested.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.ctx.gateway.close();
+        let _ = self.wake.try_send(());
+    }
+
+    /// Shuts the bot down, and waits until `listen` is about to return
+    pub async fn shutdown(&self) {
+        self.request();
+        self.stopped().await;
+    }
+
+    /// Waits until the bot has shut down, without starting to shut it down
+    pub async fn stopped(&self) {
+        let mut stopped = self.stopped.clone();
+        while !*stopped.borrow() {
+            // An error means the bot was dropped, which stops it as well
+            if stopped.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Returns true once shutting down has started
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+}
+
+impl ShutdownListener {
+    /// Returns true once shutting down has started
+    pub(crate) fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Waits for the requests that were already sent to be answered, then resolves the `shutdown` futures
+    pub(crate) async fn finish(self) {
+        if self.is_requested() {
+            let deadline = Instant::now() + DRAIN_TIMEOUT;
+            while self.ctx.pending_requests() > 0 && Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        }
+        let _ = self.stopped.send(true);
+    }
+}
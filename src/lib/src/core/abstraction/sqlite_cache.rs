@@ -0,0 +1,328 @@
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+
+use crate::{
+    discord::{resources::channel::message::Message, snowflake::Snowflake},
+    util::{
+        error::{Error, ErrorTypes},
+        logger::print_debug,
+    },
+};
+
+use super::{
+    cache_backend::{merge, update_backend, CacheBackend, CacheKey, CacheResource},
+    memory_cache::CacheConfig,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS resources (
+    key TEXT PRIMARY KEY,
+    guild_id INTEGER,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS resources_guild ON resources (guild_id);
+CREATE TABLE IF NOT EXISTS messages (
+    id INTEGER PRIMARY KEY,
+    channel_id INTEGER NOT NULL,
+    guild_id INTEGER,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS messages_channel ON messages (channel_id, id);
+CREATE INDEX IF NOT EXISTS messages_guild ON messages (guild_id);
+";
+
+/**
+ * A `CacheBackend` that keeps the cache in a SQLite file, so a bot doesn't start with an empty cache after a restart,
+ * without having to run a Redis server. It also keeps the latest messages of each channel, see `message` and `recent_messages`.
+ *
+ * Unlike the other backends, the channels, members and messages of a guild are removed when the bot leaves it.
+ * Queries run on the task that makes them, which is fine for the size of bots that a single file is meant for.
+ * Clones share the same connection, so keep one to read the messages from.
+ *
+ * ```rust,ignore
+ * let cache = SqliteCache::open("cache.db")?.max_messages_per_channel(200);
+ * bot.cache_backend(cache.clone());
+ * ```
+ */
+#[derive(Clone)]
+pub struct SqliteCache {
+    connection: Arc<Mutex<Connection>>,
+    max_messages_per_channel: usize,
+    /// Whether failed writes are logged, set from the bot's settings when it starts listening
+    debug: Arc<AtomicBool>,
+}
+
+impl SqliteCache {
+    /// Opens the database file, creating it and its tables if they don't exist. 50 messages are kept per channel
+    /// @param path Where the database is kept
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let connection = Connection::open(path).map_err(|e| {
+            sqlite_error(format!("Unable to open the cache at {}", path.display()), e)
+        })?;
+        Self::with_connection(connection)
+    }
+
+    /// A cache that only lives as long as the process, mostly for trying things out
+    pub fn in_memory() -> Result<Self, Error> {
+        let connection = Connection::open_in_memory()
+            .map_err(|e| sqlite_error("Unable to open the cache".to_string(), e))?;
+        Self::with_connection(connection)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, Error> {
+        // Writes happen for most gateway events, so don't wait for each one to reach the disk
+        connection
+            .execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+            .and_then(|_| connection.execute_batch(SCHEMA))
+            .map_err(|e| sqlite_error("Unable to create the cache tables".to_string(), e))?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            max_messages_per_channel: 50,
+            debug: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// How many of the latest messages are kept for each channel. 0 turns off caching messages
+    #[must_use]
+    pub fn max_messages_per_channel(mut self, max: usize) -> Self {
+        self.max_messages_per_channel = max;
+        self
+    }
+
+    /// Gets a cached message
+    /// @param message_id The id of the message
+    pub fn message(&self, message_id: Snowflake) -> Option<Message> {
+        let data: Option<String> = self
+            .connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT data FROM messages WHERE id = ?1",
+                params![sql_id(message_id)],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()?;
+        serde_json::from_str(&data?).ok()
+    }
+
+    /// The latest cached messages of a channel, newest first
+    /// @param channel_id The id of the channel
+    /// @param limit The most messages to return
+    pub fn recent_messages(&self, channel_id: Snowflake, limit: usize) -> Vec<Message> {
+        let connection = self.connection.lock().unwrap();
+        let Ok(mut statement) = connection.prepare_cached(
+            "SELECT data FROM messages WHERE channel_id = ?1 ORDER BY id DESC LIMIT ?2",
+        ) else {
+            return Vec::new();
+        };
+        let rows = statement.query_map(params![sql_id(channel_id), limit as i64], |row| {
+            row.get::<_, String>(0)
+        });
+        match rows {
+            Ok(rows) => rows
+                .filter_map(|data| serde_json::from_str(&data.ok()?).ok())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn insert_message(&self, data: &Value) {
+        let (Some(id), Some(channel_id)) = (id_of(&data["id"]), id_of(&data["channel_id"])) else {
+            return;
+        };
+        let connection = self.connection.lock().unwrap();
+        let inserted = connection.execute(
+            "INSERT OR REPLACE INTO messages (id, channel_id, guild_id, data) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                sql_id(id),
+                sql_id(channel_id),
+                id_of(&data["guild_id"]).map(sql_id),
+                data.to_string()
+            ],
+        );
+        self.log_error(inserted, || format!("cache message {}", id));
+        // Only the latest messages are kept, and message ids only go up within a channel
+        let trimmed = connection.execute(
+            "DELETE FROM messages WHERE channel_id = ?1 AND id NOT IN
+                (SELECT id FROM messages WHERE channel_id = ?1 ORDER BY id DESC LIMIT ?2)",
+            params![sql_id(channel_id), self.max_messages_per_channel as i64],
+        );
+        self.log_error(trimmed, || {
+            format!("remove the oldest messages of channel {}", channel_id)
+        });
+    }
+
+    fn update_message(&self, update: &Value) {
+        let Some(id) = id_of(&update["id"]) else {
+            return;
+        };
+        let cached: Option<String> = self
+            .connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT data FROM messages WHERE id = ?1",
+                params![sql_id(id)],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten();
+        // Messages that aren't cached are too old to be kept, so the update doesn't add them
+        if let Some(mut message) = cached.and_then(|data| serde_json::from_str::<Value>(&data).ok())
+        {
+            merge(&mut message, update);
+            let updated = self.connection.lock().unwrap().execute(
+                "UPDATE messages SET data = ?2 WHERE id = ?1",
+                params![sql_id(id), message.to_string()],
+            );
+            self.log_error(updated, || format!("update message {}", id));
+        }
+    }
+
+    fn delete_messages(&self, ids: &[Snowflake]) {
+        let connection = self.connection.lock().unwrap();
+        for id in ids {
+            let deleted =
+                connection.execute("DELETE FROM messages WHERE id = ?1", params![sql_id(*id)]);
+            self.log_error(deleted, || format!("remove message {}", id));
+        }
+    }
+
+    /// Removes everything that belongs to a guild the bot isn't in anymore
+    fn remove_guild(&self, guild_id: Snowflake) {
+        let connection = self.connection.lock().unwrap();
+        let resources = connection.execute(
+            "DELETE FROM resources WHERE guild_id = ?1",
+            params![sql_id(guild_id)],
+        );
+        self.log_error(resources, || {
+            format!("remove the resources of guild {}", guild_id)
+        });
+        let messages = connection.execute(
+            "DELETE FROM messages WHERE guild_id = ?1",
+            params![sql_id(guild_id)],
+        );
+        self.log_error(messages, || {
+            format!("remove the messages of guild {}", guild_id)
+        });
+    }
+
+    /// The writes happen while handling events, where there is nobody to return an error to
+    fn log_error(&self, result: rusqlite::Result<usize>, action: impl FnOnce() -> String) {
+        if let Err(e) = result {
+            if self.debug.load(Ordering::Relaxed) {
+                print_debug("CACHE", format!("Unable to {}: {}", action(), e));
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for SqliteCache {
+    async fn get(&self, key: &CacheKey) -> Option<Value> {
+        let data: String = self
+            .connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT data FROM resources WHERE key = ?1",
+                params![key.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()??;
+        serde_json::from_str(&data).ok()
+    }
+
+    async fn insert(&self, key: CacheKey, value: Value) {
+        // The guild that the resource is removed with
+        let guild_id = match key.resource {
+            CacheResource::Guild => Some(key.id),
+            CacheResource::Member => key.guild_id,
+            CacheResource::Channel => id_of(&value["guild_id"]),
+            CacheResource::User => None,
+        };
+        let inserted = self.connection.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO resources (key, guild_id, data) VALUES (?1, ?2, ?3)",
+            params![key.to_string(), guild_id.map(sql_id), value.to_string()],
+        );
+        self.log_error(inserted, || format!("cache {}", key));
+    }
+
+    async fn evict(&self, key: &CacheKey) {
+        let evicted = self.connection.lock().unwrap().execute(
+            "DELETE FROM resources WHERE key = ?1",
+            params![key.to_string()],
+        );
+        self.log_error(evicted, || format!("evict {}", key));
+    }
+
+    async fn apply_event(&self, config: &CacheConfig, event: &str, data: &Value) {
+        match event {
+            "MESSAGE_CREATE" if self.max_messages_per_channel > 0 => self.insert_message(data),
+            "MESSAGE_UPDATE" => self.update_message(data),
+            "MESSAGE_DELETE" => {
+                if let Some(id) = id_of(&data["id"]) {
+                    self.delete_messages(&[id]);
+                }
+            }
+            "MESSAGE_DELETE_BULK" => {
+                let ids: Vec<Snowflake> = data["ids"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(id_of)
+                    .collect();
+                self.delete_messages(&ids);
+            }
+            "CHANNEL_DELETE" | "THREAD_DELETE" => {
+                if let Some(channel_id) = id_of(&data["id"]) {
+                    let deleted = self.connection.lock().unwrap().execute(
+                        "DELETE FROM messages WHERE channel_id = ?1",
+                        params![sql_id(channel_id)],
+                    );
+                    self.log_error(deleted, || {
+                        format!("remove the messages of channel {}", channel_id)
+                    });
+                }
+            }
+            // An unavailable guild is still there, it's just in an outage
+            "GUILD_DELETE" if data["unavailable"].as_bool() != Some(true) => {
+                if let Some(guild_id) = id_of(&data["id"]) {
+                    self.remove_guild(guild_id);
+                }
+            }
+            _ => {}
+        }
+        update_backend(self, config, event, data).await;
+    }
+
+    fn set_debug(&self, debug: bool) {
+        self.debug.store(debug, Ordering::Relaxed);
+    }
+}
+
+/// Snowflakes fit in an i64 (SQLite's integer type) until 2084, and keep their order there
+fn sql_id(id: Snowflake) -> i64 {
+    u64::from(id) as i64
+}
+
+fn id_of(value: &Value) -> Option<Snowflake> {
+    serde_json::from_value(value.clone()).ok()
+}
+
+fn sqlite_error(message: String, e: rusqlite::Error) -> Error {
+    Error::with_source(format!("{}: {}", message, e), ErrorTypes::REQUEST, e)
+}
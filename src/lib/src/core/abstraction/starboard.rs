@@ -0,0 +1,274 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+
+use crate::{
+    core::interactions::handler::events::dispatch_payloads::{
+        MessageReactionAdd, MessageReactionRemove,
+    },
+    discord::{
+        ids::params,
+        image_formats::Animated,
+        resources::channel::{
+            embed::{EmbedAuthor, EmbedBuilder, EmbedField, EmbedImage},
+            message::{Message, MessageBuilder},
+        },
+        snowflake::Snowflake,
+    },
+    util::error::Error,
+};
+
+use super::context::Context;
+
+/// What to do when the author of a message stars their own message
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SelfStarPolicy {
+    /// The author's own star counts towards the threshold
+    Count,
+    /// The author's own star is ignored
+    Ignore,
+}
+
+/// Remembers which starboard post belongs to which message, so that a message is only posted once.
+/// Implement it to keep the posts across restarts (e.g. in a database), `MemoryStarboardStore` keeps them in memory.
+#[async_trait]
+pub trait StarboardStore: Send + Sync {
+    /// The id of the starboard post for a message, if it was posted
+    async fn get(&self, message_id: Snowflake) -> Option<Snowflake>;
+    /// Remembers the starboard post for a message
+    async fn set(&self, message_id: Snowflake, post_id: Snowflake);
+}
+
+/// A `StarboardStore` that keeps the posts in memory
+#[derive(Default)]
+pub struct MemoryStarboardStore {
+    posts: Mutex<HashMap<Snowflake, Snowflake>>,
+}
+
+#[async_trait]
+impl StarboardStore for MemoryStarboardStore {
+    async fn get(&self, message_id: Snowflake) -> Option<Snowflake> {
+        self.posts.lock().unwrap().get(&message_id).copied()
+    }
+
+    async fn set(&self, message_id: Snowflake, post_id: Snowflake) {
+        self.posts.lock().unwrap().insert(message_id, post_id);
+    }
+}
+
+/**
+ * Reposts messages that get enough reactions into a starboard channel, and keeps the count on the post up to date.
+ * Pass it the reaction events from your event handlers:
+ *
+ * ```rust,ignore
+ * let starboard = Starboard::new(starboard_channel_id).threshold(5);
+ *
+ * // In a MessageReactionAdd handler
+ * starboard.handle_reaction_add(ctx, &reaction).await?;
+ * // In a MessageReactionRemove handler
+ * starboard.handle_reaction_remove(ctx, &reaction).await?;
+ * ```
+ */
+pub struct Starboard {
+    /// The channel that the posts are sent to
+    channel_id: Snowflake,
+    /// A unicode emoji, or a custom emoji in the form `name:id`
+    emoji: String,
+    /// The number of reactions a message needs to be posted
+    threshold: usize,
+    self_star: SelfStarPolicy,
+    store: Box<dyn StarboardStore>,
+    /// Handles one reaction at a time, so that two reactions arriving together can't post the same message twice
+    lock: tokio::sync::Mutex<()>,
+}
+
+impl Starboard {
+    /// Creates a starboard that posts messages with 3 ⭐ reactions, and ignores self stars
+    /// @param channel_id The channel to post to
+    pub fn new(channel_id: Snowflake) -> Self {
+        Self {
+            channel_id,
+            emoji: "⭐".to_string(),
+            threshold: 3,
+            self_star: SelfStarPolicy::Ignore,
+            store: Box::new(MemoryStarboardStore::default()),
+            lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Sets the emoji that is counted
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    #[must_use]
+    pub fn emoji(mut self, emoji: &str) -> Self {
+        self.emoji = emoji.to_string();
+        self
+    }
+
+    /// Sets the number of reactions a message needs to be posted
+    #[must_use]
+    pub fn threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold.max(1);
+        self
+    }
+
+    /// Sets whether the author's own reaction counts
+    #[must_use]
+    pub fn self_star(mut self, policy: SelfStarPolicy) -> Self {
+        self.self_star = policy;
+        self
+    }
+
+    /// Sets where the posts are remembered
+    #[must_use]
+    pub fn store(mut self, store: impl StarboardStore + 'static) -> Self {
+        self.store = Box::new(store);
+        self
+    }
+
+    /// Updates the starboard for a MESSAGE_REACTION_ADD event. Reactions with other emojis are ignored.
+    pub async fn handle_reaction_add(
+        &self,
+        ctx: Context,
+        reaction: &MessageReactionAdd,
+    ) -> Result<(), Error> {
+        if !reaction.emoji.matches(&self.emoji) {
+            return Ok(());
+        }
+        self.update(ctx, reaction.channel_id, reaction.message_id)
+            .await
+    }
+
+    /// Updates the starboard for a MESSAGE_REACTION_REMOVE event. Reactions with other emojis are ignored.
+    pub async fn handle_reaction_remove(
+        &self,
+        ctx: Context,
+        reaction: &MessageReactionRemove,
+    ) -> Result<(), Error> {
+        if !reaction.emoji.matches(&self.emoji) {
+            return Ok(());
+        }
+        self.update(ctx, reaction.channel_id, reaction.message_id)
+            .await
+    }
+
+    /// Counts the reactions on a message, and posts it or updates its post
+    async fn update(
+        &self,
+        ctx: Context,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+    ) -> Result<(), Error> {
+        // Starring the posts themselves would post them again
+        if channel_id == self.channel_id {
+            return Ok(());
+        }
+        let _guard = self.lock.lock().await;
+
+        let message = Message::get(ctx.clone(), params::channel(channel_id), params::message(message_id)).await?;
+        let count = self.count(ctx.clone(), &message).await?;
+        let post_id = self.store.get(message_id).await;
+
+        match post_id {
+            // Posts stay up when the count drops, but show the new count
+            Some(post_id) => {
+                Message::edit(
+                    ctx,
+                    params::channel(self.channel_id),
+                    params::message(post_id),
+                    self.post(&message, count),
+                ).await?;
+            }
+            None if count >= self.threshold => {
+                let post = Message::send(ctx, params::channel(self.channel_id), self.post(&message, count)).await?;
+                self.store.set(message_id, post.id).await;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// The number of reactions that count towards the threshold
+    async fn count(&self, ctx: Context, message: &Message) -> Result<usize, Error> {
+        let count = message
+            .reactions
+            .iter()
+            .flatten()
+            .find(|reaction| reaction.emoji.matches(&self.emoji))
+            .map(|reaction| reaction.count as usize)
+            .unwrap_or(0);
+
+        let author_id = message.author.as_ref().map(|author| author.id);
+        if self.self_star == SelfStarPolicy::Count || count == 0 || author_id.is_none() {
+            return Ok(count);
+        }
+        let mut users = Message::reactions(
+            ctx,
+            params::channel(message.channel_id),
+            params::message(message.id),
+            &self.emoji,
+        );
+        while let Some(user) = users.next().await {
+            if Some(user?.id) == author_id {
+                return Ok(count - 1);
+            }
+        }
+        Ok(count)
+    }
+
+    /// The starboard post for a message
+    fn post(&self, message: &Message, count: usize) -> MessageBuilder {
+        let mut embed = EmbedBuilder::new();
+        if let Some(author) = &message.author {
+            embed.set_author(EmbedAuthor {
+                name: Some(author.username.clone()),
+                url: None,
+                icon_url: author.get_avatar_url(Animated::PNG, Some(64)),
+                proxy_icon_url: None,
+            });
+        }
+        if !message.content.is_empty() {
+            embed.set_description(&message.content);
+        }
+        embed.add_field(EmbedField {
+            name: "Source".to_string(),
+            value: format!("[Jump to message]({})", message.link()),
+            inline: false,
+        });
+        // Show the first image, if there is one
+        let image = message.attachments.iter().find(|attachment| {
+            attachment
+                .content_type
+                .as_deref()
+                .is_some_and(|content_type| content_type.starts_with("image/"))
+        });
+        if let Some(image) = image {
+            embed.set_image(EmbedImage {
+                url: image.url.clone(),
+                proxy_url: None,
+                height: None,
+                width: None,
+            });
+        }
+
+        MessageBuilder::new()
+            .set_content(&format!(
+                "{} **{}** <#{}>",
+                self.emoji_display(),
+                count,
+                message.channel_id
+            ))
+            .embed(embed.build())
+    }
+
+    /// The emoji in the form that shows up in a message
+    fn emoji_display(&self) -> String {
+        match self.emoji.rsplit_once(':') {
+            Some((name, id)) => match name.strip_prefix("a:") {
+                Some(name) => format!("<a:{}:{}>", name, id),
+                None => format!("<:{}:{}>", name, id),
+            },
+            None => self.emoji.clone(),
+        }
+    }
+}
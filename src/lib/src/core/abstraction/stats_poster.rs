@@ -0,0 +1,147 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use hyper::{header::AUTHORIZATION, Body, Client, Method, Request};
+use hyper_tls::HttpsConnector;
+
+use crate::{
+    discord::snowflake::Snowflake,
+    util::{
+        error::{Error, ErrorTypes},
+        logger::print_debug,
+    },
+};
+
+use super::{context::Context, guild_tracker::GuildTracker};
+
+/// The stats that are given to a `StatsPoster`
+#[derive(Clone, Debug)]
+pub struct BotStats {
+    /// The id of the bot's user
+    pub bot_id: Snowflake,
+    /// The number of guilds the bot is in
+    pub guild_count: usize,
+    /// The number of shards the bot is using
+    pub shard_count: u64,
+    /// The number of guilds on each shard, indexed by the shard id
+    pub shard_distribution: Vec<usize>,
+}
+
+/// Posts the bot's stats somewhere (usually a bot list like top.gg). Registered posters are called periodically with `Bot::add_stats_poster`.
+#[async_trait]
+pub trait StatsPoster: Send + Sync {
+    async fn post(&self, stats: &BotStats) -> Result<(), Error>;
+}
+
+/**
+ * A `StatsPoster` that sends the stats to a url. The url, authorization header, and body are templates where
+ * `{bot_id}`, `{guild_count}`, and `{shard_count}` are replaced with the current stats.
+ *
+ * ```rust,ignore
+ * let top_gg = HttpStatsPoster::new("https://top.gg/api/bots/{bot_id}/stats")
+ *     .authorization(TOP_GG_TOKEN)
+ *     .body(r#"{"server_count": {guild_count}, "shard_count": {shard_count}}"#);
+ * bot.add_stats_poster(top_gg, Duration::from_secs(30 * 60));
+ * ```
+ */
+pub struct HttpStatsPoster {
+    method: Method,
+    url: String,
+    authorization: Option<String>,
+    body: String,
+}
+
+impl HttpStatsPoster {
+    /// Creates a poster that POSTs `{"server_count": .., "shard_count": ..}` to the url
+    /// @param url The url template to send the stats to
+    pub fn new(url: &str) -> Self {
+        Self {
+            method: Method::POST,
+            url: url.to_string(),
+            authorization: None,
+            body: r#"{"server_count": {guild_count}, "shard_count": {shard_count}}"#.to_string(),
+        }
+    }
+
+    /// Sets the http method, POST by default
+    #[must_use]
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Sets the template for the authorization header
+    #[must_use]
+    pub fn authorization(mut self, authorization: &str) -> Self {
+        self.authorization = Some(authorization.to_string());
+        self
+    }
+
+    /// Sets the template for the json body
+    #[must_use]
+    pub fn body(mut self, body: &str) -> Self {
+        self.body = body.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl StatsPoster for HttpStatsPoster {
+    async fn post(&self, stats: &BotStats) -> Result<(), Error> {
+        let mut request = Request::builder()
+            .method(self.method.clone())
+            .uri(fill_template(&self.url, stats))
+            .header("content-type", "application/json");
+        if let Some(authorization) = &self.authorization {
+            request = request.header(AUTHORIZATION, fill_template(authorization, stats));
+        }
+        let request = request.body(Body::from(fill_template(&self.body, stats)))?;
+
+        let client = Client::builder().build::<_, Body>(HttpsConnector::new());
+        let response = client.request(request).await?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(Error::new(
+                format!("Posting stats failed with status {}", response.status()),
+                ErrorTypes::REQUEST,
+            ))
+        }
+    }
+}
+
+fn fill_template(template: &str, stats: &BotStats) -> String {
+    template
+        .replace("{bot_id}", &stats.bot_id.to_string())
+        .replace("{guild_count}", &stats.guild_count.to_string())
+        .replace("{shard_count}", &stats.shard_count.to_string())
+}
+
+/// Calls the poster every `interval` until the bot stops listening. The first post is after one interval, so that the guilds have had time to load.
+pub(crate) async fn post_stats_loop(
+    ctx: Context,
+    poster: Arc<dyn StatsPoster>,
+    guilds: Arc<GuildTracker>,
+    interval: Duration,
+) {
+    loop {
+        tokio::time::sleep(interval).await;
+
+        // The bot hasn't connected yet
+        let bot_id = match guilds.bot_id() {
+            Some(id) => id,
+            None => continue,
+        };
+        let stats = BotStats {
+            bot_id,
+            guild_count: guilds.guild_count(),
+            shard_count: guilds.shard_count(),
+            shard_distribution: guilds.shard_distribution(),
+        };
+        if let Err(e) = poster.post(&stats).await {
+            if ctx.settings.debug {
+                print_debug("STATS", format!("Unable to post stats: {}", e));
+            }
+        }
+    }
+}
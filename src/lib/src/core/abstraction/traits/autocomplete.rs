@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+
+use crate::{
+    api::{ApplicationCommandOptionChoice, ApplicationCommandOptionValue},
+    events::InteractionCtx,
+    util::logger::print_debug,
+};
+
+/// This trait is used to suggest values for command options that have `autocomplete` turned on.
+/// When combined with the `#[autocomplete_handler]` macro, `autocomplete` is called as the user types in an option of the command named `COMMAND`,
+/// and the choices it returns are shown to the user.
+/// ```rust,ignore
+/// struct ColorAutocomplete;
+///
+/// #[autocomplete_handler(command = "paint")]
+/// impl AutocompleteHandler for ColorAutocomplete {
+///     async fn autocomplete(&self, _ctx: &InteractionCtx, _option: &str, value: &str) -> Vec<ApplicationCommandOptionChoice> {
+///         COLORS
+///             .iter()
+///             .filter(|color| color.starts_with(value))
+///             .map(|color| ApplicationCommandOptionChoice::new_str(color.to_string(), color.to_string()))
+///             .collect()
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait AutocompleteHandler {
+    /// The name of the command this handler suggests values for
+    const COMMAND: &'static str;
+
+    /// This function is called while the user types in an autocomplete option of the command
+    /// @param ctx The context of the interaction.
+    /// @param option The name of the option the user is typing in
+    /// @param value What the user has typed so far
+    /// @return The choices to suggest. Only the first 25 are shown
+    async fn autocomplete(
+        &self,
+        ctx: &InteractionCtx,
+        option: &str,
+        value: &str,
+    ) -> Vec<ApplicationCommandOptionChoice>;
+
+    /// Finds the option the user is typing in, and responds with the suggestions for it. Called by the `#[autocomplete_handler]` macro
+    async fn suggest(&self, ctx: InteractionCtx)
+    where
+        Self: Sync,
+    {
+        let focused = ctx
+            .data
+            .as_ref()
+            .and_then(|data| data.focused_option())
+            .map(|option| {
+                // Discord sends what was typed so far, which may not be a valid number yet
+                let value = match &option.value {
+                    Some(ApplicationCommandOptionValue::String(value)) => value.clone(),
+                    Some(ApplicationCommandOptionValue::Integer(value)) => value.to_string(),
+                    Some(ApplicationCommandOptionValue::Number(value)) => value.to_string(),
+                    Some(ApplicationCommandOptionValue::Boolean(value)) => value.to_string(),
+                    None => String::new(),
+                };
+                (option.name.clone(), value)
+            });
+        let choices = match focused {
+            Some((option, value)) => self.autocomplete(&ctx, &option, &value).await,
+            None => Vec::new(),
+        };
+        if let Err(e) = ctx.respond_autocomplete(choices).await {
+            if ctx.__ctx__.settings.debug {
+                print_debug(
+                    "INTERACTIONS",
+                    format!("Unable to send autocomplete choices: {}", e),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::api::ApplicationCommandOptionChoice;
+
+/// An enum that can be used as the choices of a string option.
+/// Use `#[derive(CommandChoice)]` to implement it, which also implements `TryFrom<&str>` so that the value can be parsed
+/// back from the interaction. Variants can be renamed with `#[choice(name = "Shown Name", value = "sent_value")]`.
+///
+/// ```rust,ignore
+/// #[derive(CommandChoice)]
+/// enum Color {
+///     Red,
+///     #[choice(name = "Light Blue")]
+///     LightBlue,
+/// }
+///
+/// StringOptionBuilder::new("color".to_string(), "Pick a color".to_string()).choices_from::<Color>();
+/// ```
+pub trait CommandChoice: Sized + for<'a> TryFrom<&'a str, Error = String> {
+    /// The choices to register with the option
+    fn choices() -> Vec<ApplicationCommandOptionChoice>;
+
+    /// The value that discord sends back when this choice is picked
+    fn value(&self) -> &'static str;
+}
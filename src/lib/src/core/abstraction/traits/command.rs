@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 
-use crate::{api::{ApplicationCommandType, Snowflake, ApplicationCommandOption, ApplicationCommandOptionType}, events::InteractionCtx, SubsVector, core::interactions::typing::InteractionData};
+use crate::{api::{ApplicationCommandType, Snowflake, ApplicationCommandOption, ApplicationCommandOptionType}, events::InteractionCtx, SubsVector, core::{interactions::typing::InteractionData, abstraction::option_rules::{OptionRule, validate_options, respond_invalid_options}}, util::logger::print_debug};
 
 
 #[async_trait]
@@ -26,6 +26,29 @@ pub trait CommandHandler {
         Vec::new()
     }
 
+    /// Rules about how the options relate to each other (e.g. two options that can't be used together).
+    /// They are checked before the handler is called, and the user is told what is wrong if one is broken.
+    fn option_rules() -> Vec<OptionRule> {
+        Vec::new()
+    }
+
+    /// Checks the option rules, and then calls the handler if they pass.
+    /// This is what the generated code calls when an interaction comes in.
+    async fn validated_handler(&self, ctx: InteractionCtx) {
+        if let Err(message) = validate_options(&ctx, &Self::option_rules()) {
+            if let Err(e) = respond_invalid_options(&ctx, &message).await {
+                if ctx.__ctx__.settings.debug {
+                    print_debug(
+                        "INTERACTIONS",
+                        format!("[{}] Unable to respond to invalid options: {}", Self::NAME, e),
+                    );
+                }
+            }
+            return;
+        }
+        self.handler(ctx).await;
+    }
+
     /// This function is called when the interaction associated with the command is triggered.
     /// By default, this function will route the interaction down to any subcommands. If
     /// this function doesn't have any subcommands to route down to, it will panic.
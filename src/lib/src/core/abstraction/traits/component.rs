@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+
+use crate::events::InteractionCtx;
+
+/// This trait is used to create handlers for message components (buttons and select menus).
+/// When combined with the `#[component_handler]` macro, the handler is called for every component interaction whose `custom_id` matches `CUSTOM_ID`.
+///
+/// `CUSTOM_ID` can be an exact id (`"confirm"`), or a pattern where `*` matches anything (`"confirm_*"`), which is useful
+/// for components that carry data in their custom id (e.g. `"confirm_<user id>"`).
+/// ```rust,ignore
+/// struct Confirm;
+///
+/// #[component_handler(custom_id = "confirm_*")]
+/// impl ComponentHandler for Confirm {
+///     async fn handler(&self, ctx: InteractionCtx) {
+///         let custom_id = ctx.data.as_ref().unwrap().custom_id.clone().unwrap();
+///         // ...
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait ComponentHandler {
+    /// The custom id (or pattern) of the components this handler is for
+    const CUSTOM_ID: &'static str;
+
+    /// This function is called when a matching component is used
+    /// @param ctx The context of the interaction.
+    async fn handler(&self, ctx: InteractionCtx);
+}
@@ -1,7 +1,10 @@
+#[cfg(feature = "gateway")]
 use std::panic::{UnwindSafe, RefUnwindSafe};
 
+#[cfg(feature = "gateway")]
 use async_trait::async_trait;
 
+#[cfg(feature = "gateway")]
 use crate::{Context, Events};
 
 
@@ -11,6 +14,7 @@ pub trait CommandArg {}
 /// This trait is used to help users create event handlers for the event dispatcher.
 /// When combined with the `#[event_handler]` macro, this struct will be used to implement the `InternalEventHandler` and the `Registerable` traits.
 /// The reason why this struct is needed is that handlers can't have consts, and also it allows the user to have an async function as the handler.
+#[cfg(feature = "gateway")]
 #[async_trait]
 pub trait EventHandler<T: CommandArg> {
     const EVENT_TYPE: Events;
@@ -21,7 +25,8 @@ pub trait EventHandler<T: CommandArg> {
 /// This trait is used behind the scenes to wrap the user's event handler,
 /// and enable it to be called by the event dispatcher or interaction handler as a sync function.
 /// It is implemented by the `#[event_handler]` or the `#[command]` macro.
-pub trait __InternalEventHandler<T: CommandArg>: UnwindSafe + RefUnwindSafe {
+#[cfg(feature = "gateway")]
+pub trait __InternalEventHandler<T: CommandArg>: UnwindSafe + RefUnwindSafe + Send + Sync {
     /// This function is called by the event dispatcher or interaction handler.
     fn handler(&self, _: Context, _: T);
 }
\ No newline at end of file
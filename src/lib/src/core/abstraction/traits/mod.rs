@@ -1,7 +1,25 @@
+#[cfg(feature = "gateway")]
+mod autocomplete;
+mod choice;
+#[cfg(feature = "gateway")]
 mod command;
+#[cfg(feature = "gateway")]
+mod component;
 mod event;
+#[cfg(feature = "gateway")]
+mod modal;
+#[cfg(feature = "gateway")]
 mod registerable;
 
+#[cfg(feature = "gateway")]
+pub use autocomplete::*;
+pub use choice::*;
+#[cfg(feature = "gateway")]
 pub use command::*;
+#[cfg(feature = "gateway")]
+pub use component::*;
 pub use event::*;
-pub use registerable::*;
\ No newline at end of file
+#[cfg(feature = "gateway")]
+pub use modal::*;
+#[cfg(feature = "gateway")]
+pub use registerable::*;
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+
+use crate::events::InteractionCtx;
+
+/// This trait is used to create handlers for submitted modals.
+/// When combined with the `#[modal_handler]` macro, the handler is called for every modal submit whose `custom_id` matches `CUSTOM_ID`.
+///
+/// Like with components, `CUSTOM_ID` can be an exact id (`"report"`), or a pattern where `*` matches anything (`"report_*"`).
+/// The values the user entered can be read with `ctx.field_value` and `ctx.parse_field`.
+/// ```rust,ignore
+/// struct Report;
+///
+/// #[modal_handler(custom_id = "report")]
+/// impl ModalHandler for Report {
+///     async fn handler(&self, ctx: InteractionCtx) {
+///         let reason = ctx.field_value("reason").unwrap_or_default();
+///         // ...
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait ModalHandler {
+    /// The custom id (or pattern) of the modals this handler is for
+    const CUSTOM_ID: &'static str;
+
+    /// This function is called when a matching modal is submitted
+    /// @param ctx The context of the interaction.
+    async fn handler(&self, ctx: InteractionCtx);
+}
@@ -7,6 +7,9 @@ use crate::{api::{ApplicationCommandOptionType, ApplicationCommandType, Applicat
 pub enum RegisterableType {
     Event,
     Command,
+    Component,
+    Modal,
+    Autocomplete,
     SubCommandGroup,
     SubCommand
 }
@@ -59,8 +62,11 @@ pub trait Registerable {
 }
 
 pub trait RegFns {
-    fn reg_event(self: &Arc<Self>, _: &mut EventDispatcher) {}
+    fn reg_event(self: &Arc<Self>, _: &EventDispatcher) {}
     fn reg_command(self: &Arc<Self>, _: Context, _: Arc<InteractionRouter>) {}
+    fn reg_component(self: &Arc<Self>, _: Arc<InteractionRouter>) {}
+    fn reg_modal(self: &Arc<Self>, _: Arc<InteractionRouter>) {}
+    fn reg_autocomplete(self: &Arc<Self>, _: Arc<InteractionRouter>) {}
 }
 
 #[async_trait]
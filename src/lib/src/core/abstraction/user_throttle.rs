@@ -0,0 +1,81 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::discord::snowflake::Snowflake;
+
+/// Buckets of users that haven't used an interaction for a while are dropped once there are this many
+const PRUNE_AFTER_USERS: usize = 10_000;
+
+/**
+ * Limits how often each user can use the bot's commands, components and modals, so that one user spamming a command can't overload the handlers.
+ * Each user gets `limit` uses every `per`, plus `burst` extra uses that are only refilled while they aren't using the bot.
+ * Interactions over the limit aren't passed to the handler, and the user gets an ephemeral message telling them to slow down instead.
+ *
+ * ```rust,ignore
+ * let mut bot = Bot::new(token);
+ * // 5 uses every 10 seconds, and up to 3 more in a quick burst
+ * bot.throttle_users(UserThrottle::new(5, Duration::from_secs(10)).burst(3));
+ * ```
+ */
+pub struct UserThrottle {
+    /// The number of uses per `per`
+    limit: u32,
+    per: Duration,
+    /// Extra uses on top of `limit`
+    burst: u32,
+    /// The remaining uses of each user, and when they were last refilled
+    buckets: Mutex<HashMap<Snowflake, (f64, Instant)>>,
+}
+
+impl UserThrottle {
+    /// @param limit How many interactions a user can use every `per`
+    /// @param per The window the limit is for
+    pub fn new(limit: u32, per: Duration) -> Self {
+        Self {
+            limit: limit.max(1),
+            per,
+            burst: 0,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lets users go over the limit by this many uses at once, as long as they were idle before
+    #[must_use]
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /**
+     * Uses up one use of the user, if they have one left
+     * @param user_id The user that used an interaction
+     * @return None if the interaction is allowed, or how long until the user can use the bot again
+     */
+    pub fn check(&self, user_id: Snowflake) -> Option<Duration> {
+        let now = Instant::now();
+        let capacity = (self.limit + self.burst) as f64;
+        let per_second = self.limit as f64 / self.per.as_secs_f64().max(f64::EPSILON);
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= PRUNE_AFTER_USERS {
+            // Users whose bucket would be full again don't need to be remembered
+            buckets.retain(|_, (remaining, refilled)| {
+                *remaining + now.duration_since(*refilled).as_secs_f64() * per_second < capacity
+            });
+        }
+
+        let (remaining, refilled) = buckets.entry(user_id).or_insert((capacity, now));
+        *remaining =
+            (*remaining + now.duration_since(*refilled).as_secs_f64() * per_second).min(capacity);
+        *refilled = now;
+        if *remaining >= 1f64 {
+            *remaining -= 1f64;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1f64 - *remaining) / per_second))
+        }
+    }
+}
@@ -0,0 +1,532 @@
+use std::{
+    panic::{RefUnwindSafe, UnwindSafe},
+    path::Path,
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use wasmtime::{Caller, Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::{
+    api::{ApplicationCommand, ApplicationCommandType, Snowflake},
+    core::interactions::{
+        interaction_event::InteractionCtx,
+        typing::{InteractionCallbackData, MessageData},
+    },
+    discord::{
+        ids::params,
+        interactions::application_command::CreateApplicationCommand,
+        resources::{
+            channel::{message::Message, Channel},
+            guild::{guild_member::GuildMember, guild_object::Guild},
+            user::User,
+        },
+    },
+    util::{
+        error::{Error, ErrorTypes},
+        logger::print_debug,
+    },
+    Context,
+};
+
+use super::{
+    command_group::CommandGroup,
+    interaction_router::InteractionRouter,
+    traits::{__InternalEventHandler, RegFns, Registerable, RegisterableType},
+};
+
+/// The module that the host functions are imported from
+const HOST_MODULE: &str = "discrab";
+
+/// How much a plugin can use while handling one interaction
+#[derive(Clone, Copy, Debug)]
+pub struct WasmLimits {
+    /// The fuel (roughly, the number of instructions) that a plugin can use before it is stopped
+    pub fuel: u64,
+    /// The most linear memory a plugin can grow to, in bytes
+    pub memory_bytes: usize,
+    /// The most resources a plugin can fetch
+    pub max_fetches: u32,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 50_000_000,
+            memory_bytes: 16 * 1024 * 1024,
+            max_fetches: 10,
+        }
+    }
+}
+
+/// What a plugin can ask for with `fetch`
+#[derive(Deserialize)]
+#[serde(tag = "resource", rename_all = "snake_case")]
+enum FetchRequest {
+    /// The guild the interaction came from
+    Guild,
+    /// A channel of the guild the interaction came from
+    Channel {
+        id: Snowflake,
+    },
+    /// A member of the guild the interaction came from
+    Member {
+        id: Snowflake,
+    },
+    User {
+        id: Snowflake,
+    },
+    /// A message in a channel of the guild the interaction came from
+    Message {
+        channel_id: Snowflake,
+        id: Snowflake,
+    },
+}
+
+/// The data of a `Store`, which the host functions can reach
+struct HostState {
+    /// The interaction being handled, or None while the commands are read
+    ictx: Option<InteractionCtx>,
+    limits: StoreLimits,
+    fetches_left: u32,
+    responded: bool,
+}
+
+/// The compiled module, shared by the commands of a plugin
+struct PluginModule {
+    name: String,
+    engine: Engine,
+    module: Module,
+    linker: Linker<HostState>,
+    limits: WasmLimits,
+}
+
+// A plugin that panics or traps is stopped by wasmtime, and never leaves the module in a broken state
+impl UnwindSafe for PluginModule {}
+impl RefUnwindSafe for PluginModule {}
+
+/**
+ * Command handlers loaded from a WASM module, so that bot platforms can run code their users upload without trusting it.
+ * The module is sandboxed: it can only respond to the interaction it is handling, and fetch the resources of the guild
+ * the interaction came from. It runs with a fresh instance for each interaction, and is stopped once it goes over its `WasmLimits`.
+ *
+ * The module exports:
+ * - `memory`
+ * - `alloc(len: i32) -> i32`, which reserves `len` bytes for the host to write into
+ * - `commands() -> i64`, the json list of the commands to create (`CreateApplicationCommand`s)
+ * - `handle(ptr: i32, len: i32)`, called with `{"command", "options", "guild_id", "channel_id", "user_id"}` as json
+ *
+ * And can import from the `discrab` module:
+ * - `respond(ptr: i32, len: i32) -> i32` responds with a message (`MessageData` as json). 0 on success, negative on an error
+ * - `fetch(ptr: i32, len: i32) -> i64` gets a resource, e.g. `{"resource": "channel", "id": "..."}`.
+ *   The json of the resource is written with `alloc`. Negative on an error
+ * - `log(ptr: i32, len: i32)` prints a debug log when the bot is in debug mode
+ *
+ * Strings are passed as a pointer and a length into the module's memory. An `i64` return packs them as `ptr << 32 | len`.
+ *
+ * ```rust,ignore
+ * let plugin = WasmPlugin::from_file("dice", "plugins/dice.wasm", WasmLimits::default())?.for_guild(guild_id);
+ * bot.handlers().install(ctx.clone(), plugin.into_group());
+ * ```
+ */
+pub struct WasmPlugin {
+    module: Arc<PluginModule>,
+    commands: Vec<CreateApplicationCommand>,
+    guild_id: Option<Snowflake>,
+}
+
+impl WasmPlugin {
+    /// Compiles a plugin, and reads the commands it handles
+    /// @param name The name of the plugin, which is the name of its `CommandGroup`
+    /// @param wasm The module, as a binary or in the text format
+    pub fn load(name: &str, wasm: &[u8], limits: WasmLimits) -> Result<Self, Error> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| plugin_error(name, e))?;
+        let module = Module::new(&engine, wasm).map_err(|e| plugin_error(name, e))?;
+        let mut linker = Linker::new(&engine);
+        link_host_functions(&mut linker).map_err(|e| plugin_error(name, e))?;
+
+        let mut plugin = Self {
+            module: Arc::new(PluginModule {
+                name: name.to_string(),
+                engine,
+                module,
+                linker,
+                limits,
+            }),
+            commands: Vec::new(),
+            guild_id: None,
+        };
+        plugin.commands = plugin.read_commands()?;
+        Ok(plugin)
+    }
+
+    /// Compiles a plugin from a file, see `load`
+    pub fn from_file(
+        name: &str,
+        path: impl AsRef<Path>,
+        limits: WasmLimits,
+    ) -> Result<Self, Error> {
+        let wasm = std::fs::read(path.as_ref()).map_err(|e| {
+            Error::with_source(
+                format!("Unable to read plugin {}: {}", path.as_ref().display(), e),
+                ErrorTypes::PLUGIN,
+                e,
+            )
+        })?;
+        Self::load(name, &wasm, limits)
+    }
+
+    /// Creates the plugin's commands in a single guild instead of globally. Platforms that install plugins per guild should use this
+    #[must_use]
+    pub fn for_guild(mut self, guild_id: Snowflake) -> Self {
+        self.guild_id = Some(guild_id);
+        self
+    }
+
+    /// The commands that the plugin handles
+    pub fn commands(&self) -> &[CreateApplicationCommand] {
+        &self.commands
+    }
+
+    /// The plugin's commands as a group named after the plugin, to register with `Bot::register_group` or `HandlerRegistry::install`.
+    /// Installing a new version of the plugin under the same name replaces the old one
+    pub fn into_group(self) -> CommandGroup {
+        let mut group = CommandGroup::new(&self.module.name);
+        for command in self.commands {
+            group = group.handler(WasmCommand {
+                module: self.module.clone(),
+                // Command names are `&'static str` everywhere else, so this is leaked once per load
+                name: Box::leak(command.name.clone().into_boxed_str()),
+                command,
+                guild_id: self.guild_id,
+            });
+        }
+        group
+    }
+
+    fn read_commands(&self) -> Result<Vec<CreateApplicationCommand>, Error> {
+        let name = &self.module.name;
+        let state = HostState {
+            ictx: None,
+            limits: StoreLimitsBuilder::new()
+                .memory_size(self.module.limits.memory_bytes)
+                .build(),
+            fetches_left: 0,
+            responded: true,
+        };
+        let mut store = self.module.new_store(state)?;
+        let instance = self
+            .module
+            .linker
+            .instantiate(&mut store, &self.module.module)
+            .map_err(|e| plugin_error(name, e))?;
+        let commands = instance
+            .get_typed_func::<(), i64>(&mut store, "commands")
+            .and_then(|f| f.call(&mut store, ()))
+            .map_err(|e| plugin_error(name, e))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| plugin_error(name, "the module doesn't export its memory"))?;
+        let (ptr, len) = unpack(commands);
+        let bytes = memory.data(&store).get(ptr..ptr + len).ok_or_else(|| {
+            plugin_error(name, "commands() returned a string outside of its memory")
+        })?;
+        serde_json::from_slice(bytes).map_err(|e| plugin_error(name, e))
+    }
+}
+
+impl PluginModule {
+    fn new_store(&self, state: HostState) -> Result<Store<HostState>, Error> {
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(self.limits.fuel)
+            .map_err(|e| plugin_error(&self.name, e))?;
+        Ok(store)
+    }
+
+    /// Runs `handle` with a fresh instance
+    fn handle(&self, ictx: InteractionCtx) -> Result<(), Error> {
+        let data = ictx.data.as_ref();
+        let input = json!({
+            "command": data.map(|data| data.name.clone()),
+            "options": data.and_then(|data| data.options.clone()),
+            "guild_id": ictx.guild_id,
+            "channel_id": ictx.channel_id,
+            "user_id": ictx
+                .member
+                .as_ref()
+                .and_then(|member| member.user.as_ref())
+                .or(ictx.user.as_ref())
+                .map(|user| user.id),
+        });
+        let input = serde_json::to_vec(&input)?;
+
+        let state = HostState {
+            ictx: Some(ictx),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(self.limits.memory_bytes)
+                .instances(1)
+                .build(),
+            fetches_left: self.limits.max_fetches,
+            responded: false,
+        };
+        let mut store = self.new_store(state)?;
+        let instance = self
+            .linker
+            .instantiate(&mut store, &self.module)
+            .map_err(|e| plugin_error(&self.name, e))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| plugin_error(&self.name, "the module doesn't export its memory"))?;
+        let ptr = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .and_then(|alloc| alloc.call(&mut store, input.len() as i32))
+            .map_err(|e| plugin_error(&self.name, e))?;
+        memory
+            .write(&mut store, ptr as usize, &input)
+            .map_err(|e| plugin_error(&self.name, e))?;
+        instance
+            .get_typed_func::<(i32, i32), ()>(&mut store, "handle")
+            .and_then(|handle| handle.call(&mut store, (ptr, input.len() as i32)))
+            .map_err(|e| plugin_error(&self.name, e))
+    }
+}
+
+/// A command of a plugin, registered like the commands made with `#[command]`
+struct WasmCommand {
+    module: Arc<PluginModule>,
+    name: &'static str,
+    command: CreateApplicationCommand,
+    guild_id: Option<Snowflake>,
+}
+
+impl Registerable for WasmCommand {
+    fn get_reg_type(&self) -> RegisterableType {
+        RegisterableType::Command
+    }
+
+    fn get_application_command_type(&self) -> Option<ApplicationCommandType> {
+        self.command.type_.clone()
+    }
+
+    fn get_name(&self) -> Option<&'static str> {
+        Some(self.name)
+    }
+}
+
+impl RegFns for WasmCommand {
+    fn reg_command(self: &Arc<Self>, ctx: Context, router: Arc<InteractionRouter>) {
+        // Creating a command with the name of an existing one overwrites it, so this also updates the command
+        let created = async_std::task::block_on(async {
+            match self.guild_id {
+                Some(guild_id) => {
+                    ApplicationCommand::create_guild(
+                        ctx.clone(),
+                        params::guild(guild_id),
+                        self.command.clone(),
+                    )
+                    .await
+                }
+                None => ApplicationCommand::create_global(ctx.clone(), self.command.clone()).await,
+            }
+        });
+        match created {
+            Ok(command) => {
+                router.register_command(command.id, self.clone());
+                router.registry.insert(self.name, self.guild_id, command.id);
+            }
+            Err(e) => print_debug(
+                "PLUGINS",
+                format!(
+                    "Unable to create command {} of plugin {}: {}",
+                    self.name, self.module.name, e
+                ),
+            ),
+        }
+    }
+}
+
+impl __InternalEventHandler<InteractionCtx> for WasmCommand {
+    fn handler(&self, ctx: Context, ictx: InteractionCtx) {
+        if let Err(e) = self.module.handle(ictx) {
+            if ctx.settings.debug {
+                print_debug(
+                    "PLUGINS",
+                    format!(
+                        "Command {} of plugin {} failed: {}",
+                        self.name, self.module.name, e
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn link_host_functions(linker: &mut Linker<HostState>) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        HOST_MODULE,
+        "respond",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i32 {
+            if caller.data().responded {
+                return -3;
+            }
+            let message: MessageData = match read_string(&mut caller, ptr, len)
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            {
+                Some(message) => message,
+                None => return -1,
+            };
+            let responded = match &caller.data().ictx {
+                Some(ictx) => async_std::task::block_on(
+                    ictx.respond_message(InteractionCallbackData::Message(message)),
+                ),
+                None => return -3,
+            };
+            match responded {
+                Ok(_) => {
+                    caller.data_mut().responded = true;
+                    0
+                }
+                Err(_) => -2,
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "fetch",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i64 {
+            if caller.data().fetches_left == 0 {
+                return -3;
+            }
+            caller.data_mut().fetches_left -= 1;
+            let request: FetchRequest = match read_string(&mut caller, ptr, len)
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            {
+                Some(request) => request,
+                None => return -1,
+            };
+            let fetched = match &caller.data().ictx {
+                Some(ictx) => async_std::task::block_on(fetch(ictx, request)),
+                None => None,
+            };
+            match fetched {
+                Some(value) => write_string(&mut caller, &value.to_string()).unwrap_or(-2),
+                None => -2,
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            let debug = caller
+                .data()
+                .ictx
+                .as_ref()
+                .map(|ictx| ictx.__ctx__.settings.debug)
+                .unwrap_or(false);
+            if !debug {
+                return;
+            }
+            if let Some(bytes) = read_string(&mut caller, ptr, len) {
+                print_debug("PLUGINS", String::from_utf8_lossy(&bytes).to_string());
+            }
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Gets a resource for a plugin, as long as it belongs to the guild the interaction came from
+async fn fetch(ictx: &InteractionCtx, request: FetchRequest) -> Option<Value> {
+    let ctx = ictx.__ctx__.clone();
+    let guild_id: Option<Snowflake> = ictx
+        .guild_id
+        .as_ref()
+        .and_then(|id| id.parse::<u64>().ok())
+        .map(Snowflake::from);
+    let in_guild = |channel: &Channel| channel.guild_id.is_some() && channel.guild_id == guild_id;
+
+    let value = match request {
+        FetchRequest::Guild => {
+            serde_json::to_value(Guild::get(ctx, params::guild(guild_id?)).await.ok()?)
+        }
+        FetchRequest::Channel { id } => {
+            let channel = Channel::get(ctx, params::channel(id)).await.ok()?;
+            if !in_guild(&channel) {
+                return None;
+            }
+            serde_json::to_value(channel)
+        }
+        FetchRequest::Member { id } => serde_json::to_value(
+            GuildMember::get(ctx, params::guild(guild_id?), params::user(id))
+                .await
+                .ok()?,
+        ),
+        FetchRequest::User { id } => {
+            serde_json::to_value(User::get(ctx, id.to_string()).await.ok()?)
+        }
+        FetchRequest::Message { channel_id, id } => {
+            let channel = Channel::get(ctx.clone(), params::channel(channel_id))
+                .await
+                .ok()?;
+            if !in_guild(&channel) {
+                return None;
+            }
+            serde_json::to_value(
+                Message::get(ctx, params::channel(channel_id), params::message(id))
+                    .await
+                    .ok()?,
+            )
+        }
+    };
+    value.ok()
+}
+
+/// Reads `len` bytes at `ptr` from the plugin's memory
+fn read_string(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Option<Vec<u8>> {
+    let memory = caller.get_export("memory")?.into_memory()?;
+    let mut bytes = vec![0; usize::try_from(len).ok()?];
+    memory
+        .read(&caller, usize::try_from(ptr).ok()?, &mut bytes)
+        .ok()?;
+    Some(bytes)
+}
+
+/// Writes a string into memory that the plugin allocates with `alloc`
+/// @return The pointer and length, packed into an i64
+fn write_string(caller: &mut Caller<'_, HostState>, text: &str) -> Option<i64> {
+    let alloc = caller
+        .get_export("alloc")?
+        .into_func()?
+        .typed::<i32, i32>(&caller)
+        .ok()?;
+    let len = i32::try_from(text.len()).ok()?;
+    let ptr = alloc.call(&mut *caller, len).ok()?;
+    let memory = caller.get_export("memory")?.into_memory()?;
+    memory
+        .write(&mut *caller, usize::try_from(ptr).ok()?, text.as_bytes())
+        .ok()?;
+    Some(((ptr as i64) << 32) | len as i64)
+}
+
+/// Splits an i64 returned by a plugin into a pointer and a length
+fn unpack(packed: i64) -> (usize, usize) {
+    (
+        (packed as u64 >> 32) as usize,
+        (packed as u64 & 0xffff_ffff) as usize,
+    )
+}
+
+fn plugin_error(name: &str, e: impl std::fmt::Display) -> Error {
+    Error::new(
+        format!("Plugin {} failed: {:#}", name, e),
+        ErrorTypes::PLUGIN,
+    )
+}
@@ -0,0 +1,160 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use hyper::{HeaderMap, StatusCode};
+
+use crate::util::requests::get_header_as;
+
+/// The number of requests that can be made per second across all routes
+pub const GLOBAL_RATE_LIMIT_PER_SEC: f64 = 50f64;
+/// Discord bans the IP for a while after this many invalid requests (401, 403 or 429) in INVALID_REQUEST_WINDOW
+pub const INVALID_REQUEST_LIMIT: usize = 10_000;
+/// The window that invalid requests are counted in
+pub const INVALID_REQUEST_WINDOW: Duration = Duration::from_secs(10 * 60);
+/// Requests are held back once this many invalid requests were made in the window, so that the limit is never reached
+const INVALID_REQUEST_SAFETY_MARGIN: usize = 100;
+
+/**
+ * A snapshot of the rate limits that apply to every request the bot makes.
+ * Get it with `ctx.rate_limit_info()`.
+ * @docs <https://discord.com/developers/docs/topics/rate-limits#global-rate-limit>
+ */
+#[derive(Clone, Debug)]
+pub struct RateLimitInfo {
+    /// The number of requests that can be made per second
+    pub global_limit: u32,
+    /// The number of requests that can be made right now without waiting
+    pub global_remaining: u32,
+    /// Set while discord has globally rate limited the bot. No requests are sent until then
+    pub globally_limited_until: Option<Instant>,
+    /// The number of 401, 403 and 429 responses in the last 10 minutes
+    pub invalid_requests: usize,
+    /// The number of invalid requests in 10 minutes that gets the bot's IP banned
+    pub invalid_request_limit: usize,
+}
+
+impl Default for RateLimitInfo {
+    fn default() -> Self {
+        Self {
+            global_limit: GLOBAL_RATE_LIMIT_PER_SEC as u32,
+            global_remaining: GLOBAL_RATE_LIMIT_PER_SEC as u32,
+            globally_limited_until: None,
+            invalid_requests: 0,
+            invalid_request_limit: INVALID_REQUEST_LIMIT,
+        }
+    }
+}
+
+/// Throttles all outgoing requests to the global rate limit, and keeps track of the invalid requests so that the bot doesn't get banned
+pub struct GlobalLimiter {
+    /// The number of requests that can be made right now. Refills at GLOBAL_RATE_LIMIT_PER_SEC
+    allowance: f64,
+    last_refill: Instant,
+    /// Set when discord returns a global rate limit
+    limited_until: Option<Instant>,
+    /// The times of the invalid requests in the last INVALID_REQUEST_WINDOW
+    invalid_requests: VecDeque<Instant>,
+}
+
+impl GlobalLimiter {
+    pub fn new() -> Self {
+        Self {
+            allowance: GLOBAL_RATE_LIMIT_PER_SEC,
+            last_refill: Instant::now(),
+            limited_until: None,
+            invalid_requests: VecDeque::new(),
+        }
+    }
+
+    /// Adds the allowance that built up since the last refill, and forgets invalid requests that left the window
+    pub fn refill(&mut self, now: Instant) {
+        self.allowance = (self.allowance
+            + now.duration_since(self.last_refill).as_secs_f64() * GLOBAL_RATE_LIMIT_PER_SEC)
+            .min(GLOBAL_RATE_LIMIT_PER_SEC);
+        self.last_refill = now;
+
+        if self.limited_until.is_some_and(|until| until <= now) {
+            self.limited_until = None;
+        }
+        while self
+            .invalid_requests
+            .front()
+            .is_some_and(|time| now.duration_since(*time) > INVALID_REQUEST_WINDOW)
+        {
+            self.invalid_requests.pop_front();
+        }
+    }
+
+    /**
+     * The time until the next request can be sent
+     * @return Zero if a request can be sent right now
+     */
+    pub fn wait_time(&self, now: Instant) -> Duration {
+        if let Some(until) = self.limited_until {
+            return until.saturating_duration_since(now);
+        }
+        if self.invalid_requests.len() >= INVALID_REQUEST_LIMIT - INVALID_REQUEST_SAFETY_MARGIN {
+            // Wait for the oldest invalid request to leave the window
+            let oldest = *self.invalid_requests.front().unwrap();
+            return (oldest + INVALID_REQUEST_WINDOW).saturating_duration_since(now);
+        }
+        if self.allowance < 1f64 {
+            return Duration::from_secs_f64((1f64 - self.allowance) / GLOBAL_RATE_LIMIT_PER_SEC);
+        }
+        Duration::ZERO
+    }
+
+    /// Returns true if a request can be sent right now
+    pub fn can_send(&self, now: Instant) -> bool {
+        self.wait_time(now).is_zero()
+    }
+
+    /// Uses up the allowance for a request that is being sent
+    pub fn acquire(&mut self) {
+        self.allowance -= 1f64;
+    }
+
+    /// Stops all requests until the given time
+    pub fn limit_until(&mut self, until: Instant) {
+        self.limited_until = Some(match self.limited_until {
+            Some(limited_until) => limited_until.max(until),
+            None => until,
+        });
+    }
+
+    /**
+     * Records a response, so that invalid requests are counted.
+     * 429s for shared resources (e.g. a busy channel) don't count, since the bot couldn't have avoided them
+     */
+    pub fn record_response(&mut self, status: StatusCode, headers: &HeaderMap) {
+        let invalid = match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => true,
+            StatusCode::TOO_MANY_REQUESTS => {
+                get_header_as::<String>(headers, "X-RateLimit-Scope").as_deref() != Some("shared")
+            }
+            _ => false,
+        };
+        if invalid {
+            self.invalid_requests.push_back(Instant::now());
+        }
+    }
+
+    /// A snapshot of the current limits
+    pub fn info(&self) -> RateLimitInfo {
+        RateLimitInfo {
+            global_limit: GLOBAL_RATE_LIMIT_PER_SEC as u32,
+            global_remaining: self.allowance.max(0f64) as u32,
+            globally_limited_until: self.limited_until,
+            invalid_requests: self.invalid_requests.len(),
+            invalid_request_limit: INVALID_REQUEST_LIMIT,
+        }
+    }
+}
+
+impl Default for GlobalLimiter {
+    fn default() -> Self {
+        GlobalLimiter::new()
+    }
+}
@@ -1,3 +1,4 @@
+pub mod global_limiter;
 pub mod rate_limit_client;
 mod request_bucket;
 mod request_future;
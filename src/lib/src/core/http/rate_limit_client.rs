@@ -1,15 +1,34 @@
-use hyper::{body::Body, header::AUTHORIZATION, Request};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::Duration,
+};
+
+use hyper::{
+    body::Body,
+    client::HttpConnector,
+    header::{HeaderName, HeaderValue, AUTHORIZATION, USER_AGENT},
+    Client, Request, Response, StatusCode,
+};
+use hyper_tls::HttpsConnector;
 use serde::de::DeserializeOwned;
 
 use crate::{
     core::abstraction::context::Context,
-    util::{error::Error, logger::print_debug},
+    util::{
+        error::{Error, ErrorTypes},
+        logger::print_debug,
+    },
+    BASE_URL,
 };
 
 use super::{
+    global_limiter::RateLimitInfo,
     request_future::{self},
     request_queue::HttpQueue,
-    request_thread,
+    request_thread::{self, RequestCopy},
 };
 
 use crossbeam_channel::{unbounded, Receiver, Sender};
@@ -24,19 +43,18 @@ unsafe impl Send for RequestRoute {}
 
 pub struct RequestObject {
     pub route: RequestRoute,
-    pub future: *mut request_future::HttpFuture,
+    pub request: request_future::RequestHandle,
 }
 
-unsafe impl Send for RequestObject {}
-
 impl RequestObject {
-    pub fn new(route: RequestRoute, future: *mut request_future::HttpFuture) -> RequestObject {
-        RequestObject { route, future }
+    pub fn new(route: RequestRoute, request: request_future::RequestHandle) -> RequestObject {
+        RequestObject { route, request }
     }
 }
 
 pub struct RLClient {
     sender: Sender<RequestObject>,
+    rate_limit_info: Arc<Mutex<RateLimitInfo>>,
 }
 
 impl RLClient {
@@ -45,7 +63,10 @@ impl RLClient {
         T: HttpQueue + Send + 'static,
     {
         let (s, r) = unbounded();
-        let mut c = RLClient { sender: s };
+        let mut c = RLClient {
+            sender: s,
+            rate_limit_info: Arc::new(Mutex::new(RateLimitInfo::default())),
+        };
         c.spawn_req_thread::<T>(queue, r);
         c
     }
@@ -58,94 +79,251 @@ impl RLClient {
     where
         T: HttpQueue + Send + 'static,
     {
-        request_thread::create_thread::<T>(queue, receiver);
+        request_thread::create_thread::<T>(queue, receiver, self.rate_limit_info.clone());
     }
 
     pub fn get_req_sender(&self) -> Sender<RequestObject> {
         self.sender.clone()
     }
+
+    /// The global limits, as kept up to date by the request thread
+    pub fn get_rate_limit_info(&self) -> Arc<Mutex<RateLimitInfo>> {
+        self.rate_limit_info.clone()
+    }
+}
+
+/// The user agent that is sent when `Settings::user_agent` isn't set
+const DEFAULT_USER_AGENT: &str = concat!(
+    "DiscordBot (https://github.com/KolCrooks/rust-discord-wrapper, ",
+    env!("CARGO_PKG_VERSION"),
+    ")"
+);
+/// How many times a request is sent when the local rate limiter is disabled and it keeps getting a 429
+const DIRECT_ATTEMPTS: u32 = 3;
+
+/// Adds the headers every request needs, and points the request at the rate limit proxy if one is set
+fn prepare_request(ctx: &Context, request: &mut Request<Body>) -> Result<(), Error> {
+    let settings = &ctx.settings;
+    let headers = request.headers_mut();
+    // Some endpoints (like executing a webhook) don't need a token
+    if !ctx.token.is_empty() {
+        headers.insert(AUTHORIZATION, format!("Bot {}", ctx.token).parse().unwrap());
+    }
+    let user_agent = settings.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+    headers.insert(USER_AGENT, parse_header_value(user_agent)?);
+    // Ask for the reset times in milliseconds. Newer api versions always do this, but some proxies still look at the header
+    headers.insert(
+        "X-RateLimit-Precision",
+        HeaderValue::from_static("millisecond"),
+    );
+
+    let proxy_url = match settings.proxy_url.as_deref() {
+        Some(proxy_url) => proxy_url,
+        None => return Ok(()),
+    };
+    let uri = request.uri().to_string();
+    // Only requests to discord go through the proxy (e.g. not the ones to attachment urls)
+    let path = match uri.strip_prefix(BASE_URL) {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    *request.uri_mut() = format!("{}{}", proxy_url, path).parse().map_err(|_| {
+        Error::new(
+            format!("Invalid proxy url: {}", proxy_url),
+            ErrorTypes::REQUEST,
+        )
+    })?;
+    for (name, value) in &settings.proxy_headers {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+            Error::new(format!("Invalid proxy header: {}", name), ErrorTypes::REQUEST)
+        })?;
+        request.headers_mut().insert(name, parse_header_value(value)?);
+    }
+    Ok(())
+}
+
+fn parse_header_value(value: &str) -> Result<HeaderValue, Error> {
+    value.parse().map_err(|_| {
+        Error::new(
+            format!("Invalid header value: {}", value),
+            ErrorTypes::REQUEST,
+        )
+    })
+}
+
+/// Counts a request as pending until it is answered (or given up on), so that shutting down can wait for it
+struct PendingRequest(Arc<AtomicUsize>);
+
+impl PendingRequest {
+    fn start(in_flight: &Arc<AtomicUsize>) -> Self {
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        Self(in_flight.clone())
+    }
+}
+
+impl Drop for PendingRequest {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 /**
- * Send a request. This will queue the request and then execute when it is able to.
- *
+ * Sends a request through the request thread, or straight away if the local rate limiter is disabled.
  * @param route The route identifier that the request belongs to
- * @param request The request to send
- * @return The response from discord
+ * @param request The request to send. The headers are added here
  */
-pub async fn send_request<T: DeserializeOwned>(
-    ctx: Context,
+async fn execute(
+    ctx: &Context,
     route: RequestRoute,
     mut request: Request<Body>,
-) -> Result<T, Error> {
-    request
-        .headers_mut()
-        .insert(AUTHORIZATION, format!("Bot {}", ctx.token).parse().unwrap());
+) -> Result<Response<Body>, Error> {
+    let _pending = PendingRequest::start(&ctx.in_flight);
+    prepare_request(ctx, &mut request)?;
+    if ctx.settings.disable_local_rate_limiter {
+        return send_direct(request).await;
+    }
 
-    let mut future = request_future::HttpFuture::new(request);
+    let future = request_future::HttpFuture::new(request);
     // TODO Maybe use req_thread.unpark() to reduce cpu load while the thread is waiting for requests.
     // This would have the downside of increasing the power required make a request since we have to attempt to unpark it every time.
     // We could maybe get around this by having a parked flag, but this would require a mutex which also increases the power required.
     ctx.request_stream
-        .send(RequestObject::new(route, &mut future as *mut _))
+        .send(RequestObject::new(route, future.handle()))
         .unwrap();
+    Ok(future.await?)
+}
+
+/**
+ * Sends a request without going through the local rate limiter, for when a proxy is doing the rate limiting.
+ * The proxy can still pass a 429 on from discord, in which case the request is sent again once the rate limit is over.
+ */
+async fn send_direct(request: Request<Body>) -> Result<Response<Body>, Error> {
+    static CLIENT: OnceLock<Client<HttpsConnector<HttpConnector>>> = OnceLock::new();
+    let client = CLIENT.get_or_init(|| Client::builder().build(HttpsConnector::new()));
+
+    // Keep a copy of the request, since sending it consumes it
+    let (parts, body) = request.into_parts();
+    let copy = RequestCopy::from_parts(parts, hyper::body::to_bytes(body).await?);
 
-    let res = match future.await {
+    let mut attempt = 1;
+    loop {
+        let response = client.request(copy.build()).await?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt == DIRECT_ATTEMPTS {
+            return Ok(response);
+        }
+        let headers = response.headers().clone();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        let (retry_after, _) = request_thread::parse_rate_limited(&headers, &body);
+        tokio::time::sleep(Duration::from_secs_f64(retry_after.max(0f64))).await;
+        attempt += 1;
+    }
+}
+
+/**
+ * Send a request. This will queue the request and then execute when it is able to.
+ *
+ * @param route The route identifier that the request belongs to
+ * @param request The request to send
+ * @return The response from discord
+ */
+pub async fn send_request<T: DeserializeOwned>(
+    ctx: Context,
+    route: RequestRoute,
+    request: Request<Body>,
+) -> Result<T, Error> {
+    let res = match execute(&ctx, route, request).await {
         Ok(res) => res,
         Err(e) => {
             if ctx.settings.debug {
                 print_debug("REQUEST", format!("Error: {:?}", e));
             }
-            return Err(Error::new(
-                format!("{:?}", e),
-                crate::util::error::ErrorTypes::REQUEST,
-            ));
+            return Err(e);
         }
     };
-    let bytes = hyper::body::to_bytes(res).await.unwrap();
+    let status = res.status();
+    let bytes = hyper::body::to_bytes(res).await?;
+    if !status.is_success() {
+        let e = Error::from_response(status.as_u16(), &bytes);
+        if ctx.settings.debug {
+            print_debug("REQUEST", format!("Error: {:?}", e));
+        }
+        return Err(e);
+    }
 
     serde_json::from_slice::<T>(&bytes.to_vec()).map_err(|e| {
         if ctx.settings.debug {
             print_debug("REQUEST", format!("Error: {:?}", e));
         }
-        Error::new(format!("{:?}", e), crate::util::error::ErrorTypes::PARSE)
+        e.into()
     })
 }
 
 /**
  * Send a request. This will queue the request and then execute when it is able to.
- * This function will not try to parse the response
+ * This function will not try to parse the response, other than discord's error when the request failed
  * @param route The route identifier that the request belongs to
  * @param request The request to send
- * @return The response from discord
+ * @return Ok if discord answered with a 2xx
  */
 pub async fn send_request_noparse(
     ctx: Context,
     route: RequestRoute,
-    mut request: Request<Body>,
+    request: Request<Body>,
 ) -> Result<(), Error> {
-    request
-        .headers_mut()
-        .insert(AUTHORIZATION, format!("Bot {}", ctx.token).parse().unwrap());
+    let error = match execute(&ctx, route, request).await {
+        Ok(res) if res.status().is_success() => return Ok(()),
+        Ok(res) => {
+            let status = res.status().as_u16();
+            let bytes = hyper::body::to_bytes(res).await?;
+            Error::from_response(status, &bytes)
+        }
+        Err(e) => e,
+    };
+    if ctx.settings.debug {
+        print_debug("REQUEST", format!("Error: {:?}", error));
+    }
+    Err(error)
+}
 
-    let mut future = request_future::HttpFuture::new(request);
-    // TODO Maybe use req_thread.unpark() to reduce cpu load while the thread is waiting for requests.
-    // This would have the downside of increasing the power required make a request since we have to attempt to unpark it every time.
-    // We could maybe get around this by having a parked flag, but this would require a mutex which also increases the power required.
-    ctx.request_stream
-        .send(RequestObject::new(route, &mut future as *mut _))
-        .unwrap();
+/// How many times an idempotent request is attempted before giving up
+const IDEMPOTENT_ATTEMPTS: u32 = 3;
 
-    match future.await {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            if ctx.settings.debug {
-                print_debug("REQUEST", format!("Error: {:?}", e));
+/**
+ * Send a request that is safe to repeat, like adding a role to a member or adding a reaction.
+ * Discord answers these with a 204 whether or not the role/reaction was already there, so any 2xx counts as success.
+ * Because repeating the request can't do anything twice, it is retried when the connection fails or discord returns a 5xx.
+ * @param route The route identifier that the request belongs to
+ * @param make_request Builds the request. It is called again for every attempt, since a request can only be sent once.
+ */
+pub async fn send_request_idempotent(
+    ctx: Context,
+    route: RequestRoute,
+    make_request: impl Fn() -> Request<Body>,
+) -> Result<(), Error> {
+    let mut attempt = 1;
+    loop {
+        let error: Error = match execute(&ctx, route.clone(), make_request()).await {
+            Ok(res) if res.status().is_success() => return Ok(()),
+            Ok(res) if res.status().is_server_error() => Error::new(
+                format!("Request failed with status {}", res.status()),
+                ErrorTypes::REQUEST,
+            ),
+            Ok(res) => {
+                // A 4xx won't go away by retrying
+                let status = res.status().as_u16();
+                let bytes = hyper::body::to_bytes(res).await.unwrap_or_default();
+                return Err(Error::from_response(status, &bytes));
             }
-            return Err(Error::new(
-                format!("{:?}", e),
-                crate::util::error::ErrorTypes::REQUEST,
-            ));
+            Err(e) => e,
+        };
+
+        if ctx.settings.debug {
+            print_debug("REQUEST", format!("Attempt {} failed: {}", attempt, error));
+        }
+        if attempt == IDEMPOTENT_ATTEMPTS {
+            return Err(error);
         }
+        tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64)).await;
+        attempt += 1;
     }
 }
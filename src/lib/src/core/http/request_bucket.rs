@@ -1,5 +1,6 @@
-#[derive(PartialEq)]
+use std::time::Instant;
 
+#[derive(PartialEq)]
 /// Contains information on an http bucket so that the route can be rate limited
 pub struct Bucket {
     /// The maxiumum number of requests that can be made in the bucket
@@ -7,7 +8,7 @@ pub struct Bucket {
     /// The number of requests that are remaining in the bucket
     pub remaining_requests: i32,
     /// The time that the buckets rate limit will reset
-    pub reset_at: i64,
+    pub reset_at: Instant,
 }
 
 impl Bucket {
@@ -15,7 +16,14 @@ impl Bucket {
         Self {
             max_requests: 1,
             remaining_requests: 1,
-            reset_at: 0,
+            reset_at: Instant::now(),
+        }
+    }
+
+    /// Refills the bucket if its reset time has passed
+    pub fn refill(&mut self, now: Instant) {
+        if self.reset_at <= now {
+            self.remaining_requests = self.max_requests;
         }
     }
 }
@@ -1,7 +1,7 @@
 use std::{
     future::Future,
     pin::Pin,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, Weak},
     task::{Context, Poll, Waker},
 };
 
@@ -48,6 +48,48 @@ impl HttpFuture {
 
         HttpFuture { shared_state }
     }
+
+    /// Creates the handle that is sent to the http client
+    pub fn handle(&self) -> RequestHandle {
+        RequestHandle {
+            shared_state: Arc::downgrade(&self.shared_state),
+        }
+    }
+}
+
+/// The http client's side of a request. It only holds a weak reference to the request state, so if the future is dropped
+/// while the request is still queued, the request is dropped with it and the client skips it instead of touching freed memory.
+pub struct RequestHandle {
+    shared_state: Weak<Mutex<RequestState>>,
+}
+
+impl RequestHandle {
+    /// Takes the request out of the state so that it can be sent.
+    /// @return None if the future was dropped (or the request was already taken)
+    pub fn take_request(&self) -> Option<Request<Body>> {
+        let shared_state = self.shared_state.upgrade()?;
+        let mut shared_state = shared_state.lock().unwrap();
+        shared_state.request.take()
+    }
+
+    /// Puts a request back into the state, so that it can be sent again (e.g. after being rate limited)
+    pub fn restore_request(&self, request: Request<Body>) {
+        if let Some(shared_state) = self.shared_state.upgrade() {
+            shared_state.lock().unwrap().request = Some(request);
+        }
+    }
+
+    /// Returns true if nobody is waiting for the response anymore
+    pub fn is_cancelled(&self) -> bool {
+        self.shared_state.strong_count() == 0
+    }
+
+    /// Hands the response to the future. Does nothing if the future was dropped
+    pub fn commit(&self, response: Result<hyper::Response<Body>, Error>) {
+        if let Some(shared_state) = self.shared_state.upgrade() {
+            shared_state.lock().unwrap().commit(response);
+        }
+    }
 }
 
 impl Future for HttpFuture {
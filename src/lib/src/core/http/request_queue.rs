@@ -3,16 +3,16 @@ use std::{
     time::Instant,
 };
 
-use super::{rate_limit_client::RequestRoute, request_future};
+use super::{rate_limit_client::RequestRoute, request_future::RequestHandle};
 
 /// This is a generic queue that supplies the http client with requests in a given order as designated by the queue.
 pub trait HttpQueue {
     /**
      * Add a request to the queue
      * @param route The route of the request
-     * @param future The request
+     * @param request The request
      */
-    fn push(&mut self, route: &RequestRoute, future: *mut request_future::HttpFuture);
+    fn push(&mut self, route: &RequestRoute, request: RequestHandle);
     /// Get the requests as sorted by the queue
     fn get_sorted_requests(&self) -> Vec<RequestRoute>;
     /// Get the queue for a given route
@@ -30,7 +30,7 @@ pub struct BucketQueue {
     /// The time that the bucket became empty
     time_of_empty: Instant,
     /// The queue of requests
-    queue: LinkedList<(u64, *mut request_future::HttpFuture)>,
+    queue: LinkedList<(u64, RequestHandle)>,
 }
 
 impl BucketQueue {
@@ -44,19 +44,21 @@ impl BucketQueue {
     /**
      * Add a request to the queue.
      * @param time The time that the request was added
-     * @param future The request future
+     * @param request The request
      */
-    pub fn push(&mut self, time: u64, future: *mut request_future::HttpFuture) {
-        self.queue.push_back((time, future));
+    pub fn push(&mut self, time: u64, request: RequestHandle) {
+        self.queue.push_back((time, request));
     }
 
     /// Get the oldest request in the queue
-    pub fn get_oldest(&self) -> Option<&(u64, *mut request_future::HttpFuture)> {
+    pub fn get_oldest(&self) -> Option<&(u64, RequestHandle)> {
         self.queue.front()
     }
 
-    /// Removes the first request in the queue, and returns the request
-    pub fn pop(&mut self) -> Option<(u64, *mut request_future::HttpFuture)> {
+    /// Removes the first request in the queue, and returns the request.
+    /// Requests whose future was dropped while they were waiting are skipped
+    pub fn pop(&mut self) -> Option<(u64, RequestHandle)> {
+        self.remove_cancelled();
         self.queue.pop_front()
     }
 
@@ -65,6 +67,17 @@ impl BucketQueue {
         self.queue.is_empty()
     }
 
+    /// Removes the requests at the front of the queue that nobody is waiting for anymore
+    fn remove_cancelled(&mut self) {
+        while self
+            .queue
+            .front()
+            .is_some_and(|(_, request)| request.is_cancelled())
+        {
+            self.queue.pop_front();
+        }
+    }
+
     /// Returns the time that the bucket became empty
     pub fn get_time_of_empty(&self) -> Instant {
         self.time_of_empty
@@ -88,7 +101,6 @@ pub struct BasicHttpQueue {
 
     active_requests_set: HashSet<RequestRoute>,
 }
-unsafe impl Send for BasicHttpQueue {}
 
 impl BasicHttpQueue {
     /**
@@ -106,13 +118,13 @@ impl BasicHttpQueue {
 
 impl HttpQueue for BasicHttpQueue {
     /// Add a request to the queue
-    fn push(&mut self, route: &RequestRoute, future: *mut request_future::HttpFuture) {
+    fn push(&mut self, route: &RequestRoute, request: RequestHandle) {
         let queue = self
             .queue_map
             .entry(route.clone())
             .or_insert_with(BucketQueue::new);
 
-        queue.push(self.req_id_cnt, future);
+        queue.push(self.req_id_cnt, request);
         self.req_id_cnt += 1;
         self.active_requests_set.insert(route.clone());
     }
@@ -158,3 +170,54 @@ impl HttpQueue for BasicHttpQueue {
         !self.queue_map.iter().any(|v| !v.1.is_empty())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Body, Request};
+
+    use super::BucketQueue;
+    use crate::core::http::request_future::HttpFuture;
+
+    fn future(path: &str) -> HttpFuture {
+        HttpFuture::new(Request::get(path).body(Body::empty()).unwrap())
+    }
+
+    /// The path of the next request that pop returns
+    fn pop_path(queue: &mut BucketQueue) -> Option<String> {
+        let (_, handle) = queue.pop()?;
+        Some(handle.take_request().unwrap().uri().path().to_string())
+    }
+
+    #[test]
+    fn pop_skips_dropped_futures() {
+        let futures: Vec<_> = ["/a", "/b", "/c", "/d", "/e"]
+            .iter()
+            .map(|path| future(path))
+            .collect();
+        let mut queue = BucketQueue::new();
+        for (time, future) in futures.iter().enumerate() {
+            queue.push(time as u64, future.handle());
+        }
+
+        // Drop the first one, one in the middle, and the last one while they are queued
+        let mut futures: Vec<_> = futures.into_iter().map(Some).collect();
+        futures[0] = None;
+        futures[2] = None;
+        futures[4] = None;
+
+        assert_eq!(pop_path(&mut queue).as_deref(), Some("/b"));
+        assert_eq!(pop_path(&mut queue).as_deref(), Some("/d"));
+        assert_eq!(pop_path(&mut queue), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn pop_returns_none_when_every_future_was_dropped() {
+        let mut queue = BucketQueue::new();
+        for time in 0..3 {
+            queue.push(time, future("/a").handle());
+        }
+        assert!(queue.pop().is_none());
+        assert!(queue.is_empty());
+    }
+}
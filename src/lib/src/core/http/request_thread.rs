@@ -1,28 +1,143 @@
-use std::{collections::HashMap, thread, time::Instant};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use crossbeam_channel::Receiver;
-use hyper::{client::ResponseFuture, Client};
+use hyper::{
+    body::Bytes, client::ResponseFuture, header::HeaderMap, http::request::Parts, Body, Client,
+    Method, Request, StatusCode, Uri, Version,
+};
 use hyper_tls::HttpsConnector;
+use serde::Deserialize;
 
 use crate::util::requests::get_header_as;
 
 use super::{
+    global_limiter::{GlobalLimiter, RateLimitInfo},
     rate_limit_client::{RequestObject, RequestRoute},
     request_bucket,
-    request_future::{self},
+    request_future::RequestHandle,
     request_queue::HttpQueue,
 };
 
-const GLOBAL_RATE_LIMIT_PER_SEC: f64 = 50f64;
 const CLEAN_EVERY_N_REQUESTS: u64 = 10_000;
 
+/// The body of a 429 response
+/// @docs <https://discord.com/developers/docs/topics/rate-limits#exceeding-a-rate-limit>
+#[derive(Deserialize)]
+struct RateLimitedBody {
+    /// The number of seconds to wait before making another request
+    retry_after: f64,
+    /// If true, every request is rate limited, not only the ones to this bucket
+    #[serde(default)]
+    global: bool,
+}
+
+/// A copy of a request that was sent, so that it can be sent again if it gets rate limited.
+/// Hyper requests can only be sent once, since sending them consumes their body.
+pub(super) struct RequestCopy {
+    method: Method,
+    uri: Uri,
+    version: Version,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl RequestCopy {
+    fn new(request: Request<Body>) -> Self {
+        let (parts, body) = request.into_parts();
+        // The bodies are built in memory, so this doesn't actually wait for anything
+        let body = async_std::task::block_on(hyper::body::to_bytes(body)).unwrap_or_default();
+        Self::from_parts(parts, body)
+    }
+
+    pub(super) fn from_parts(parts: Parts, body: Bytes) -> Self {
+        Self {
+            method: parts.method,
+            uri: parts.uri,
+            version: parts.version,
+            headers: parts.headers,
+            body,
+        }
+    }
+
+    /// Builds a new request that is the same as the original one
+    pub(super) fn build(&self) -> Request<Body> {
+        let mut request = Request::new(Body::from(self.body.clone()));
+        *request.method_mut() = self.method.clone();
+        *request.uri_mut() = self.uri.clone();
+        *request.version_mut() = self.version;
+        *request.headers_mut() = self.headers.clone();
+        request
+    }
+}
+
+/**
+ * Reads how long to wait after a 429
+ * @param headers The headers of the 429 response
+ * @param body The body of the 429 response
+ * @return The number of seconds to wait, and whether every request is rate limited
+ */
+pub(super) fn parse_rate_limited(headers: &HeaderMap, body: &[u8]) -> (f64, bool) {
+    let global_header = get_header_as::<bool>(headers, "X-RateLimit-Global").unwrap_or(false);
+    match serde_json::from_slice::<RateLimitedBody>(body) {
+        Ok(body) => (body.retry_after, body.global || global_header),
+        // Some proxies answer with an empty body, so fall back to the header
+        Err(_) => (
+            get_header_as::<f64>(headers, "Retry-After").unwrap_or(1f64),
+            global_header,
+        ),
+    }
+}
+
+/// The number of seconds until the bucket resets. Falls back to the absolute `X-RateLimit-Reset`
+/// for proxies that don't pass `X-RateLimit-Reset-After` on
+fn reset_after(headers: &HeaderMap) -> f64 {
+    if let Some(reset_after) = get_header_as::<f64>(headers, "X-RateLimit-Reset-After") {
+        return reset_after;
+    }
+    match get_header_as::<f64>(headers, "X-RateLimit-Reset") {
+        Some(reset) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64();
+            reset - now
+        }
+        None => 0f64,
+    }
+}
+
+/**
+ * Waits until a new request comes in or the timeout is over. New requests are added to the queue.
+ * @param timeout The longest time to wait
+ */
+fn wait_for_requests<T: HttpQueue>(
+    http_queue: &mut T,
+    receiver: &Receiver<RequestObject>,
+    timeout: Duration,
+) {
+    if let Ok(obj) = receiver.recv_timeout(timeout) {
+        http_queue.push(&obj.route, obj.request);
+    }
+}
+
 /**
  * Creates the request thread that will batch requests out according to rate limit headers that are returned by discord, and also the
- * Global rate limit of GLOBAL_RATE_LIMIT_PER_SEC
+ * Global rate limit of GLOBAL_RATE_LIMIT_PER_SEC.
+ * Requests that are rate limited anyway (429) are put back into the queue, and sent again once the rate limit is over.
  * @param send_queue The Shared Queue that requests can be added to
+ * @param rate_limit_info Kept up to date with the global limits, so that they can be read from the context
+ * @docs <https://discord.com/developers/docs/topics/rate-limits>
  */
-pub fn create_thread<T>(mut http_queue: T, receiver: Receiver<RequestObject>)
-where
+pub fn create_thread<T>(
+    mut http_queue: T,
+    receiver: Receiver<RequestObject>,
+    rate_limit_info: Arc<Mutex<RateLimitInfo>>,
+) where
     T: HttpQueue + Send + 'static,
 {
     thread::Builder::new()
@@ -31,8 +146,7 @@ where
             let https = HttpsConnector::new();
             let client = Client::builder().build::<_, hyper::Body>(https);
 
-            let mut global_allowance: f64 = GLOBAL_RATE_LIMIT_PER_SEC;
-            let mut last_timestamp = Instant::now();
+            let mut limiter = GlobalLimiter::new();
             let mut requests_sent: u64 = 0;
 
             // TODO: Clean the buckets at certain times, also clean the send_queue so that the hashmap doesn't continuously grow in size
@@ -45,12 +159,12 @@ where
             loop {
                 if http_queue.is_empty() {
                     let obj = receiver.recv().unwrap();
-                    http_queue.push(&obj.route, obj.future);
+                    http_queue.push(&obj.route, obj.request);
                 }
                 // Add incoming requests to the queue
                 while !receiver.is_empty() {
                     let obj = receiver.recv().unwrap();
-                    http_queue.push(&obj.route, obj.future);
+                    http_queue.push(&obj.route, obj.request);
                 }
 
                 // TODO Figure out a smarter way to do this
@@ -60,25 +174,28 @@ where
                 // }
 
                 // Add more allowance to the global limit
-                let temp_time = Instant::now();
-                global_allowance += temp_time.duration_since(last_timestamp).as_secs_f64()
-                    * GLOBAL_RATE_LIMIT_PER_SEC;
+                let now = Instant::now();
+                limiter.refill(now);
+                *rate_limit_info.lock().unwrap() = limiter.info();
 
-                if global_allowance > GLOBAL_RATE_LIMIT_PER_SEC {
-                    global_allowance = GLOBAL_RATE_LIMIT_PER_SEC;
-                    thread::yield_now();
+                // Globally rate limited, out of global allowance, or too close to the invalid request limit
+                let global_wait = limiter.wait_time(now);
+                if !global_wait.is_zero() {
+                    wait_for_requests(&mut http_queue, &receiver, global_wait);
+                    continue;
                 }
 
-                last_timestamp = Instant::now();
-
                 let sorted_routes = http_queue.get_sorted_requests();
 
                 let mut responses: Vec<(
                     RequestRoute,
-                    &mut request_future::HttpFuture,
+                    RequestHandle,
+                    RequestCopy,
                     ResponseFuture,
                     String,
                 )> = Vec::new();
+                // The earliest time that one of the waiting buckets resets
+                let mut next_reset: Option<Instant> = None;
 
                 // Iterate through all of the requests in the queue, and add them to the futures vector if they can be executed
                 for route in sorted_routes {
@@ -92,28 +209,32 @@ where
                     };
 
                     // Reset the bucket if it is past the reset time
-                    if bucket.1.reset_at < chrono::Utc::now().timestamp() {
-                        bucket.1.remaining_requests = bucket.1.max_requests;
-                    }
+                    bucket.1.refill(now);
 
                     // get the queue for the route, and then get as many requests as possible from the queue
                     // This means it will take min(global_limit, bucket.remaining_requests) requests from the queue
                     let queue = http_queue.get_bucket_queue(&route).unwrap();
-                    while bucket.1.remaining_requests > 0 && global_allowance >= 1f64 {
+                    while bucket.1.remaining_requests > 0 && limiter.can_send(now) {
                         // Pop the front and add it to the futures vector if it exists, or break out if the queue is empty
                         match queue.pop() {
-                            Some((_, req_future)) => {
-                                let future_ptr = unsafe { &mut *req_future };
-
-                                let req = {
-                                    let mut shared_state = future_ptr.shared_state.lock().unwrap();
-                                    client.request(shared_state.request.take().unwrap())
+                            Some((_, handle)) => {
+                                // The future may have been dropped since it was popped, in which case there is nothing to send
+                                let copy = match handle.take_request() {
+                                    Some(request) => RequestCopy::new(request),
+                                    None => continue,
                                 };
-                                responses.push((route.clone(), future_ptr, req, bucket.0.clone()));
+                                let req = client.request(copy.build());
+                                responses.push((
+                                    route.clone(),
+                                    handle,
+                                    copy,
+                                    req,
+                                    bucket.0.clone(),
+                                ));
                                 requests_sent += 1;
 
                                 bucket.1.remaining_requests -= 1;
-                                global_allowance -= 1f64;
+                                limiter.acquire();
                             }
                             None => {
                                 break;
@@ -122,85 +243,122 @@ where
                     }
                     if queue.is_empty() {
                         http_queue.notify_empty(&route);
+                    } else if bucket.1.remaining_requests <= 0 {
+                        next_reset = Some(match next_reset {
+                            Some(next_reset) => next_reset.min(bucket.1.reset_at),
+                            None => bucket.1.reset_at,
+                        });
                     }
-                    if global_allowance < 1f64 {
+                    if !limiter.can_send(now) {
                         break;
                     }
                 }
 
-                // Convert the requests into a vector of response futures by having the hyper client make them
+                // Nothing could be sent, so sleep until a bucket resets or a new request comes in instead of spinning
+                if responses.is_empty() {
+                    let wait = next_reset
+                        .map(|reset_at| reset_at.saturating_duration_since(now))
+                        .unwrap_or_default();
+                    wait_for_requests(&mut http_queue, &receiver, wait);
+                    continue;
+                }
 
                 let mut last_date_map: HashMap<RequestRoute, i64> = HashMap::new();
 
                 // Collect the responses, and resolve all of the Request Futures
-                for (route, req, future, bucket_name) in responses {
+                for (route, req, copy, future, bucket_name) in responses {
                     // Block execution until the future is resolved, and then process the rate limit information from the response
                     // TODO figure out how to make this run in parallel
-                    let receives = match async_std::task::block_on(future) {
-                        Ok(received) => {
-                            // Get the date of the response execution so that we know the last time the route was used,
-                            // And therefore the most up to date rate limit information for each route
-                            let date_raw = received.headers().get("Date").unwrap().as_bytes();
-                            let date = chrono::DateTime::parse_from_rfc2822(
-                                std::str::from_utf8(date_raw).unwrap(),
-                            )
-                            .unwrap()
-                            .timestamp();
-
-                            // Only update rate limit information if this request is more recent than the rest
-                            if date > *last_date_map.get(&route).or(Some(&0)).unwrap() {
-                                last_date_map.insert(route.clone(), date);
-
-                                let remaining_requests = get_header_as::<i32>(
-                                    received.headers(),
-                                    "X-RateLimit-Remaining",
-                                )
-                                .unwrap_or(0);
-
-                                let max_requests =
-                                    get_header_as::<i32>(received.headers(), "X-RateLimit-Limit")
-                                        .unwrap_or(1);
-
-                                let reset_at =
-                                    get_header_as::<i64>(received.headers(), "X-RateLimit-Reset")
-                                        .unwrap_or(0); // TODO make this an actual value
-
-                                let bucket = if bucket_name == "UNKNOWN" {
-                                    let bucket_name = get_header_as::<String>(
-                                        received.headers(),
-                                        "X-RateLimit-Bucket",
-                                    );
-
-                                    rate_buckets.get_mut("UNKNOWN").unwrap().remaining_requests = 1;
-                                    if let Some(bucket_name) = bucket_name {
-                                        route_to_bucket
-                                            .insert(route.clone(), bucket_name.to_string());
-                                        Some(rate_buckets.entry(bucket_name).or_insert_with(|| {
-                                            request_bucket::Bucket {
-                                                max_requests,
-                                                remaining_requests,
-                                                reset_at,
-                                            }
-                                        }))
-                                    } else {
-                                        None
+                    let received = match async_std::task::block_on(future) {
+                        Ok(received) => received,
+                        Err(e) => {
+                            req.commit(Err(e));
+                            continue;
+                        }
+                    };
+                    let headers = received.headers();
+                    limiter.record_response(received.status(), headers);
+
+                    // Get the date of the response execution so that we know the last time the route was used,
+                    // And therefore the most up to date rate limit information for each route
+                    let date = headers
+                        .get("Date")
+                        .and_then(|date| date.to_str().ok())
+                        .and_then(|date| chrono::DateTime::parse_from_rfc2822(date).ok())
+                        .map(|date| date.timestamp())
+                        .unwrap_or(i64::MAX);
+
+                    // Only update rate limit information if this request is more recent than the rest
+                    if date > *last_date_map.get(&route).or(Some(&0)).unwrap() {
+                        last_date_map.insert(route.clone(), date);
+
+                        let remaining_requests =
+                            get_header_as::<i32>(headers, "X-RateLimit-Remaining").unwrap_or(0);
+
+                        let max_requests =
+                            get_header_as::<i32>(headers, "X-RateLimit-Limit").unwrap_or(1);
+
+                        // Reset-After is relative, so it doesn't depend on the clocks being in sync
+                        let reset_at = Instant::now()
+                            + Duration::from_secs_f64(reset_after(headers).max(0f64));
+
+                        let bucket = if bucket_name == "UNKNOWN" {
+                            let bucket_name =
+                                get_header_as::<String>(headers, "X-RateLimit-Bucket");
+
+                            rate_buckets.get_mut("UNKNOWN").unwrap().remaining_requests = 1;
+                            if let Some(bucket_name) = bucket_name {
+                                route_to_bucket.insert(route.clone(), bucket_name.to_string());
+                                Some(rate_buckets.entry(bucket_name).or_insert_with(|| {
+                                    request_bucket::Bucket {
+                                        max_requests,
+                                        remaining_requests,
+                                        reset_at,
                                     }
-                                } else {
-                                    rate_buckets.get_mut(&bucket_name)
-                                };
-                                if let Some(bucket) = bucket {
-                                    bucket.remaining_requests = remaining_requests;
-                                    bucket.max_requests = max_requests;
-                                    bucket.reset_at = reset_at;
-                                }
+                                }))
+                            } else {
+                                None
                             }
-                            Ok(received)
+                        } else {
+                            rate_buckets.get_mut(&bucket_name)
+                        };
+                        if let Some(bucket) = bucket {
+                            bucket.remaining_requests = remaining_requests;
+                            bucket.max_requests = max_requests;
+                            bucket.reset_at = reset_at;
                         }
-                        Err(e) => Err(e),
-                    };
+                    }
+
+                    if received.status() != StatusCode::TOO_MANY_REQUESTS {
+                        req.commit(Ok(received));
+                        continue;
+                    }
 
-                    let mut shared_state = req.shared_state.lock().unwrap();
-                    shared_state.commit(receives);
+                    // Rate limited anyway, so wait for as long as discord says and then send the request again
+                    let headers = headers.clone();
+                    let body =
+                        async_std::task::block_on(hyper::body::to_bytes(received.into_body()))
+                            .unwrap_or_default();
+                    let (retry_after, global) = parse_rate_limited(&headers, &body);
+                    let reset_at = Instant::now() + Duration::from_secs_f64(retry_after.max(0f64));
+
+                    if global {
+                        limiter.limit_until(reset_at);
+                    } else {
+                        let bucket_name = route_to_bucket
+                            .get(&route)
+                            .cloned()
+                            .unwrap_or_else(|| "UNKNOWN".to_string());
+                        if let Some(bucket) = rate_buckets.get_mut(&bucket_name) {
+                            bucket.remaining_requests = 0;
+                            bucket.reset_at = reset_at;
+                        }
+                    }
+
+                    if !req.is_cancelled() {
+                        req.restore_request(copy.build());
+                        http_queue.push(&route, req);
+                    }
                 }
             }
         })
@@ -0,0 +1,754 @@
+use std::{fmt::Display, io::Read};
+
+use flate2::read::ZlibDecoder;
+use serde::de::{
+    self, value::SeqDeserializer, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess,
+    SeqAccess, Visitor,
+};
+use serde_json::{Map, Number, Value};
+
+// The erlang term format (ETF) that the gateway speaks with `encoding=etf`. Messages are deserialized straight from it,
+// the same way serde_json deserializes json, so that the rest of the bot doesn't care which encoding the connection uses.
+// https://www.erlang.org/doc/apps/erts/erl_ext_dist.html
+
+const FORMAT_VERSION: u8 = 131;
+
+const NEW_FLOAT_EXT: u8 = 70;
+const COMPRESSED: u8 = 80;
+const SMALL_INTEGER_EXT: u8 = 97;
+const INTEGER_EXT: u8 = 98;
+const FLOAT_EXT: u8 = 99;
+const ATOM_EXT: u8 = 100;
+const SMALL_TUPLE_EXT: u8 = 104;
+const LARGE_TUPLE_EXT: u8 = 105;
+const NIL_EXT: u8 = 106;
+const STRING_EXT: u8 = 107;
+const LIST_EXT: u8 = 108;
+const BINARY_EXT: u8 = 109;
+const SMALL_BIG_EXT: u8 = 110;
+const LARGE_BIG_EXT: u8 = 111;
+const SMALL_ATOM_EXT: u8 = 115;
+const MAP_EXT: u8 = 116;
+const ATOM_UTF8_EXT: u8 = 118;
+const SMALL_ATOM_UTF8_EXT: u8 = 119;
+
+/// The most that compressed data can inflate to. The size comes from the data, so it can't be trusted
+const MAX_INFLATED: usize = 64 * 1024 * 1024;
+
+/// Deserializes a whole gateway message.
+/// Atoms are strings (except `nil`, `true` and `false`), binaries are strings and big integers are numbers
+/// @docs <https://discord.com/developers/docs/topics/gateway#etfjson>
+pub(crate) fn from_slice<T: DeserializeOwned>(data: &[u8]) -> Result<T, String> {
+    let mut decoder = Decoder { data, pos: 0 };
+    if decoder.u8()? != FORMAT_VERSION {
+        return Err("Unknown ETF version".to_string());
+    }
+    T::deserialize(&mut decoder).map_err(|e| e.0)
+}
+
+/// Decodes a whole gateway message into a json value
+#[cfg(test)]
+pub(crate) fn decode(data: &[u8]) -> Result<Value, String> {
+    let mut decoder = Decoder { data, pos: 0 };
+    if decoder.u8()? != FORMAT_VERSION {
+        return Err("Unknown ETF version".to_string());
+    }
+    decoder.term()
+}
+
+/// Encodes a payload to send to the gateway
+pub(crate) fn encode(value: &Value) -> Vec<u8> {
+    let mut out = vec![FORMAT_VERSION];
+    encode_term(value, &mut out);
+    out
+}
+
+#[derive(Debug)]
+pub(crate) struct EtfError(String);
+
+impl Display for EtfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for EtfError {}
+
+impl de::Error for EtfError {
+    fn custom<T: Display>(msg: T) -> Self {
+        EtfError(msg.to_string())
+    }
+}
+
+impl From<String> for EtfError {
+    fn from(e: String) -> Self {
+        EtfError(e)
+    }
+}
+
+/// An integer, which is signed only when it has to be
+enum Integer {
+    Unsigned(u64),
+    Signed(i64),
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| "Unexpected end of the ETF data".to_string())?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<usize, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+    }
+
+    fn u32(&mut self) -> Result<usize, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        let bytes = self.take(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn new_float(&mut self) -> Result<f64, String> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(f64::from_be_bytes(bytes))
+    }
+
+    /// The old float format, a float printed as text
+    fn old_float(&mut self) -> Result<f64, String> {
+        let text = self.string(31)?;
+        let text = text.trim_end_matches('\0');
+        text.parse()
+            .map_err(|_| format!("Invalid ETF float {}", text))
+    }
+
+    fn string(&mut self, len: usize) -> Result<String, String> {
+        let bytes = self.take(len)?;
+        // Binaries aren't always valid utf8, but everything discord sends as one is
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// The text of an atom or binary, after its tag
+    fn text(&mut self, tag: u8) -> Result<&'a [u8], String> {
+        let len = match tag {
+            ATOM_EXT | ATOM_UTF8_EXT => self.u16()?,
+            SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT => self.u8()? as usize,
+            _ => self.u32()?,
+        };
+        self.take(len)
+    }
+
+    /// Returns true if the next term is the `nil` atom, which is how null is sent
+    fn peek_nil(&self) -> bool {
+        let rest = &self.data[self.pos.min(self.data.len())..];
+        let atom = match rest {
+            [ATOM_EXT | ATOM_UTF8_EXT, _, len, atom @ ..] => atom.get(..*len as usize),
+            [SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT, len, atom @ ..] => atom.get(..*len as usize),
+            _ => None,
+        };
+        matches!(atom, Some(b"nil" | b"null"))
+    }
+
+    /// Inflates compressed data, which starts with the size it inflates to
+    fn inflate(&mut self) -> Result<Vec<u8>, String> {
+        let len = self.u32()?;
+        if len > MAX_INFLATED {
+            return Err(format!("Compressed ETF data is too big ({} bytes)", len));
+        }
+        // Let the buffer grow instead of trusting the size for the allocation, and don't read more than it
+        let mut inflated = Vec::new();
+        ZlibDecoder::new(&self.data[self.pos..])
+            .take(len as u64)
+            .read_to_end(&mut inflated)
+            .map_err(|e| format!("Can't inflate the ETF data: {}", e))?;
+        self.pos = self.data.len();
+        Ok(inflated)
+    }
+
+    /// Decodes a term into a json value
+    fn term(&mut self) -> Result<Value, String> {
+        match self.u8()? {
+            SMALL_INTEGER_EXT => Ok(Value::from(self.u8()?)),
+            INTEGER_EXT => Ok(Value::from(self.i32()?)),
+            NEW_FLOAT_EXT => self.new_float().map(float),
+            FLOAT_EXT => self.old_float().map(float),
+            tag @ (ATOM_EXT | ATOM_UTF8_EXT | SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT) => {
+                let atom = self.text(tag)?;
+                Ok(match atom {
+                    b"nil" | b"null" => Value::Null,
+                    b"true" => Value::Bool(true),
+                    b"false" => Value::Bool(false),
+                    _ => Value::String(String::from_utf8_lossy(atom).into_owned()),
+                })
+            }
+            SMALL_TUPLE_EXT => {
+                let len = self.u8()? as usize;
+                self.array(len)
+            }
+            LARGE_TUPLE_EXT => {
+                let len = self.u32()?;
+                self.array(len)
+            }
+            NIL_EXT => Ok(Value::Array(Vec::new())),
+            // A list of bytes
+            STRING_EXT => {
+                let len = self.u16()?;
+                Ok(Value::Array(
+                    self.take(len)?.iter().map(|b| Value::from(*b)).collect(),
+                ))
+            }
+            LIST_EXT => {
+                let len = self.u32()?;
+                let list = self.array(len)?;
+                // Proper lists end with an empty list
+                self.term()?;
+                Ok(list)
+            }
+            BINARY_EXT => {
+                let len = self.u32()?;
+                self.string(len).map(Value::String)
+            }
+            SMALL_BIG_EXT => {
+                let len = self.u8()? as usize;
+                self.big(len).map(big_value)
+            }
+            LARGE_BIG_EXT => {
+                let len = self.u32()?;
+                self.big(len).map(big_value)
+            }
+            MAP_EXT => {
+                let len = self.u32()?;
+                let mut map = Map::new();
+                for _ in 0..len {
+                    let key = match self.term()? {
+                        Value::String(key) => key,
+                        key => key.to_string(),
+                    };
+                    map.insert(key, self.term()?);
+                }
+                Ok(Value::Object(map))
+            }
+            COMPRESSED => {
+                let inflated = self.inflate()?;
+                Decoder {
+                    data: &inflated,
+                    pos: 0,
+                }
+                .term()
+            }
+            tag => Err(format!("Unsupported ETF tag {}", tag)),
+        }
+    }
+
+    fn array(&mut self, len: usize) -> Result<Value, String> {
+        // The length comes from the data, so don't trust it for the allocation
+        let mut array = Vec::with_capacity(len.min(self.data.len() - self.pos));
+        for _ in 0..len {
+            array.push(self.term()?);
+        }
+        Ok(Value::Array(array))
+    }
+
+    /// Big integers are how snowflakes are sent. Discord's never need more than 64 bits
+    fn big(&mut self, len: usize) -> Result<Integer, String> {
+        let negative = self.u8()? != 0;
+        let digits = self.take(len)?;
+        if len > 8 {
+            return Err("ETF integer is too big".to_string());
+        }
+        let magnitude = digits
+            .iter()
+            .rev()
+            .fold(0u64, |acc, digit| (acc << 8) | *digit as u64);
+        match (negative, i64::try_from(magnitude)) {
+            (false, _) => Ok(Integer::Unsigned(magnitude)),
+            (true, Ok(magnitude)) => Ok(Integer::Signed(-magnitude)),
+            (true, Err(_)) => Err("ETF integer is too small".to_string()),
+        }
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+fn big_value(n: Integer) -> Value {
+    match n {
+        Integer::Unsigned(n) => Value::from(n),
+        Integer::Signed(n) => Value::from(n),
+    }
+}
+
+/// Passes the text of a binary or atom to a visitor, without copying it when it is valid utf8
+fn visit_text<'de, V: Visitor<'de>>(text: &[u8], visitor: V) -> Result<V::Value, EtfError> {
+    match std::str::from_utf8(text) {
+        Ok(text) => visitor.visit_str(text),
+        Err(_) => visitor.visit_string(String::from_utf8_lossy(text).into_owned()),
+    }
+}
+
+// The visitors are never given borrowed data, so the data doesn't have to outlive what is deserialized from it
+impl<'de, 'a, 'b> de::Deserializer<'de> for &'b mut Decoder<'a> {
+    type Error = EtfError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, EtfError> {
+        match self.u8()? {
+            SMALL_INTEGER_EXT => visitor.visit_u64(self.u8()? as u64),
+            INTEGER_EXT => match self.i32()? {
+                n if n >= 0 => visitor.visit_u64(n as u64),
+                n => visitor.visit_i64(n as i64),
+            },
+            NEW_FLOAT_EXT => visitor.visit_f64(self.new_float()?),
+            FLOAT_EXT => visitor.visit_f64(self.old_float()?),
+            tag @ (ATOM_EXT | ATOM_UTF8_EXT | SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT) => {
+                match self.text(tag)? {
+                    b"nil" | b"null" => visitor.visit_unit(),
+                    b"true" => visitor.visit_bool(true),
+                    b"false" => visitor.visit_bool(false),
+                    atom => visit_text(atom, visitor),
+                }
+            }
+            SMALL_TUPLE_EXT => {
+                let len = self.u8()? as usize;
+                visitor.visit_seq(Elements {
+                    de: self,
+                    left: len,
+                })
+            }
+            LARGE_TUPLE_EXT => {
+                let len = self.u32()?;
+                visitor.visit_seq(Elements {
+                    de: self,
+                    left: len,
+                })
+            }
+            NIL_EXT => visitor.visit_seq(Elements { de: self, left: 0 }),
+            // A list of bytes
+            STRING_EXT => {
+                let len = self.u16()?;
+                let bytes = self.take(len)?;
+                visitor.visit_seq(SeqDeserializer::<_, EtfError>::new(bytes.iter().copied()))
+            }
+            LIST_EXT => {
+                let len = self.u32()?;
+                let list = visitor.visit_seq(Elements {
+                    de: self,
+                    left: len,
+                })?;
+                // Proper lists end with an empty list
+                self.term()?;
+                Ok(list)
+            }
+            tag @ BINARY_EXT => {
+                let text = self.text(tag)?;
+                visit_text(text, visitor)
+            }
+            SMALL_BIG_EXT => {
+                let len = self.u8()? as usize;
+                match self.big(len)? {
+                    Integer::Unsigned(n) => visitor.visit_u64(n),
+                    Integer::Signed(n) => visitor.visit_i64(n),
+                }
+            }
+            LARGE_BIG_EXT => {
+                let len = self.u32()?;
+                match self.big(len)? {
+                    Integer::Unsigned(n) => visitor.visit_u64(n),
+                    Integer::Signed(n) => visitor.visit_i64(n),
+                }
+            }
+            MAP_EXT => {
+                let len = self.u32()?;
+                visitor.visit_map(Elements {
+                    de: self,
+                    left: len,
+                })
+            }
+            COMPRESSED => {
+                let inflated = self.inflate()?;
+                let mut decoder = Decoder {
+                    data: &inflated,
+                    pos: 0,
+                };
+                de::Deserializer::deserialize_any(&mut decoder, visitor)
+            }
+            tag => Err(EtfError(format!("Unsupported ETF tag {}", tag))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, EtfError> {
+        if self.peek_nil() {
+            self.term()?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, EtfError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Only enums without data can be sent, as the name of the variant
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, EtfError> {
+        match self.term()? {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            value => Err(EtfError(format!("Expected an enum variant, got {}", value))),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, EtfError> {
+        self.term()?;
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+/// The elements of a tuple, list or map
+struct Elements<'a, 'b> {
+    de: &'b mut Decoder<'a>,
+    left: usize,
+}
+
+impl<'de, 'a, 'b> SeqAccess<'de> for Elements<'a, 'b> {
+    type Error = EtfError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, EtfError> {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // The length comes from the data, so don't let it decide an allocation
+        Some(self.left.min(self.de.data.len() - self.de.pos))
+    }
+}
+
+impl<'de, 'a, 'b> MapAccess<'de> for Elements<'a, 'b> {
+    type Error = EtfError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, EtfError> {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        seed.deserialize(Key { de: &mut *self.de }).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, EtfError> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.left.min(self.de.data.len() - self.de.pos))
+    }
+}
+
+/// A map key, which is always passed as a string like json's keys are
+struct Key<'a, 'b> {
+    de: &'b mut Decoder<'a>,
+}
+
+impl<'de, 'a, 'b> de::Deserializer<'de> for Key<'a, 'b> {
+    type Error = EtfError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, EtfError> {
+        match self.de.peek() {
+            Some(
+                tag
+                @ (BINARY_EXT | ATOM_EXT | ATOM_UTF8_EXT | SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT),
+            ) => {
+                self.de.pos += 1;
+                let text = self.de.text(tag)?;
+                visit_text(text, visitor)
+            }
+            _ => match self.de.term()? {
+                Value::String(key) => visitor.visit_string(key),
+                key => visitor.visit_string(key.to_string()),
+            },
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+fn encode_term(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => encode_atom("nil", out),
+        Value::Bool(true) => encode_atom("true", out),
+        Value::Bool(false) => encode_atom("false", out),
+        Value::Number(n) => {
+            if let Some(n) = n.as_u64().filter(|n| *n <= u8::MAX as u64) {
+                out.push(SMALL_INTEGER_EXT);
+                out.push(n as u8);
+            } else if let Some(n) = n.as_i64().and_then(|n| i32::try_from(n).ok()) {
+                out.push(INTEGER_EXT);
+                out.extend_from_slice(&n.to_be_bytes());
+            } else if let Some(n) = n.as_u64() {
+                encode_big(false, n, out);
+            } else if let Some(n) = n.as_i64() {
+                encode_big(true, n.unsigned_abs(), out);
+            } else {
+                out.push(NEW_FLOAT_EXT);
+                out.extend_from_slice(&n.as_f64().unwrap_or_default().to_be_bytes());
+            }
+        }
+        Value::String(s) => encode_binary(s, out),
+        Value::Array(array) => {
+            if !array.is_empty() {
+                out.push(LIST_EXT);
+                out.extend_from_slice(&(array.len() as u32).to_be_bytes());
+                for item in array {
+                    encode_term(item, out);
+                }
+            }
+            out.push(NIL_EXT);
+        }
+        Value::Object(map) => {
+            out.push(MAP_EXT);
+            out.extend_from_slice(&(map.len() as u32).to_be_bytes());
+            for (key, value) in map {
+                encode_binary(key, out);
+                encode_term(value, out);
+            }
+        }
+    }
+}
+
+fn encode_atom(atom: &str, out: &mut Vec<u8>) {
+    out.push(SMALL_ATOM_UTF8_EXT);
+    out.push(atom.len() as u8);
+    out.extend_from_slice(atom.as_bytes());
+}
+
+fn encode_binary(s: &str, out: &mut Vec<u8>) {
+    out.push(BINARY_EXT);
+    out.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn encode_big(negative: bool, n: u64, out: &mut Vec<u8>) {
+    let digits = n.to_le_bytes();
+    let len = 8 - n.leading_zeros() as usize / 8;
+    out.push(SMALL_BIG_EXT);
+    out.push(len as u8);
+    out.push(negative as u8);
+    out.extend_from_slice(&digits[..len]);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, time::Instant};
+
+    use flate2::{write::ZlibEncoder, Compression};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    use super::{decode, encode, from_slice, COMPRESSED, FORMAT_VERSION, SMALL_BIG_EXT};
+    use crate::{
+        core::interactions::handler::gateway_payload::PayloadBase, discord::snowflake::Snowflake,
+    };
+
+    fn sample() -> Value {
+        json!({
+            "op": 0,
+            "s": 42,
+            "t": "MESSAGE_CREATE",
+            "d": {
+                "id": "1043218329487573123",
+                "content": "héllo \"world\"",
+                "pinned": false,
+                "edited_timestamp": null,
+                "nonce": -12,
+                "score": 1.5,
+                "mentions": [],
+                "attachments": [{ "id": "1", "size": 100000 }],
+            }
+        })
+    }
+
+    /// The data of an ETF message, compressed
+    fn compress(data: &[u8], declared_len: u32) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data[1..]).unwrap();
+        let mut out = vec![FORMAT_VERSION, COMPRESSED];
+        out.extend_from_slice(&declared_len.to_be_bytes());
+        out.extend_from_slice(&encoder.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn deserializes_what_was_encoded() {
+        let data = encode(&sample());
+        assert_eq!(from_slice::<Value>(&data).unwrap(), sample());
+        assert_eq!(decode(&data).unwrap(), sample());
+
+        let payload: PayloadBase<Value> = from_slice(&data).unwrap();
+        assert_eq!(payload.sequence_num, Some(42));
+        assert_eq!(payload.event_name.as_deref(), Some("MESSAGE_CREATE"));
+        assert_eq!(payload.data, sample()["d"]);
+    }
+
+    #[test]
+    fn deserializes_typed_structs() {
+        #[derive(Deserialize)]
+        struct Message {
+            id: Snowflake,
+            author_id: Snowflake,
+            edited_timestamp: Option<String>,
+            pinned: Option<bool>,
+            tags: Vec<String>,
+        }
+
+        let mut data = encode(&json!({
+            "id": "1043218329487573123",
+            "edited_timestamp": null,
+            "pinned": true,
+            "tags": ["a", "b"],
+        }));
+        // Discord sends some snowflakes as big integers, so add one to the map by hand
+        data[5] += 1;
+        data.extend_from_slice(&[109, 0, 0, 0, 9]);
+        data.extend_from_slice(b"author_id");
+        data.extend_from_slice(&[SMALL_BIG_EXT, 8, 0]);
+        data.extend_from_slice(&1043218329487573123u64.to_le_bytes());
+
+        let message: Message = from_slice(&data).unwrap();
+        assert_eq!(message.id, Snowflake::new(1043218329487573123));
+        assert_eq!(message.author_id, Snowflake::new(1043218329487573123));
+        assert_eq!(message.edited_timestamp, None);
+        assert_eq!(message.pinned, Some(true));
+        assert_eq!(message.tags, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn deserializes_compressed_terms() {
+        let data = encode(&sample());
+        let compressed = compress(&data, data.len() as u32 - 1);
+        assert_eq!(from_slice::<Value>(&compressed).unwrap(), sample());
+        assert_eq!(decode(&compressed).unwrap(), sample());
+    }
+
+    #[test]
+    fn rejects_huge_compressed_sizes() {
+        let data = encode(&sample());
+        let compressed = compress(&data, u32::MAX);
+        assert!(from_slice::<Value>(&compressed).is_err());
+        assert!(decode(&compressed).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let data = encode(&sample());
+        for len in 1..data.len() {
+            assert!(from_slice::<Value>(&data[..len]).is_err());
+        }
+    }
+
+    /// A GUILD_CREATE for a guild with 20000 members, which is the biggest kind of event
+    fn guild_create() -> Value {
+        let members: Vec<Value> = (0..20_000u64)
+            .map(|i| {
+                json!({
+                    "user": {
+                        "id": (1043218329487573123 + i).to_string(),
+                        "username": format!("user{}", i),
+                        "discriminator": "0",
+                        "avatar": "a_0123456789abcdef0123456789abcdef",
+                        "bot": false,
+                    },
+                    "nick": null,
+                    "roles": ["1043218329487573000", "1043218329487573001"],
+                    "joined_at": "2022-11-18T12:00:00.000000+00:00",
+                    "deaf": false,
+                    "mute": false,
+                    "flags": 0,
+                })
+            })
+            .collect();
+        json!({
+            "op": 0,
+            "s": 1,
+            "t": "GUILD_CREATE",
+            "d": { "id": "1043218329487573000", "name": "Big guild", "member_count": 20000, "members": members },
+        })
+    }
+
+    /// Compares decoding a GUILD_CREATE from json and from ETF. Run it with
+    /// `cargo test --release -p discrab etf_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn etf_benchmark() {
+        const RUNS: u32 = 20;
+        let event = guild_create();
+        let json = serde_json::to_vec(&event).unwrap();
+        let etf = encode(&event);
+        println!("json: {} bytes, etf: {} bytes", json.len(), etf.len());
+
+        let time = |name: &str, parse: &dyn Fn() -> PayloadBase<Value>| {
+            assert_eq!(parse().data, event["d"]);
+            let start = Instant::now();
+            for _ in 0..RUNS {
+                parse();
+            }
+            println!("{}: {:?} per decode", name, start.elapsed() / RUNS);
+        };
+        time("json", &|| serde_json::from_slice(&json).unwrap());
+        time("etf through a json value", &|| {
+            serde_json::from_value(decode(&etf).unwrap()).unwrap()
+        });
+        time("etf deserialized directly", &|| from_slice(&etf).unwrap());
+    }
+}
@@ -26,3 +26,22 @@ impl PayloadData for HeartBeatPayloadData {
         PayloadOpcode::Heartbeat
     }
 }
+
+#[derive(Serialize, Deserialize)]
+/**
+   Used to replay the events that were missed while the connection was down.
+   @docs <https://discord.com/developers/docs/topics/gateway#resume>
+*/
+pub struct ResumePayloadData {
+    /// session token
+    pub token: String,
+    /// session id
+    pub session_id: String,
+    /// last sequence number received
+    pub seq: Option<u64>,
+}
+impl PayloadData for ResumePayloadData {
+    fn get_opcode(&self) -> PayloadOpcode {
+        PayloadOpcode::Resume
+    }
+}
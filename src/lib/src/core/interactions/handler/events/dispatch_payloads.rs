@@ -1,6 +1,5 @@
 use discrab_codegen::CommandArg;
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::{
     core::abstraction::traits::CommandArg,
@@ -21,6 +20,8 @@ use crate::{
     },
 };
 
+pub use crate::discord::resources::invite::InviteTargetType;
+
 /**
  * Channel Pins Update
  * Sent when a message is pinned or unpinned in a text channel. This is not sent when a pinned message is deleted.
@@ -104,6 +105,8 @@ pub struct GuildMemberUpdate {
     pub nick: Option<String>,
     /// the member's guild avatar hash
     pub avatar: Option<String>,
+    /// the member's guild banner hash
+    pub banner: Option<String>,
     /// when the user joined the guild
     pub joined_at: Option<String>,
     /// when the user started boosting the guild
@@ -176,6 +179,8 @@ pub struct GuildMemberAdd {
     pub nick: Option<String>,
     /// the member's guild avatar hash
     pub avatar: Option<String>,
+    /// the member's guild banner hash
+    pub banner: Option<String>,
     /// array of role object ids
     pub roles: Vec<Snowflake>,
     /// when the user joined the guild
@@ -190,6 +195,8 @@ pub struct GuildMemberAdd {
     pub pending: Option<bool>,
     /// total permissions of the member in the channel, including overwrites, returned when in the interaction object
     pub permissions: Option<String>,
+    /// when the user's timeout will expire, null or a time in the past if the user is not timed out
+    pub communication_disabled_until: Option<String>,
     /// id of the guild
     pub guild_id: Snowflake,
 }
@@ -329,7 +336,7 @@ pub struct InviteCreate {
     /// the maximum number of times the invite can be used
     pub max_uses: Option<u64>,
     /// the type of target for this voice channel invite
-    pub target_type: Option<String>,
+    pub target_type: Option<InviteTargetType>,
     /// the user whose stream to display for this voice channel stream invite
     pub target_user: Option<User>,
     /// the embedded application to open for this voice channel embedded application invite
@@ -340,17 +347,6 @@ pub struct InviteCreate {
     pub uses: Option<u64>,
 }
 
-/**
- * Invite Target
- * @docs <https://discord.com/developers/docs/resources/invite#invite-object-invite-target-types>
- */
-#[derive(Serialize_repr, Deserialize_repr, Clone)]
-#[repr(u8)]
-pub enum InviteTargetType {
-    Stream = 1,
-    EmbeddedApplication = 2,
-}
-
 /**
  * Invite Delete Event
  * @docs <https://discord.com/developers/docs/topics/gateway#invite-delete>
@@ -504,3 +500,26 @@ pub struct WebhooksUpdate {
     /// the id of the channel
     pub channel_id: Snowflake,
 }
+
+/**
+ * Handler Timeout
+ * This isn't a gateway event. It is sent by the interaction router when a command, component, or modal handler
+ * runs longer than its timeout (see `Bot::handler_timeout`). The handler keeps running, but the user was already told it timed out.
+ */
+#[derive(Serialize, Deserialize, Clone, CommandArg)]
+pub struct HandlerTimeout {
+    /// the name of the command, or the custom id of the component or modal
+    pub name: String,
+    /// the id of the interaction the handler was called for
+    pub interaction_id: Snowflake,
+    /// the guild the interaction was used in
+    pub guild_id: Option<Snowflake>,
+    /// the channel the interaction was used in
+    pub channel_id: Option<Snowflake>,
+    /// the user that used the interaction
+    pub user_id: Option<Snowflake>,
+    /// how long the handler had been running when it timed out, in milliseconds
+    pub elapsed_ms: u64,
+    /// the timeout that the handler went over, in milliseconds
+    pub timeout_ms: u64,
+}
@@ -49,6 +49,11 @@ pub struct IdentifyProperties {
 }
 
 bitflags! {
+    /**
+     * The groups of gateway events that the bot receives, set with `Bot::intents`.
+     * GUILD_MEMBERS, GUILD_PRESENCES and MESSAGE_CONTENT are privileged, and have to be enabled in the developer portal first.
+     * @docs <https://discord.com/developers/docs/topics/gateway#gateway-intents>
+     */
     #[derive(Serialize)]
     pub struct Intents: u64 {
         const GUILDS = 1 << 0;
@@ -66,7 +71,11 @@ bitflags! {
         const DIRECT_MESSAGES = 1 << 12;
         const DIRECT_MESSAGE_REACTIONS = 1 << 13;
         const DIRECT_MESSAGE_TYPING = 1 << 14;
+        /// The content, embeds, attachments and components of messages that don't mention the bot (privileged)
+        const MESSAGE_CONTENT = 1 << 15;
         const GUILD_SCHEDULED_EVENTS = 1 << 16;
+        const AUTO_MODERATION_CONFIGURATION = 1 << 20;
+        const AUTO_MODERATION_EXECUTION = 1 << 21;
     }
 }
 
@@ -82,6 +91,18 @@ impl<'de> Deserialize<'de> for Intents {
     }
 }
 
+impl Intents {
+    /// The intents that have to be enabled in the developer portal before the bot can use them
+    pub fn privileged() -> Self {
+        Self::GUILD_MEMBERS | Self::GUILD_PRESENCES | Self::MESSAGE_CONTENT
+    }
+
+    /// Every intent that doesn't need to be enabled in the developer portal
+    pub fn non_privileged() -> Self {
+        Self::all() - Self::privileged()
+    }
+}
+
 impl Default for Intents {
     fn default() -> Self {
         Self::GUILDS
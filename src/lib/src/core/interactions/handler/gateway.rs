@@ -14,8 +14,7 @@ use serde::Deserialize;
  * Gateway Object
  * @docs <https://discord.com/developers/docs/topics/gateway#get-gateway-bot>
  */
-#[derive(Deserialize)]
-
+#[derive(Deserialize, Clone)]
 pub struct Gateway {
     /// The WSS URL that can be used for connecting to the gateway
     pub url: String,
@@ -29,7 +28,7 @@ pub struct Gateway {
  * Session Start Limit Object
  * @docs <https://discord.com/developers/docs/topics/gateway#session-start-limit-object>
  */
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct SessionStartLimit {
     /// The total number of session starts the current user is allowed
     pub total: u64,
@@ -2,6 +2,7 @@ use crossbeam_channel::Receiver;
 use serde_json::Value;
 
 pub mod events;
+mod etf;
 pub mod gateway;
 mod gateway_payload;
 pub mod websocket;
@@ -1,77 +1,281 @@
 use std::{
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     thread,
+    time::Duration,
 };
 
-use crate::core::{
-    abstraction::context::Context,
-    interactions::handler::{events::core::HelloPayloadData, gateway_payload::PayloadBase},
+use crate::{
+    core::{
+        abstraction::context::Context,
+        interactions::handler::{events::core::HelloPayloadData, gateway_payload::PayloadBase},
+        settings::GatewayEncoding,
+    },
+    util::logger::print_debug,
 };
 
 use super::{
-    events::core::HeartBeatPayloadData,
-    gateway::{get_gateway, Gateway},
+    etf,
+    events::core::{HeartBeatPayloadData, ResumePayloadData},
+    gateway::Gateway,
     gateway_payload::PayloadOpcode,
     SocketClient,
 };
 use async_std::task::block_on;
 use crossbeam_channel::{unbounded, Receiver, Sender};
+use flate2::{Decompress, DecompressError, FlushDecompress};
 
 use futures_util::{
     stream::{SplitSink, SplitStream, StreamExt},
     SinkExt,
 };
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 use tokio::net::TcpStream;
 use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 
+/// The longest time to wait between reconnect attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The bytes that a compressed message ends with. Frames without it are only part of a message
+const ZLIB_SUFFIX: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
 pub struct WebsocketEventHandler {
     event_receiver: Receiver<(String, Value)>,
     command_sender: Sender<Message>,
+    session: Arc<Mutex<Session>>,
+}
+
+/// What is needed to resume the session after the connection drops. Also read by `ctx.debug_report()`
+#[derive(Default)]
+pub(crate) struct Session {
+    /// The id of the session, from READY
+    pub(crate) session_id: Option<String>,
+    /// The url to reconnect to when resuming, from READY
+    pub(crate) resume_gateway_url: Option<String>,
+    /// The Sequence number. See https://discord.com/developers/docs/topics/gateway#heartbeat
+    pub(crate) sequence_num: HeartBeatPayloadData,
+    /// Whether the shard is connected and received READY or RESUMED
+    pub(crate) connected: bool,
+    /// The number of times the shard lost its connection
+    pub(crate) reconnects: u32,
+}
+
+/// How a connection to the gateway ended, which decides how the next connection starts
+enum ConnectionEnd {
+    /// The connection dropped, or discord asked us to reconnect. The session can be resumed.
+    Resume,
+    /// The session can't be resumed, so the next connection has to identify again
+    Reidentify,
+    /// Reconnecting won't help (e.g. the token is invalid)
+    Fatal(String),
+}
+
+/**
+ * Inflates the messages of a connection that uses `compress=zlib-stream`.
+ * The whole connection is a single zlib stream, so the same inflater has to be used for every message of the connection.
+ * @docs <https://discord.com/developers/docs/topics/gateway#transport-compression>
+ */
+struct ZlibStream {
+    inflater: Decompress,
+    /// The frames of the message that isn't complete yet
+    buffer: Vec<u8>,
+}
+
+impl ZlibStream {
+    fn new() -> Self {
+        Self {
+            inflater: Decompress::new(true),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Adds a frame, and returns the inflated message once it is complete
+    fn push(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, DecompressError> {
+        self.buffer.extend_from_slice(frame);
+        if !self.buffer.ends_with(&ZLIB_SUFFIX) {
+            return Ok(None);
+        }
+
+        let mut output = Vec::with_capacity(self.buffer.len() * 4);
+        let mut input = &self.buffer[..];
+        // The inflater stops at its window size, so keep going until it has nothing left to give
+        loop {
+            if output.len() == output.capacity() {
+                output.reserve(output.capacity());
+            }
+            let (total_in, total_out) = (self.inflater.total_in(), self.inflater.total_out());
+            self.inflater
+                .decompress_vec(input, &mut output, FlushDecompress::Sync)?;
+            input = &input[(self.inflater.total_in() - total_in) as usize..];
+            if self.inflater.total_in() == total_in && self.inflater.total_out() == total_out {
+                break;
+            }
+        }
+        self.buffer.clear();
+        Ok(Some(output))
+    }
 }
 
 impl WebsocketEventHandler {
-    pub async fn create(ctx: Context) -> WebsocketEventHandler {
+    /// Connects to the gateway and identifies with the given identify payload.
+    /// The handler reconnects on its own when the connection drops, resuming the session when discord allows it
+    /// and identifying again when it doesn't, so the events keep coming from the same channel.
+    /// @param gateway The gateway to connect to, from `get_gateway`
+    /// @param identify The identify payload, including the shard if the bot is sharded
+    pub fn create(ctx: Context, gateway: Gateway, identify: Value) -> WebsocketEventHandler {
         let (s, r) = unbounded();
         let (s2, r2) = unbounded();
+        let session = Arc::new(Mutex::new(Session::default()));
 
         let handler = WebsocketEventHandler {
             event_receiver: r,
             command_sender: s2,
+            session: session.clone(),
         };
-        async {
-            // TODO so the gateway says that it shouldn't be cached. WHAT DOES THIS MEAN????
-            // does it mean not cached between instances, and having it get a new gateway on startup?
-            // or does it want use to periodically get a new gateway while the bot is running? plz help
-            let gateway = get_gateway(ctx).await.unwrap();
-            thread::Builder::new()
-                .name("Websocket_Interaction_Handler".to_string())
-                .spawn(move || block_on(WebsocketEventHandler::run(s, r2, gateway)))
-                .unwrap()
-        }
-        .await;
+        thread::Builder::new()
+            .name("Websocket_Interaction_Handler".to_string())
+            .spawn(move || {
+                block_on(WebsocketEventHandler::run(ctx, s, r2, gateway, identify, session))
+            })
+            .unwrap();
 
         handler
     }
 
     async fn run(
+        ctx: Context,
         event_output: Sender<(String, Value)>,
         incoming_commands: Receiver<Message>,
         gateway: Gateway,
+        identify: Value,
+        session: Arc<Mutex<Session>>,
     ) {
+        // The number of connection attempts in a row that failed, used for the backoff
+        let mut failed_attempts: u32 = 0;
+
+        loop {
+            let (url, resuming) = {
+                let session = session.lock().unwrap();
+                let url = session
+                    .resume_gateway_url
+                    .clone()
+                    .unwrap_or_else(|| gateway.url.clone());
+                (url, session.session_id.is_some())
+            };
+            if ctx.settings.debug {
+                let action = if resuming { "Resuming" } else { "Identifying" };
+                print_debug("GATEWAY", format!("{} on {}", action, url));
+            }
+
+            let end = WebsocketEventHandler::connect(
+                &ctx,
+                &url,
+                &event_output,
+                &incoming_commands,
+                &identify,
+                &session,
+                &mut failed_attempts,
+            )
+            .await;
+            session.lock().unwrap().connected = false;
+
+            // The bot is shutting down, and the connection was closed on purpose
+            if ctx.gateway.is_closing() {
+                if ctx.settings.debug {
+                    print_debug("GATEWAY", "Connection closed".to_string());
+                }
+                return;
+            }
+            match end {
+                ConnectionEnd::Resume => {}
+                ConnectionEnd::Reidentify => {
+                    let mut session = session.lock().unwrap();
+                    *session = Session {
+                        reconnects: session.reconnects,
+                        ..Session::default()
+                    };
+                }
+                ConnectionEnd::Fatal(reason) => {
+                    print_debug("GATEWAY", format!("Not reconnecting: {}", reason));
+                    return;
+                }
+            }
+
+            // Back off when connecting keeps failing, 1s, 2s, 4s, ... up to a minute
+            let backoff = Duration::from_secs(1 << failed_attempts.min(6)).min(MAX_BACKOFF);
+            if ctx.settings.debug {
+                print_debug(
+                    "GATEWAY",
+                    format!("Disconnected, reconnecting in {:?}", backoff),
+                );
+            }
+            session.lock().unwrap().reconnects += 1;
+            thread::sleep(backoff);
+        }
+    }
+
+    /// Runs a single connection to the gateway until it ends
+    async fn connect(
+        ctx: &Context,
+        url: &str,
+        event_output: &Sender<(String, Value)>,
+        incoming_commands: &Receiver<Message>,
+        identify: &Value,
+        session: &Arc<Mutex<Session>>,
+        failed_attempts: &mut u32,
+    ) -> ConnectionEnd {
         // Url of the websocket
-        let url = url::Url::parse(&format!("{}/?v=9&encoding=json", gateway.url)).unwrap();
-        // println!("Connecting to {}", url);
+        let compression = if ctx.settings.compress_gateway {
+            "&compress=zlib-stream"
+        } else {
+            ""
+        };
+        let encoding = ctx.settings.gateway_encoding;
+        let url = match url::Url::parse(&format!(
+            "{}/?v=9&encoding={}{}",
+            url,
+            encoding.as_str(),
+            compression
+        )) {
+            Ok(url) => url,
+            Err(e) => return ConnectionEnd::Fatal(format!("Invalid gateway url: {}", e)),
+        };
 
         // Connect to the websocket
-        let (mut socket, _) = connect_async(url).await.expect("Can't connect");
-        // println!("{}", response.status());
+        let mut socket = match connect_async(url).await {
+            Ok((socket, _)) => socket,
+            Err(e) => {
+                *failed_attempts += 1;
+                if ctx.settings.debug {
+                    print_debug("GATEWAY", format!("Can't connect: {}", e));
+                }
+                return ConnectionEnd::Resume;
+            }
+        };
 
-        // Receive the hello message from the websocket and then parse it
-        let mut hello_msg = socket.next().await.unwrap().unwrap().into_data();
+        // Every connection is a new zlib stream
+        let mut zlib = ctx.settings.compress_gateway.then(ZlibStream::new);
 
-        let hello_payload: PayloadBase<HelloPayloadData> =
-            serde_json::from_slice(&mut *hello_msg).unwrap();
+        // Receive the hello message from the websocket and then parse it
+        let hello_payload: PayloadBase<HelloPayloadData> = loop {
+            let data = match socket.next().await {
+                Some(Ok(message)) => WebsocketEventHandler::decode(&mut zlib, message),
+                _ => Err("Connection closed before hello".to_string()),
+            };
+            match data.map(|data| data.map(|data| WebsocketEventHandler::parse(encoding, &data))) {
+                Ok(None) => continue,
+                Ok(Some(Ok(hello))) => break hello,
+                _ => {
+                    *failed_attempts += 1;
+                    return ConnectionEnd::Resume;
+                }
+            }
+        };
+        *failed_attempts = 0;
+        let heartbeat_interval = hello_payload.data.heartbeat_interval;
 
         // Split the socket so that different threads can handle different parts of the websocket
         let (socket_sink, socket_recv) = socket.split();
@@ -79,38 +283,73 @@ impl WebsocketEventHandler {
         // Used to send messages to the websocket
         let (heartbeat_send, heartbeat_receiver) = unbounded();
 
-        // The Sequence number. See https://discord.com/developers/docs/topics/gateway#heartbeat
-        let sequence_num = Arc::new(Mutex::new(None));
+        // Set to false when the connection ends, which stops the sender and heartbeat threads of this connection
+        let alive = Arc::new(AtomicBool::new(true));
+
+        // Start the session before anything else is sent
+        let start = {
+            let session = session.lock().unwrap();
+            match &session.session_id {
+                Some(session_id) => serde_json::to_string(&PayloadBase::new(ResumePayloadData {
+                    token: ctx.token.to_string(),
+                    session_id: session_id.clone(),
+                    seq: session.sequence_num,
+                }))
+                .unwrap(),
+                None => {
+                    // Identify with the presence that was set last, instead of the one the bot started with
+                    let mut identify = identify.clone();
+                    if let Some(presence) = ctx.gateway.presence() {
+                        identify["d"]["presence"] = serde_json::to_value(presence).unwrap();
+                    }
+                    identify.to_string()
+                }
+            }
+        };
+        heartbeat_send.send(Message::Text(start)).unwrap();
 
         // This will send requests to the websocket that are sent through the incoming_commands channel and the heartbeat_receiver channel
-        thread::spawn(move || {
+        let incoming_commands = incoming_commands.clone();
+        let alive_cp = alive.clone();
+        let sender = thread::spawn(move || {
             block_on(WebsocketEventHandler::sender(
                 socket_sink,
                 incoming_commands,
                 heartbeat_receiver,
+                alive_cp,
+                encoding,
             ))
         });
 
         let heatbeat_send1 = heartbeat_send.clone();
-        let seq_num_cp = sequence_num.clone();
+        let session_cp = session.clone();
+        let alive_cp = alive.clone();
 
         // Heartbeat loop
         thread::spawn(move || {
             block_on(WebsocketEventHandler::heartbeat_loop(
                 heatbeat_send1,
-                hello_payload.data.heartbeat_interval,
-                seq_num_cp,
+                heartbeat_interval,
+                session_cp,
+                alive_cp,
             ))
         });
 
         // Listen for events, and then send them when they are available
-        WebsocketEventHandler::event_receiver(
+        let end = WebsocketEventHandler::event_receiver(
+            ctx,
             event_output,
             socket_recv,
+            zlib,
             heartbeat_send,
-            sequence_num,
+            session,
+            heartbeat_interval,
         )
         .await;
+        alive.store(false, Ordering::SeqCst);
+        // Every connection's sender reads the same commands, so this one has to be stopped before the next connection starts its own
+        let _ = sender.join();
+        end
     }
 
     /// This will send requests to the websocket that are sent through the incoming_commands channel and the heartbeat_receiver channel
@@ -118,6 +357,8 @@ impl WebsocketEventHandler {
         mut socket_send: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
         to_send: Receiver<Message>,
         to_send_heartbeat: Receiver<Message>,
+        alive: Arc<AtomicBool>,
+        encoding: GatewayEncoding,
     ) {
         let max_allowance = 120.0;
         // Allowance per second
@@ -134,18 +375,26 @@ impl WebsocketEventHandler {
         sel.recv(&to_send_heartbeat);
 
         // This loop will handle the messages
-        loop {
+        while alive.load(Ordering::SeqCst) {
             let start = std::time::Instant::now();
 
-            // Block the thread until there is a message to send
-            sel.ready();
+            // Block the thread until there is a message to send, waking up now and then to see if the connection ended
+            if sel.ready_timeout(Duration::from_secs(1)).is_err() {
+                continue;
+            }
 
             // Send the heartbeat if there is allowance to do so, and if there is a heartbeat message to send
             while let Ok(msg) = to_send_heartbeat.try_recv() {
                 if allowance <= 1.0 {
                     break;
                 }
-                socket_send.send(msg).await.unwrap();
+                if socket_send
+                    .send(WebsocketEventHandler::encode(encoding, msg))
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
                 allowance -= 1.0;
             }
 
@@ -154,7 +403,13 @@ impl WebsocketEventHandler {
                 if allowance <= 1.0 {
                     break;
                 }
-                socket_send.send(msg).await.unwrap();
+                if socket_send
+                    .send(WebsocketEventHandler::encode(encoding, msg))
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
                 allowance -= 1.0;
             }
 
@@ -170,60 +425,194 @@ impl WebsocketEventHandler {
     async fn heartbeat_loop(
         socket_send: Sender<Message>,
         heartbeat_interval: u64,
-        sequence_num: Arc<Mutex<HeartBeatPayloadData>>,
+        session: Arc<Mutex<Session>>,
+        alive: Arc<AtomicBool>,
     ) {
-        loop {
-            let seq = *sequence_num.lock().unwrap();
+        while alive.load(Ordering::SeqCst) {
+            let seq = session.lock().unwrap().sequence_num;
             let heartbeat = Message::Text(serde_json::to_string(&PayloadBase::new(seq)).unwrap());
-            socket_send.send(heartbeat).unwrap();
+            if socket_send.send(heartbeat).is_err() {
+                return;
+            }
             thread::sleep(std::time::Duration::from_millis(heartbeat_interval));
         }
     }
 
-    /// Will receive events from the websocket and send them to the event_output channel
+    /// Will receive events from the websocket and send them to the event_output channel, until the connection ends
     async fn event_receiver(
-        events: Sender<(String, Value)>,
+        ctx: &Context,
+        events: &Sender<(String, Value)>,
         mut socket_recv: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+        mut zlib: Option<ZlibStream>,
         socket_send: Sender<Message>,
-        sequence_num: Arc<Mutex<HeartBeatPayloadData>>,
-    ) {
+        session: &Arc<Mutex<Session>>,
+        heartbeat_interval: u64,
+    ) -> ConnectionEnd {
+        // Discord acknowledges every heartbeat, so a connection that is quiet for two intervals is dead (a "zombied" connection)
+        let timeout = Duration::from_millis(heartbeat_interval * 2);
+
         // Listen for the socket to receive a message
-        while let Ok(message) = socket_recv.next().await.unwrap() {
+        loop {
+            let message = match async_std::future::timeout(timeout, socket_recv.next()).await {
+                Ok(Some(Ok(message))) => message,
+                Ok(Some(Err(e))) => {
+                    if ctx.settings.debug {
+                        print_debug("GATEWAY", format!("Connection error: {}", e));
+                    }
+                    return ConnectionEnd::Resume;
+                }
+                Ok(None) => return ConnectionEnd::Resume,
+                Err(_) => {
+                    if ctx.settings.debug {
+                        print_debug("GATEWAY", "No heartbeat ack, reconnecting".to_string());
+                    }
+                    return ConnectionEnd::Resume;
+                }
+            };
+
+            let message = match message {
+                Message::Close(frame) => {
+                    let code = frame.map(|frame| u16::from(frame.code)).unwrap_or(1000);
+                    return WebsocketEventHandler::close_code_end(code);
+                }
+                Message::Text(_) | Message::Binary(_) => message,
+                _ => continue,
+            };
+            let data = match WebsocketEventHandler::decode(&mut zlib, message) {
+                Ok(Some(data)) => data,
+                Ok(None) => continue,
+                Err(e) => {
+                    // The rest of the stream can't be inflated either
+                    if ctx.settings.debug {
+                        print_debug("GATEWAY", e);
+                    }
+                    return ConnectionEnd::Resume;
+                }
+            };
+
             // Parse the payload
             let payload: PayloadBase<Value> =
-                serde_json::from_slice(&*message.into_data()).unwrap();
+                match WebsocketEventHandler::parse(ctx.settings.gateway_encoding, &data) {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        if ctx.settings.debug {
+                            print_debug("GATEWAY", format!("Invalid payload: {}", e));
+                        }
+                        continue;
+                    }
+                };
 
             // Handle the payload depending on the opcode
             match payload.op_code {
                 PayloadOpcode::Dispatch => {
-                    // Update the sequence number
+                    let event_name = payload.event_name.unwrap_or_default();
+                    // Update the sequence number, and remember the session so that it can be resumed
                     {
-                        *sequence_num.lock().unwrap() = Some(payload.sequence_num.unwrap() as u64);
+                        let mut session = session.lock().unwrap();
+                        if let Some(seq) = payload.sequence_num {
+                            session.sequence_num = Some(seq as u64);
+                        }
+                        if event_name == "READY" {
+                            session.session_id =
+                                payload.data["session_id"].as_str().map(str::to_string);
+                            session.resume_gateway_url = payload.data["resume_gateway_url"]
+                                .as_str()
+                                .map(str::to_string);
+                        }
+                        if event_name == "READY" || event_name == "RESUMED" {
+                            session.connected = true;
+                        }
+                    }
+                    if events.send((event_name, payload.data)).is_err() {
+                        return ConnectionEnd::Fatal("The bot stopped listening".to_string());
                     }
-                    let event_name = payload.event_name.unwrap();
-                    events.send((event_name.to_string(), payload.data)).unwrap();
                 }
                 PayloadOpcode::Heartbeat => {
                     // Send a heartbeat if it is requested
-                    let seq = *sequence_num.lock().unwrap();
+                    let seq = session.lock().unwrap().sequence_num;
                     let heartbeat =
                         Message::Text(serde_json::to_string(&PayloadBase::new(seq)).unwrap());
-                    socket_send.send(heartbeat).unwrap();
+                    let _ = socket_send.send(heartbeat);
+                }
+                PayloadOpcode::Reconnect => return ConnectionEnd::Resume,
+                PayloadOpcode::InvalidSession => {
+                    // The data says whether the session can still be resumed
+                    return if payload.data.as_bool().unwrap_or(false) {
+                        ConnectionEnd::Resume
+                    } else {
+                        ConnectionEnd::Reidentify
+                    };
                 }
-                PayloadOpcode::Reconnect => {}
-                PayloadOpcode::InvalidSession => {}
                 PayloadOpcode::Hello => {
                     // This shouldn't happen so it is weird that we are in this branch
                 }
                 PayloadOpcode::HeartbeatAck => {
                     // Acknowledged heartbeat
-                    // println!("Heartbeat acknowledged");
                 }
                 _ => {}
             }
-            // interactions.send(interaction).await.unwrap();
         }
     }
+
+    /// The data of a message, inflated if the connection is compressed. None until a compressed message is complete
+    fn decode(zlib: &mut Option<ZlibStream>, message: Message) -> Result<Option<Vec<u8>>, String> {
+        match (zlib, message) {
+            (Some(zlib), Message::Binary(frame)) => zlib
+                .push(&frame)
+                .map_err(|e| format!("Can't inflate the gateway message: {}", e)),
+            (_, message) => Ok(Some(message.into_data())),
+        }
+    }
+
+    /// Parses a (decoded) message in the encoding of the connection
+    fn parse<T: DeserializeOwned>(encoding: GatewayEncoding, data: &[u8]) -> Result<T, String> {
+        match encoding {
+            GatewayEncoding::Json => serde_json::from_slice(data).map_err(|e| e.to_string()),
+            GatewayEncoding::Etf => etf::from_slice(data),
+        }
+    }
+
+    /// Everything is queued as json, so it has to be converted when the connection uses ETF
+    fn encode(encoding: GatewayEncoding, message: Message) -> Message {
+        match (encoding, message) {
+            (GatewayEncoding::Etf, Message::Text(text)) => match serde_json::from_str(&text) {
+                Ok(value) => Message::Binary(etf::encode(&value)),
+                Err(_) => Message::Text(text),
+            },
+            (_, message) => message,
+        }
+    }
+
+    /// Decides what to do after discord closes the connection
+    /// @docs <https://discord.com/developers/docs/topics/opcodes-and-status-codes#gateway-gateway-close-event-codes>
+    fn close_code_end(code: u16) -> ConnectionEnd {
+        match code {
+            4004 => ConnectionEnd::Fatal("Authentication failed, the token is invalid".to_string()),
+            4010 => ConnectionEnd::Fatal("Invalid shard".to_string()),
+            4011 => ConnectionEnd::Fatal("Sharding required".to_string()),
+            4012 => ConnectionEnd::Fatal("Invalid API version".to_string()),
+            4013 => ConnectionEnd::Fatal("Invalid intents".to_string()),
+            4014 => ConnectionEnd::Fatal(
+                "Disallowed intents, enable them in the developer portal".to_string(),
+            ),
+            // Invalid sequence and session timed out can't be resumed
+            4007 | 4009 => ConnectionEnd::Reidentify,
+            // If the session is gone, discord answers the resume with an invalid session
+            _ => ConnectionEnd::Resume,
+        }
+    }
+}
+
+impl WebsocketEventHandler {
+    /// Where the commands for this connection are sent
+    pub(crate) fn command_sender(&self) -> Sender<Message> {
+        self.command_sender.clone()
+    }
+
+    /// The state of the shard's session, which is updated as it connects
+    pub(crate) fn session(&self) -> Arc<Mutex<Session>> {
+        self.session.clone()
+    }
 }
 
 impl SocketClient for WebsocketEventHandler {
@@ -1,19 +1,27 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
 use discrab_codegen::CommandArg;
 use hyper::{Body, Method, Request};
 
 use crate::{
-    api::{guild::guild_member::GuildMember, user::User, Message, Snowflake, ApplicationCommandOptionValue},
+    api::{channel::attachment::AttachmentFile, guild::guild_member::GuildMember, user::User, Message, Snowflake, ApplicationCommandOptionValue, ApplicationCommandOptionChoice},
     core::{
-        abstraction::traits::CommandArg,
-        http::rate_limit_client::{send_request_noparse, RequestRoute},
+        abstraction::traits::{CommandArg, CommandChoice},
+        http::rate_limit_client::{send_request, send_request_noparse, RequestRoute},
     },
-    util::error::Error,
+    util::{error::{Error, ErrorTypes}, multipart::message_request},
     Context, BASE_URL,
 };
+#[cfg(feature = "rest")]
+use crate::{
+    api::channel::message::{Delivery, MessageBuilder},
+    discord::ids::params,
+};
 
 use super::typing::{
     Interaction, InteractionCallbackData, InteractionCallbackType, InteractionData,
-    InteractionResponse, InteractionType, InteractionDataOption,
+    InteractionResponse, InteractionType, InteractionDataOption, MessageData, MessageDataFlags,
+    ModalBuilder, AutocompleteData,
 };
 
 #[derive(CommandArg)]
@@ -43,6 +51,30 @@ pub struct InteractionCtx {
     pub message: Option<Box<Message>>,
     /// internal context object
     pub __ctx__: Context,
+    /// Whether the first response was ephemeral, once it has been sent
+    pub(crate) response_visibility: ResponseVisibility,
+}
+
+/// Whether the first response to an interaction was ephemeral. Ephemeral messages can't be reacted to, pinned, or made public later
+#[derive(Default)]
+pub(crate) struct ResponseVisibility(AtomicU8);
+
+impl ResponseVisibility {
+    const PUBLIC: u8 = 1;
+    const EPHEMERAL: u8 = 2;
+
+    fn set(&self, ephemeral: bool) {
+        let visibility = if ephemeral { Self::EPHEMERAL } else { Self::PUBLIC };
+        self.0.store(visibility, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> Option<bool> {
+        match self.0.load(Ordering::Relaxed) {
+            Self::PUBLIC => Some(false),
+            Self::EPHEMERAL => Some(true),
+            _ => None,
+        }
+    }
 }
 
 pub struct InteractionOption<T>{
@@ -101,9 +133,15 @@ impl InteractionCtx {
             type_: int.type_,
             user: int.user,
             version: int.version,
+            response_visibility: ResponseVisibility::default(),
         }
     }
 
+    /// Whether the first response to the interaction was ephemeral, or None if it hasn't been responded to with a message (or loading state) yet
+    pub fn is_response_ephemeral(&self) -> Option<bool> {
+        self.response_visibility.get()
+    }
+
     /// Responds to an interaction with a loading state.
     pub async fn respond_loading(&self) -> Result<(), Error> {
         self.respond(
@@ -115,12 +153,99 @@ impl InteractionCtx {
                 type_: InteractionCallbackType::DeferredChannelMessageWithSource,
                 data: None,
             },
+            &[],
+        )
+        .await?;
+        self.response_visibility.set(false);
+        Ok(())
+    }
+
+    /// Responds to an interaction with a loading state that only the user who used the command can see.
+    /// The response that replaces it will be ephemeral too.
+    pub async fn respond_loading_ephemeral(&self) -> Result<(), Error> {
+        self.respond(
+            format!(
+                "{}/interactions/{}/{}/callback",
+                BASE_URL, self.id, self.token
+            ),
+            InteractionResponse {
+                type_: InteractionCallbackType::DeferredChannelMessageWithSource,
+                data: Some(InteractionCallbackData::Message(MessageData {
+                    tts: None,
+                    content: None,
+                    embeds: None,
+                    allowed_mentions: None,
+                    flags: Some(MessageDataFlags::EPHEMERAL.bits()),
+                    components: None,
+                    attachments: None,
+                })),
+            },
+            &[],
+        )
+        .await?;
+        self.response_visibility.set(true);
+        Ok(())
+    }
+
+    /// Responds to an interaction with a popup modal. The values the user enters arrive in a modal submit interaction with the modal's custom id.
+    /// Modals can't be shown in response to a modal submit, or after deferring.
+    /// @param modal The modal to show
+    pub async fn respond_modal(&self, modal: ModalBuilder) -> Result<(), Error> {
+        self.respond(
+            format!(
+                "{}/interactions/{}/{}/callback",
+                BASE_URL, self.id, self.token
+            ),
+            InteractionResponse {
+                type_: InteractionCallbackType::Modal,
+                data: Some(InteractionCallbackData::Modal(modal.build()?)),
+            },
+            &[],
+        )
+        .await
+    }
+
+    /// Responds to an autocomplete interaction with the choices to suggest
+    /// @param choices Up to 25 choices. The rest are dropped
+    pub async fn respond_autocomplete(
+        &self,
+        choices: Vec<ApplicationCommandOptionChoice>,
+    ) -> Result<(), Error> {
+        self.respond(
+            format!(
+                "{}/interactions/{}/{}/callback",
+                BASE_URL, self.id, self.token
+            ),
+            InteractionResponse {
+                type_: InteractionCallbackType::ApplicationCommandAutocompleteResult,
+                data: Some(InteractionCallbackData::Autocomplete(AutocompleteData::new(choices))),
+            },
+            &[],
         )
         .await
     }
 
     // Responds to an interaction with a message
     pub async fn respond_message(&self, msg: InteractionCallbackData) -> Result<(), Error> {
+        self.respond_message_with_files(msg, Vec::new()).await
+    }
+
+    /// Responds to an interaction with a message, and uploads files along with it.
+    /// The files can be referenced in embeds with `attachment://<filename>`.
+    /// @param msg The message to respond with
+    /// @param files The files to attach to the message (up to 10)
+    pub async fn respond_message_with_files(
+        &self,
+        msg: InteractionCallbackData,
+        files: Vec<AttachmentFile>,
+    ) -> Result<(), Error> {
+        let ephemeral = match &msg {
+            InteractionCallbackData::Message(msg) => {
+                msg.validate()?;
+                is_ephemeral(msg)
+            }
+            _ => false,
+        };
         self.respond(
             format!(
                 "{}/interactions/{}/{}/callback",
@@ -130,8 +255,11 @@ impl InteractionCtx {
                 type_: InteractionCallbackType::ChannelMessageWithSource,
                 data: Some(msg),
             },
+            &files,
         )
-        .await
+        .await?;
+        self.response_visibility.set(ephemeral);
+        Ok(())
     }
 
     // Update the response that was sent with a new response
@@ -139,22 +267,168 @@ impl InteractionCtx {
         self.respond(
             format!("{}/interactions/{}/{}/m", BASE_URL, self.id, self.token),
             response,
+            &[],
+        )
+        .await
+    }
+
+    /// Sends a followup message for the interaction. Followups can be sent for up to 15 minutes after the interaction was received.
+    /// @param msg The message to send
+    /// @param files The files to attach to the message (up to 10), can be empty
+    /// @return The message that was sent
+    /// @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#create-followup-message>
+    pub async fn followup_message(
+        &self,
+        msg: MessageData,
+        files: Vec<AttachmentFile>,
+    ) -> Result<Message, Error> {
+        msg.validate()?;
+        let route = RequestRoute {
+            base_route: "/webhooks/{application.id}/{interaction.token}".to_string(),
+            major_param: self.token.clone(),
+        };
+        let request_builder = message_request(
+            Request::builder().method(Method::POST).uri(format!(
+                "{}/webhooks/{}/{}",
+                BASE_URL, self.application_id, self.token
+            )),
+            serde_json::to_value(&msg)?,
+            "",
+            &files,
+        );
+
+        send_request(self.__ctx__.clone(), route, request_builder).await
+    }
+
+    /// Edits the response to the interaction (e.g. to replace the loading state). Works for up to 15 minutes after the interaction was received.
+    /// The response stays ephemeral (or public) as it was first sent, so setting flags that would change that returns an `EPHEMERAL_RESPONSE` error.
+    /// @param msg The new message
+    /// @return The edited message
+    /// @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#edit-original-interaction-response>
+    pub async fn edit_original_response(&self, msg: MessageData) -> Result<Message, Error> {
+        msg.validate()?;
+        if let (Some(ephemeral), Some(_)) = (self.is_response_ephemeral(), msg.flags) {
+            if ephemeral != is_ephemeral(&msg) {
+                return Err(Error::new(
+                    format!(
+                        "Can't make the response {} after it was sent as {}",
+                        if ephemeral { "public" } else { "ephemeral" },
+                        if ephemeral { "ephemeral" } else { "public" },
+                    ),
+                    ErrorTypes::EPHEMERAL_RESPONSE,
+                ));
+            }
+        }
+        let route = RequestRoute {
+            base_route: "/webhooks/{application.id}/{interaction.token}/messages/@original".to_string(),
+            major_param: self.token.clone(),
+        };
+        let request = Request::builder()
+            .method(Method::PATCH)
+            .uri(format!(
+                "{}/webhooks/{}/{}/messages/@original",
+                BASE_URL, self.application_id, self.token
+            ))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&msg)?))
+            .unwrap();
+
+        send_request(self.__ctx__.clone(), route, request).await
+    }
+
+    /// Gets the message that the interaction was responded with
+    /// @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#get-original-interaction-response>
+    pub async fn original_response(&self) -> Result<Message, Error> {
+        let route = RequestRoute {
+            base_route: "/webhooks/{application.id}/{interaction.token}/messages/@original".to_string(),
+            major_param: self.token.clone(),
+        };
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/webhooks/{}/{}/messages/@original",
+                BASE_URL, self.application_id, self.token
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(self.__ctx__.clone(), route, request).await
+    }
+
+    /// Reacts to the message that the interaction was responded with.
+    /// Ephemeral responses can't have reactions, so this returns an `EPHEMERAL_RESPONSE` error for them instead of making the requests.
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    #[cfg(feature = "rest")]
+    pub async fn react_to_response(&self, emoji: &str) -> Result<(), Error> {
+        if self.is_response_ephemeral() == Some(true) {
+            return Err(Error::new(
+                "Can't react to the response because it is ephemeral".to_string(),
+                ErrorTypes::EPHEMERAL_RESPONSE,
+            ));
+        }
+        let response = self.original_response().await?;
+        Message::add_reaction(
+            self.__ctx__.clone(),
+            params::channel(response.channel_id),
+            params::message(response.id),
+            emoji,
+        )
+        .await
+    }
+
+    /**
+     * Sends a message to the channel the interaction came from, or DMs it to the user that used the command if the bot isn't allowed to post there.
+     * Useful for results that are posted after the interaction token expired.
+     * @param message The message to send
+     * @param notice Put above the message in the DM, to tell the user why they got it
+     */
+    #[cfg(feature = "rest")]
+    pub async fn send_with_fallback(
+        &self,
+        message: MessageBuilder,
+        notice: &str,
+    ) -> Result<Delivery, Error> {
+        let no_field = |field: &str| {
+            Error::new(
+                format!("The interaction has no {}", field),
+                ErrorTypes::REQUEST,
+            )
+        };
+        let channel_id = self.channel_id.ok_or_else(|| no_field("channel"))?;
+        let user_id = self
+            .member
+            .as_ref()
+            .and_then(|member| member.user.as_ref())
+            .or(self.user.as_ref())
+            .map(|user| user.id)
+            .ok_or_else(|| no_field("user"))?;
+        Message::send_with_fallback(
+            self.__ctx__.clone(),
+            params::channel(channel_id),
+            params::user(user_id),
+            message,
+            notice,
         )
         .await
     }
 
     // TODO I think I have to move this into the individual thread because the requests have to be different for each one
-    async fn respond(&self, uri: String, payload: InteractionResponse) -> Result<(), Error> {
+    async fn respond(
+        &self,
+        uri: String,
+        payload: InteractionResponse,
+        files: &[AttachmentFile],
+    ) -> Result<(), Error> {
         let route = RequestRoute {
             base_route: "interactions/<interaction_id>/<interaction_token>".to_string(),
             major_param: "".to_string(),
         };
-        let request_builder = Request::builder()
-            .method(Method::POST)
-            .uri(uri)
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&payload).unwrap()))
-            .unwrap();
+        let request_builder = message_request(
+            Request::builder().method(Method::POST).uri(uri),
+            serde_json::to_value(&payload)?,
+            "/data",
+            files,
+        );
 
         let req = send_request_noparse(self.__ctx__.clone(), route, request_builder)
             .await;
@@ -164,6 +438,8 @@ impl InteractionCtx {
         req
     }
 
+    super::typing::option_getters!();
+
     /// Gets an option from the interaction as type T. Panics if there is a data type mismatch.
     pub fn get_option<T>(&self, name: &str) -> Option<InteractionOption<T>>
     where InteractionOption<T>: From<InteractionDataOption> {
@@ -173,4 +449,24 @@ impl InteractionCtx {
         find(|o|o.name == name)
         .map(|o|o.to_owned().into())
     }
+
+    /// Gets a string option from the interaction, parsed as an enum that derives `CommandChoice`.
+    /// @return None if the option wasn't given, or a PARSE error if the value isn't one of the enum's choices
+    /// (the command was registered with other choices, and discord still sends those)
+    pub fn get_choice<T: CommandChoice>(&self, name: &str) -> Result<Option<T>, Error> {
+        let option = match self.get_option::<String>(name) {
+            Some(option) => option,
+            None => return Ok(None),
+        };
+        T::try_from(option.value.as_str())
+            .map(Some)
+            .map_err(|e| Error::new(e, ErrorTypes::PARSE))
+    }
+}
+
+/// Whether a message has the ephemeral flag
+fn is_ephemeral(msg: &MessageData) -> bool {
+    msg.flags
+        .map(|flags| MessageDataFlags::from_bits_truncate(flags).contains(MessageDataFlags::EPHEMERAL))
+        .unwrap_or(false)
 }
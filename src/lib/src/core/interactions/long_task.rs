@@ -0,0 +1,197 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::util::error::{Error, ErrorTypes};
+
+use super::{interaction_event::InteractionCtx, typing::MessageData};
+
+/// Interaction tokens stop working 15 minutes after the interaction was received
+const TOKEN_LIFETIME: Duration = Duration::from_secs(15 * 60);
+/// Stop using the token a bit early, so that a request that is still queued doesn't fail
+const TOKEN_MARGIN: Duration = Duration::from_secs(30);
+/// The number of blocks in the progress bar
+const PROGRESS_BAR_LENGTH: usize = 10;
+
+/**
+ * Runs the response side of a long running command: the interaction is deferred right away so it doesn't time out,
+ * the loading message is edited with the progress as the work goes on, and then replaced with the result.
+ * Progress updates are throttled so that they don't hit the rate limit, and once the interaction token is about to expire (after 15 minutes)
+ * the progress updates are skipped and the result is posted as a normal message in the channel instead.
+ *
+ * ```rust,ignore
+ * let mut task = LongTask::start(&ctx).await?;
+ * for (i, file) in files.iter().enumerate() {
+ *     process(file).await;
+ *     task.progress((i * 100 / files.len()) as u8, &format!("Processed {}", file)).await?;
+ * }
+ * task.finish("All files were processed!").await?;
+ * ```
+ */
+pub struct LongTask<'a> {
+    ictx: &'a InteractionCtx,
+    /// When the interaction was deferred. The token is counted from here
+    started: Instant,
+    /// When the progress was last shown
+    last_update: Option<Instant>,
+    /// The shortest time between two progress updates
+    min_interval: Duration,
+}
+
+impl<'a> LongTask<'a> {
+    /// Defers the interaction, and shows the loading state to everyone in the channel
+    /// @param ictx The interaction to respond to. It can't have been responded to yet
+    pub async fn start(ictx: &'a InteractionCtx) -> Result<LongTask<'a>, Error> {
+        ictx.respond_loading().await?;
+        Ok(Self::new(ictx))
+    }
+
+    /// Defers the interaction, and shows the loading state only to the user that used the command
+    /// @param ictx The interaction to respond to. It can't have been responded to yet
+    pub async fn start_ephemeral(ictx: &'a InteractionCtx) -> Result<LongTask<'a>, Error> {
+        ictx.respond_loading_ephemeral().await?;
+        Ok(Self::new(ictx))
+    }
+
+    fn new(ictx: &'a InteractionCtx) -> Self {
+        Self {
+            ictx,
+            started: Instant::now(),
+            last_update: None,
+            min_interval: Duration::from_secs(2),
+        }
+    }
+
+    /// Sets the shortest time between two progress updates (2 seconds by default). Updates that come sooner are skipped
+    #[must_use]
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /**
+     * Shows the progress of the task. Skipped if the last update was too recent, or the interaction token expired.
+     * @param percent How far along the task is, from 0 to 100
+     * @param note What the task is doing right now
+     */
+    pub async fn progress(&mut self, percent: u8, note: &str) -> Result<(), Error> {
+        let now = Instant::now();
+        let too_soon = self
+            .last_update
+            .is_some_and(|last_update| now.duration_since(last_update) < self.min_interval);
+        if too_soon || self.token_expired() {
+            return Ok(());
+        }
+        self.last_update = Some(now);
+        self.ictx
+            .edit_original_response(message(progress_text(percent, note)))
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces the loading message with the result
+    /// @param content The result to show
+    pub async fn finish(self, content: &str) -> Result<(), Error> {
+        self.show_result(content.to_string()).await
+    }
+
+    /// Replaces the loading message with an error message
+    /// @param error What went wrong
+    pub async fn fail(self, error: impl Display) -> Result<(), Error> {
+        self.show_result(format!("❌ {}", error)).await
+    }
+
+    /**
+     * Shows the result of the task, whether it succeeded or failed
+     * @param result The result of the task
+     * @param describe Turns a successful result into the message to show
+     */
+    pub async fn finish_with<T, E: Display>(
+        self,
+        result: Result<T, E>,
+        describe: impl FnOnce(T) -> String,
+    ) -> Result<(), Error> {
+        match result {
+            Ok(value) => self.show_result(describe(value)).await,
+            Err(e) => self.fail(e).await,
+        }
+    }
+
+    /// Returns true if the interaction token can't be used anymore
+    pub fn token_expired(&self) -> bool {
+        self.started.elapsed() >= TOKEN_LIFETIME - TOKEN_MARGIN
+    }
+
+    async fn show_result(self, content: String) -> Result<(), Error> {
+        if self.token_expired() {
+            return self.send_to_channel(content).await;
+        }
+        self.ictx.edit_original_response(message(content)).await?;
+        Ok(())
+    }
+
+    /// Posts the result in the channel, since the interaction can't be responded to anymore
+    #[cfg(feature = "rest")]
+    async fn send_to_channel(&self, content: String) -> Result<(), Error> {
+        use crate::{
+            api::{channel::message::MessageBuilder, Message},
+            discord::ids::params,
+        };
+
+        let channel_id = self.ictx.channel_id.ok_or_else(|| {
+            Error::new(
+                "The interaction token expired, and the interaction has no channel".to_string(),
+                ErrorTypes::REQUEST,
+            )
+        })?;
+        let mention = self
+            .ictx
+            .member
+            .as_ref()
+            .and_then(|member| member.user.as_ref())
+            .or(self.ictx.user.as_ref())
+            .map(|user| format!("<@{}> ", user.id))
+            .unwrap_or_default();
+        Message::send(
+            self.ictx.__ctx__.clone(),
+            params::channel(channel_id),
+            MessageBuilder::new().set_content(&format!("{}{}", mention, content)),
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "rest"))]
+    async fn send_to_channel(&self, _content: String) -> Result<(), Error> {
+        Err(Error::new(
+            "The interaction token expired".to_string(),
+            ErrorTypes::REQUEST,
+        ))
+    }
+}
+
+fn message(content: String) -> MessageData {
+    MessageData {
+        tts: None,
+        content: Some(content),
+        embeds: None,
+        allowed_mentions: None,
+        flags: None,
+        components: None,
+        attachments: None,
+    }
+}
+
+/// The progress message, e.g. "▰▰▰▰▱▱▱▱▱▱ 40% Processing files"
+fn progress_text(percent: u8, note: &str) -> String {
+    let percent = percent.min(100) as usize;
+    let filled = percent * PROGRESS_BAR_LENGTH / 100;
+    format!(
+        "{}{} {}% {}",
+        "▰".repeat(filled),
+        "▱".repeat(PROGRESS_BAR_LENGTH - filled),
+        percent,
+        note
+    )
+}
@@ -0,0 +1,344 @@
+use crate::{
+    discord::resources::{channel::reaction::ReactionType, emoji::Emoji},
+    util::error::{Error, ErrorTypes},
+};
+
+use super::{MessageButtonStyle, MessageComponent, MessageComponentType, MessageSelectOption};
+
+/// The most action rows a message can have
+pub const MAX_ACTION_ROWS: usize = 5;
+/// The most buttons an action row can have
+pub const MAX_BUTTONS: usize = 5;
+/// The most options a select menu can have
+pub const MAX_SELECT_OPTIONS: usize = 25;
+
+/**
+ * Builds a button. Put it in an `ActionRow` to add it to a message.
+ *
+ * ```rust,ignore
+ * let row = ActionRow::new()
+ *     .button(Button::success("accept", "Accept").emoji("✅"))
+ *     .button(Button::danger("decline", "Decline"))
+ *     .button(Button::link("https://example.com/rules", "Rules"))
+ *     .build()?;
+ * ```
+ * @docs <https://discord.com/developers/docs/interactions/message-components#buttons>
+ */
+#[derive(Clone)]
+pub struct Button {
+    component: MessageComponent,
+}
+
+impl Button {
+    /// A blurple button
+    /// @param custom_id The id the click interaction can be routed by, max 100 characters
+    /// @param label The text on the button, max 80 characters
+    pub fn primary(custom_id: &str, label: &str) -> Self {
+        Self::new(MessageButtonStyle::Primary, custom_id, label)
+    }
+
+    /// A grey button
+    /// @param custom_id The id the click interaction can be routed by, max 100 characters
+    /// @param label The text on the button, max 80 characters
+    pub fn secondary(custom_id: &str, label: &str) -> Self {
+        Self::new(MessageButtonStyle::Secondary, custom_id, label)
+    }
+
+    /// A green button
+    /// @param custom_id The id the click interaction can be routed by, max 100 characters
+    /// @param label The text on the button, max 80 characters
+    pub fn success(custom_id: &str, label: &str) -> Self {
+        Self::new(MessageButtonStyle::Success, custom_id, label)
+    }
+
+    /// A red button
+    /// @param custom_id The id the click interaction can be routed by, max 100 characters
+    /// @param label The text on the button, max 80 characters
+    pub fn danger(custom_id: &str, label: &str) -> Self {
+        Self::new(MessageButtonStyle::Danger, custom_id, label)
+    }
+
+    /// A button that opens a url. Clicking it doesn't send an interaction
+    /// @param url The url to open
+    /// @param label The text on the button, max 80 characters
+    pub fn link(url: &str, label: &str) -> Self {
+        let mut component = empty_component(MessageComponentType::Button);
+        component.style = Some(MessageButtonStyle::Link);
+        component.url = Some(url.to_string());
+        component.label = Some(label.to_string());
+        Self { component }
+    }
+
+    fn new(style: MessageButtonStyle, custom_id: &str, label: &str) -> Self {
+        let mut component = empty_component(MessageComponentType::Button);
+        component.style = Some(style);
+        component.custom_id = Some(custom_id.to_string());
+        component.label = Some(label.to_string());
+        Self { component }
+    }
+
+    /// Shows an emoji before the label
+    /// @param emoji A unicode emoji, or a custom emoji in any of the forms `ReactionType::parse` reads
+    #[must_use]
+    pub fn emoji(mut self, emoji: &str) -> Self {
+        self.component.emoji = Some(partial_emoji(emoji));
+        self
+    }
+
+    /// Greys out the button so it can't be clicked
+    #[must_use]
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.component.disabled = Some(disabled);
+        self
+    }
+}
+
+impl From<Button> for MessageComponent {
+    fn from(button: Button) -> Self {
+        button.component
+    }
+}
+
+/**
+ * Builds a dropdown of choices. A select menu takes up a whole `ActionRow`.
+ *
+ * ```rust,ignore
+ * let row = ActionRow::new()
+ *     .select_menu(
+ *         SelectMenu::new("color")
+ *             .placeholder("Pick a color")
+ *             .option(MessageSelectOption::new("Red", "red").emoji("🟥"))
+ *             .option(MessageSelectOption::new("Blue", "blue").description("The best one")),
+ *     )
+ *     .build()?;
+ * ```
+ * @docs <https://discord.com/developers/docs/interactions/message-components#select-menus>
+ */
+#[derive(Clone)]
+pub struct SelectMenu {
+    component: MessageComponent,
+}
+
+impl SelectMenu {
+    /// @param custom_id The id the select interaction can be routed by, max 100 characters
+    pub fn new(custom_id: &str) -> Self {
+        let mut component = empty_component(MessageComponentType::SelectMenu);
+        component.custom_id = Some(custom_id.to_string());
+        component.options = Some(Vec::new());
+        Self { component }
+    }
+
+    /// Adds a choice to the menu. A menu can have up to 25
+    #[must_use]
+    pub fn option(mut self, option: MessageSelectOption) -> Self {
+        self.component
+            .options
+            .get_or_insert_with(Vec::new)
+            .push(option);
+        self
+    }
+
+    /// Sets the text that is shown while nothing is selected, max 100 characters
+    #[must_use]
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.component.placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    /// Sets how many options can be chosen, 1 and 1 by default
+    /// @param min The least options that have to be chosen, between 0 and 25
+    /// @param max The most options that can be chosen, up to 25
+    #[must_use]
+    pub fn values(mut self, min: u32, max: u32) -> Self {
+        self.component.min_values = Some(min);
+        self.component.max_values = Some(max);
+        self
+    }
+
+    /// Greys out the menu so nothing can be chosen
+    #[must_use]
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.component.disabled = Some(disabled);
+        self
+    }
+}
+
+impl From<SelectMenu> for MessageComponent {
+    fn from(menu: SelectMenu) -> Self {
+        menu.component
+    }
+}
+
+impl MessageSelectOption {
+    /// @param label The name of the option shown to the user, max 100 characters
+    /// @param value The value the interaction has when the option is chosen, max 100 characters
+    pub fn new(label: &str, value: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            value: value.to_string(),
+            description: None,
+            emoji: None,
+            default: None,
+        }
+    }
+
+    /// Shows a line of text under the label, max 100 characters
+    #[must_use]
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Shows an emoji before the label
+    /// @param emoji A unicode emoji, or a custom emoji in any of the forms `ReactionType::parse` reads
+    #[must_use]
+    pub fn emoji(mut self, emoji: &str) -> Self {
+        self.emoji = Some(partial_emoji(emoji));
+        self
+    }
+
+    /// Selects the option when the menu is first shown
+    #[must_use]
+    pub fn default(mut self, default: bool) -> Self {
+        self.default = Some(default);
+        self
+    }
+}
+
+/**
+ * Builds a row of components. A row has either up to 5 buttons or a single select menu, and a message can have up to 5 rows.
+ * Add the built row to a message with `MessageBuilder::component` or `InteractionMessageBuilder::component`.
+ * @docs <https://discord.com/developers/docs/interactions/message-components#action-rows>
+ */
+#[derive(Clone, Default)]
+pub struct ActionRow {
+    components: Vec<MessageComponent>,
+}
+
+impl ActionRow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a button to the row
+    #[must_use]
+    pub fn button(mut self, button: Button) -> Self {
+        self.components.push(button.into());
+        self
+    }
+
+    /// Adds a select menu to the row. It has to be the only component in the row
+    #[must_use]
+    pub fn select_menu(mut self, menu: SelectMenu) -> Self {
+        self.components.push(menu.into());
+        self
+    }
+
+    /// @return An error if the row is empty, has more than 5 buttons, or mixes a select menu with other components, since discord would reject it
+    pub fn build(self) -> Result<MessageComponent, Error> {
+        let mut row = empty_component(MessageComponentType::ActionRow);
+        row.components = Some(self.components);
+        validate_row(&row)?;
+        Ok(row)
+    }
+}
+
+/// Checks the components of a message against discord's limits: up to 5 action rows, each with up to 5 buttons or one select menu
+/// @param components The top level components of the message
+pub fn validate_components(components: &[MessageComponent]) -> Result<(), Error> {
+    if components.len() > MAX_ACTION_ROWS {
+        return Err(component_error(format!(
+            "A message can have up to {} action rows, but has {}",
+            MAX_ACTION_ROWS,
+            components.len()
+        )));
+    }
+    for row in components {
+        if !matches!(row.type_, MessageComponentType::ActionRow) {
+            return Err(component_error(
+                "Buttons and select menus have to be put in an action row".to_string(),
+            ));
+        }
+        validate_row(row)?;
+    }
+    Ok(())
+}
+
+fn validate_row(row: &MessageComponent) -> Result<(), Error> {
+    let children = row.components.as_deref().unwrap_or_default();
+    let buttons = children
+        .iter()
+        .filter(|child| matches!(child.type_, MessageComponentType::Button))
+        .count();
+    let menus = children
+        .iter()
+        .filter(|child| matches!(child.type_, MessageComponentType::SelectMenu))
+        .count();
+    if children.is_empty() {
+        return Err(component_error("An action row can't be empty".to_string()));
+    }
+    if buttons > MAX_BUTTONS {
+        return Err(component_error(format!(
+            "An action row can have up to {} buttons, but has {}",
+            MAX_BUTTONS, buttons
+        )));
+    }
+    if menus > 0 && children.len() > 1 {
+        return Err(component_error(
+            "A select menu has to be the only component in its action row".to_string(),
+        ));
+    }
+    for menu in children
+        .iter()
+        .filter(|child| matches!(child.type_, MessageComponentType::SelectMenu))
+    {
+        let options = menu.options.as_ref().map_or(0, Vec::len);
+        if options == 0 || options > MAX_SELECT_OPTIONS {
+            return Err(component_error(format!(
+                "A select menu needs between 1 and {} options, but has {}",
+                MAX_SELECT_OPTIONS, options
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn component_error(message: String) -> Error {
+    Error::new(message, ErrorTypes::REQUEST)
+}
+
+/// The id, name and animated fields that components use for emojis
+fn partial_emoji(emoji: &str) -> Emoji {
+    let (id, name) = match ReactionType::parse(emoji) {
+        ReactionType::Unicode(name) => (None, name),
+        ReactionType::Custom { name, id } => (Some(id), name),
+    };
+    Emoji {
+        id,
+        name: Some(name),
+        roles: Vec::new(),
+        user: None,
+        require_colons: false,
+        managed: false,
+        animated: emoji.starts_with("<a:"),
+        available: true,
+    }
+}
+
+fn empty_component(type_: MessageComponentType) -> MessageComponent {
+    MessageComponent {
+        type_,
+        custom_id: None,
+        disabled: None,
+        style: None,
+        label: None,
+        emoji: None,
+        url: None,
+        options: None,
+        placeholder: None,
+        min_values: None,
+        max_values: None,
+        components: None,
+        value: None,
+    }
+}
@@ -70,6 +70,32 @@ pub struct MessageComponent {
      * @valid-for Action Rows
      */
     pub components: Option<Vec<MessageComponent>>,
+    /**
+     * The value the user entered
+     * @valid-for Text Inputs (only in modal submit interactions)
+     */
+    pub value: Option<String>,
+}
+
+impl MessageComponent {
+    /// Whether users can interact with the component (buttons and select menus)
+    pub fn is_interactive(&self) -> bool {
+        matches!(
+            self.type_,
+            MessageComponentType::Button | MessageComponentType::SelectMenu
+        )
+    }
+
+    /// Disables or enables this component and every interactive component inside of it (e.g. all the buttons in an action row)
+    /// @param disabled Whether the components should be disabled
+    pub fn set_disabled_recursive(&mut self, disabled: bool) {
+        if self.is_interactive() {
+            self.disabled = Some(disabled);
+        }
+        for child in self.components.iter_mut().flatten() {
+            child.set_disabled_recursive(disabled);
+        }
+    }
 }
 
 /**
@@ -85,6 +111,8 @@ pub enum MessageComponentType {
     Button = 2,
     /// A select menu for picking from choices
     SelectMenu = 3,
+    /// A text input object (only valid in modals)
+    TextInput = 4,
 }
 
 /**
@@ -138,3 +166,120 @@ pub struct MessageSelectOption {
     /// Will render this option as selected by default
     pub default: Option<bool>,
 }
+
+/**
+ * Text Input Styles
+ * @docs <https://discord.com/developers/docs/interactions/message-components#text-input-object-text-input-styles>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone)]
+#[repr(u8)]
+pub enum TextInputStyle {
+    /// A single-line input
+    Short = 1,
+    /// A multi-line input
+    Paragraph = 2,
+}
+
+/**
+ * Text Input Object, a field in a modal
+ * @docs <https://discord.com/developers/docs/interactions/message-components#text-input-object-text-input-structure>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TextInput {
+    /// Always TextInput
+    #[serde(rename = "type")]
+    pub type_: MessageComponentType,
+    /// A developer-defined identifier for the input, max 100 characters. The submitted value is found by it
+    pub custom_id: String,
+    /// The style of the input
+    pub style: TextInputStyle,
+    /// The label shown above the input, max 45 characters
+    pub label: String,
+    /// The minimum input length, min 0, max 4000
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u32>,
+    /// The maximum input length, min 1, max 4000
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u32>,
+    /// Whether the input has to be filled in, default true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    /// A pre-filled value, max 4000 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Custom placeholder text if the input is empty, max 100 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+}
+
+impl TextInput {
+    /// Creates a single-line input
+    /// @param custom_id The id the submitted value can be found by
+    /// @param label The label shown above the input
+    pub fn short(custom_id: &str, label: &str) -> Self {
+        Self::new(custom_id, label, TextInputStyle::Short)
+    }
+
+    /// Creates a multi-line input
+    /// @param custom_id The id the submitted value can be found by
+    /// @param label The label shown above the input
+    pub fn paragraph(custom_id: &str, label: &str) -> Self {
+        Self::new(custom_id, label, TextInputStyle::Paragraph)
+    }
+
+    fn new(custom_id: &str, label: &str, style: TextInputStyle) -> Self {
+        Self {
+            type_: MessageComponentType::TextInput,
+            custom_id: custom_id.to_string(),
+            style,
+            label: label.to_string(),
+            min_length: None,
+            max_length: None,
+            required: None,
+            value: None,
+            placeholder: None,
+        }
+    }
+
+    /// Sets the minimum and maximum length of the input
+    #[must_use]
+    pub fn length(mut self, min: u32, max: u32) -> Self {
+        self.min_length = Some(min);
+        self.max_length = Some(max);
+        self
+    }
+
+    /// Sets whether the input has to be filled in (it does by default)
+    #[must_use]
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = Some(required);
+        self
+    }
+
+    /// Pre-fills the input
+    #[must_use]
+    pub fn value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    /// Sets the text that is shown while the input is empty
+    #[must_use]
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+}
+
+/**
+ * An action row in a modal. Modals can only contain text inputs, one per row
+ * @docs <https://discord.com/developers/docs/interactions/message-components#action-rows>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ModalActionRow {
+    /// Always ActionRow
+    #[serde(rename = "type")]
+    pub type_: MessageComponentType,
+    /// The text input in the row
+    pub components: Vec<TextInput>,
+}
@@ -1,3 +1,5 @@
+mod builder;
 mod message_object;
 
+pub use builder::*;
 pub use message_object::*;
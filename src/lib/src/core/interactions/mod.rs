@@ -1,4 +1,10 @@
+#[cfg(feature = "gateway")]
 pub mod handler;
+#[cfg(feature = "gateway")]
 pub mod interaction_event;
+#[cfg(feature = "gateway")]
+pub mod long_task;
 pub mod message;
+#[cfg(feature = "gateway")]
+pub mod testing;
 pub mod typing;
@@ -0,0 +1,407 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crossbeam_channel::{unbounded, Receiver};
+use hyper::{header::CONTENT_TYPE, Body, Method, Response, StatusCode};
+use serde_json::{json, Value};
+
+use crate::{
+    api::{
+        guild::guild_member::GuildMember, user::User, ApplicationCommandOptionType,
+        ApplicationCommandOptionValue, ApplicationCommandType, Message, Snowflake,
+    },
+    core::{abstraction::traits::__InternalEventHandler, http::rate_limit_client::RequestObject},
+    Context, InteractionRouter,
+};
+
+use super::{
+    message::{MessageComponent, MessageComponentType},
+    typing::{
+        Interaction, InteractionData, InteractionDataOption, InteractionResponse, InteractionType,
+    },
+};
+
+/**
+ * Builds realistic interaction payloads and drives them through an `InteractionRouter`, so that command handlers can be tested without connecting to discord.
+ * Every http request the handler makes is captured instead of being sent, and returned from `dispatch` for assertions.
+ *
+ * ```rust,ignore
+ * let router = InteractionRouter::new();
+ * router.register_command(Snowflake::from(1), Arc::new(PingCommand));
+ *
+ * let sent = TestInteraction::chat_input(Snowflake::from(1), "ping")
+ *     .option("times", ApplicationCommandOptionValue::Integer(2))
+ *     .dispatch(&router);
+ * assert!(sent[0].is_callback());
+ * assert_eq!(sent[0].json().unwrap()["data"]["content"], "pong pong");
+ * ```
+ *
+ * Component clicks and modal submits go to the component or modal handler that matches their custom id. If there is none, they are
+ * delivered to the handler that is registered under the `command_id` they are built with.
+ */
+pub struct TestInteraction {
+    interaction: Interaction,
+    /// The body that is returned for requests that aren't interaction callbacks (e.g. followup messages)
+    http_response: Value,
+}
+
+impl TestInteraction {
+    /// Creates a slash command interaction
+    /// @param command_id The id the command handler is registered under
+    /// @param name The name of the command
+    pub fn chat_input(command_id: Snowflake, name: &str) -> Self {
+        Self::new(
+            InteractionType::ApplicationCommand,
+            InteractionData {
+                id: command_id,
+                name: name.to_string(),
+                type_: ApplicationCommandType::ChatInput,
+                options: None,
+                custom_id: None,
+                component_type: None,
+                values: None,
+                target_id: None,
+                components: None,
+                resolved: None,
+            },
+        )
+    }
+
+    /// Creates an autocomplete interaction, sent while the user types in an option. Use `focused` to add the option that is being typed in.
+    /// @param command_id The id of the command
+    /// @param name The name of the command, which the autocomplete handler is registered under
+    pub fn autocomplete(command_id: Snowflake, name: &str) -> Self {
+        let mut interaction = Self::chat_input(command_id, name);
+        interaction.interaction.type_ = InteractionType::ApplicationCommandAutocomplete;
+        interaction
+    }
+
+    /// Creates an interaction for a click on a button or select menu
+    /// @param command_id The id the handler is registered under
+    /// @param custom_id The custom id of the component that was used
+    /// @param component_type The type of the component that was used
+    pub fn component(
+        command_id: Snowflake,
+        custom_id: &str,
+        component_type: MessageComponentType,
+    ) -> Self {
+        Self::new(
+            InteractionType::MessageComponent,
+            InteractionData {
+                id: command_id,
+                name: String::new(),
+                type_: ApplicationCommandType::ChatInput,
+                options: None,
+                custom_id: Some(custom_id.to_string()),
+                component_type: Some(component_type),
+                values: None,
+                target_id: None,
+                components: None,
+                resolved: None,
+            },
+        )
+    }
+
+    /// Creates an interaction for a submitted modal. Use `text_input` to fill in the fields of the modal.
+    /// @param command_id The id the handler is registered under
+    /// @param custom_id The custom id of the modal
+    pub fn modal_submit(command_id: Snowflake, custom_id: &str) -> Self {
+        Self::new(
+            InteractionType::ModalSubmit,
+            InteractionData {
+                id: command_id,
+                name: String::new(),
+                type_: ApplicationCommandType::ChatInput,
+                options: None,
+                custom_id: Some(custom_id.to_string()),
+                component_type: None,
+                values: None,
+                target_id: None,
+                components: Some(Vec::new()),
+                resolved: None,
+            },
+        )
+    }
+
+    fn new(type_: InteractionType, data: InteractionData) -> Self {
+        Self {
+            interaction: Interaction {
+                id: Snowflake::from(1),
+                application_id: Snowflake::from(1),
+                type_,
+                data: Some(data),
+                guild_id: None,
+                channel_id: None,
+                member: None,
+                user: None,
+                token: "test-interaction-token".to_string(),
+                version: 1,
+                message: None,
+            },
+            http_response: json!({}),
+        }
+    }
+
+    /// Adds an option to the command. If `subcommand` was called before, the option is added to that subcommand.
+    /// @param name The name of the option
+    /// @param value The value the user entered
+    pub fn option(self, name: &str, value: ApplicationCommandOptionValue) -> Self {
+        self.push_option(name, value, None)
+    }
+
+    /// Adds the option the user is typing in to an autocomplete interaction
+    /// @param name The name of the option
+    /// @param value What the user has typed so far
+    pub fn focused(self, name: &str, value: &str) -> Self {
+        self.push_option(
+            name,
+            ApplicationCommandOptionValue::String(value.to_string()),
+            Some(true),
+        )
+    }
+
+    fn push_option(
+        mut self,
+        name: &str,
+        value: ApplicationCommandOptionValue,
+        focused: Option<bool>,
+    ) -> Self {
+        let type_ = match value {
+            ApplicationCommandOptionValue::String(_) => ApplicationCommandOptionType::String,
+            ApplicationCommandOptionValue::Integer(_) => ApplicationCommandOptionType::Integer,
+            ApplicationCommandOptionValue::Number(_) => ApplicationCommandOptionType::Number,
+            ApplicationCommandOptionValue::Boolean(_) => ApplicationCommandOptionType::Boolean,
+        };
+        let option = InteractionDataOption {
+            name: name.to_string(),
+            type_,
+            value: Some(value),
+            options: None,
+            focused,
+        };
+
+        let options = self.data().options.get_or_insert_with(Vec::new);
+        match options.last_mut() {
+            Some(sub)
+                if sub.type_ == ApplicationCommandOptionType::SubCommand
+                    || sub.type_ == ApplicationCommandOptionType::SubCommandGroup =>
+            {
+                sub.options.get_or_insert_with(Vec::new).push(option)
+            }
+            _ => options.push(option),
+        }
+        self
+    }
+
+    /// Routes the command to a subcommand. Options added after this are passed to the subcommand
+    /// @param name The name of the subcommand
+    pub fn subcommand(mut self, name: &str) -> Self {
+        self.data()
+            .options
+            .get_or_insert_with(Vec::new)
+            .push(InteractionDataOption {
+                name: name.to_string(),
+                type_: ApplicationCommandOptionType::SubCommand,
+                value: None,
+                options: None,
+                focused: None,
+            });
+        self
+    }
+
+    /// Sets the values the user picked in a select menu
+    pub fn values(mut self, values: Vec<String>) -> Self {
+        self.data().values = Some(values);
+        self
+    }
+
+    /// Adds a filled in text input to a modal submit
+    /// @param custom_id The custom id of the text input
+    /// @param value The text the user entered
+    pub fn text_input(mut self, custom_id: &str, value: &str) -> Self {
+        let input = MessageComponent {
+            custom_id: Some(custom_id.to_string()),
+            value: Some(value.to_string()),
+            ..empty_component(MessageComponentType::TextInput)
+        };
+        self.data()
+            .components
+            .get_or_insert_with(Vec::new)
+            .push(MessageComponent {
+                components: Some(vec![input]),
+                ..empty_component(MessageComponentType::ActionRow)
+            });
+        self
+    }
+
+    /// Sets the guild the interaction was sent from
+    pub fn guild(mut self, guild_id: Snowflake) -> Self {
+        self.interaction.guild_id = Some(guild_id.to_string());
+        self
+    }
+
+    /// Sets the channel the interaction was sent from
+    pub fn channel(mut self, channel_id: Snowflake) -> Self {
+        self.interaction.channel_id = Some(channel_id);
+        self
+    }
+
+    /// Sets the invoking user (for interactions in DMs)
+    pub fn user(mut self, user: User) -> Self {
+        self.interaction.user = Some(user);
+        self
+    }
+
+    /// Sets the invoking member (for interactions in guilds)
+    pub fn member(mut self, member: GuildMember) -> Self {
+        self.interaction.member = Some(member);
+        self
+    }
+
+    /// Sets the message a component was attached to
+    pub fn message(mut self, message: Message) -> Self {
+        self.interaction.message = Some(Box::new(message));
+        self
+    }
+
+    /// Sets the json body that is returned for captured requests that aren't interaction callbacks.
+    /// Handlers that send followups should set this to a message object so that the response can be parsed.
+    pub fn http_response(mut self, body: Value) -> Self {
+        self.http_response = body;
+        self
+    }
+
+    /// Returns the interaction payload without dispatching it
+    pub fn build(self) -> Interaction {
+        self.interaction
+    }
+
+    /// Sends the interaction through the router, and waits for the handler to finish.
+    /// @return Every request the handler made, in the order they were made
+    pub fn dispatch(self, router: &InteractionRouter) -> Vec<CapturedRequest> {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let (sender, receiver) = unbounded();
+        spawn_capture_thread(receiver, captured.clone(), self.http_response);
+
+        let ctx = Context::with_request_stream("test-token".to_string(), sender);
+        router.handler(ctx, self.interaction);
+
+        let mut captured = captured.lock().unwrap();
+        std::mem::take(&mut *captured)
+    }
+
+    fn data(&mut self) -> &mut InteractionData {
+        self.interaction.data.as_mut().unwrap()
+    }
+}
+
+/// A request that a handler made while being driven by a `TestInteraction`
+#[derive(Debug, Clone)]
+pub struct CapturedRequest {
+    /// The http method of the request
+    pub method: Method,
+    /// The full uri of the request
+    pub uri: String,
+    /// The content type of the body, if it has one
+    pub content_type: Option<String>,
+    /// The raw body of the request
+    pub body: Vec<u8>,
+}
+
+impl CapturedRequest {
+    /// Whether this request is an initial response to the interaction (`POST /interactions/{id}/{token}/callback`)
+    pub fn is_callback(&self) -> bool {
+        self.uri.contains("/interactions/") && self.uri.ends_with("/callback")
+    }
+
+    /// Parses the json payload of the request. For multipart requests (with files) this is the `payload_json` field.
+    pub fn json(&self) -> Option<Value> {
+        let content_type = self.content_type.as_deref()?;
+        if content_type.starts_with("multipart/form-data") {
+            let body = String::from_utf8_lossy(&self.body);
+            let part = body.split("name=\"payload_json\"").nth(1)?;
+            let start = part.find("\r\n\r\n")? + 4;
+            let end = part[start..].find("\r\n--")? + start;
+            serde_json::from_str(&part[start..end]).ok()
+        } else {
+            serde_json::from_slice(&self.body).ok()
+        }
+    }
+
+    /// Parses the request as an interaction response. Returns None if this isn't an interaction callback
+    pub fn interaction_response(&self) -> Option<InteractionResponse> {
+        if !self.is_callback() {
+            return None;
+        }
+        serde_json::from_value(self.json()?).ok()
+    }
+}
+
+/// Answers the requests made through a test context instead of sending them to discord
+fn spawn_capture_thread(
+    receiver: Receiver<RequestObject>,
+    captured: Arc<Mutex<Vec<CapturedRequest>>>,
+    http_response: Value,
+) {
+    thread::Builder::new()
+        .name("Test_Request_Thread".to_string())
+        .spawn(move || {
+            // Stops once the test context (and every clone of it) is dropped
+            while let Ok(obj) = receiver.recv() {
+                // The future was dropped before the request got here
+                let request = match obj.request.take_request() {
+                    Some(request) => request,
+                    None => continue,
+                };
+
+                let (parts, body) = request.into_parts();
+                let body = async_std::task::block_on(hyper::body::to_bytes(body))
+                    .map(|b| b.to_vec())
+                    .unwrap_or_default();
+                let request = CapturedRequest {
+                    method: parts.method,
+                    uri: parts.uri.to_string(),
+                    content_type: parts
+                        .headers
+                        .get(CONTENT_TYPE)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string),
+                    body,
+                };
+
+                let response = if request.is_callback() {
+                    Response::builder()
+                        .status(StatusCode::NO_CONTENT)
+                        .body(Body::empty())
+                } else {
+                    Response::builder()
+                        .status(StatusCode::OK)
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(Body::from(http_response.to_string()))
+                };
+                captured.lock().unwrap().push(request);
+                obj.request.commit(Ok(response.unwrap()));
+            }
+        })
+        .unwrap();
+}
+
+fn empty_component(type_: MessageComponentType) -> MessageComponent {
+    MessageComponent {
+        type_,
+        custom_id: None,
+        disabled: None,
+        style: None,
+        label: None,
+        emoji: None,
+        url: None,
+        options: None,
+        placeholder: None,
+        min_values: None,
+        max_values: None,
+        components: None,
+        value: None,
+    }
+}
@@ -1,22 +1,40 @@
+use std::{collections::HashMap, str::FromStr};
+
 use bitflags::bitflags;
 use discrab_codegen::CommandArg;
+use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::{
-    api::{channel::{attachment::Attachment, embed::Embed}, ApplicationCommandOptionValue},
+    api::{channel::{attachment::{Attachment, AttachmentFile}, embed::{validate_embeds, Embed}}, ApplicationCommandOptionValue},
     api::ApplicationCommandType,
-    core::abstraction::traits::CommandArg,
+    core::{
+        abstraction::{context::Context, traits::CommandArg},
+        http::rate_limit_client::{send_request, send_request_noparse, RequestRoute},
+    },
     discord::{
         interactions::application_command::{
             ApplicationCommandOptionChoice, ApplicationCommandOptionType,
         },
-        resources::{channel::message::Message, guild::guild_member::GuildMember, user::User},
+        resources::{
+            channel::{message::Message, Channel},
+            guild::{guild_member::GuildMember, role::Role},
+            user::User,
+        },
         snowflake::Snowflake,
     },
+    util::{
+        error::{Error, ErrorTypes},
+        multipart::message_request,
+    },
+    BASE_URL,
 };
 
-use super::message::MessageComponent;
+use super::message::{
+    validate_components, MessageComponent, MessageComponentType, ModalActionRow, TextInput,
+};
 
 /**
  * Interaction
@@ -59,6 +77,7 @@ pub enum InteractionType {
     ApplicationCommand = 2,
     MessageComponent = 3,
     ApplicationCommandAutocomplete = 4,
+    ModalSubmit = 5,
 }
 
 /**
@@ -67,23 +86,289 @@ pub enum InteractionType {
 */
 #[derive(Serialize, Deserialize, Clone)]
 pub struct InteractionData {
-    /// The id of the invoked command
+    /// The id of the invoked command. Components and modals don't have one, so it is 0 for them
+    #[serde(default)]
     pub id: Snowflake,
-    /// The name of the invoked command
+    /// The name of the invoked command. Empty for components and modals
+    #[serde(default)]
     pub name: String,
     /// The type of the invoked command
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default)]
     pub type_: ApplicationCommandType,
     /// The params + values from the user
     pub options: Option<Vec<InteractionDataOption>>,
     /// The custom_id of the component
     pub custom_id: Option<String>,
     /// The type of the component
-    pub component_type: Option<String>,
+    pub component_type: Option<MessageComponentType>,
     /// The values the user selected
     pub values: Option<Vec<String>>,
     /// The id of user or message targetted by a user or message command
     pub target_id: Option<Snowflake>,
+    /// The values submitted by the user in a modal
+    pub components: Option<Vec<MessageComponent>>,
+    /// The users, members, roles, channels, messages and attachments that the options refer to
+    pub resolved: Option<ResolvedData>,
+}
+
+/**
+ * Resolved Data Structure
+ * The objects that user, channel, role, mentionable and attachment options refer to. The options only contain the ids.
+ * @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-object-resolved-data-structure>
+ */
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ResolvedData {
+    #[serde(default)]
+    pub users: HashMap<Snowflake, User>,
+    /// Partial members, without the user (it is in `users`), deaf, and mute
+    #[serde(default)]
+    pub members: HashMap<Snowflake, GuildMember>,
+    #[serde(default)]
+    pub roles: HashMap<Snowflake, Role>,
+    /// Partial channels, with only the id, name, type, permissions, and (for threads) thread metadata and parent id
+    #[serde(default)]
+    pub channels: HashMap<Snowflake, Channel>,
+    #[serde(default)]
+    pub messages: HashMap<Snowflake, Message>,
+    #[serde(default)]
+    pub attachments: HashMap<Snowflake, Attachment>,
+}
+
+impl InteractionData {
+    /// The options the user filled in. For subcommands, these are the options of the subcommand that was used
+    pub fn leaf_options(&self) -> &[InteractionDataOption] {
+        let mut options = self.options.as_deref().unwrap_or_default();
+        while let Some(sub) = options.iter().find(|option| {
+            option.type_ == ApplicationCommandOptionType::SubCommand
+                || option.type_ == ApplicationCommandOptionType::SubCommandGroup
+        }) {
+            options = sub.options.as_deref().unwrap_or_default();
+        }
+        options
+    }
+
+    /// The value of an option, None if the user didn't fill it in
+    pub fn option_value(&self, name: &str) -> Option<&ApplicationCommandOptionValue> {
+        self.leaf_options()
+            .iter()
+            .find(|option| option.name == name)?
+            .value
+            .as_ref()
+    }
+
+    /// Gets a string option
+    /// @return None if the option wasn't given, or an error if it isn't a string
+    pub fn get_string(&self, name: &str) -> Result<Option<String>, Error> {
+        match self.option_value(name) {
+            None => Ok(None),
+            Some(ApplicationCommandOptionValue::String(value)) => Ok(Some(value.clone())),
+            Some(value) => Err(wrong_type(name, "a string", value)),
+        }
+    }
+
+    /// Gets an integer option
+    /// @return None if the option wasn't given, or an error if it isn't an integer
+    pub fn get_integer(&self, name: &str) -> Result<Option<i64>, Error> {
+        match self.option_value(name) {
+            None => Ok(None),
+            Some(ApplicationCommandOptionValue::Integer(value)) => Ok(Some(*value)),
+            Some(value) => Err(wrong_type(name, "an integer", value)),
+        }
+    }
+
+    /// Gets a number option. Integers are converted
+    /// @return None if the option wasn't given, or an error if it isn't a number
+    pub fn get_number(&self, name: &str) -> Result<Option<f64>, Error> {
+        match self.option_value(name) {
+            None => Ok(None),
+            Some(ApplicationCommandOptionValue::Number(value)) => Ok(Some(*value)),
+            Some(ApplicationCommandOptionValue::Integer(value)) => Ok(Some(*value as f64)),
+            Some(value) => Err(wrong_type(name, "a number", value)),
+        }
+    }
+
+    /// Gets a boolean option
+    /// @return None if the option wasn't given, or an error if it isn't a boolean
+    pub fn get_boolean(&self, name: &str) -> Result<Option<bool>, Error> {
+        match self.option_value(name) {
+            None => Ok(None),
+            Some(ApplicationCommandOptionValue::Boolean(value)) => Ok(Some(*value)),
+            Some(value) => Err(wrong_type(name, "a boolean", value)),
+        }
+    }
+
+    /// Gets the id in a user, channel, role, mentionable, or attachment option
+    /// @return None if the option wasn't given, or an error if it isn't an id
+    pub fn get_id(&self, name: &str) -> Result<Option<Snowflake>, Error> {
+        match self.option_value(name) {
+            None => Ok(None),
+            Some(ApplicationCommandOptionValue::String(value)) => value
+                .parse::<u64>()
+                .map(|id| Some(Snowflake::from(id)))
+                .map_err(|_| wrong_type(name, "an id", &ApplicationCommandOptionValue::String(value.clone()))),
+            Some(value) => Err(wrong_type(name, "an id", value)),
+        }
+    }
+
+    /// Gets the user in a user or mentionable option
+    /// @return None if the option wasn't given, or an error if the user isn't in the resolved data
+    pub fn get_user(&self, name: &str) -> Result<Option<User>, Error> {
+        self.get_resolved(name, "user", |resolved| &resolved.users)
+    }
+
+    /// Gets the member in a user or mentionable option, with its user filled in. Only available in guilds
+    /// @return None if the option wasn't given, or an error if the member isn't in the resolved data
+    pub fn get_member(&self, name: &str) -> Result<Option<GuildMember>, Error> {
+        let member = self.get_resolved(name, "member", |resolved| &resolved.members)?;
+        Ok(member.map(|mut member| {
+            if member.user.is_none() {
+                member.user = self.get_user(name).ok().flatten();
+            }
+            member
+        }))
+    }
+
+    /// Gets the (partial) channel in a channel option
+    /// @return None if the option wasn't given, or an error if the channel isn't in the resolved data
+    pub fn get_channel(&self, name: &str) -> Result<Option<Channel>, Error> {
+        self.get_resolved(name, "channel", |resolved| &resolved.channels)
+    }
+
+    /// Gets the role in a role or mentionable option
+    /// @return None if the option wasn't given, or an error if the role isn't in the resolved data
+    pub fn get_role(&self, name: &str) -> Result<Option<Role>, Error> {
+        self.get_resolved(name, "role", |resolved| &resolved.roles)
+    }
+
+    /// Gets the file in an attachment option
+    /// @return None if the option wasn't given, or an error if the attachment isn't in the resolved data
+    pub fn get_attachment(&self, name: &str) -> Result<Option<Attachment>, Error> {
+        self.get_resolved(name, "attachment", |resolved| &resolved.attachments)
+    }
+
+    /// Looks up the object an option refers to in the resolved data
+    fn get_resolved<T: Clone>(
+        &self,
+        name: &str,
+        kind: &str,
+        map: impl FnOnce(&ResolvedData) -> &HashMap<Snowflake, T>,
+    ) -> Result<Option<T>, Error> {
+        let id = match self.get_id(name)? {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        self.resolved
+            .as_ref()
+            .and_then(|resolved| map(resolved).get(&id))
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| {
+                Error::new(
+                    format!("Option `{}` refers to {} {}, which isn't in the resolved data", name, kind, id),
+                    ErrorTypes::PARSE,
+                )
+            })
+    }
+
+    /// The option the user is typing in, for autocomplete interactions
+    pub fn focused_option(&self) -> Option<&InteractionDataOption> {
+        self.leaf_options()
+            .iter()
+            .find(|option| option.focused.unwrap_or(false))
+    }
+
+    /// The value the user entered in a text input of a submitted modal
+    /// @param custom_id The custom id of the text input
+    /// @return None if there is no such input. Inputs that weren't required and were left empty are an empty string
+    pub fn field_value(&self, custom_id: &str) -> Option<&str> {
+        self.fields()
+            .into_iter()
+            .find(|(id, _)| *id == custom_id)
+            .map(|(_, value)| value)
+    }
+
+    /// All the text inputs of a submitted modal, as (custom id, value)
+    pub fn fields(&self) -> Vec<(&str, &str)> {
+        // The inputs are sent back inside of their action rows
+        self.components
+            .iter()
+            .flatten()
+            .flat_map(|row| row.components.iter().flatten().chain(std::iter::once(row)))
+            .filter_map(|component| {
+                Some((component.custom_id.as_deref()?, component.value.as_deref()?))
+            })
+            .collect()
+    }
+
+    /// Parses the value of a text input of a submitted modal, e.g. into a number
+    /// @param custom_id The custom id of the text input
+    /// @return None if there is no such input or it was left empty, or an error if the value can't be parsed
+    pub fn parse_field<T: FromStr>(&self, custom_id: &str) -> Result<Option<T>, Error> {
+        match self.field_value(custom_id) {
+            None | Some("") => Ok(None),
+            Some(value) => value.trim().parse().map(Some).map_err(|_| {
+                Error::new(
+                    format!("Field `{}` has an invalid value: {}", custom_id, value),
+                    ErrorTypes::PARSE,
+                )
+            }),
+        }
+    }
+}
+
+fn wrong_type(name: &str, expected: &str, value: &ApplicationCommandOptionValue) -> Error {
+    Error::new(
+        format!("Option `{}` should be {}, but is {:?}", name, expected, value),
+        ErrorTypes::PARSE,
+    )
+}
+
+/// Adds the typed option and modal field getters of `InteractionData` to a type with a `data: Option<InteractionData>` field.
+/// Each getter returns None if there is no data.
+macro_rules! option_getters {
+    ($($getter:ident -> $type_:ty;)+) => {
+        $(
+        /// See the method with the same name on `InteractionData`
+        pub fn $getter(&self, name: &str) -> Result<Option<$type_>, $crate::util::error::Error> {
+            match &self.data {
+                Some(data) => data.$getter(name),
+                None => Ok(None),
+            }
+        }
+        )+
+
+        /// See the method with the same name on `InteractionData`
+        pub fn field_value(&self, custom_id: &str) -> Option<&str> {
+            self.data.as_ref()?.field_value(custom_id)
+        }
+
+        /// See the method with the same name on `InteractionData`
+        pub fn parse_field<T: std::str::FromStr>(&self, custom_id: &str) -> Result<Option<T>, $crate::util::error::Error> {
+            match &self.data {
+                Some(data) => data.parse_field(custom_id),
+                None => Ok(None),
+            }
+        }
+    };
+    () => {
+        $crate::core::interactions::typing::option_getters! {
+            get_string -> String;
+            get_integer -> i64;
+            get_number -> f64;
+            get_boolean -> bool;
+            get_id -> $crate::discord::snowflake::Snowflake;
+            get_user -> $crate::discord::resources::user::User;
+            get_member -> $crate::discord::resources::guild::guild_member::GuildMember;
+            get_channel -> $crate::discord::resources::channel::Channel;
+            get_role -> $crate::discord::resources::guild::role::Role;
+            get_attachment -> $crate::discord::resources::channel::attachment::Attachment;
+        }
+    };
+}
+pub(crate) use option_getters;
+
+impl Interaction {
+    option_getters!();
 }
 
 /**
@@ -120,8 +405,10 @@ pub struct InteractionResponse {
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum InteractionCallbackData {
-    Message(MessageData),
+    // Before Message, since every field of MessageData is optional and it would match these too
+    Modal(ModalData),
     Autocomplete(AutocompleteData),
+    Message(MessageData),
 }
 
 impl InteractionCallbackData {
@@ -139,13 +426,38 @@ impl InteractionCallbackData {
 }
 
 /**
- * Interaction Callback Type
+ * Autocomplete
  * @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-response-object-autocomplete>
  */
 #[derive(Clone, Deserialize, Serialize)]
 pub struct AutocompleteData {
     /// autocomplete choices (max of 25 choices)
-    pub choices: ApplicationCommandOptionChoice,
+    pub choices: Vec<ApplicationCommandOptionChoice>,
+}
+
+impl AutocompleteData {
+    /// The most choices that can be suggested
+    pub const MAX_CHOICES: usize = 25;
+
+    /// Creates the response data, dropping the choices past the first 25
+    pub fn new(mut choices: Vec<ApplicationCommandOptionChoice>) -> Self {
+        choices.truncate(Self::MAX_CHOICES);
+        Self { choices }
+    }
+}
+
+/**
+ * Modal
+ * @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-response-object-modal>
+ */
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ModalData {
+    /// A developer-defined identifier for the modal, max 100 characters. The submit interaction has the same custom id
+    pub custom_id: String,
+    /// The title of the popup modal, max 45 characters
+    pub title: String,
+    /// Between 1 and 5 action rows, each with one text input
+    pub components: Vec<ModalActionRow>,
 }
 
 /**
@@ -171,12 +483,24 @@ pub struct MessageData {
     pub attachments: Option<Vec<Attachment>>,
 }
 
+impl MessageData {
+    /// Checks the embeds and components against discord's limits, so that the message fails before it is sent
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_embeds(self.embeds.as_deref().unwrap_or_default())?;
+        validate_components(self.components.as_deref().unwrap_or_default())
+    }
+}
+
 bitflags! {
     /// Interaction Callback Data Flags
     /// https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-response-object-interaction-callback-data-flags
     pub struct MessageDataFlags: u64 {
+        /// do not include any embeds when serializing this message
+        const SUPPRESS_EMBEDS = 1 << 2;
         /// only the user receiving the message can see it
         const EPHEMERAL = 1 << 6;
+        /// this message will not trigger push and desktop notifications
+        const SUPPRESS_NOTIFICATIONS = 1 << 12;
     }
 }
 
@@ -201,12 +525,16 @@ pub enum AllowedMentionType {
 #[derive(Clone, Deserialize, Serialize)]
 pub struct AllowedMentions {
     /// An array of allowed mention types to parse from the content.
+    #[serde(default)]
     pub parse: Vec<String>,
     /// Array of role_ids to mention (Max size of 100)
+    #[serde(default)]
     pub roles: Vec<Snowflake>,
     /// Array of user_ids to mention (Max size of 100)
+    #[serde(default)]
     pub users: Vec<Snowflake>,
     /// For replies, whether to mention the author of the message being replied to (default false)
+    #[serde(default)]
     pub replied_user: bool,
 }
 
@@ -228,4 +556,379 @@ pub enum InteractionCallbackType {
     UpdateMessage = 7,
     /// respond to an autocomplete interaction with suggested choices
     ApplicationCommandAutocompleteResult = 8,
+    /// respond to an interaction with a popup modal
+    Modal = 9,
+}
+
+/**
+ * Builds the message for an interaction response, edit or followup
+ *
+ * ```rust,ignore
+ * interaction
+ *     .reply(ctx, InteractionMessageBuilder::new().content("Only you can see this").ephemeral())
+ *     .await?;
+ * ```
+ */
+#[derive(Clone)]
+pub struct InteractionMessageBuilder {
+    data: MessageData,
+    files: Vec<AttachmentFile>,
+}
+
+impl InteractionMessageBuilder {
+    pub fn new() -> Self {
+        Self {
+            data: MessageData {
+                tts: None,
+                content: None,
+                embeds: None,
+                allowed_mentions: None,
+                flags: None,
+                components: None,
+                attachments: None,
+            },
+            files: Vec::new(),
+        }
+    }
+
+    /// Sets the text of the message
+    #[must_use]
+    pub fn content(mut self, content: &str) -> Self {
+        self.data.content = Some(content.to_string());
+        self
+    }
+
+    /// Adds an embed to the message (up to 10)
+    #[must_use]
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.data.embeds.get_or_insert_with(Vec::new).push(embed);
+        self
+    }
+
+    /// Adds a component (usually an action row) to the message
+    #[must_use]
+    pub fn component(mut self, component: MessageComponent) -> Self {
+        self.data
+            .components
+            .get_or_insert_with(Vec::new)
+            .push(component);
+        self
+    }
+
+    /// Makes the message only visible to the user that used the interaction. Only works for the first response and followups
+    #[must_use]
+    pub fn ephemeral(self) -> Self {
+        self.add_flags(MessageDataFlags::EPHEMERAL)
+    }
+
+    /// Sends the message without a push or desktop notification, like `@silent` in the client
+    #[must_use]
+    pub fn silent(self) -> Self {
+        self.add_flags(MessageDataFlags::SUPPRESS_NOTIFICATIONS)
+    }
+
+    /// Doesn't show the embeds of the links in the content
+    #[must_use]
+    pub fn suppress_embeds(self) -> Self {
+        self.add_flags(MessageDataFlags::SUPPRESS_EMBEDS)
+    }
+
+    fn add_flags(mut self, flags: MessageDataFlags) -> Self {
+        self.data.flags = Some(self.data.flags.unwrap_or(0) | flags.bits());
+        self
+    }
+
+    /// Makes the message text to speech
+    #[must_use]
+    pub fn tts(mut self, tts: bool) -> Self {
+        self.data.tts = Some(tts);
+        self
+    }
+
+    /// Sets who can be mentioned by the message
+    #[must_use]
+    pub fn allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.data.allowed_mentions = Some(allowed_mentions);
+        self
+    }
+
+    /// Uploads a file with the message. It can be referenced in embeds with `attachment://<filename>`
+    #[must_use]
+    pub fn attach_file(mut self, file: AttachmentFile) -> Self {
+        self.files.push(file);
+        self
+    }
+
+    /// The message data, without the files
+    pub fn build(self) -> MessageData {
+        self.data
+    }
+}
+
+impl Default for InteractionMessageBuilder {
+    fn default() -> Self {
+        InteractionMessageBuilder::new()
+    }
+}
+
+impl From<MessageData> for InteractionMessageBuilder {
+    fn from(data: MessageData) -> Self {
+        Self {
+            data,
+            files: Vec::new(),
+        }
+    }
+}
+
+/**
+ * Builds a popup modal with text inputs. Respond to an interaction with it using `show_modal` (or `respond_modal` on an `InteractionCtx`),
+ * and the values the user entered arrive in a modal submit interaction with the same custom id.
+ *
+ * ```rust,ignore
+ * let modal = ModalBuilder::new("report", "Report a user")
+ *     .input(TextInput::short("user", "Who are you reporting?"))
+ *     .input(TextInput::paragraph("reason", "What happened?").length(10, 1000));
+ * ctx.respond_modal(modal).await?;
+ * ```
+ */
+#[derive(Clone)]
+pub struct ModalBuilder {
+    data: ModalData,
+}
+
+impl ModalBuilder {
+    /// The most text inputs a modal can have
+    pub const MAX_INPUTS: usize = 5;
+
+    /// @param custom_id The id the submit interaction can be routed by, max 100 characters
+    /// @param title The title of the modal, max 45 characters
+    pub fn new(custom_id: &str, title: &str) -> Self {
+        Self {
+            data: ModalData {
+                custom_id: custom_id.to_string(),
+                title: title.to_string(),
+                components: Vec::new(),
+            },
+        }
+    }
+
+    /// Adds a text input, on its own row. A modal can have up to 5
+    #[must_use]
+    pub fn input(mut self, input: TextInput) -> Self {
+        self.data.components.push(ModalActionRow {
+            type_: MessageComponentType::ActionRow,
+            components: vec![input],
+        });
+        self
+    }
+
+    /// @return An error if the modal has no inputs or too many, since discord would reject it
+    pub fn build(self) -> Result<ModalData, Error> {
+        let inputs = self.data.components.len();
+        if inputs == 0 || inputs > Self::MAX_INPUTS {
+            return Err(Error::new(
+                format!(
+                    "A modal needs between 1 and {} text inputs, but has {}",
+                    Self::MAX_INPUTS,
+                    inputs
+                ),
+                ErrorTypes::REQUEST,
+            ));
+        }
+        Ok(self.data)
+    }
+}
+
+impl Interaction {
+    /**
+     * Responds to an autocomplete interaction with the choices to suggest
+     * @param choices Up to 25 choices. The rest are dropped
+     * @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-response-object-autocomplete>
+     */
+    pub async fn autocomplete(
+        &self,
+        ctx: Context,
+        choices: Vec<ApplicationCommandOptionChoice>,
+    ) -> Result<(), Error> {
+        self.callback(
+            ctx,
+            InteractionResponse {
+                type_: InteractionCallbackType::ApplicationCommandAutocompleteResult,
+                data: Some(InteractionCallbackData::Autocomplete(AutocompleteData::new(
+                    choices,
+                ))),
+            },
+            &[],
+        )
+        .await
+    }
+
+    /**
+     * Responds to the interaction with a popup modal. Modals can't be shown in response to a modal submit, or after deferring.
+     * @param modal The modal to show
+     * @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-response-object-modal>
+     */
+    pub async fn show_modal(&self, ctx: Context, modal: ModalBuilder) -> Result<(), Error> {
+        self.callback(
+            ctx,
+            InteractionResponse {
+                type_: InteractionCallbackType::Modal,
+                data: Some(InteractionCallbackData::Modal(modal.build()?)),
+            },
+            &[],
+        )
+        .await
+    }
+
+    /**
+     * Responds to the interaction with a message. An interaction can only be responded to once, within 3 seconds of receiving it.
+     * @param message The message to respond with
+     * @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#create-interaction-response>
+     */
+    pub async fn reply(&self, ctx: Context, message: InteractionMessageBuilder) -> Result<(), Error> {
+        let InteractionMessageBuilder { data, files } = message;
+        data.validate()?;
+        self.callback(
+            ctx,
+            InteractionResponse {
+                type_: InteractionCallbackType::ChannelMessageWithSource,
+                data: Some(InteractionCallbackData::Message(data)),
+            },
+            &files,
+        )
+        .await
+    }
+
+    /**
+     * Acknowledges the interaction and shows a loading state, so that the response can be sent later with `edit_response`.
+     * Use this when responding takes longer than 3 seconds. The token stays valid for 15 minutes.
+     * @param ephemeral If true, the loading state and the response are only visible to the user that used the interaction
+     */
+    pub async fn defer(&self, ctx: Context, ephemeral: bool) -> Result<(), Error> {
+        let mut data = InteractionMessageBuilder::new();
+        if ephemeral {
+            data = data.ephemeral();
+        }
+        self.callback(
+            ctx,
+            InteractionResponse {
+                type_: InteractionCallbackType::DeferredChannelMessageWithSource,
+                data: Some(InteractionCallbackData::Message(data.build())),
+            },
+            &[],
+        )
+        .await
+    }
+
+    /// For components, acknowledges the interaction without a loading state. The message the component is on can be edited later with `edit_response`
+    pub async fn defer_update(&self, ctx: Context) -> Result<(), Error> {
+        self.callback(
+            ctx,
+            InteractionResponse {
+                type_: InteractionCallbackType::DeferredUpdateMessage,
+                data: None,
+            },
+            &[],
+        )
+        .await
+    }
+
+    /**
+     * Edits the response to the interaction. Fields that aren't set in the message are left as they are.
+     * @param message The new message
+     * @return The edited message
+     * @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#edit-original-interaction-response>
+     */
+    pub async fn edit_response(
+        &self,
+        ctx: Context,
+        message: InteractionMessageBuilder,
+    ) -> Result<Message, Error> {
+        let InteractionMessageBuilder { data, files } = message;
+        data.validate()?;
+        let mut payload = serde_json::to_value(&data)?;
+        if let Value::Object(fields) = &mut payload {
+            fields.retain(|_, value| !value.is_null());
+        }
+        let request = message_request(
+            Request::builder().method(Method::PATCH).uri(format!(
+                "{}/webhooks/{}/{}/messages/@original",
+                BASE_URL, self.application_id, self.token
+            )),
+            payload,
+            "",
+            &files,
+        );
+        send_request(ctx, self.webhook_route(), request).await
+    }
+
+    /// Deletes the response to the interaction
+    /// @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#delete-original-interaction-response>
+    pub async fn delete_response(&self, ctx: Context) -> Result<(), Error> {
+        let request = Request::builder()
+            .method(Method::DELETE)
+            .uri(format!(
+                "{}/webhooks/{}/{}/messages/@original",
+                BASE_URL, self.application_id, self.token
+            ))
+            .body(Body::empty())
+            .unwrap();
+        send_request_noparse(ctx, self.webhook_route(), request).await
+    }
+
+    /**
+     * Sends another message for the interaction, after it was responded to or deferred. Followups can be sent for up to 15 minutes.
+     * @param message The message to send
+     * @return The message that was sent
+     * @docs <https://discord.com/developers/docs/interactions/receiving-and-responding#create-followup-message>
+     */
+    pub async fn followup(
+        &self,
+        ctx: Context,
+        message: InteractionMessageBuilder,
+    ) -> Result<Message, Error> {
+        let InteractionMessageBuilder { data, files } = message;
+        data.validate()?;
+        let request = message_request(
+            Request::builder().method(Method::POST).uri(format!(
+                "{}/webhooks/{}/{}",
+                BASE_URL, self.application_id, self.token
+            )),
+            serde_json::to_value(&data)?,
+            "",
+            &files,
+        );
+        send_request(ctx, self.webhook_route(), request).await
+    }
+
+    /// Sends the interaction response
+    async fn callback(
+        &self,
+        ctx: Context,
+        response: InteractionResponse,
+        files: &[AttachmentFile],
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/interactions/{interaction.id}/{interaction.token}/callback".to_string(),
+            major_param: self.id.to_string(),
+        };
+        let request = message_request(
+            Request::builder().method(Method::POST).uri(format!(
+                "{}/interactions/{}/{}/callback",
+                BASE_URL, self.id, self.token
+            )),
+            serde_json::to_value(&response)?,
+            "/data",
+            files,
+        );
+        send_request_noparse(ctx, route, request).await
+    }
+
+    /// The edits and followups are rate limited by the interaction's token
+    fn webhook_route(&self) -> RequestRoute {
+        RequestRoute {
+            base_route: "/webhooks/{application.id}/{interaction.token}".to_string(),
+            major_param: self.token.clone(),
+        }
+    }
 }
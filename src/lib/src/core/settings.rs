@@ -1,10 +1,80 @@
+use super::abstraction::memory_cache::CacheConfig;
+
 #[derive(Clone, Default)]
 pub struct Settings {
     pub debug: bool,
+    /// Where REST requests are sent instead of `https://discord.com/api/v9`, e.g. an external rate limit proxy like `http://localhost:8080/api/v9`
+    pub proxy_url: Option<String>,
+    /// Extra headers sent with every request that goes through the proxy (e.g. the proxy's own authorization)
+    pub proxy_headers: Vec<(String, String)>,
+    /// Sends requests as soon as they are made instead of queueing them for the rate limits.
+    /// Only turn this on when something else (like a rate limit proxy) is doing the rate limiting, otherwise the bot will get rate limited by discord
+    pub disable_local_rate_limiter: bool,
+    /// The user agent sent with every request. Discord wants it in the format `DiscordBot ($url, $versionNumber)`
+    pub user_agent: Option<String>,
+    /// What the cache keeps. Everything is cached by default
+    pub cache: CacheConfig,
+    /// Asks discord to compress the gateway connection with zlib-stream. Set it with `Bot::compress_gateway`
+    pub compress_gateway: bool,
+    /// How the gateway encodes its payloads. Set it with `Bot::gateway_encoding`
+    pub gateway_encoding: GatewayEncoding,
+}
+
+/**
+ * The encoding of the gateway connection. The events that reach the handlers are the same with either one.
+ * @docs <https://discord.com/developers/docs/topics/gateway#etfjson>
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GatewayEncoding {
+    /// Plain json text
+    #[default]
+    Json,
+    /// The erlang term format, which is smaller than json and faster to decode, especially for big GUILD_CREATEs
+    Etf,
+}
+
+impl GatewayEncoding {
+    /// The value of the `encoding` query parameter of the gateway url
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GatewayEncoding::Json => "json",
+            GatewayEncoding::Etf => "etf",
+        }
+    }
 }
 
 impl Settings {
     pub fn set_debug(&mut self, debug: bool) {
         self.debug = debug;
     }
+
+    /**
+     * Sends the REST requests to a rate limit proxy instead of discord.
+     * The proxy is now in charge of the rate limits, so the local rate limiter is disabled to stop the two from both throttling the requests.
+     * @param url The url that replaces `https://discord.com/api/v9`
+     */
+    pub fn set_proxy(&mut self, url: &str) {
+        self.proxy_url = Some(url.trim_end_matches('/').to_string());
+        self.disable_local_rate_limiter = true;
+    }
+
+    /// Adds a header that is sent with every request that goes through the proxy
+    pub fn add_proxy_header(&mut self, name: &str, value: &str) {
+        self.proxy_headers
+            .push((name.to_string(), value.to_string()));
+    }
+
+    /// Turns the local rate limiter off (or back on after `set_proxy`)
+    pub fn set_disable_local_rate_limiter(&mut self, disable: bool) {
+        self.disable_local_rate_limiter = disable;
+    }
+
+    pub fn set_user_agent(&mut self, user_agent: &str) {
+        self.user_agent = Some(user_agent.to_string());
+    }
+
+    /// Sets what the cache keeps, e.g. `CacheConfig::disabled()` to not cache anything
+    pub fn set_cache(&mut self, cache: CacheConfig) {
+        self.cache = cache;
+    }
 }
@@ -11,6 +11,13 @@ bitfield! {
     pub b, _: 7, 0;
 }
 
+impl From<u32> for Color {
+    /// A color from its hex value, like `0x3498db`
+    fn from(rgb: u32) -> Self {
+        Color(rgb & 0xffffff)
+    }
+}
+
 impl Display for Color {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         write!(f, "{:#08x}", self.0)
@@ -1,11 +1,14 @@
 use bitflags::bitflags;
-use serde::{Deserialize, Deserializer, Serialize};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::discord::{resources::emoji::Emoji, snowflake::Snowflake};
 
 /**
  * Activity Object
+ * Received in presences with everything the user's client set. A bot's own activity can only have a name, type, url and state,
+ * so `UpdatePresence` only sends those, see `Activity::playing` and the other constructors.
  * @docs <https://discord.com/developers/docs/topics/gateway#activity-object>
  */
 #[derive(Serialize, Deserialize, Clone)]
@@ -16,38 +19,189 @@ pub struct Activity {
     #[serde(rename = "type")]
     pub type_: ActivityType,
     /// stream url, is validated when type is 1
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     /// unix timestamp (in milliseconds) of when the activity was added to the user's session
+    #[serde(default, skip_serializing_if = "is_zero")]
     pub created_at: i64,
     /// unix timestamps for start and/or end of the game
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamps: Option<ActivityTimestamps>,
     /// application id for the game
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub application_id: Option<Snowflake>,
     /// what the player is currently doing
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
     /// the user's current party status
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<String>,
     /// emoji used for a custom status
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub emoji: Option<Emoji>,
     /// information for the current party of the player
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub party: Option<ActivityParty>,
     /// images for the presence and their hover texts
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub assets: Option<ActivityAssets>,
     /// secrets for Rich Presence joining and spectating
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub secrets: Option<ActivitySecrets>,
     /// whether or not the activity is an instanced game session
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub instance: Option<bool>,
     /// activity flags ORd together, describes what the payload includes
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub flags: Option<ActivityFlags>,
-    /// the custom buttons shown in the Rich Presence (max 2)
+    /// the custom buttons shown in the Rich Presence (max 2). Received buttons only have a label, see `ActivityButton`
+    #[serde(
+        default,
+        deserialize_with = "deserialize_buttons",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub buttons: Option<Vec<ActivityButton>>,
 }
 
+impl Activity {
+    /// An activity with only a name and type, which is all that bots can set
+    fn new(type_: ActivityType, name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            type_,
+            url: None,
+            created_at: 0,
+            timestamps: None,
+            application_id: None,
+            details: None,
+            state: None,
+            emoji: None,
+            party: None,
+            assets: None,
+            secrets: None,
+            instance: None,
+            flags: None,
+            buttons: None,
+        }
+    }
+
+    /// "Playing {name}"
+    pub fn playing(name: &str) -> Self {
+        Self::new(ActivityType::Game, name)
+    }
+
+    /// "Streaming {name}", linking to the stream
+    /// @param url A twitch or youtube url, otherwise it is shown as playing
+    pub fn streaming(name: &str, url: &str) -> Self {
+        Self {
+            url: Some(url.to_string()),
+            ..Self::new(ActivityType::Streaming, name)
+        }
+    }
+
+    /// "Listening to {name}"
+    pub fn listening(name: &str) -> Self {
+        Self::new(ActivityType::Listening, name)
+    }
+
+    /// "Watching {name}"
+    pub fn watching(name: &str) -> Self {
+        Self::new(ActivityType::Watching, name)
+    }
+
+    /// "Competing in {name}"
+    pub fn competing(name: &str) -> Self {
+        Self::new(ActivityType::Competing, name)
+    }
+
+    /// A custom status, shown as the text on its own
+    pub fn custom(status: &str) -> Self {
+        Self {
+            state: Some(status.to_string()),
+            ..Self::new(ActivityType::Custom, "Custom Status")
+        }
+    }
+
+    /// Adds a line under the activity, e.g. `Activity::playing("Chess").with_state("Ranked match")`
+    #[must_use]
+    pub fn with_state(mut self, state: &str) -> Self {
+        self.state = Some(state.to_string());
+        self
+    }
+
+    /// The url of the large image, if the activity has one
+    pub fn large_image_url(&self) -> Option<String> {
+        let assets = self.assets.as_ref()?;
+        asset_url(assets.large_image.as_deref()?, self.application_id)
+    }
+
+    /// The url of the small image, if the activity has one
+    pub fn small_image_url(&self) -> Option<String> {
+        let assets = self.assets.as_ref()?;
+        asset_url(assets.small_image.as_deref()?, self.application_id)
+    }
+}
+
+/// Turns an asset key into a url. Keys are either the id of an image uploaded to the application, or prefixed by where the image is from
+/// @docs <https://discord.com/developers/docs/topics/gateway#activity-object-activity-asset-image>
+fn asset_url(key: &str, application_id: Option<Snowflake>) -> Option<String> {
+    let url = match key.split_once(':') {
+        Some(("mp", path)) => format!("https://media.discordapp.net/{}", path),
+        Some(("spotify", id)) => format!("https://i.scdn.co/image/{}", id),
+        Some(("twitch", name)) => format!(
+            "https://static-cdn.jtvnw.net/previews-ttv/live_user_{}.png",
+            name
+        ),
+        Some(("youtube", id)) => format!("https://i.ytimg.com/vi/{}/hqdefault_live.jpg", id),
+        Some(_) => return None,
+        None => format!(
+            "https://cdn.discordapp.com/app-assets/{}/{}.png",
+            application_id?,
+            key
+        ),
+    };
+    Some(url)
+}
+
+fn is_zero(n: &i64) -> bool {
+    *n == 0
+}
+
+/// The parts of an activity that bots can set, which is what `UpdatePresence` sends
+/// @docs <https://discord.com/developers/docs/topics/gateway#activity-object>
+#[derive(Serialize)]
+struct BotActivity<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    type_: &'a ActivityType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<&'a str>,
+}
+
+/// Serializes the activities of an `UpdatePresence` without the fields that only clients can set
+pub(crate) fn serialize_bot_activities<S: Serializer>(
+    activities: &[Activity],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(activities.iter().map(|activity| BotActivity {
+        name: &activity.name,
+        type_: &activity.type_,
+        // Only streams have a url
+        url: match activity.type_ {
+            ActivityType::Streaming => activity.url.as_deref(),
+            _ => None,
+        },
+        state: activity.state.as_deref(),
+    }))
+}
+
 /**
  * Activity Type
  * @docs <https://discord.com/developers/docs/topics/gateway#activity-object-activity-types>
  */
-#[derive(Serialize_repr, Deserialize_repr, Clone)]
+#[derive(Serialize_repr, Deserialize_repr, Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum ActivityType {
     /// Playing {name}
@@ -77,11 +231,25 @@ pub enum ActivityType {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ActivityParty {
     /// the id of the party
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     /// used to show the party's current and maximum size
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<(i32, i32)>,
 }
 
+impl ActivityParty {
+    /// How many players are in the party
+    pub fn current_size(&self) -> Option<i32> {
+        self.size.map(|(current, _)| current)
+    }
+
+    /// How many players the party can have
+    pub fn max_size(&self) -> Option<i32> {
+        self.size.map(|(_, max)| max)
+    }
+}
+
 /**
  * Timestamps for when the activity started and ended
  * @docs <https://discord.com/developers/docs/topics/gateway#activity-object-activity-timestamps>
@@ -89,24 +257,42 @@ pub struct ActivityParty {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ActivityTimestamps {
     /// unix timestamp (in milliseconds) of when the activity started
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start: Option<i64>,
     /// unix timestamp (in milliseconds) of when the activity ends
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub end: Option<i64>,
 }
 
+impl ActivityTimestamps {
+    /// When the activity started, e.g. to show how long a user has been playing
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.start.and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+    }
+
+    /// When the activity ends, e.g. when a song finishes
+    pub fn ends_at(&self) -> Option<DateTime<Utc>> {
+        self.end.and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+    }
+}
+
 /**
 * Activity Assets
 * @docs <https://discord.com/developers/docs/topics/gateway#activity-object-activity-assets>
 */
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ActivityAssets {
-    /// the id for a large asset of the activity, usually a snowflake
+    /// the id for a large asset of the activity, usually a snowflake. See `Activity::large_image_url`
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub large_image: Option<String>,
     /// text displayed when hovering over the large image of the activity
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub large_text: Option<String>,
     /// the id for a small asset of the activity, usually a snowflake
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub small_image: Option<String>,
     /// text displayed when hovering over the small image of the activity
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub small_text: Option<String>,
 }
 
@@ -117,11 +303,13 @@ pub struct ActivityAssets {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ActivitySecrets {
     /// the secret for joining a party
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub join: Option<String>,
     /// the secret for spectating a game
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub spectate: Option<String>,
     /// the secret for a specific instanced match
-    #[serde(rename = "match")]
+    #[serde(rename = "match", skip_serializing_if = "Option::is_none")]
     pub match_: Option<String>,
 }
 
@@ -158,7 +346,8 @@ impl<'de> Deserialize<'de> for ActivityFlags {
 
 /**
  * Activity Buttons
- * When received over the gateway, the buttons field is an array of strings, which are the button labels. Bots cannot access a user's activity button URLs. When sending, the buttons field must be an array of the below object:
+ * When received over the gateway, the buttons field is an array of strings, which are the button labels. Bots cannot access a user's activity button URLs,
+ * so the url of a received button is empty.
  * @docs <https://discord.com/developers/docs/topics/gateway#activity-object-activity-buttons>
  */
 #[derive(Serialize, Deserialize, Clone)]
@@ -166,5 +355,33 @@ pub struct ActivityButton {
     /// the text shown on the button (1-32 characters)
     pub label: String,
     /// the url opened when clicking the button (1-512 characters)
+    #[serde(default)]
     pub url: String,
 }
+
+/// Reads buttons that are either labels (as the gateway sends them) or full button objects
+fn deserialize_buttons<'de, D>(deserializer: D) -> Result<Option<Vec<ActivityButton>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Button {
+        Label(String),
+        Full(ActivityButton),
+    }
+
+    let buttons = Option::<Vec<Button>>::deserialize(deserializer)?;
+    Ok(buttons.map(|buttons| {
+        buttons
+            .into_iter()
+            .map(|button| match button {
+                Button::Label(label) => ActivityButton {
+                    label,
+                    url: String::new(),
+                },
+                Button::Full(button) => button,
+            })
+            .collect()
+    }))
+}
@@ -6,7 +6,7 @@ use crate::{
     discord::{resources::user::User, snowflake::Snowflake},
 };
 
-use super::activity::Activity;
+use super::activity::{serialize_bot_activities, Activity};
 
 /**
  * Presence Update Event
@@ -39,3 +39,50 @@ pub struct ClientStatus {
     /// the user's status set for an active web (browser, bot account) application session
     pub web: Option<String>,
 }
+
+/**
+ * Online Status
+ * The status the bot shows with `ctx.set_presence`. Bots can't be offline while connected, so invisible is the closest.
+ * @docs <https://discord.com/developers/docs/topics/gateway#update-presence-status-types>
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Online,
+    /// Do not disturb
+    Dnd,
+    Idle,
+    /// Shown as offline
+    Invisible,
+}
+
+/**
+ * Gateway Presence Update
+ * Sent to change the bot's status and activity, and in the identify payload so the bot starts with it.
+ * @docs <https://discord.com/developers/docs/topics/gateway#update-presence-gateway-presence-update-structure>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UpdatePresence {
+    /// unix time (in milliseconds) of when the client went idle, or null if the client is not idle
+    pub since: Option<i64>,
+    /// the bot's activities. Bots can only show one, and only its name, type, url and state are sent
+    #[serde(serialize_with = "serialize_bot_activities")]
+    pub activities: Vec<Activity>,
+    /// the bot's new status
+    pub status: Status,
+    /// whether or not the client is afk
+    pub afk: bool,
+}
+
+impl UpdatePresence {
+    /// @param status The status to show
+    /// @param activity What the bot is doing, or None to clear it
+    pub fn new(status: Status, activity: Option<Activity>) -> Self {
+        Self {
+            since: None,
+            activities: activity.into_iter().collect(),
+            status,
+            afk: false,
+        }
+    }
+}
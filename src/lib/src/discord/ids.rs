@@ -0,0 +1,190 @@
+use std::fmt::{Debug, Display};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::snowflake::Snowflake;
+
+/// Turns an id of any kind back into a plain snowflake, for the code that works the same with or without the `typed-ids` feature
+#[cfg(any(feature = "rest", feature = "gateway"))]
+pub(crate) trait RawId: Copy {
+    fn raw(self) -> Snowflake;
+}
+
+#[cfg(any(feature = "rest", feature = "gateway"))]
+impl RawId for Snowflake {
+    fn raw(self) -> Snowflake {
+        self
+    }
+}
+
+macro_rules! typed_id {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        pub struct $name(pub Snowflake);
+
+        impl $name {
+            pub fn new(id: u64) -> Self {
+                Self(Snowflake::from(id))
+            }
+
+            /// The id as a plain snowflake
+            pub fn get(self) -> Snowflake {
+                self.0
+            }
+        }
+
+        #[cfg(any(feature = "rest", feature = "gateway"))]
+        impl RawId for $name {
+            fn raw(self) -> Snowflake {
+                self.0
+            }
+        }
+
+        impl From<Snowflake> for $name {
+            fn from(id: Snowflake) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for Snowflake {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self::new(id)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}({})", stringify!($name), self.0)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Snowflake::deserialize(deserializer).map(Self)
+            }
+        }
+    };
+}
+
+typed_id!(
+    /// The id of a guild
+    GuildId
+);
+typed_id!(
+    /// The id of a channel (including threads and DMs)
+    ChannelId
+);
+typed_id!(
+    /// The id of a user
+    UserId
+);
+typed_id!(
+    /// The id of a message
+    MessageId
+);
+typed_id!(
+    /// The id of a role
+    RoleId
+);
+
+/**
+ * The id types that the endpoints take.
+ * With the `typed-ids` feature these are the typed ids above, so passing a channel id where a guild id is expected doesn't compile.
+ * Without it they are all `Snowflake`, so existing code keeps working while it is migrated.
+ * Passing ids with `.into()` works either way.
+ */
+#[cfg(feature = "typed-ids")]
+pub mod params {
+    #[cfg(feature = "rest")]
+    use super::{ChannelId, GuildId, MessageId, RoleId, UserId};
+    #[cfg(feature = "rest")]
+    use crate::discord::snowflake::Snowflake;
+
+    pub type GuildIdParam = super::GuildId;
+    #[cfg(feature = "rest")]
+    pub type ChannelIdParam = super::ChannelId;
+    #[cfg(feature = "rest")]
+    pub type UserIdParam = super::UserId;
+    #[cfg(any(feature = "rest", feature = "webhook-client"))]
+    pub type MessageIdParam = super::MessageId;
+    #[cfg(feature = "rest")]
+    pub type RoleIdParam = super::RoleId;
+
+    // For passing the ids the library has as snowflakes to the endpoints
+    #[cfg(feature = "rest")]
+    pub(crate) fn guild(id: Snowflake) -> GuildIdParam {
+        GuildId(id)
+    }
+    #[cfg(feature = "rest")]
+    pub(crate) fn channel(id: Snowflake) -> ChannelIdParam {
+        ChannelId(id)
+    }
+    #[cfg(feature = "rest")]
+    pub(crate) fn user(id: Snowflake) -> UserIdParam {
+        UserId(id)
+    }
+    #[cfg(feature = "rest")]
+    pub(crate) fn message(id: Snowflake) -> MessageIdParam {
+        MessageId(id)
+    }
+    #[cfg(feature = "rest")]
+    pub(crate) fn role(id: Snowflake) -> RoleIdParam {
+        RoleId(id)
+    }
+}
+
+#[cfg(not(feature = "typed-ids"))]
+pub mod params {
+    use crate::discord::snowflake::Snowflake;
+
+    pub type GuildIdParam = Snowflake;
+    #[cfg(feature = "rest")]
+    pub type ChannelIdParam = Snowflake;
+    #[cfg(feature = "rest")]
+    pub type UserIdParam = Snowflake;
+    #[cfg(any(feature = "rest", feature = "webhook-client"))]
+    pub type MessageIdParam = Snowflake;
+    #[cfg(feature = "rest")]
+    pub type RoleIdParam = Snowflake;
+
+    // For passing the ids the library has as snowflakes to the endpoints
+    #[cfg(feature = "rest")]
+    pub(crate) fn guild(id: Snowflake) -> GuildIdParam {
+        id
+    }
+    #[cfg(feature = "rest")]
+    pub(crate) fn channel(id: Snowflake) -> ChannelIdParam {
+        id
+    }
+    #[cfg(feature = "rest")]
+    pub(crate) fn user(id: Snowflake) -> UserIdParam {
+        id
+    }
+    #[cfg(feature = "rest")]
+    pub(crate) fn message(id: Snowflake) -> MessageIdParam {
+        id
+    }
+    #[cfg(feature = "rest")]
+    pub(crate) fn role(id: Snowflake) -> RoleIdParam {
+        id
+    }
+}
@@ -2,6 +2,10 @@
 
 use std::fmt::Display;
 
+use serde::{Serialize, Serializer};
+
+use crate::util::error::{Error, ErrorTypes};
+
 /**
  * Possible basic image formats. This excludes animated formats (GIF).
  * If you are looking for the one with GIFs, see ImageFormats::Animated
@@ -42,3 +46,58 @@ impl Display for Animated {
         }
     }
 }
+
+/**
+ * An image that is uploaded as part of a json payload (like a role icon, or an emoji), which discord takes as a data uri.
+ * @docs <https://discord.com/developers/docs/reference#image-data>
+ */
+#[derive(Clone)]
+pub struct ImageData {
+    content_type: &'static str,
+    data: Vec<u8>,
+}
+
+impl ImageData {
+    /**
+     * Creates the image data, figuring out the format from the bytes
+     * @param data The contents of a PNG, JPG, GIF or WEBP file
+     * @return An error if the bytes aren't one of those formats
+     */
+    pub fn new(data: Vec<u8>) -> Result<Self, Error> {
+        let content_type = if data.starts_with(b"\x89PNG") {
+            "image/png"
+        } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            "image/jpeg"
+        } else if data.starts_with(b"GIF8") {
+            "image/gif"
+        } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            "image/webp"
+        } else {
+            return Err(Error::new(
+                "The image isn't a PNG, JPG, GIF or WEBP".to_string(),
+                ErrorTypes::PARSE,
+            ));
+        };
+        Ok(Self { content_type, data })
+    }
+
+    /// The mime type of the image, e.g. `image/png`
+    pub fn content_type(&self) -> &'static str {
+        self.content_type
+    }
+
+    /// The image as a data uri, e.g. `data:image/png;base64,iVBORw0KGgo...`
+    pub fn to_data_uri(&self) -> String {
+        format!(
+            "data:{};base64,{}",
+            self.content_type,
+            base64::encode(&self.data)
+        )
+    }
+}
+
+impl Serialize for ImageData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_data_uri())
+    }
+}
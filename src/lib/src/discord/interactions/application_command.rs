@@ -1,14 +1,21 @@
-use std::{sync::Arc, hash::{Hash, Hasher}};
+use std::hash::{Hash, Hasher};
 
+use crate::api::{channel::typing::ChannelType, Snowflake};
+#[cfg(any(feature = "rest", feature = "gateway"))]
 use crate::{
-    api::{application::Application, channel::typing::ChannelType, Snowflake},
-    core::{http::rate_limit_client::{send_request, RequestRoute, send_request_noparse}},
+    core::{http::rate_limit_client::{send_request, RequestRoute, send_request_idempotent}},
     util::error::Error,
-    Context, BASE_URL, SubRegisterable,
+    Context, BASE_URL,
 };
+#[cfg(feature = "gateway")]
+use {crate::SubRegisterable, std::sync::Arc};
+#[cfg(any(feature = "rest", feature = "gateway"))]
 use hyper::{Body, Method, Request};
 use serde::{self, Deserialize, Serialize};
+use crate::util::common::options_equal;
 use serde_repr::{Deserialize_repr, Serialize_repr};
+#[cfg(any(feature = "rest", feature = "gateway"))]
+use crate::discord::ids::{params::GuildIdParam, RawId};
 
 /**
  * Application Command Structure
@@ -45,9 +52,10 @@ pub struct ApplicationCommand {
  * Application Command Types
  * @docs <https://discord.com/developers/docs/interactions/application-commands#application-command-object-application-command-types>
  */
-#[derive(Serialize_repr, Deserialize_repr, Clone, PartialEq)]
+#[derive(Serialize_repr, Deserialize_repr, Clone, PartialEq, Default)]
 #[repr(u8)]
 pub enum ApplicationCommandType {
+    #[default]
     ChatInput = 1,
     User = 2,
     Message = 3,
@@ -120,6 +128,7 @@ impl Default for ApplicationCommandOption {
     }
 }
 
+#[cfg(feature = "gateway")]
 impl From<&Arc<dyn SubRegisterable>> for ApplicationCommandOption {
     fn from(sub: &Arc<dyn SubRegisterable>) -> Self {
         let sub_options = sub.get_options();
@@ -137,6 +146,7 @@ impl From<&Arc<dyn SubRegisterable>> for ApplicationCommandOption {
     }
 }
 
+#[cfg(feature = "gateway")]
 impl From<Arc<dyn SubRegisterable>> for ApplicationCommandOption {
     fn from(sub: Arc<dyn SubRegisterable>) -> Self {
         let sub_options = sub.get_options();
@@ -214,6 +224,7 @@ pub enum ApplicationCommandOptionValue {
     String(String),
     Integer(i64),
     Number(f64),
+    Boolean(bool),
 }
 
 impl Hash for ApplicationCommandOptionValue {
@@ -222,6 +233,7 @@ impl Hash for ApplicationCommandOptionValue {
             ApplicationCommandOptionValue::String(s) => s.hash(state),
             ApplicationCommandOptionValue::Integer(i) => i.hash(state),
             ApplicationCommandOptionValue::Number(n) => n.to_bits().hash(state),
+            ApplicationCommandOptionValue::Boolean(b) => b.hash(state),
         }
     }
 }
@@ -249,29 +261,38 @@ pub struct CreateApplicationCommand {
 
 /**
  * Application Command Edit Structure
+ * Only the fields that are set are changed, so the rest of the command stays as it is.
  * @docs <https://discord.com/developers/docs/interactions/application-commands#edit-global-application-command>
  */
-#[derive(Serialize, Deserialize, Clone)]
+#[cfg(any(feature = "rest", feature = "gateway"))]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct EditApplicationCommand {
     /// 1-32 character name
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     /// 1-100 character description
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     /// the parameters for the command
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<Vec<ApplicationCommandOption>>,
     /// Set of permissions represented as a bit set
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub default_member_permissions: Option<String>,
     /// Indicates whether the command is available in DMs with the app, only for globally-scoped commands. By default, commands are visible.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub dm_permission: Option<bool>,
     /// Replaced by default_member_permissions and will be deprecated in the future. Indicates whether the command is enabled by default when the app is added to a guild.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub default_permission: Option<bool>,
 }
 
+#[cfg(any(feature = "rest", feature = "gateway"))]
 impl ApplicationCommand {
     /// Gets a global application command
     /// @param id The id of the command
     pub async fn get_global(ctx: Context, id: Snowflake) -> Result<ApplicationCommand, Error> {
-        let slf = Application::get_self(ctx.clone()).await?;
+        let application_id = ctx.application_id().await?;
 
         let route = RequestRoute {
             base_route: "/applications/{application.id}/commands/{command.id}/".to_string(),
@@ -281,7 +302,7 @@ impl ApplicationCommand {
             .method(Method::GET)
             .uri(format!(
                 "{}/applications/{}/commands/{}/",
-                BASE_URL, slf.id, id
+                BASE_URL, application_id, id
             ))
             .header("content-type", "application/json")
             .body(Body::empty())
@@ -292,7 +313,7 @@ impl ApplicationCommand {
 
     /// Lists the global application commands associated with the application
     pub async fn list_global(ctx: Context) -> Result<Vec<ApplicationCommand>, Error> {
-        let slf = Application::get_self(ctx.clone()).await?;
+        let application_id = ctx.application_id().await?;
 
         let route = RequestRoute {
             base_route: "/applications/{application.id}/commands".to_string(),
@@ -300,7 +321,7 @@ impl ApplicationCommand {
         };
         let request_builder = Request::builder()
             .method(Method::GET)
-            .uri(format!("{}/applications/{}/commands", BASE_URL, slf.id))
+            .uri(format!("{}/applications/{}/commands", BASE_URL, application_id))
             .header("content-type", "application/json")
             .body(Body::empty())
             .unwrap();
@@ -319,7 +340,7 @@ impl ApplicationCommand {
         ctx: Context,
         payload: CreateApplicationCommand,
     ) -> Result<ApplicationCommand, Error> {
-        let slf = Application::get_self(ctx.clone()).await?;
+        let application_id = ctx.application_id().await?;
 
         let route = RequestRoute {
             base_route: "/applications/{application.id}/commands".to_string(),
@@ -327,7 +348,7 @@ impl ApplicationCommand {
         };
         let request_builder = Request::builder()
             .method(Method::POST)
-            .uri(format!("{}/applications/{}/commands", BASE_URL, slf.id))
+            .uri(format!("{}/applications/{}/commands", BASE_URL, application_id))
             .header("content-type", "application/json")
             .body(Body::from(serde_json::to_string(&payload).unwrap()))
             .unwrap();
@@ -341,27 +362,218 @@ impl ApplicationCommand {
      * Edit a global command. Updates will be available in all guilds after 1 hour.
      * Returns 200 and an application command object. All fields are optional, but
      * any fields provided will entirely overwrite the existing values of those fields.
-     * 
-     * @param payload Payload of information for the command
+     *
+     * ```rust,ignore
+     * ApplicationCommand::edit_global(ctx, command_id, EditApplicationCommand {
+     *     description: Some("Bans a member from the server".to_string()),
+     *     ..Default::default()
+     * })
+     * .await?;
+     * ```
+     * @param id The id of the command
+     * @param payload The fields to change
+     * @return The command as it is now
+     * @docs <https://discord.com/developers/docs/interactions/application-commands#edit-global-application-command>
      */
     pub async fn edit_global(
         ctx: Context,
         id: Snowflake,
         payload: EditApplicationCommand,
-    ) -> Result<(), Error> {
-        let slf = Application::get_self(ctx.clone()).await?;
+    ) -> Result<ApplicationCommand, Error> {
+        let application_id = ctx.application_id().await?;
 
-        let route = RequestRoute {
-            base_route: "/applications/{application.id}/commands/{}".to_string(),
-            major_param: "".to_string(),
-        };
         let request_builder = Request::builder()
             .method(Method::PATCH)
-            .uri(format!("{}/applications/{}/commands/{}", BASE_URL, slf.id, id))
+            .uri(format!("{}/{}", commands_uri(application_id, None), id))
             .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .body(Body::from(serde_json::to_string(&payload)?))
+            .unwrap();
+
+        send_request(ctx, command_route(), request_builder).await
+    }
+
+    /**
+     * Deletes a global application command. It disappears from all guilds after up to an hour.
+     * @param id The id of the command
+     * @docs <https://discord.com/developers/docs/interactions/application-commands#delete-global-application-command>
+     */
+    pub async fn delete_global(ctx: Context, id: Snowflake) -> Result<(), Error> {
+        let application_id = ctx.application_id().await?;
+        let uri = format!("{}/{}", commands_uri(application_id, None), id);
+
+        send_request_idempotent(ctx, command_route(), || {
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(uri.clone())
+                .body(Body::empty())
+                .unwrap()
+        })
+        .await
+    }
+
+    /// Lists the application commands of a guild
+    /// @param guild_id The guild to list the commands of
+    pub async fn list_guild(
+        ctx: Context,
+        guild_id: GuildIdParam,
+    ) -> Result<Vec<ApplicationCommand>, Error> {
+        let application_id = ctx.application_id().await?;
+
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(commands_uri(application_id, Some(guild_id.raw())))
+            .header("content-type", "application/json")
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, commands_route(Some(guild_id.raw())), request_builder).await
+    }
+
+    /**
+     * Creates a command that is only available in one guild, which shows up right away (unlike global commands).
+     * Creating a command with the same name as an existing command of the guild overwrites the old command.
+     * @param guild_id The guild to create the command in
+     * @param payload Payload of information for the command
+     * @docs <https://discord.com/developers/docs/interactions/application-commands#create-guild-application-command>
+     */
+    pub async fn create_guild(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        payload: CreateApplicationCommand,
+    ) -> Result<ApplicationCommand, Error> {
+        let application_id = ctx.application_id().await?;
+
+        let request_builder = Request::builder()
+            .method(Method::POST)
+            .uri(commands_uri(application_id, Some(guild_id.raw())))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload)?))
+            .unwrap();
+
+        send_request(ctx, commands_route(Some(guild_id.raw())), request_builder).await
+    }
+
+    /**
+     * Replaces all of the global commands with the given ones in a single request.
+     * Commands that aren't in the list are deleted, and commands with the same name keep their id.
+     * Unlike creating commands one at a time, this doesn't count towards the daily limit of new commands for commands that already exist.
+     * @param commands The complete list of global commands
+     * @return The commands as they are now
+     * @docs <https://discord.com/developers/docs/interactions/application-commands#bulk-overwrite-global-application-commands>
+     */
+    pub async fn bulk_overwrite_global(
+        ctx: Context,
+        commands: Vec<CreateApplicationCommand>,
+    ) -> Result<Vec<ApplicationCommand>, Error> {
+        Self::bulk_overwrite(ctx, None, commands).await
+    }
+
+    /**
+     * Replaces all of the commands of a guild with the given ones in a single request
+     * @param guild_id The guild to replace the commands of
+     * @param commands The complete list of the guild's commands
+     * @return The commands as they are now
+     * @docs <https://discord.com/developers/docs/interactions/application-commands#bulk-overwrite-guild-application-commands>
+     */
+    pub async fn bulk_overwrite_guild(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        commands: Vec<CreateApplicationCommand>,
+    ) -> Result<Vec<ApplicationCommand>, Error> {
+        Self::bulk_overwrite(ctx, Some(guild_id), commands).await
+    }
+
+    async fn bulk_overwrite(
+        ctx: Context,
+        guild_id: Option<GuildIdParam>,
+        commands: Vec<CreateApplicationCommand>,
+    ) -> Result<Vec<ApplicationCommand>, Error> {
+        let application_id = ctx.application_id().await?;
+        let guild_id = guild_id.map(RawId::raw);
+
+        let request_builder = Request::builder()
+            .method(Method::PUT)
+            .uri(commands_uri(application_id, guild_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&commands)?))
             .unwrap();
 
-        send_request_noparse(ctx, route, request_builder).await
+        send_request(ctx, commands_route(guild_id), request_builder).await
+    }
+
+    /**
+     * Makes the live commands match the given ones, but only sends the overwrite if something changed.
+     * Syncing on every start up then costs a single list request when the commands are already up to date.
+     * @param guild_id The guild to sync the commands of, or None for the global commands
+     * @param commands The complete list of commands
+     * @return The commands as they are now
+     */
+    pub async fn sync(
+        ctx: Context,
+        guild_id: Option<GuildIdParam>,
+        commands: Vec<CreateApplicationCommand>,
+    ) -> Result<Vec<ApplicationCommand>, Error> {
+        let live = match guild_id {
+            Some(guild_id) => Self::list_guild(ctx.clone(), guild_id).await?,
+            None => Self::list_global(ctx.clone()).await?,
+        };
+        let up_to_date = live.len() == commands.len()
+            && commands
+                .iter()
+                .all(|command| live.iter().any(|live| live.matches(command)));
+        if up_to_date {
+            return Ok(live);
+        }
+        Self::bulk_overwrite(ctx, guild_id, commands).await
+    }
+}
+
+#[cfg(any(feature = "rest", feature = "gateway"))]
+fn commands_uri(application_id: Snowflake, guild_id: Option<Snowflake>) -> String {
+    match guild_id {
+        Some(guild_id) => format!(
+            "{}/applications/{}/guilds/{}/commands",
+            BASE_URL, application_id, guild_id
+        ),
+        None => format!("{}/applications/{}/commands", BASE_URL, application_id),
+    }
+}
+
+/// The route of a single global command
+#[cfg(any(feature = "rest", feature = "gateway"))]
+fn command_route() -> RequestRoute {
+    RequestRoute {
+        base_route: "/applications/{application.id}/commands/{command.id}".to_string(),
+        major_param: "".to_string(),
+    }
+}
+
+#[cfg(any(feature = "rest", feature = "gateway"))]
+fn commands_route(guild_id: Option<Snowflake>) -> RequestRoute {
+    match guild_id {
+        Some(guild_id) => RequestRoute {
+            base_route: "/applications/{application.id}/guilds/{guild.id}/commands".to_string(),
+            major_param: guild_id.to_string(),
+        },
+        None => RequestRoute {
+            base_route: "/applications/{application.id}/commands".to_string(),
+            major_param: "".to_string(),
+        },
+    }
+}
+
+impl ApplicationCommand {
+    /// Whether the command has the same definition as the given one, so that overwriting it wouldn't change anything
+    /// @param command The definition to compare with
+    pub fn matches(&self, command: &CreateApplicationCommand) -> bool {
+        // Discord leaves out empty option lists
+        let non_empty = |options: &Option<Vec<ApplicationCommandOption>>| {
+            options.clone().filter(|options| !options.is_empty())
+        };
+        self.name == command.name
+            && self.type_ == command.type_.clone().unwrap_or_default()
+            && self.description.as_deref().unwrap_or_default() == command.description
+            && self.default_member_permissions == command.default_member_permissions
+            && options_equal(&non_empty(&self.options), &non_empty(&command.options))
     }
 }
@@ -1,8 +1,12 @@
 pub mod color;
 pub mod gateway;
+pub mod ids;
 pub mod image_formats;
 pub mod interactions;
 pub mod permissions;
 pub mod resources;
 pub mod snowflake;
 pub mod teams;
+
+#[cfg(feature = "webhook-events")]
+pub mod webhook_events;
@@ -1,6 +1,11 @@
 use bitflags::bitflags;
 use serde::{Deserialize, Deserializer, Serialize};
 
+use super::{
+    resources::{channel::typing::PermissionsOverwriteObject, guild::guild_object::Guild},
+    snowflake::Snowflake,
+};
+
 bitflags! {
     #[derive(Serialize)]
     pub struct Permissions: u64 {
@@ -43,6 +48,22 @@ bitflags! {
         const USE_EXTERNAL_STICKERS = (1 << 37);
         const SEND_MESSAGES_IN_THREADS = (1 << 38);
         const START_EMBEDDED_ACTIVITIES = (1 << 39);
+        const MODERATE_MEMBERS = (1 << 40);
+    }
+}
+
+impl Permissions {
+    /// Whether these permissions allow everything in `required`. ADMINISTRATOR allows everything
+    pub fn has(&self, required: Permissions) -> bool {
+        self.contains(Permissions::ADMINISTRATOR) || self.contains(required)
+    }
+
+    /// The permissions in `required` that these don't have. Empty with ADMINISTRATOR
+    pub fn missing(&self, required: Permissions) -> Permissions {
+        if self.contains(Permissions::ADMINISTRATOR) {
+            return Permissions::empty();
+        }
+        required - *self
     }
 }
 
@@ -57,3 +78,65 @@ impl<'de> Deserialize<'de> for Permissions {
             .ok_or_else(|| serde::de::Error::custom(format!("Unexpected flags value {}", bits)))
     }
 }
+
+/**
+ * Computes the permissions that a member has in a channel: the permissions of the @everyone role and the member's roles,
+ * then the channel's overwrites for @everyone, the member's roles and the member.
+ * Members that are timed out can only view the channel and read its history, unless they are administrators.
+ * Pass the overwrites of the parent channel for threads.
+ * @docs <https://discord.com/developers/docs/topics/permissions#permission-overwrites>
+ */
+pub(crate) fn compute_permissions(
+    guild: &Guild,
+    user_id: Snowflake,
+    member_roles: &[Snowflake],
+    overwrites: &[PermissionsOverwriteObject],
+    timed_out: bool,
+) -> Permissions {
+    if guild.owner_id == user_id {
+        return Permissions::all();
+    }
+
+    let mut permissions = guild
+        .roles
+        .iter()
+        // The @everyone role has the same id as the guild
+        .filter(|role| role.id == guild.id || member_roles.contains(&role.id))
+        .filter_map(|role| role.permissions.as_deref()?.parse::<u64>().ok())
+        .fold(Permissions::empty(), |permissions, bits| {
+            permissions | Permissions::from_bits_truncate(bits)
+        });
+    if permissions.contains(Permissions::ADMINISTRATOR) {
+        return Permissions::all();
+    }
+
+    let mut apply = |allow: u64, deny: u64| {
+        permissions.remove(Permissions::from_bits_truncate(deny));
+        permissions.insert(Permissions::from_bits_truncate(allow));
+    };
+    if let Some(everyone) = overwrites.iter().find(|overwrite| overwrite.id == guild.id) {
+        apply(everyone.allow, everyone.deny);
+    }
+    let (allow, deny) = overwrites
+        .iter()
+        .filter(|overwrite| overwrite.type_ == 0 && member_roles.contains(&overwrite.id))
+        .fold((0, 0), |(allow, deny), overwrite| {
+            (allow | overwrite.allow, deny | overwrite.deny)
+        });
+    apply(allow, deny);
+    if let Some(member) = overwrites
+        .iter()
+        .find(|overwrite| overwrite.type_ == 1 && overwrite.id == user_id)
+    {
+        apply(member.allow, member.deny);
+    }
+
+    // Members that can't see a channel can't do anything else in it either
+    if !permissions.contains(Permissions::VIEW_CHANNEL) {
+        return Permissions::empty();
+    }
+    if timed_out {
+        return permissions & (Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY);
+    }
+    permissions
+}
@@ -1,11 +1,13 @@
+use crate::discord::{snowflake::Snowflake, teams::Team};
+#[cfg(any(feature = "rest", feature = "gateway"))]
 use crate::{
     core::http::rate_limit_client::{send_request, RequestRoute},
-    discord::{snowflake::Snowflake, teams::Team},
     util::error::Error,
     Context, BASE_URL,
 };
 
 use bitflags::bitflags;
+#[cfg(any(feature = "rest", feature = "gateway"))]
 use hyper::{Body, Method, Request};
 use serde::{Deserialize, Deserializer, Serialize};
 
@@ -83,6 +85,7 @@ impl<'de> Deserialize<'de> for ApplicationFlags {
     }
 }
 
+#[cfg(any(feature = "rest", feature = "gateway"))]
 impl Application {
     /// Gets the application associated with the bot
     pub async fn get_self(ctx: Context) -> Result<Application, Error> {
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+
+use super::guild_scheduled_event::{GuildScheduledEvent, GuildScheduledEventStatus};
+
+/// iCalendar lines can't be longer than 75 octets, longer lines are folded onto continuation lines
+const MAX_LINE_LENGTH: usize = 75;
+
+/**
+ * Converts scheduled events into an iCalendar (ICS) file that calendar apps can import or subscribe to.
+ * Recurring events keep their recurrence as an RRULE.
+ *
+ * ```rust,ignore
+ * let events = GuildScheduledEvent::list(ctx, guild_id).await?;
+ * std::fs::write("events.ics", events_to_ics("My Server", &events))?;
+ * ```
+ * @param calendar_name The name the calendar app shows for the calendar (usually the name of the guild)
+ * @param events The events to export
+ * @docs <https://datatracker.ietf.org/doc/html/rfc5545>
+ */
+pub fn events_to_ics(calendar_name: &str, events: &[GuildScheduledEvent]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//discrab//Guild Scheduled Events//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(calendar_name)),
+    ];
+    let now = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    for event in events {
+        lines.extend(event_lines(event, &now));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in lines {
+        ics.push_str(&fold_line(&line));
+    }
+    ics
+}
+
+/// The VEVENT lines for an event
+fn event_lines(event: &GuildScheduledEvent, now: &str) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@discord.com", event.id),
+        format!("DTSTAMP:{}", now),
+    ];
+    if let Some(start) = ical_timestamp(&event.scheduled_start_time) {
+        lines.push(format!("DTSTART:{}", start));
+    }
+    if let Some(end) = event.scheduled_end_time.as_deref().and_then(ical_timestamp) {
+        lines.push(format!("DTEND:{}", end));
+    }
+    lines.push(format!("SUMMARY:{}", escape_text(&event.name)));
+    if let Some(description) = &event.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+
+    let url = format!("https://discord.com/events/{}/{}", event.guild_id, event.id);
+    // External events have a location, the others happen in a channel
+    let location = event
+        .entity_metadata
+        .as_ref()
+        .and_then(|metadata| metadata.location.clone())
+        .or_else(|| {
+            event.channel_id.map(|channel_id| {
+                format!(
+                    "https://discord.com/channels/{}/{}",
+                    event.guild_id, channel_id
+                )
+            })
+        });
+    if let Some(location) = location {
+        lines.push(format!("LOCATION:{}", escape_text(&location)));
+    }
+    lines.push(format!("URL:{}", url));
+
+    if let Some(rule) = &event.recurrence_rule {
+        lines.push(format!("RRULE:{}", rule.to_rrule()));
+    }
+    let status = match event.status {
+        GuildScheduledEventStatus::Canceled => "CANCELLED",
+        _ => "CONFIRMED",
+    };
+    lines.push(format!("STATUS:{}", status));
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+/// Converts an ISO8601 timestamp from discord into the UTC form iCalendar uses (`20211203T200000Z`)
+pub(crate) fn ical_timestamp(timestamp: &str) -> Option<String> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|time| {
+        time.with_timezone(&Utc)
+            .format("%Y%m%dT%H%M%SZ")
+            .to_string()
+    })
+}
+
+/// Escapes the characters that have a meaning in iCalendar text values
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds a line into 75 octet pieces and ends it with CRLF. Continuation lines start with a space.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+    for c in line.chars() {
+        // Never split a multi-byte character
+        if line_length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
@@ -27,4 +27,117 @@ pub struct Attachment {
     pub width: Option<u64>,
     /// whether this attachment is ephemeral
     pub ephemeral: Option<bool>,
+    /// the duration of the audio file (currently for voice messages)
+    pub duration_secs: Option<f64>,
+    /// base64 encoded bytearray representing a sampled waveform (currently for voice messages)
+    pub waveform: Option<String>,
+}
+
+/**
+ * A file to upload along with a message. Discord turns these into attachments once the message is sent.
+ * @docs <https://discord.com/developers/docs/reference#uploading-files>
+ */
+#[derive(Clone)]
+pub struct AttachmentFile {
+    /// name of the file (including its extension)
+    pub filename: String,
+    /// description for the file (alt text)
+    pub description: Option<String>,
+    /// the contents of the file
+    pub data: Vec<u8>,
+    /// the duration of the audio, only for voice messages
+    pub duration_secs: Option<f64>,
+    /// base64 encoded waveform of the audio, only for voice messages
+    pub waveform: Option<String>,
+}
+
+impl AttachmentFile {
+    /// Creates a new file to upload
+    /// @param filename The name of the file, including its extension (discord uses the extension to decide how to display it)
+    /// @param data The contents of the file
+    pub fn new(filename: &str, data: Vec<u8>) -> Self {
+        Self {
+            filename: filename.to_string(),
+            description: None,
+            data,
+            duration_secs: None,
+            waveform: None,
+        }
+    }
+
+    /**
+     * Creates a voice message. Discord only plays voice messages that are OGG files encoded with opus.
+     * Send it with `Channel::send_voice_message`.
+     *
+     * @param ogg_data The encoded audio
+     * @param pcm The decoded audio as mono 16 bit samples, used to compute the duration and the waveform
+     * @param sample_rate The sample rate of `pcm` (e.g. 48000)
+     */
+    pub fn voice_message(ogg_data: Vec<u8>, pcm: &[i16], sample_rate: u32) -> Self {
+        Self {
+            duration_secs: Some(pcm.len() as f64 / sample_rate.max(1) as f64),
+            waveform: Some(base64::encode(compute_waveform(pcm))),
+            ..Self::new("voice-message.ogg", ogg_data)
+        }
+    }
+
+    /// Sets the description (alt text) of the file
+    #[must_use]
+    pub fn set_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Guesses the media type of the file from its extension
+    pub fn content_type(&self) -> &'static str {
+        let extension = self
+            .filename
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "mp4" => "video/mp4",
+            "mp3" => "audio/mpeg",
+            "ogg" => "audio/ogg",
+            "txt" => "text/plain",
+            "json" => "application/json",
+            _ => "application/octet-stream",
+        }
+    }
+}
+
+/// The maximum number of points in a voice message waveform
+const MAX_WAVEFORM_POINTS: usize = 256;
+
+/// Computes the waveform discord shows for a voice message: up to 256 points, each the loudness (0-255) of a slice of the audio.
+/// The points are scaled so that the loudest one is 255.
+/// @param pcm The audio as mono 16 bit samples
+pub fn compute_waveform(pcm: &[i16]) -> Vec<u8> {
+    if pcm.is_empty() {
+        return Vec::new();
+    }
+
+    let points = pcm.len().min(MAX_WAVEFORM_POINTS);
+    let levels: Vec<f64> = (0..points)
+        .map(|i| {
+            let chunk = &pcm[i * pcm.len() / points..(i + 1) * pcm.len() / points];
+            // Root mean square of the chunk
+            let sum: f64 = chunk.iter().map(|&s| (s as f64) * (s as f64)).sum();
+            (sum / chunk.len() as f64).sqrt()
+        })
+        .collect();
+
+    let loudest = levels.iter().cloned().fold(0.0, f64::max);
+    if loudest == 0.0 {
+        return vec![0; points];
+    }
+    levels
+        .iter()
+        .map(|level| (level / loudest * 255.0).round() as u8)
+        .collect()
 }
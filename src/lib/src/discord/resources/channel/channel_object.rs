@@ -1,23 +1,37 @@
 use discrab_codegen::CommandArg;
+#[cfg(feature = "rest")]
 use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    core::abstraction::traits::CommandArg,
+    discord::{resources::user::User, snowflake::Snowflake},
+};
+#[cfg(feature = "rest")]
 use crate::{
     api::Message,
     core::{
-        abstraction::traits::CommandArg,
+        abstraction::cache::{cache_or_http, cached_channel, CacheOrHttp},
         http::rate_limit_client::{send_request, RequestRoute},
     },
-    discord::{resources::user::User, snowflake::Snowflake},
-    util::error::Error,
+    util::{
+        error::{Error, ErrorTypes},
+        multipart::message_request,
+    },
     Context, BASE_URL,
 };
+#[cfg(feature = "rest")]
+use serde_json::json;
 
-use super::{
-    message_builder::MessageBuilder,
-    typing::{
-        ChannelType, PermissionsOverwriteObject, ThreadMember, ThreadMetadata, VideoQualityMode,
-    },
+use super::typing::{
+    ChannelType, PermissionsOverwriteObject, ThreadMember, ThreadMetadata, VideoQualityMode,
+};
+#[cfg(feature = "rest")]
+use super::{attachment::AttachmentFile, message_builder::MessageBuilder, typing::MessageFlags};
+#[cfg(feature = "rest")]
+use crate::discord::ids::{
+    params::{self, ChannelIdParam},
+    RawId,
 };
 
 /**
@@ -29,6 +43,7 @@ pub struct Channel {
     /// The id of this channel
     pub id: Snowflake,
     /// The type of channel
+    #[serde(rename = "type")]
     pub channel_type: ChannelType,
     /// The id of the guild (may be missing for some channel objects received over gateway guild dispatches)
     pub guild_id: Option<Snowflake>,
@@ -80,7 +95,7 @@ pub struct Channel {
     pub permissions: Option<String>,
 }
 
-
+#[cfg(feature = "rest")]
 impl Channel {
     /// Sends a message to a given channel.
     /// @param channel_id The id of the channel to send the message to.
@@ -91,24 +106,171 @@ impl Channel {
         channel_id: String,
         message: MessageBuilder,
     ) -> Result<Message, Error> {
+        message.validate()?;
         let route = RequestRoute {
             base_route: format!("/channels/{}/messages", channel_id.clone()),
             major_param: channel_id.clone(),
         };
 
-        let body = Body::from(serde_json::to_string(&message).unwrap());
-
-        let request_builder = Request::builder()
-            .method(Method::POST)
-            .uri(format!(
+        let request_builder = message_request(
+            Request::builder().method(Method::POST).uri(format!(
                 "{}/channels/{}/messages",
                 BASE_URL,
                 channel_id.clone()
-            ))
+            )),
+            serde_json::to_value(&message).unwrap(),
+            "",
+            message.files(),
+        );
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Sends a voice message to a given channel. Voice messages can't have any content, embeds, or other files.
+    /// @param channel_id The id of the channel to send the voice message to
+    /// @param voice_message The voice message, created with `AttachmentFile::voice_message`
+    /// @docs <https://discord.com/developers/docs/resources/channel#create-message>
+    pub async fn send_voice_message(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        voice_message: AttachmentFile,
+    ) -> Result<Message, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = message_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{}/channels/{}/messages", BASE_URL, channel_id)),
+            json!({ "flags": MessageFlags::IS_VOICE_MESSAGE.bits() }),
+            "",
+            &[voice_message],
+        );
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Gets a channel from discord
+    /// @param channel_id The id of the channel
+    /// @docs <https://discord.com/developers/docs/resources/channel#get-channel>
+    pub async fn get(ctx: Context, channel_id: ChannelIdParam) -> Result<Channel, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!("{}/channels/{}", BASE_URL, channel_id))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Gets a channel from the cache, or from discord if it isn't cached
+    /// @param channel_id The id of the channel
+    pub async fn fetch(ctx: Context, channel_id: ChannelIdParam) -> Result<CacheOrHttp<Channel>, Error> {
+        let cached = cached_channel(&ctx, channel_id.raw()).await;
+        cache_or_http(cached, Channel::get(ctx, channel_id)).await
+    }
+
+    /// Deletes a channel, or closes a private message. Requires the MANAGE_CHANNELS permission for guild channels.
+    /// Deleting a category does not delete its child channels.
+    /// @param channel_id The id of the channel to delete
+    /// @return The deleted channel
+    /// @docs <https://discord.com/developers/docs/resources/channel#deleteclose-channel>
+    pub async fn delete(ctx: Context, channel_id: ChannelIdParam) -> Result<Channel, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::DELETE)
+            .uri(format!("{}/channels/{}", BASE_URL, channel_id))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Replaces the channel's permission overwrites with the ones of its category, like the "Sync Now" button in the client.
+    /// The category is fetched from discord (not the cache) so that the newest overwrites are copied. Requires the MANAGE_ROLES permission.
+    /// @return The updated channel
+    /// @docs <https://discord.com/developers/docs/resources/channel#modify-channel>
+    pub async fn sync_permissions_with_parent(&self, ctx: Context) -> Result<Channel, Error> {
+        let parent_id = self.parent_id.ok_or_else(|| {
+            Error::new(
+                format!("Channel {} isn't in a category", self.id),
+                ErrorTypes::REQUEST,
+            )
+        })?;
+        let parent = Channel::get(ctx.clone(), params::channel(parent_id)).await?;
+
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}".to_string(),
+            major_param: self.id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::PATCH)
+            .uri(format!("{}/channels/{}", BASE_URL, self.id))
             .header("content-type", "application/json")
-            .body(body)
+            .body(Body::from(
+                json!({ "permission_overwrites": parent.permission_overwrites.unwrap_or_default() })
+                    .to_string(),
+            ))
             .unwrap();
 
         send_request(ctx, route, request_builder).await
     }
 }
+
+impl Channel {
+    /// Whether the channel has the same permission overwrites as the given category, which is when the client shows it as synced.
+    /// The order of the overwrites doesn't matter.
+    /// @param parent The channel's category
+    pub fn permissions_synced_with(&self, parent: &Channel) -> bool {
+        let mut own = self.permission_overwrites.clone().unwrap_or_default();
+        let mut parents = parent.permission_overwrites.clone().unwrap_or_default();
+        own.sort_by_key(|overwrite| overwrite.id);
+        parents.sort_by_key(|overwrite| overwrite.id);
+        own.len() == parents.len()
+            && own.iter().zip(parents.iter()).all(|(a, b)| {
+                a.id == b.id && a.type_ == b.type_ && a.allow == b.allow && a.deny == b.deny
+            })
+    }
+
+    /// Converts an arbitrary string into a valid text channel name, following the same rules the discord client applies:
+    /// the name is lowercased, whitespace and punctuation become dashes (with repeated dashes collapsed), and the result is clamped to 100 characters.
+    /// Emojis and other non-ascii symbols are kept since discord allows them in channel names.
+    /// Returns `None` if nothing usable is left in the name (e.g. it was only punctuation).
+    /// @param name The raw name to normalize (usually user-provided)
+    pub fn normalize_name(name: &str) -> Option<String> {
+        let mut normalized = String::with_capacity(name.len());
+        for c in name.chars().flat_map(char::to_lowercase) {
+            let keep = c.is_alphanumeric()
+                || c == '_'
+                || (!c.is_ascii() && !c.is_whitespace() && !c.is_control());
+            if keep {
+                normalized.push(c);
+            } else if !normalized.is_empty() && !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        }
+
+        let mut normalized: String = normalized.chars().take(MAX_CHANNEL_NAME_LENGTH).collect();
+        // Clamping can leave a dangling dash or a half emoji sequence (zero width joiner / variation selector)
+        while normalized.ends_with(['-', '\u{200d}', '\u{fe0f}']) {
+            normalized.pop();
+        }
+
+        if normalized.is_empty() {
+            None
+        } else {
+            Some(normalized)
+        }
+    }
+}
+
+/// The maximum number of characters allowed in a channel name
+const MAX_CHANNEL_NAME_LENGTH: usize = 100;
@@ -1,8 +1,10 @@
+use chrono::{DateTime, TimeZone};
+
 use crate::discord::color::Color;
 
 use super::{
     typing::{EmbedAuthor, EmbedField, EmbedFooter, EmbedImage, EmbedThumbnail, EmbedType},
-    Embed,
+    Embed, EmbedLimitError,
 };
 
 pub struct EmbedBuilder {
@@ -81,10 +83,71 @@ impl EmbedBuilder {
         self
     }
 
+    /// Sets the title of the embed (up to 256 characters)
+    pub fn title(&mut self, title: &str) -> &mut Self {
+        self.set_title(title)
+    }
+
+    /// Sets the description of the embed (up to 4096 characters)
+    pub fn description(&mut self, description: &str) -> &mut Self {
+        self.set_description(description)
+    }
+
+    /// Sets the color of the embed, from a `Color` or a hex value like `0x3498db`
+    pub fn color(&mut self, color: impl Into<Color>) -> &mut Self {
+        self.set_color(color.into())
+    }
+
+    /// Adds a field to the embed. An embed can have up to 25 fields
+    /// @param name The name of the field (up to 256 characters)
+    /// @param value The value of the field (up to 1024 characters)
+    /// @param inline Whether the field is shown next to the other inline fields
+    pub fn field(&mut self, name: &str, value: &str, inline: bool) -> &mut Self {
+        self.add_field(EmbedField {
+            name: name.to_string(),
+            value: value.to_string(),
+            inline,
+        })
+    }
+
+    /// Sets the footer text of the embed (up to 2048 characters)
+    pub fn footer(&mut self, text: &str) -> &mut Self {
+        self.set_footer(EmbedFooter {
+            text: text.to_string(),
+            icon_url: None,
+            proxy_icon_url: None,
+        })
+    }
+
+    /// Sets the name of the author of the embed (up to 256 characters)
+    pub fn author(&mut self, name: &str) -> &mut Self {
+        self.set_author(EmbedAuthor {
+            name: Some(name.to_string()),
+            url: None,
+            icon_url: None,
+            proxy_icon_url: None,
+        })
+    }
+
+    /// Sets the time shown in the footer of the embed, in the viewer's timezone
+    pub fn timestamp<T: TimeZone>(&mut self, time: DateTime<T>) -> &mut Self
+    where
+        T::Offset: std::fmt::Display,
+    {
+        self.embed.timestamp = Some(time.to_rfc3339());
+        self
+    }
+
     /// Builds the embed.
     pub fn build(self) -> Embed {
         self.embed
     }
+
+    /// Builds the embed, checking it against discord's limits first
+    pub fn try_build(self) -> Result<Embed, EmbedLimitError> {
+        self.embed.validate()?;
+        Ok(self.embed)
+    }
 }
 
 impl Default for EmbedBuilder {
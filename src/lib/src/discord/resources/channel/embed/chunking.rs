@@ -0,0 +1,171 @@
+use super::{typing::EmbedField, Embed, EmbedBuilder};
+
+/// The maximum number of characters in a field value
+pub const MAX_FIELD_VALUE_LENGTH: usize = 1024;
+/// The maximum number of characters in a field name
+pub const MAX_FIELD_NAME_LENGTH: usize = 256;
+/// The maximum number of fields in an embed
+pub const MAX_FIELDS: usize = 25;
+/// The maximum number of characters across the title, description, fields, footer, and author of an embed
+pub const MAX_EMBED_LENGTH: usize = 6000;
+
+/// Field names can't be empty, so continuation fields use a zero width space
+const BLANK_FIELD_NAME: &str = "\u{200b}";
+
+/**
+ * Splits text into chunks of at most `limit` characters. Chunks are split at line breaks where possible,
+ * then at whitespace, and only split in the middle of a word when a single word is longer than the limit.
+ * @param text The text to split
+ * @param limit The maximum number of characters in a chunk
+ */
+pub fn split_text(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for line in text.split('\n') {
+        let line_len = line.chars().count();
+        // +1 for the line break that joins it to the current chunk
+        let joined_len = if current.is_empty() {
+            line_len
+        } else {
+            current_len + 1 + line_len
+        };
+
+        if joined_len <= limit {
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(line);
+            current_len = joined_len;
+            continue;
+        }
+
+        if !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if line_len <= limit {
+            current = line.to_string();
+            current_len = line_len;
+        } else {
+            let mut pieces = split_line(line, limit);
+            // The last piece may still have room for the next lines
+            let last = pieces.pop().unwrap_or_default();
+            chunks.extend(pieces);
+            current_len = last.chars().count();
+            current = last;
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Splits a single line that is longer than the limit at whitespace, or in the middle of words if it has to
+fn split_line(line: &str, limit: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest: Vec<char> = line.chars().collect();
+
+    while rest.len() > limit {
+        let split_at = rest[..=limit]
+            .iter()
+            .rposition(|c| c.is_whitespace())
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+        pieces.push(rest[..split_at].iter().collect::<String>());
+        // Drop the whitespace that the line was split at
+        let skip = if split_at < rest.len() && rest[split_at].is_whitespace() {
+            split_at + 1
+        } else {
+            split_at
+        };
+        rest.drain(..skip);
+    }
+    pieces.push(rest.into_iter().collect());
+    pieces
+}
+
+/**
+ * Splits long text into as many fields as needed to fit it. The first field gets `name`, the rest get a blank name
+ * so that they read as one continuous field.
+ * @param name The name of the field (truncated to 256 characters)
+ * @param text The text to put in the field(s)
+ * @param inline Whether the fields display inline
+ */
+pub fn chunk_into_fields(name: &str, text: &str, inline: bool) -> Vec<EmbedField> {
+    let name: String = name.chars().take(MAX_FIELD_NAME_LENGTH).collect();
+    let mut chunks = split_text(text, MAX_FIELD_VALUE_LENGTH);
+    if chunks.is_empty() {
+        // Field values can't be empty either
+        chunks.push(BLANK_FIELD_NAME.to_string());
+    }
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| EmbedField {
+            name: if i == 0 {
+                name.clone()
+            } else {
+                BLANK_FIELD_NAME.to_string()
+            },
+            value,
+            inline,
+        })
+        .collect()
+}
+
+/**
+ * Spreads fields over as many embeds as needed so that each embed has at most 25 fields and stays under the 6000 character limit.
+ * Every page gets the title, with the page number appended when there is more than one page (e.g. `Members (2/3)`).
+ * @param title The title of every page
+ * @param fields The fields to spread over the pages
+ * @return The embeds, ready to send. Up to 10 embeds can be sent in one message.
+ */
+pub fn paginate_fields(title: &str, fields: Vec<EmbedField>) -> Vec<Embed> {
+    // Leave room for the " (99/99)" page suffix
+    let title: String = title.chars().take(240).collect();
+    let budget = MAX_EMBED_LENGTH - title.chars().count() - 8;
+
+    let mut pages: Vec<Vec<EmbedField>> = vec![Vec::new()];
+    let mut page_len = 0;
+    for field in fields {
+        let field_len = field.name.chars().count() + field.value.chars().count();
+        let page = pages.last_mut().unwrap();
+        if !page.is_empty() && (page.len() == MAX_FIELDS || page_len + field_len > budget) {
+            pages.push(vec![field]);
+            page_len = field_len;
+        } else {
+            page.push(field);
+            page_len += field_len;
+        }
+    }
+
+    let page_count = pages.len();
+    pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, fields)| {
+            let mut builder = EmbedBuilder::new();
+            if page_count > 1 {
+                builder.set_title(&format!("{} ({}/{})", title, i + 1, page_count));
+            } else {
+                builder.set_title(&title);
+            }
+            for field in fields {
+                builder.add_field(field);
+            }
+            builder.build()
+        })
+        .collect()
+}
+
+/**
+ * Turns long text (like log output or a long list) into ready to send embeds, splitting it into fields and pages as needed.
+ * @param title The title of every page
+ * @param text The text to show
+ */
+pub fn chunk_into_embeds(title: &str, text: &str) -> Vec<Embed> {
+    paginate_fields(title, chunk_into_fields(BLANK_FIELD_NAME, text, false))
+}
@@ -1,7 +1,11 @@
 mod builder;
+mod chunking;
 mod embed_object;
 mod typing;
+mod validation;
 
 pub use builder::*;
+pub use chunking::*;
 pub use embed_object::Embed;
 pub use typing::*;
+pub use validation::*;
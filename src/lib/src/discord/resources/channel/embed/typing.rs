@@ -138,5 +138,6 @@ pub struct EmbedField {
     /// Value of field
     pub value: String,
     /// Whether or not this field should display inline
+    #[serde(default)]
     pub inline: bool,
 }
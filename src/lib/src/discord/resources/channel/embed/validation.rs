@@ -0,0 +1,188 @@
+use std::fmt::Display;
+
+use super::{
+    Embed, MAX_EMBED_LENGTH, MAX_FIELDS, MAX_FIELD_NAME_LENGTH, MAX_FIELD_VALUE_LENGTH,
+};
+
+/// The maximum number of characters in an embed title
+pub const MAX_TITLE_LENGTH: usize = 256;
+/// The maximum number of characters in an embed description
+pub const MAX_DESCRIPTION_LENGTH: usize = 4096;
+/// The maximum number of characters in an embed footer
+pub const MAX_FOOTER_LENGTH: usize = 2048;
+/// The maximum number of characters in the name of an embed author
+pub const MAX_AUTHOR_NAME_LENGTH: usize = 256;
+/// The maximum number of embeds in a message
+pub const MAX_EMBEDS: usize = 10;
+
+/**
+ * A limit on embeds that discord would reject the message for. Lengths are counted in characters.
+ * @docs <https://discord.com/developers/docs/resources/channel#embed-object-embed-limits>
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EmbedLimitError {
+    /// The title is longer than 256 characters
+    TitleTooLong(usize),
+    /// The description is longer than 4096 characters
+    DescriptionTooLong(usize),
+    /// There are more than 25 fields
+    TooManyFields(usize),
+    /// A field has an empty name or value, which discord doesn't allow
+    EmptyField { index: usize },
+    /// A field name is longer than 256 characters
+    FieldNameTooLong { index: usize, length: usize },
+    /// A field value is longer than 1024 characters
+    FieldValueTooLong { index: usize, length: usize },
+    /// The footer is longer than 2048 characters
+    FooterTooLong(usize),
+    /// The author name is longer than 256 characters
+    AuthorNameTooLong(usize),
+    /// The title, description, fields, footer and author of every embed of the message add up to more than 6000 characters
+    TooLong(usize),
+    /// The message has more than 10 embeds
+    TooManyEmbeds(usize),
+}
+
+impl Display for EmbedLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmbedLimitError::TitleTooLong(length) => write!(
+                f,
+                "The embed title is {} characters long, the limit is {}",
+                length, MAX_TITLE_LENGTH
+            ),
+            EmbedLimitError::DescriptionTooLong(length) => write!(
+                f,
+                "The embed description is {} characters long, the limit is {}",
+                length, MAX_DESCRIPTION_LENGTH
+            ),
+            EmbedLimitError::TooManyFields(count) => write!(
+                f,
+                "The embed has {} fields, the limit is {}",
+                count, MAX_FIELDS
+            ),
+            EmbedLimitError::EmptyField { index } => {
+                write!(f, "Embed field {} has an empty name or value", index)
+            }
+            EmbedLimitError::FieldNameTooLong { index, length } => write!(
+                f,
+                "The name of embed field {} is {} characters long, the limit is {}",
+                index, length, MAX_FIELD_NAME_LENGTH
+            ),
+            EmbedLimitError::FieldValueTooLong { index, length } => write!(
+                f,
+                "The value of embed field {} is {} characters long, the limit is {}",
+                index, length, MAX_FIELD_VALUE_LENGTH
+            ),
+            EmbedLimitError::FooterTooLong(length) => write!(
+                f,
+                "The embed footer is {} characters long, the limit is {}",
+                length, MAX_FOOTER_LENGTH
+            ),
+            EmbedLimitError::AuthorNameTooLong(length) => write!(
+                f,
+                "The embed author name is {} characters long, the limit is {}",
+                length, MAX_AUTHOR_NAME_LENGTH
+            ),
+            EmbedLimitError::TooLong(length) => write!(
+                f,
+                "The embeds are {} characters long in total, the limit is {}",
+                length, MAX_EMBED_LENGTH
+            ),
+            EmbedLimitError::TooManyEmbeds(count) => write!(
+                f,
+                "The message has {} embeds, the limit is {}",
+                count, MAX_EMBEDS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EmbedLimitError {}
+
+impl Embed {
+    /// Checks the embed against discord's limits
+    pub fn validate(&self) -> Result<(), EmbedLimitError> {
+        let length = |text: &Option<String>| text.as_deref().map_or(0, |text| text.chars().count());
+
+        let title = length(&self.title);
+        if title > MAX_TITLE_LENGTH {
+            return Err(EmbedLimitError::TitleTooLong(title));
+        }
+        let description = length(&self.description);
+        if description > MAX_DESCRIPTION_LENGTH {
+            return Err(EmbedLimitError::DescriptionTooLong(description));
+        }
+        let fields = self.fields.as_deref().unwrap_or_default();
+        if fields.len() > MAX_FIELDS {
+            return Err(EmbedLimitError::TooManyFields(fields.len()));
+        }
+        for (index, field) in fields.iter().enumerate() {
+            let (name, value) = (field.name.chars().count(), field.value.chars().count());
+            if name == 0 || value == 0 {
+                return Err(EmbedLimitError::EmptyField { index });
+            }
+            if name > MAX_FIELD_NAME_LENGTH {
+                return Err(EmbedLimitError::FieldNameTooLong {
+                    index,
+                    length: name,
+                });
+            }
+            if value > MAX_FIELD_VALUE_LENGTH {
+                return Err(EmbedLimitError::FieldValueTooLong {
+                    index,
+                    length: value,
+                });
+            }
+        }
+        let footer = self
+            .footer
+            .as_ref()
+            .map_or(0, |footer| footer.text.chars().count());
+        if footer > MAX_FOOTER_LENGTH {
+            return Err(EmbedLimitError::FooterTooLong(footer));
+        }
+        let author = self
+            .author
+            .as_ref()
+            .map_or(0, |author| length(&author.name));
+        if author > MAX_AUTHOR_NAME_LENGTH {
+            return Err(EmbedLimitError::AuthorNameTooLong(author));
+        }
+        let total = self.length();
+        if total > MAX_EMBED_LENGTH {
+            return Err(EmbedLimitError::TooLong(total));
+        }
+        Ok(())
+    }
+
+    /// The number of characters that count towards the 6000 character limit
+    pub fn length(&self) -> usize {
+        let length = |text: Option<&str>| text.map_or(0, |text| text.chars().count());
+        length(self.title.as_deref())
+            + length(self.description.as_deref())
+            + self
+                .fields
+                .iter()
+                .flatten()
+                .map(|field| field.name.chars().count() + field.value.chars().count())
+                .sum::<usize>()
+            + length(self.footer.as_ref().map(|footer| footer.text.as_str()))
+            + length(self.author.as_ref().and_then(|author| author.name.as_deref()))
+    }
+}
+
+/// Checks the embeds of a message against discord's limits, including the limits on the message as a whole
+pub fn validate_embeds(embeds: &[Embed]) -> Result<(), EmbedLimitError> {
+    if embeds.len() > MAX_EMBEDS {
+        return Err(EmbedLimitError::TooManyEmbeds(embeds.len()));
+    }
+    for embed in embeds {
+        embed.validate()?;
+    }
+    let total: usize = embeds.iter().map(Embed::length).sum();
+    if total > MAX_EMBED_LENGTH {
+        return Err(EmbedLimitError::TooLong(total));
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use std::{
+    pin::Pin,
+    task::{Context as TaskContext, Poll},
+};
+
+use futures_util::{Stream, StreamExt};
+use hyper::{Body, Method, Request};
+
+use crate::{
+    core::http::rate_limit_client::{send_request, RequestRoute},
+    discord::{
+        ids::{
+            params::{ChannelIdParam, MessageIdParam},
+            RawId,
+        },
+        snowflake::Snowflake,
+    },
+    util::{
+        error::Error,
+        pagination::{Direction, Page, PageQuery, Paginated},
+    },
+    Context, BASE_URL,
+};
+
+use super::{message::Message, Channel};
+
+/// The most messages that discord returns in a page of `Channel::get_messages`
+const MAX_MESSAGES_PER_PAGE: u64 = 100;
+
+/**
+ * The messages of a channel, made by `Channel::messages`. Set where to start with `before` or `after`,
+ * and how many messages to walk through with `limit`, then poll it as a stream.
+ * Nothing is fetched until the stream is first polled.
+ *
+ * ```rust,ignore
+ * let mut history = Channel::messages(ctx, channel_id).before(message_id).limit(500);
+ * while let Some(message) = history.next().await {
+ *     let message = message?;
+ *     // ...
+ * }
+ * ```
+ */
+pub struct MessageHistory {
+    ctx: Context,
+    channel_id: ChannelIdParam,
+    before: Option<Snowflake>,
+    after: Option<Snowflake>,
+    limit: Option<u64>,
+    /// Started on the first poll, once the query can't change anymore
+    stream: Option<Paginated<Message>>,
+}
+
+impl MessageHistory {
+    /// Only walks the messages before this one, newest first. This is the default, starting from the latest message
+    #[must_use]
+    pub fn before(mut self, message_id: MessageIdParam) -> Self {
+        self.before = Some(message_id.raw());
+        self.after = None;
+        self
+    }
+
+    /// Only walks the messages after this one, oldest first
+    #[must_use]
+    pub fn after(mut self, message_id: MessageIdParam) -> Self {
+        self.after = Some(message_id.raw());
+        self.before = None;
+        self
+    }
+
+    /// The most messages to walk through in total. Without a limit, the stream goes until the start (or end) of the channel
+    #[must_use]
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Fetches every remaining message and returns them, or the first error
+    pub async fn collect_all(mut self) -> Result<Vec<Message>, Error> {
+        let mut messages = Vec::new();
+        while let Some(message) = self.next().await {
+            messages.push(message?);
+        }
+        Ok(messages)
+    }
+
+    fn start(&self) -> Paginated<Message> {
+        let per_page = self
+            .limit
+            .unwrap_or(MAX_MESSAGES_PER_PAGE)
+            .clamp(1, MAX_MESSAGES_PER_PAGE);
+        let query = PageQuery {
+            before: self.before,
+            after: self.after,
+            limit: Some(per_page),
+        };
+        let direction = if self.after.is_some() {
+            Direction::Forward
+        } else {
+            Direction::Backward
+        };
+        let channel_id = self.channel_id;
+
+        let pages = Paginated::new(
+            self.ctx.clone(),
+            direction,
+            query,
+            move |ctx, query| async move {
+                Channel::get_messages(ctx, channel_id, query)
+                    .await
+                    .map(|mut messages| {
+                        // Discord returns every page newest first, so the last message is always where the next page continues from
+                        if direction == Direction::Forward {
+                            messages.reverse();
+                        }
+                        Page::from_list(messages, per_page)
+                    })
+            },
+            |message| Some(message.id),
+        );
+
+        match self.limit {
+            Some(limit) => Paginated::from_stream(pages.take(limit as usize)),
+            None => pages,
+        }
+    }
+}
+
+impl Stream for MessageHistory {
+    type Item = Result<Message, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        if self.stream.is_none() {
+            let stream = self.start();
+            self.stream = Some(stream);
+        }
+        self.stream.as_mut().unwrap().poll_next_unpin(cx)
+    }
+}
+
+impl Channel {
+    /// Gets a page of the messages in a channel, newest first. Requires the READ_MESSAGE_HISTORY permission
+    /// @param channel_id The id of the channel
+    /// @param query The message id to start before or after, and the max number of messages to return (1-100, 50 by default)
+    /// @docs <https://discord.com/developers/docs/resources/channel#get-channel-messages>
+    pub async fn get_messages(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        query: PageQuery,
+    ) -> Result<Vec<Message>, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/channels/{}/messages?{}",
+                BASE_URL,
+                channel_id,
+                query.to_query_string()
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Walks through the messages of a channel, starting from the latest one.
+     * Pages are fetched as the stream is polled, see `MessageHistory`.
+     *
+     * @param channel_id The id of the channel
+     */
+    pub fn messages(ctx: Context, channel_id: ChannelIdParam) -> MessageHistory {
+        MessageHistory {
+            ctx,
+            channel_id,
+            before: None,
+            after: None,
+            limit: None,
+            stream: None,
+        }
+    }
+}
@@ -1,4 +1,6 @@
 use discrab_codegen::CommandArg;
+#[cfg(feature = "rest")]
+use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
 
 pub use super::message_builder::*;
@@ -6,6 +8,7 @@ use super::{
     attachment::Attachment,
     embed::Embed,
     mention::ChannelMention,
+    message_link::MessageLink,
     reaction::Reaction,
     typing::{MessageActivity, MessageFlags, MessageReference, MessageType},
     Channel,
@@ -25,6 +28,31 @@ use crate::{
         snowflake::Snowflake,
     },
 };
+#[cfg(feature = "rest")]
+use crate::{
+    core::http::rate_limit_client::{
+        send_request, send_request_idempotent, send_request_noparse, RequestRoute,
+    },
+    discord::resources::{
+        channel::attachment::AttachmentFile, emoji::UsableEmoji, guild::guild_object::Guild,
+    },
+    util::{
+        error::{Error, ErrorTypes},
+        multipart::message_request,
+        pagination::{Direction, Page, PageQuery, Paginated},
+        requests::with_audit_log_reason,
+    },
+    Context, BASE_URL,
+};
+#[cfg(feature = "rest")]
+use super::reaction::ReactionType;
+#[cfg(feature = "rest")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "rest")]
+use crate::discord::ids::{
+    params::{self, ChannelIdParam, MessageIdParam, UserIdParam},
+    RawId,
+};
 /**
  * Message Object
  * Represents a message sent in a channel within Discord.
@@ -107,6 +135,641 @@ impl Message {
     pub fn builder() -> MessageBuilder {
         MessageBuilder::new()
     }
+
+    /// The link to the message, which opens it in the discord client
+    pub fn link(&self) -> MessageLink {
+        MessageLink {
+            guild_id: self.guild_id,
+            channel_id: self.channel_id,
+            message_id: self.id,
+        }
+    }
+
+    /// Whether the bot has already reacted to the message with an emoji, according to this message object
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    pub fn has_own_reaction(&self, emoji: &str) -> bool {
+        self.reactions
+            .iter()
+            .flatten()
+            .any(|reaction| reaction.me && reaction.emoji.matches(emoji))
+    }
+}
+
+/// Where `Message::send_with_fallback` ended up sending the message
+#[cfg(feature = "rest")]
+pub enum Delivery {
+    /// The message was sent in the channel
+    Channel(Message),
+    /// The bot couldn't post in the channel, so the message was DMed to the user
+    DirectMessage(Message),
+}
+
+#[cfg(feature = "rest")]
+impl Delivery {
+    /// The message that was sent, wherever it ended up
+    pub fn message(&self) -> &Message {
+        match self {
+            Delivery::Channel(message) | Delivery::DirectMessage(message) => message,
+        }
+    }
+}
+
+#[cfg(feature = "rest")]
+impl Message {
+    /// Gets a message from discord. Requires the READ_MESSAGE_HISTORY permission in guild channels.
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message
+    /// @docs <https://discord.com/developers/docs/resources/channel#get-channel-message>
+    pub async fn get(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+    ) -> Result<Message, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages/{message.id}".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/channels/{}/messages/{}",
+                BASE_URL, channel_id, message_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Sends a message to a channel. If the builder has files attached, they are uploaded with the message.
+     *
+     * ```rust,ignore
+     * let message = Message::builder()
+     *     .set_content("Here is the report")
+     *     .attach_file(AttachmentFile::new("report.txt", report.into_bytes()))
+     *     .reply_to(original.id);
+     * Message::send(ctx, channel_id, message).await?;
+     * ```
+     * @param channel_id The id of the channel to send the message to
+     * @param message The message to send
+     * @docs <https://discord.com/developers/docs/resources/channel#create-message>
+     */
+    pub async fn send(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message: MessageBuilder,
+    ) -> Result<Message, Error> {
+        message.validate()?;
+        let payload = serde_json::to_value(&message).unwrap();
+        Message::create(ctx, channel_id.raw(), payload, message.files()).await
+    }
+
+    /**
+     * Sends a message to a channel, and DMs it to a user instead if the bot isn't allowed to post in the channel.
+     * Any other error (including the user having their DMs closed) is returned as is.
+     *
+     * ```rust,ignore
+     * let sent = Message::send_with_fallback(
+     *     ctx,
+     *     channel_id,
+     *     user_id,
+     *     Message::builder().set_content("Your report is ready"),
+     *     "I can't post in that channel, so here is your message:",
+     * )
+     * .await?;
+     * ```
+     * @param channel_id The id of the channel to send the message to
+     * @param user_id The user to DM if the channel can't be used, usually the one that used the command
+     * @param message The message to send
+     * @param notice Put above the message in the DM, to tell the user why they got it
+     */
+    pub async fn send_with_fallback(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        user_id: UserIdParam,
+        message: MessageBuilder,
+        notice: &str,
+    ) -> Result<Delivery, Error> {
+        message.validate()?;
+        let mut payload = serde_json::to_value(&message).unwrap();
+        match Message::create(ctx.clone(), channel_id.raw(), payload.clone(), message.files()).await
+        {
+            Ok(sent) => return Ok(Delivery::Channel(sent)),
+            Err(e) if !e.is_missing_permissions() => return Err(e),
+            Err(_) => {}
+        }
+
+        let dm = User::create_dm(ctx.clone(), user_id.raw()).await?;
+        if let serde_json::Value::Object(fields) = &mut payload {
+            // The message it replies to isn't in the DM
+            fields.remove("message_reference");
+            let content = match fields.get("content").and_then(|content| content.as_str()) {
+                Some(content) => format!("{}\n\n{}", notice, content),
+                None => notice.to_string(),
+            };
+            fields.insert("content".to_string(), content.into());
+        }
+        let sent = Message::create(ctx, dm.id, payload, message.files()).await?;
+        Ok(Delivery::DirectMessage(sent))
+    }
+
+    async fn create(
+        ctx: Context,
+        channel_id: Snowflake,
+        payload: serde_json::Value,
+        files: &[AttachmentFile],
+    ) -> Result<Message, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = message_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{}/channels/{}/messages", BASE_URL, channel_id)),
+            payload,
+            "",
+            files,
+        );
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Edits a message that the bot sent. Only the fields that are set on the builder are changed.
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message
+    /// @param message The new content of the message
+    /// @docs <https://discord.com/developers/docs/resources/channel#edit-message>
+    pub async fn edit(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        message: MessageBuilder,
+    ) -> Result<Message, Error> {
+        message.validate()?;
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages/{message.id}".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let mut payload = serde_json::to_value(&message).unwrap();
+        // Unset fields would clear the message's content/embeds/etc, so only send the ones that are set
+        if let serde_json::Value::Object(fields) = &mut payload {
+            fields.retain(|_, value| !value.is_null());
+        }
+        let request_builder = message_request(
+            Request::builder().method(Method::PATCH).uri(format!(
+                "{}/channels/{}/messages/{}",
+                BASE_URL, channel_id, message_id
+            )),
+            payload,
+            "",
+            message.files(),
+        );
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Deletes a message. Deleting another user's message requires the MANAGE_MESSAGES permission.
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/channel#delete-message>
+    pub async fn delete(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages/{message.id}".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::DELETE)
+            .uri(format!(
+                "{}/channels/{}/messages/{}",
+                BASE_URL, channel_id, message_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+
+    /// Publishes a message in a news channel to the channels that follow it.
+    /// Requires the SEND_MESSAGES permission for the bot's own messages, and MANAGE_MESSAGES for other messages.
+    /// @param channel_id The id of the news channel the message is in
+    /// @param message_id The id of the message
+    /// @return The crossposted message
+    /// @docs <https://discord.com/developers/docs/resources/channel#crosspost-message>
+    pub async fn crosspost(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+    ) -> Result<Message, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages/{message.id}/crosspost".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::POST)
+            .uri(format!(
+                "{}/channels/{}/messages/{}/crosspost",
+                BASE_URL, channel_id, message_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Gets a page of the users that reacted to a message with an emoji, sorted by their ids
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    /// @param query The user id to start after, and the max number of users to return (1-100, 25 by default). `before` is ignored
+    /// @docs <https://discord.com/developers/docs/resources/channel#get-reactions>
+    pub async fn get_reactions(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        emoji: impl Into<ReactionType>,
+        query: PageQuery,
+    ) -> Result<Vec<User>, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages/{message.id}/reactions/{emoji}"
+                .to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let query = PageQuery {
+            before: None,
+            ..query
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/channels/{}/messages/{}/reactions/{}?{}",
+                BASE_URL,
+                channel_id,
+                message_id,
+                emoji.into().url_encoded(),
+                query.to_query_string()
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Walks through every user that reacted to a message with an emoji.
+     * Pages are fetched as the stream is polled.
+     *
+     * @param channel_id The id of the channel the message is in
+     * @param message_id The id of the message
+     * @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+     */
+    pub fn reactions(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        emoji: impl Into<ReactionType>,
+    ) -> Paginated<User> {
+        let emoji = emoji.into();
+        Paginated::new(
+            ctx,
+            Direction::Forward,
+            PageQuery::new().limit(MAX_REACTIONS_PER_PAGE),
+            move |ctx, query| {
+                let emoji = emoji.clone();
+                async move {
+                    Message::get_reactions(ctx, channel_id, message_id, emoji, query)
+                        .await
+                        .map(|users| Page::from_list(users, MAX_REACTIONS_PER_PAGE))
+                }
+            },
+            |user| Some(user.id),
+        )
+    }
+
+    /// Reacts to a message as the bot. Reacting with an emoji the bot has already reacted with does nothing, so this is safe to call repeatedly.
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    /// @docs <https://discord.com/developers/docs/resources/channel#create-reaction>
+    pub async fn add_reaction(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        emoji: impl Into<ReactionType>,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages/{message.id}/reactions/{emoji}/@me"
+                .to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let uri = format!(
+            "{}/channels/{}/messages/{}/reactions/{}/@me",
+            BASE_URL,
+            channel_id,
+            message_id,
+            emoji.into().url_encoded()
+        );
+
+        send_request_idempotent(ctx, route, || {
+            Request::builder()
+                .method(Method::PUT)
+                .uri(&uri)
+                .body(Body::empty())
+                .unwrap()
+        })
+        .await
+    }
+
+    /// Reacts to this message as the bot, see `Message::add_reaction`
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    pub async fn react(&self, ctx: Context, emoji: impl Into<ReactionType>) -> Result<(), Error> {
+        Message::add_reaction(
+            ctx,
+            params::channel(self.channel_id),
+            params::message(self.id),
+            emoji,
+        )
+        .await
+    }
+
+    /// Removes the bot's reaction from a message
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    /// @docs <https://discord.com/developers/docs/resources/channel#delete-own-reaction>
+    pub async fn delete_own_reaction(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        emoji: impl Into<ReactionType>,
+    ) -> Result<(), Error> {
+        Message::delete_reactions(
+            ctx,
+            channel_id,
+            format!("{}/@me", emoji.into().url_encoded()),
+            message_id,
+            "/{emoji}/@me",
+        )
+        .await
+    }
+
+    /// Removes another user's reaction from a message. Requires the MANAGE_MESSAGES permission.
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    /// @param user_id The user whose reaction is removed
+    /// @docs <https://discord.com/developers/docs/resources/channel#delete-user-reaction>
+    pub async fn delete_user_reaction(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        emoji: impl Into<ReactionType>,
+        user_id: UserIdParam,
+    ) -> Result<(), Error> {
+        Message::delete_reactions(
+            ctx,
+            channel_id,
+            format!("{}/{}", emoji.into().url_encoded(), user_id),
+            message_id,
+            "/{emoji}/{user.id}",
+        )
+        .await
+    }
+
+    /// Removes every reaction from a message. Requires the MANAGE_MESSAGES permission.
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message
+    /// @docs <https://discord.com/developers/docs/resources/channel#delete-all-reactions>
+    pub async fn delete_all_reactions(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+    ) -> Result<(), Error> {
+        Message::delete_reactions(ctx, channel_id, String::new(), message_id, "").await
+    }
+
+    /// Removes every reaction with an emoji from a message. Requires the MANAGE_MESSAGES permission.
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    /// @docs <https://discord.com/developers/docs/resources/channel#delete-all-reactions-for-emoji>
+    pub async fn delete_emoji_reactions(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        emoji: impl Into<ReactionType>,
+    ) -> Result<(), Error> {
+        Message::delete_reactions(
+            ctx,
+            channel_id,
+            emoji.into().url_encoded(),
+            message_id,
+            "/{emoji}",
+        )
+        .await
+    }
+
+    /// Sends a DELETE to `/reactions/{path}` of a message
+    /// @param path What comes after `/reactions`, without the leading slash
+    /// @param route_suffix The rate limit route of what comes after `/reactions`
+    async fn delete_reactions(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        path: String,
+        message_id: MessageIdParam,
+        route_suffix: &str,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: format!(
+                "/channels/{{channel.id}}/messages/{{message.id}}/reactions{}",
+                route_suffix
+            ),
+            major_param: channel_id.to_string(),
+        };
+        let uri = format!(
+            "{}/channels/{}/messages/{}/reactions",
+            BASE_URL, channel_id, message_id
+        );
+        let uri = if path.is_empty() {
+            uri
+        } else {
+            format!("{}/{}", uri, path)
+        };
+        let request_builder = Request::builder()
+            .method(Method::DELETE)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+
+    /// Reacts with a custom emoji if the bot can use it, and with a unicode emoji if it can't (see `Guild::usable_emoji`)
+    /// @param guild The guild the message is in, with its emojis
+    /// @param bot_member The bot's member in the guild
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    /// @param fallback The unicode emoji to react with when the custom emoji can't be used
+    /// @return The emoji that was used
+    pub async fn add_reaction_or(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        guild: &Guild,
+        bot_member: &GuildMember,
+        emoji: &str,
+        fallback: &str,
+    ) -> Result<UsableEmoji, Error> {
+        let usable = guild.usable_emoji(bot_member, emoji, fallback);
+        Message::add_reaction(ctx, channel_id, message_id, &usable.reaction_format()).await?;
+        Ok(usable)
+    }
+
+    /**
+     * Disables every button and select menu on the message, which is the usual way to end a component session
+     * (e.g. when a poll closes, or the collector times out). The message has to be one the bot sent.
+     * @return The edited message
+     */
+    pub async fn disable_components(&self, ctx: Context) -> Result<Message, Error> {
+        self.set_components_disabled(ctx, true).await
+    }
+
+    /// Disables or enables every button and select menu on the message. The message has to be one the bot sent.
+    /// @param disabled Whether the components should be disabled
+    /// @return The edited message
+    pub async fn set_components_disabled(
+        &self,
+        ctx: Context,
+        disabled: bool,
+    ) -> Result<Message, Error> {
+        let builder = self
+            .components
+            .iter()
+            .flatten()
+            .cloned()
+            .fold(MessageBuilder::new(), |builder, mut component| {
+                component.set_disabled_recursive(disabled);
+                builder.component(component)
+            });
+        Message::edit(
+            ctx,
+            params::channel(self.channel_id),
+            params::message(self.id),
+            builder,
+        )
+        .await
+    }
+
+    /// Makes sure the bot has reacted to the message with an emoji. No request is made if the message object
+    /// already shows the bot's reaction.
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    pub async fn ensure_reaction(&self, ctx: Context, emoji: &str) -> Result<(), Error> {
+        if self.has_own_reaction(emoji) {
+            return Ok(());
+        }
+        Message::add_reaction(
+            ctx,
+            params::channel(self.channel_id),
+            params::message(self.id),
+            emoji,
+        ).await
+    }
+}
+
+/// The most users that discord returns in a page of `Message::get_reactions`
+#[cfg(feature = "rest")]
+const MAX_REACTIONS_PER_PAGE: u64 = 100;
+
+/// The fewest messages that can be bulk deleted at once
+#[cfg(feature = "rest")]
+pub const BULK_DELETE_MIN: usize = 2;
+/// The most messages that can be bulk deleted at once
+#[cfg(feature = "rest")]
+pub const BULK_DELETE_MAX: usize = 100;
+/// Messages older than this can't be bulk deleted
+#[cfg(feature = "rest")]
+pub const BULK_DELETE_MAX_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+#[cfg(feature = "rest")]
+impl Channel {
+    /**
+     * Deletes 2 to 100 messages at once. Requires the MANAGE_MESSAGES permission.
+     * The ids are checked before the request is made, since discord rejects the whole request if any message
+     * is older than 2 weeks. Duplicate ids are only sent once.
+     * @param channel_id The id of the channel the messages are in
+     * @param message_ids The ids of the messages to delete
+     * @param reason The reason that shows up in the audit log
+     * @docs <https://discord.com/developers/docs/resources/channel#bulk-delete-messages>
+     */
+    pub async fn bulk_delete_messages(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_ids: Vec<MessageIdParam>,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        let message_ids = validate_bulk_delete(message_ids.into_iter().map(RawId::raw).collect())?;
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages/bulk-delete".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::POST)
+            .uri(format!(
+                "{}/channels/{}/messages/bulk-delete",
+                BASE_URL, channel_id
+            ))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "messages": message_ids }).to_string(),
+            ))
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+}
+
+/// Removes duplicate ids, and checks the number and age of the messages
+#[cfg(feature = "rest")]
+fn validate_bulk_delete(mut message_ids: Vec<Snowflake>) -> Result<Vec<Snowflake>, Error> {
+    message_ids.sort();
+    message_ids.dedup();
+    if !(BULK_DELETE_MIN..=BULK_DELETE_MAX).contains(&message_ids.len()) {
+        return Err(Error::new(
+            format!(
+                "Bulk delete needs {} to {} messages, got {}",
+                BULK_DELETE_MIN,
+                BULK_DELETE_MAX,
+                message_ids.len()
+            ),
+            ErrorTypes::REQUEST,
+        ));
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let oldest_allowed = now.saturating_sub(BULK_DELETE_MAX_AGE.as_millis() as u64);
+    // Sorted by id, so the first one is the oldest
+    if let Some(too_old) = message_ids
+        .iter()
+        .find(|id| id.created_at_ms() < oldest_allowed)
+    {
+        return Err(Error::new(
+            format!(
+                "Message {} is older than 2 weeks, so it can't be bulk deleted",
+                too_old
+            ),
+            ErrorTypes::REQUEST,
+        ));
+    }
+    Ok(message_ids)
 }
 
 /**
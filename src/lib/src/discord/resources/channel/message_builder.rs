@@ -1,13 +1,19 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::{
     api::Snowflake,
-    core::interactions::{message::MessageComponent, typing::AllowedMentions},
+    core::interactions::{
+        message::{validate_components, MessageComponent},
+        typing::AllowedMentions,
+    },
+    util::error::{Error, ErrorTypes},
 };
 
 use super::{
-    embed::{Embed, EmbedBuilder},
-    typing::MessageReference,
+    attachment::AttachmentFile,
+    embed::{validate_embeds, Embed, EmbedBuilder},
+    typing::{MessageFlags, MessageReference},
 };
 
 /**
@@ -29,6 +35,12 @@ pub struct MessageBuilder {
     components: Option<Vec<MessageComponent>>,
     /// IDs of up to 3 stickers in the server to send in the message
     sticker_ids: Option<Vec<Snowflake>>,
+    /// message flags combined as a bitfield (only SUPPRESS_EMBEDS and SUPPRESS_NOTIFICATIONS can be set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flags: Option<u64>,
+    /// files to upload with the message, sent as multipart form data instead of in the json
+    #[serde(skip)]
+    files: Vec<AttachmentFile>,
 }
 
 impl MessageBuilder {
@@ -41,6 +53,8 @@ impl MessageBuilder {
             message_reference: None,
             components: None,
             sticker_ids: None,
+            flags: None,
+            files: Vec::new(),
         }
     }
 
@@ -58,6 +72,23 @@ impl MessageBuilder {
         self
     }
 
+    /// Send the message without a push or desktop notification, like `@silent` in the client. Mentions are still highlighted
+    #[must_use]
+    pub fn silent(self) -> Self {
+        self.add_flags(MessageFlags::SUPPRESS_NOTIFICATIONS)
+    }
+
+    /// Don't show the embeds of the links in the content
+    #[must_use]
+    pub fn suppress_embeds(self) -> Self {
+        self.add_flags(MessageFlags::SUPPRESS_EMBEDS)
+    }
+
+    fn add_flags(mut self, flags: MessageFlags) -> Self {
+        self.flags = Some(self.flags.unwrap_or(0) | flags.bits());
+        self
+    }
+
     /// Add an embed to the message
     #[must_use]
     pub fn add_embed<F: Fn(&mut EmbedBuilder)>(mut self, embed_fn: F) -> Self {
@@ -77,6 +108,140 @@ impl MessageBuilder {
         }
         self
     }
+
+    /// Add an embed that was already built to the message
+    #[must_use]
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.embeds.get_or_insert_with(Vec::new).push(embed);
+        self
+    }
+
+    /// Add a component (usually an action row) to the message
+    #[must_use]
+    pub fn component(mut self, component: MessageComponent) -> Self {
+        self.components.get_or_insert_with(Vec::new).push(component);
+        self
+    }
+
+    /// Upload a file with the message. Embeds can show an uploaded image with `attachment://<filename>` as the url.
+    #[must_use]
+    pub fn attach_file(mut self, file: AttachmentFile) -> Self {
+        self.files.push(file);
+        self
+    }
+
+    /// Make the message a reply to another message in the same channel
+    #[must_use]
+    pub fn reply_to(mut self, message_id: Snowflake) -> Self {
+        self.message_reference = Some(MessageReference {
+            message_id: Some(message_id.to_string()),
+            channel_id: None,
+            guild_id: None,
+            fail_if_not_exists: None,
+        });
+        self
+    }
+
+    /// Set which mentions in the content actually ping
+    #[must_use]
+    pub fn set_allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.allowed_mentions = Some(allowed_mentions);
+        self
+    }
+
+    /// Add a sticker from the server to the message (up to 3)
+    #[must_use]
+    pub fn add_sticker(mut self, sticker_id: Snowflake) -> Self {
+        self.sticker_ids
+            .get_or_insert_with(Vec::new)
+            .push(sticker_id);
+        self
+    }
+
+    /// The files that will be uploaded with the message
+    pub fn files(&self) -> &[AttachmentFile] {
+        &self.files
+    }
+
+    /// Checks the embeds and components against discord's limits. Called before the message is sent, so that it fails without a request
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_embeds(self.embeds.as_deref().unwrap_or_default())?;
+        validate_components(self.components.as_deref().unwrap_or_default())
+    }
+
+    /**
+     * Creates a message from JSON, like the ones exported by embed builder websites, so that designed messages can be pasted into a bot.
+     * Accepts a plain message payload (`{"content": ..., "embeds": [...]}`), the older single `"embed"` format,
+     * and Discohook's `{"messages": [{"data": ...}]}` format (the first message is used). Webhook-only fields like `username` are ignored.
+     * @param json The JSON to read
+     * @return An error if the JSON isn't a message
+     */
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let mut value: Value = serde_json::from_str(json)?;
+        if let Some(messages) = value.get_mut("messages") {
+            value = messages
+                .get_mut(0)
+                .and_then(|message| message.get_mut("data"))
+                .map(Value::take)
+                .ok_or_else(|| {
+                    Error::new(
+                        "The JSON doesn't contain any messages".to_string(),
+                        ErrorTypes::PARSE,
+                    )
+                })?;
+        }
+        let message: MessageJson = serde_json::from_value(value)?;
+
+        let mut embeds = message.embeds.unwrap_or_default();
+        embeds.extend(message.embed);
+        Ok(Self {
+            content: message.content.filter(|content| !content.is_empty()),
+            tts: message.tts,
+            embeds: if embeds.is_empty() { None } else { Some(embeds) },
+            allowed_mentions: message.allowed_mentions,
+            message_reference: None,
+            components: message.components,
+            sticker_ids: None,
+            flags: message.flags,
+            files: Vec::new(),
+        })
+    }
+
+    /// The message as JSON, in the format that embed builder websites import. Files and replies aren't included
+    pub fn to_json(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Value::Object(fields) = &mut value {
+            fields.remove("message_reference");
+            fields.remove("sticker_ids");
+        }
+        remove_nulls(&mut value);
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    }
+}
+
+/// The fields of a message that are read by `MessageBuilder::from_json`
+#[derive(Deserialize)]
+struct MessageJson {
+    content: Option<String>,
+    tts: Option<bool>,
+    embeds: Option<Vec<Embed>>,
+    /// Older tools only support a single embed
+    embed: Option<Embed>,
+    allowed_mentions: Option<AllowedMentions>,
+    components: Option<Vec<MessageComponent>>,
+    flags: Option<u64>,
+}
+
+/// Removes the unset fields, so that the JSON only has what the message actually uses
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|_, field| !field.is_null());
+            fields.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(remove_nulls),
+        _ => (),
+    }
 }
 
 impl Default for MessageBuilder {
@@ -0,0 +1,117 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::discord::snowflake::Snowflake;
+#[cfg(feature = "rest")]
+use crate::{discord::ids::params, util::error::Error, Context};
+
+#[cfg(feature = "rest")]
+use super::message::Message;
+
+/// The hosts that the discord clients use in message links
+const HOSTS: [&str; 4] = [
+    "discord.com",
+    "ptb.discord.com",
+    "canary.discord.com",
+    "discordapp.com",
+];
+
+/**
+ * A link to a message, like the ones the "Copy Message Link" button creates:
+ * `https://discord.com/channels/{guild_id}/{channel_id}/{message_id}`. Messages in DMs use `@me` instead of a guild id.
+ *
+ * ```rust,ignore
+ * if let Some(link) = MessageLink::parse(&msg.content) {
+ *     let quoted = link.fetch(ctx).await?;
+ * }
+ * ```
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MessageLink {
+    /// The guild the message is in, None for messages in DMs
+    pub guild_id: Option<Snowflake>,
+    /// The channel the message is in
+    pub channel_id: Snowflake,
+    /// The id of the message
+    pub message_id: Snowflake,
+}
+
+impl MessageLink {
+    /// Parses a message link. The scheme is optional, and the ptb/canary hosts are accepted too.
+    /// Returns None if the text isn't a message link.
+    pub fn parse(url: &str) -> Option<MessageLink> {
+        let url = url.trim().trim_start_matches('<').trim_end_matches('>');
+        let url = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .unwrap_or(url);
+
+        let mut segments = url.split('/');
+        let host = segments.next()?;
+        if !HOSTS.contains(&host.strip_prefix("www.").unwrap_or(host)) {
+            return None;
+        }
+        if segments.next()? != "channels" {
+            return None;
+        }
+        let guild_id = match segments.next()? {
+            "@me" => None,
+            id => Some(parse_id(id)?),
+        };
+        let channel_id = parse_id(segments.next()?)?;
+        // Links can end with a slash, or have a query string
+        let message_id = parse_id(segments.next()?.split(['?', '#']).next()?)?;
+        if segments.any(|segment| !segment.is_empty()) {
+            return None;
+        }
+
+        Some(MessageLink {
+            guild_id,
+            channel_id,
+            message_id,
+        })
+    }
+
+    /// Finds the first message link in some text, like the content of a message
+    pub fn find(text: &str) -> Option<MessageLink> {
+        text.split_whitespace().find_map(MessageLink::parse)
+    }
+
+    /// Gets the message that the link points to
+    #[cfg(feature = "rest")]
+    pub async fn fetch(&self, ctx: Context) -> Result<Message, Error> {
+        Message::get(
+            ctx,
+            params::channel(self.channel_id),
+            params::message(self.message_id),
+        ).await
+    }
+}
+
+fn parse_id(id: &str) -> Option<Snowflake> {
+    id.parse::<u64>().ok().map(Snowflake::from)
+}
+
+impl Display for MessageLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.guild_id {
+            Some(guild_id) => write!(
+                f,
+                "https://discord.com/channels/{}/{}/{}",
+                guild_id, self.channel_id, self.message_id
+            ),
+            None => write!(
+                f,
+                "https://discord.com/channels/@me/{}/{}",
+                self.channel_id, self.message_id
+            ),
+        }
+    }
+}
+
+impl FromStr for MessageLink {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MessageLink::parse(s).ok_or_else(|| format!("Not a message link: {}", s))
+    }
+}
@@ -1,10 +1,19 @@
 pub mod attachment;
 mod channel_object;
 pub mod embed;
+#[cfg(feature = "rest")]
+pub mod history;
 pub mod mention;
 pub mod message;
+pub mod message_link;
+#[cfg(feature = "rest")]
+pub mod modify;
 mod message_builder;
 pub mod reaction;
+#[cfg(feature = "rest")]
+pub mod search;
+#[cfg(feature = "rest")]
+pub mod thread;
 pub mod typing;
 
 pub use channel_object::*;
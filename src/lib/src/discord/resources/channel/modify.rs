@@ -0,0 +1,358 @@
+use std::marker::PhantomData;
+
+use hyper::{Body, Method, Request};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::http::rate_limit_client::{send_request, RequestRoute},
+    discord::{
+        ids::params::ChannelIdParam,
+        resources::guild::guild_object::CreateGuildChannel,
+        snowflake::Snowflake,
+    },
+    util::error::Error,
+    Context, BASE_URL,
+};
+
+use super::{
+    typing::{ChannelType, PermissionsOverwriteObject, VideoQualityMode},
+    Channel,
+};
+
+/**
+ * Modify Group DM Payload
+ * @docs <https://discord.com/developers/docs/resources/channel#modify-channel-json-params-group-dm>
+ */
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ModifyGroupDm {
+    /// channel name (1-100 characters)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// base64 encoded icon
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+/**
+ * Modify Guild Channel Payload
+ * Only the fields that are set are changed. Which fields apply depends on the type of the channel,
+ * e.g. `bitrate` and `user_limit` are for voice channels, and `topic` is for text and news channels.
+ * @docs <https://discord.com/developers/docs/resources/channel#modify-channel-json-params-guild-channel>
+ */
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ModifyGuildChannel {
+    /// channel name (1-100 characters)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// the type of channel; only conversion between text and news is supported, and only in guilds with the "NEWS" feature
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<ChannelType>,
+    /// the position of the channel in the left-hand listing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<u64>,
+    /// channel topic (0-1024 characters)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// whether the channel is nsfw
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nsfw: Option<bool>,
+    /// amount of seconds a user has to wait before sending another message (0-21600)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<u64>,
+    /// the bitrate (in bits) of the voice channel; 8000 to 96000 (128000 for VIP servers)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u64>,
+    /// the user limit of the voice channel; 0 refers to no limit, 1 to 99 refers to a user limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_limit: Option<u64>,
+    /// channel or category-specific permissions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_overwrites: Option<Vec<PermissionsOverwriteObject>>,
+    /// id of the new parent category for a channel. `Some(None)` takes the channel out of its category
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<Option<Snowflake>>,
+    /// channel voice region id. `Some(None)` lets discord pick the region automatically
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtc_region: Option<Option<String>>,
+    /// the camera video quality mode of the voice channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_quality_mode: Option<VideoQualityMode>,
+    /// the default duration that the clients use (not the API) for newly created threads in the channel, in minutes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_auto_archive_duration: Option<u64>,
+}
+
+/**
+ * Modify Thread Payload
+ * @docs <https://discord.com/developers/docs/resources/channel#modify-channel-json-params-thread>
+ */
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ModifyThread {
+    /// thread name (1-100 characters)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// whether the thread is archived
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+    /// duration in minutes to automatically archive the thread after recent activity, can be set to: 60, 1440, 4320, 10080
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_archive_duration: Option<u64>,
+    /// whether the thread is locked; when a thread is locked, only users with MANAGE_THREADS can unarchive it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked: Option<bool>,
+    /// whether non-moderators can add other non-moderators to a thread; only available on private threads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invitable: Option<bool>,
+    /// amount of seconds a user has to wait before sending another message (0-21600)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<u64>,
+}
+
+/// The fields to change with `Channel::modify`, which depend on the kind of channel. Each payload converts into this with `.into()`
+#[derive(Serialize, Clone)]
+#[serde(untagged)]
+pub enum ModifyChannel {
+    GroupDm(ModifyGroupDm),
+    Guild(ModifyGuildChannel),
+    Thread(ModifyThread),
+}
+
+impl From<ModifyGroupDm> for ModifyChannel {
+    fn from(payload: ModifyGroupDm) -> Self {
+        ModifyChannel::GroupDm(payload)
+    }
+}
+
+impl From<ModifyGuildChannel> for ModifyChannel {
+    fn from(payload: ModifyGuildChannel) -> Self {
+        ModifyChannel::Guild(payload)
+    }
+}
+
+impl From<ModifyThread> for ModifyChannel {
+    fn from(payload: ModifyThread) -> Self {
+        ModifyChannel::Thread(payload)
+    }
+}
+
+/// Marks a `ModifyChannelBuilder` for text and news channels
+pub enum TextSettings {}
+/// Marks a `ModifyChannelBuilder` for voice and stage channels
+pub enum VoiceSettings {}
+
+/**
+ * Builds a `ModifyGuildChannel` with only the setters that make sense for the kind of channel, so that e.g. setting
+ * a bitrate on a text channel doesn't compile. Start one with `ModifyChannel::text()` or `ModifyChannel::voice()`.
+ *
+ * ```rust,ignore
+ * Channel::modify(ctx.clone(), rules_id, ModifyChannel::text().topic("Read the rules").slowmode(30)).await?;
+ * Channel::modify(ctx, lounge_id, ModifyChannel::voice().bitrate(64000).user_limit(10)).await?;
+ * ```
+ */
+#[derive(Clone)]
+pub struct ModifyChannelBuilder<K> {
+    payload: ModifyGuildChannel,
+    kind: PhantomData<K>,
+}
+
+impl<K> ModifyChannelBuilder<K> {
+    fn new() -> Self {
+        Self {
+            payload: ModifyGuildChannel::default(),
+            kind: PhantomData,
+        }
+    }
+
+    /// Renames the channel (1-100 characters)
+    #[must_use]
+    pub fn name(mut self, name: &str) -> Self {
+        self.payload.name = Some(name.to_string());
+        self
+    }
+
+    /// Moves the channel in the left-hand listing
+    #[must_use]
+    pub fn position(mut self, position: u64) -> Self {
+        self.payload.position = Some(position);
+        self
+    }
+
+    /// Moves the channel into a category, or out of its category with None
+    #[must_use]
+    pub fn parent(mut self, parent_id: Option<Snowflake>) -> Self {
+        self.payload.parent_id = Some(parent_id);
+        self
+    }
+
+    /// Replaces the channel's permission overwrites
+    #[must_use]
+    pub fn permission_overwrites(mut self, overwrites: Vec<PermissionsOverwriteObject>) -> Self {
+        self.payload.permission_overwrites = Some(overwrites);
+        self
+    }
+
+    /// Marks the channel as age-restricted
+    #[must_use]
+    pub fn nsfw(mut self, nsfw: bool) -> Self {
+        self.payload.nsfw = Some(nsfw);
+        self
+    }
+
+    /// Sets how many seconds a user has to wait between messages (0-21600). Voice channels have this for their text chat
+    #[must_use]
+    pub fn slowmode(mut self, seconds: u64) -> Self {
+        self.payload.rate_limit_per_user = Some(seconds);
+        self
+    }
+
+    /// The payload, for changing fields that the builder doesn't have a setter for
+    pub fn build(self) -> ModifyGuildChannel {
+        self.payload
+    }
+}
+
+impl ModifyChannelBuilder<TextSettings> {
+    /// Sets the channel topic (0-1024 characters)
+    #[must_use]
+    pub fn topic(mut self, topic: &str) -> Self {
+        self.payload.topic = Some(topic.to_string());
+        self
+    }
+
+    /// Turns a text channel into a news channel, or back. The guild needs the "NEWS" feature
+    #[must_use]
+    pub fn news(mut self, news: bool) -> Self {
+        self.payload.type_ = Some(match news {
+            true => ChannelType::GuildNews,
+            false => ChannelType::GuildText,
+        });
+        self
+    }
+
+    /// Sets how long it takes for new threads in the channel to be archived, in minutes: 60, 1440, 4320, or 10080
+    #[must_use]
+    pub fn default_auto_archive_duration(mut self, minutes: u64) -> Self {
+        self.payload.default_auto_archive_duration = Some(minutes);
+        self
+    }
+}
+
+impl ModifyChannelBuilder<VoiceSettings> {
+    /// Sets the bitrate in bits; 8000 to 96000, and up to 384000 in boosted guilds
+    #[must_use]
+    pub fn bitrate(mut self, bitrate: u64) -> Self {
+        self.payload.bitrate = Some(bitrate);
+        self
+    }
+
+    /// Sets how many users can be connected at once; 0 for no limit, up to 99 (10000 for stage channels)
+    #[must_use]
+    pub fn user_limit(mut self, user_limit: u64) -> Self {
+        self.payload.user_limit = Some(user_limit);
+        self
+    }
+
+    /// Sets the voice region, or None to let discord pick it automatically
+    #[must_use]
+    pub fn rtc_region(mut self, region: Option<&str>) -> Self {
+        self.payload.rtc_region = Some(region.map(str::to_string));
+        self
+    }
+
+    /// Sets the camera video quality
+    #[must_use]
+    pub fn video_quality(mut self, mode: VideoQualityMode) -> Self {
+        self.payload.video_quality_mode = Some(mode);
+        self
+    }
+}
+
+impl<K> From<ModifyChannelBuilder<K>> for ModifyChannel {
+    fn from(builder: ModifyChannelBuilder<K>) -> Self {
+        ModifyChannel::Guild(builder.payload)
+    }
+}
+
+impl ModifyChannel {
+    /// Starts modifying a text or news channel
+    pub fn text() -> ModifyChannelBuilder<TextSettings> {
+        ModifyChannelBuilder::new()
+    }
+
+    /// Starts modifying a voice or stage channel
+    pub fn voice() -> ModifyChannelBuilder<VoiceSettings> {
+        ModifyChannelBuilder::new()
+    }
+}
+
+impl Channel {
+    /**
+     * Changes a channel's settings. Requires the MANAGE_CHANNELS permission for guild channels, and MANAGE_THREADS for threads
+     * (unless the bot owns the thread).
+     *
+     * ```rust,ignore
+     * Channel::modify(ctx, channel_id, ModifyGuildChannel {
+     *     topic: Some("Read the rules".to_string()),
+     *     ..Default::default()
+     * }).await?;
+     * ```
+     * @param channel_id The id of the channel
+     * @param payload The fields to change
+     * @return The updated channel
+     * @docs <https://discord.com/developers/docs/resources/channel#modify-channel>
+     */
+    pub async fn modify(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        payload: impl Into<ModifyChannel>,
+    ) -> Result<Channel, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::PATCH)
+            .uri(format!("{}/channels/{}", BASE_URL, channel_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload.into())?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+}
+
+impl CreateGuildChannel {
+    /// A channel of the given type, with nothing else set
+    fn of_type(name: &str, type_: ChannelType) -> Self {
+        Self {
+            name: name.to_string(),
+            type_: Some(type_),
+            ..Default::default()
+        }
+    }
+
+    /// A text channel
+    pub fn text(name: &str) -> Self {
+        Self::of_type(name, ChannelType::GuildText)
+    }
+
+    /// A voice channel
+    pub fn voice(name: &str) -> Self {
+        Self::of_type(name, ChannelType::GuildVoice)
+    }
+
+    /// A category that other channels can be put in with `parent_id`
+    pub fn category(name: &str) -> Self {
+        Self::of_type(name, ChannelType::GuildCategory)
+    }
+
+    /// A news (announcement) channel. The guild needs the "NEWS" feature
+    pub fn news(name: &str) -> Self {
+        Self::of_type(name, ChannelType::GuildNews)
+    }
+
+    /// A stage channel. The guild needs the "COMMUNITY" feature
+    pub fn stage(name: &str) -> Self {
+        Self::of_type(name, ChannelType::GuildStageVoice)
+    }
+}
@@ -1,6 +1,11 @@
+use std::fmt::Display;
+
 use serde::{Deserialize, Serialize};
 
-use crate::discord::resources::emoji::Emoji;
+use crate::discord::{
+    resources::emoji::{Emoji, UsableEmoji},
+    snowflake::Snowflake,
+};
 
 /**
  * Reaction Object
@@ -15,3 +20,88 @@ pub struct Reaction {
     /// emoji information
     pub emoji: Emoji,
 }
+
+/**
+ * An emoji in the reaction endpoints, which put it in the url.
+ * Strings convert into this, in any of the forms discord uses: a unicode emoji, `name:id`, or the `<:name:id>` form of message content.
+ * Displaying it gives the reaction format (`name:id` for custom emojis).
+ */
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ReactionType {
+    /// A unicode emoji, like 👍
+    Unicode(String),
+    /// A custom emoji. Only the id matters to discord, the name can be anything
+    Custom { name: String, id: Snowflake },
+}
+
+impl ReactionType {
+    /// Reads an emoji in any of the forms discord uses. Anything that isn't a custom emoji is taken as a unicode emoji
+    /// @param emoji A unicode emoji, `name:id`, `<:name:id>`, or `<a:name:id>`
+    pub fn parse(emoji: &str) -> Self {
+        // `<:name:id>` and `<a:name:id>` are how custom emojis are written in message content
+        let inner = match emoji.strip_prefix('<').and_then(|e| e.strip_suffix('>')) {
+            Some(inner) => inner
+                .strip_prefix("a:")
+                .or_else(|| inner.strip_prefix(':'))
+                .unwrap_or(inner),
+            None => emoji,
+        };
+        match inner.rsplit_once(':') {
+            Some((name, id)) => match id.parse::<u64>() {
+                Ok(id) => ReactionType::Custom {
+                    name: name.to_string(),
+                    id: id.into(),
+                },
+                Err(_) => ReactionType::Unicode(emoji.to_string()),
+            },
+            None => ReactionType::Unicode(emoji.to_string()),
+        }
+    }
+
+    /// The emoji as it goes in the url of the reaction endpoints
+    #[cfg(feature = "rest")]
+    pub(crate) fn url_encoded(&self) -> String {
+        percent_encoding::utf8_percent_encode(&self.to_string(), percent_encoding::NON_ALPHANUMERIC)
+            .to_string()
+    }
+}
+
+impl Display for ReactionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReactionType::Unicode(emoji) => write!(f, "{}", emoji),
+            ReactionType::Custom { name, id } => write!(f, "{}:{}", name, id),
+        }
+    }
+}
+
+impl From<&str> for ReactionType {
+    fn from(emoji: &str) -> Self {
+        ReactionType::parse(emoji)
+    }
+}
+
+impl From<&String> for ReactionType {
+    fn from(emoji: &String) -> Self {
+        ReactionType::parse(emoji)
+    }
+}
+
+impl From<&Emoji> for ReactionType {
+    fn from(emoji: &Emoji) -> Self {
+        let name = emoji.name.clone().unwrap_or_default();
+        match emoji.id {
+            Some(id) => ReactionType::Custom { name, id },
+            None => ReactionType::Unicode(name),
+        }
+    }
+}
+
+impl From<&UsableEmoji> for ReactionType {
+    fn from(emoji: &UsableEmoji) -> Self {
+        match emoji {
+            UsableEmoji::Custom(emoji) => emoji.into(),
+            UsableEmoji::Unicode(emoji) => ReactionType::Unicode(emoji.clone()),
+        }
+    }
+}
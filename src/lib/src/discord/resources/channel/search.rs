@@ -0,0 +1,176 @@
+use chrono::{DateTime, TimeZone};
+use futures_util::StreamExt;
+
+use crate::{
+    discord::{
+        ids::{
+            params::{ChannelIdParam, MessageIdParam, UserIdParam},
+            RawId,
+        },
+        snowflake::Snowflake,
+    },
+    util::error::Error,
+    Context,
+};
+
+use super::{message::Message, Channel};
+
+/**
+ * What `Channel::search_messages` looks for. Every condition that is set has to match.
+ *
+ * Bots can't use discord's search, so the channel's history is read page by page, newest first.
+ * The search stops as soon as it has `limit` matches, reaches a message older than `since`, or has looked at `max_scanned` messages.
+ *
+ * ```rust,ignore
+ * let filter = MessageFilter::new()
+ *     .author(user_id)
+ *     .content("discord.gg/")
+ *     .since(Utc::now() - chrono::Duration::days(1));
+ * let invites = Channel::search_messages(ctx, channel_id, filter).await?;
+ * ```
+ */
+#[derive(Clone)]
+pub struct MessageFilter {
+    /// Lowercased, since the content is matched without caring about case
+    content: Option<String>,
+    authors: Vec<Snowflake>,
+    has_attachments: Option<bool>,
+    /// Unix timestamp in milliseconds
+    since: Option<u64>,
+    before: Option<MessageIdParam>,
+    limit: usize,
+    max_scanned: u64,
+}
+
+impl Default for MessageFilter {
+    fn default() -> Self {
+        Self {
+            content: None,
+            authors: Vec::new(),
+            has_attachments: None,
+            since: None,
+            before: None,
+            limit: 25,
+            max_scanned: 1000,
+        }
+    }
+}
+
+impl MessageFilter {
+    /// Matches every message. Up to 25 matches are returned, out of the latest 1000 messages
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only matches messages that contain the text, ignoring case
+    #[must_use]
+    pub fn content(mut self, text: &str) -> Self {
+        self.content = Some(text.to_lowercase());
+        self
+    }
+
+    /// Only matches messages sent by this user. Can be called more than once to match any of several users
+    #[must_use]
+    pub fn author(mut self, user_id: UserIdParam) -> Self {
+        self.authors.push(user_id.raw());
+        self
+    }
+
+    /// Only matches messages that have (or don't have) files attached
+    #[must_use]
+    pub fn has_attachments(mut self, has_attachments: bool) -> Self {
+        self.has_attachments = Some(has_attachments);
+        self
+    }
+
+    /// Stops at the first message that was sent before this time
+    #[must_use]
+    pub fn since<T: TimeZone>(mut self, time: DateTime<T>) -> Self {
+        self.since = Some(time.timestamp_millis().max(0) as u64);
+        self
+    }
+
+    /// Starts searching before this message instead of from the latest one
+    #[must_use]
+    pub fn before(mut self, message_id: MessageIdParam) -> Self {
+        self.before = Some(message_id);
+        self
+    }
+
+    /// The most matches to return, 25 by default
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// The most messages to look at before giving up, 1000 by default. Every 100 messages is a request
+    #[must_use]
+    pub fn max_scanned(mut self, max_scanned: u64) -> Self {
+        self.max_scanned = max_scanned;
+        self
+    }
+
+    /// Returns true if the message meets every condition of the filter, not counting `since` and `before`
+    pub fn matches(&self, message: &Message) -> bool {
+        if let Some(content) = &self.content {
+            if !message.content.to_lowercase().contains(content.as_str()) {
+                return false;
+            }
+        }
+        if !self.authors.is_empty()
+            && !message
+                .author
+                .as_ref()
+                .is_some_and(|author| self.authors.contains(&author.id))
+        {
+            return false;
+        }
+        if let Some(has_attachments) = self.has_attachments {
+            if message.attachments.is_empty() == has_attachments {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Channel {
+    /**
+     * Finds the latest messages in a channel that match a filter, newest first. Requires the READ_MESSAGE_HISTORY permission.
+     * The history is read a page at a time until the filter says to stop, see `MessageFilter`.
+     *
+     * @param channel_id The id of the channel
+     * @param filter What to look for, and when to stop looking
+     */
+    pub async fn search_messages(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        filter: MessageFilter,
+    ) -> Result<Vec<Message>, Error> {
+        let mut matches = Vec::new();
+        if filter.limit == 0 || filter.max_scanned == 0 {
+            return Ok(matches);
+        }
+        let mut history = Channel::messages(ctx, channel_id).limit(filter.max_scanned);
+        if let Some(before) = filter.before {
+            history = history.before(before);
+        }
+        while let Some(message) = history.next().await {
+            let message = message?;
+            if filter
+                .since
+                .is_some_and(|since| message.id.created_at_ms() < since)
+            {
+                break;
+            }
+            if filter.matches(&message) {
+                matches.push(message);
+                if matches.len() == filter.limit {
+                    break;
+                }
+            }
+        }
+        Ok(matches)
+    }
+}
@@ -0,0 +1,434 @@
+use hyper::{Body, Method, Request};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::http::rate_limit_client::{send_request, send_request_noparse, RequestRoute},
+    util::{
+        error::Error,
+        pagination::{Direction, Page, PageQuery, Paginated},
+        requests::with_audit_log_reason,
+    },
+    Context, BASE_URL,
+};
+
+use super::{
+    modify::ModifyThread,
+    typing::{ChannelType, ThreadMember},
+    Channel,
+};
+#[cfg(feature = "rest")]
+use crate::discord::ids::params::{ChannelIdParam, MessageIdParam, UserIdParam};
+
+/// The most thread members that discord returns in a page of `Channel::get_thread_members`
+const MAX_THREAD_MEMBERS_PER_PAGE: u64 = 100;
+
+/**
+ * Archived Threads Response
+ * Threads are ordered by their archive_timestamp, in descending order.
+ * @docs <https://discord.com/developers/docs/resources/channel#list-public-archived-threads-response-body>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ArchivedThreads {
+    /// The archived threads
+    pub threads: Vec<Channel>,
+    /// A thread member object for each returned thread the current user has joined
+    pub members: Vec<ThreadMember>,
+    /// Whether there are potentially additional threads that could be returned on a subsequent call
+    pub has_more: bool,
+}
+
+/// Which set of archived threads to list
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArchivedThreadVisibility {
+    /// Public (and news) threads. Requires the READ_MESSAGE_HISTORY permission.
+    Public,
+    /// Private threads. Requires both the READ_MESSAGE_HISTORY and MANAGE_THREADS permissions.
+    Private,
+}
+
+/**
+ * Start Thread from Message Payload
+ * @docs <https://discord.com/developers/docs/resources/channel#start-thread-from-message-json-params>
+ */
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct StartThreadFromMessage {
+    /// 1-100 character channel name
+    pub name: String,
+    /// duration in minutes to automatically archive the thread after recent activity, can be set to: 60, 1440, 4320, 10080
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_archive_duration: Option<u64>,
+    /// amount of seconds a user has to wait before sending another message (0-21600)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<u64>,
+}
+
+impl StartThreadFromMessage {
+    /// A thread with the given name, and the channel's default archive duration
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/**
+ * Start Thread without Message Payload
+ * @docs <https://discord.com/developers/docs/resources/channel#start-thread-without-message-json-params>
+ */
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct StartThreadWithoutMessage {
+    /// 1-100 character channel name
+    pub name: String,
+    /// duration in minutes to automatically archive the thread after recent activity, can be set to: 60, 1440, 4320, 10080
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_archive_duration: Option<u64>,
+    /// the type of thread to create. Private threads are created by default
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<ChannelType>,
+    /// whether non-moderators can add other non-moderators to a thread; only available when creating a private thread
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invitable: Option<bool>,
+    /// amount of seconds a user has to wait before sending another message (0-21600)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<u64>,
+}
+
+impl StartThreadWithoutMessage {
+    /// A public thread, which anyone that can see the channel can join
+    pub fn public(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            type_: Some(ChannelType::GuildPublicThread),
+            ..Default::default()
+        }
+    }
+
+    /// A private thread, which members have to be added to (or mentioned in)
+    pub fn private(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            type_: Some(ChannelType::GuildPrivateThread),
+            ..Default::default()
+        }
+    }
+}
+
+impl ArchivedThreadVisibility {
+    fn as_path(&self) -> &'static str {
+        match self {
+            ArchivedThreadVisibility::Public => "public",
+            ArchivedThreadVisibility::Private => "private",
+        }
+    }
+}
+
+impl Channel {
+    /// Gets a single page of archived threads in a channel.
+    /// Discord only supports `before` for this endpoint, `after` is ignored.
+    /// @param channel_id The id of the channel to list the threads of
+    /// @param visibility Whether to list the public or the private archived threads
+    /// @param query The ISO8601 archive timestamp to start before, and the max number of threads to return
+    /// @docs <https://discord.com/developers/docs/resources/channel#list-public-archived-threads>
+    pub async fn list_archived_threads(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        visibility: ArchivedThreadVisibility,
+        query: PageQuery<String>,
+    ) -> Result<ArchivedThreads, Error> {
+        let route = RequestRoute {
+            base_route: format!(
+                "/channels/{{channel.id}}/threads/archived/{}",
+                visibility.as_path()
+            ),
+            major_param: channel_id.to_string(),
+        };
+
+        let query = PageQuery {
+            after: None,
+            ..query
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/channels/{}/threads/archived/{}?{}",
+                BASE_URL,
+                channel_id,
+                visibility.as_path(),
+                query.to_query_string()
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Walks through every archived thread in a channel, newest first.
+     * Pages are fetched as the stream is polled.
+     *
+     * @param channel_id The id of the channel to list the threads of
+     * @param visibility Whether to list the public or the private archived threads
+     */
+    pub fn archived_threads(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        visibility: ArchivedThreadVisibility,
+    ) -> Paginated<Channel> {
+        Paginated::new(
+            ctx,
+            Direction::Backward,
+            PageQuery::new(),
+            move |ctx, query| async move {
+                Channel::list_archived_threads(ctx, channel_id, visibility, query)
+                    .await
+                    .map(|page| Page {
+                        items: page.threads,
+                        has_more: page.has_more,
+                    })
+            },
+            // The next page starts from the oldest thread in this one
+            |thread| {
+                thread
+                    .thread_metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.archive_timestamp.clone())
+            },
+        )
+    }
+
+    /// Creates a thread from a message. The thread's id is the same as the message's id
+    /// @param channel_id The id of the channel the message is in
+    /// @param message_id The id of the message to start the thread from
+    /// @param thread The name and settings of the thread
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/channel#start-thread-from-message>
+    pub async fn start_thread_from_message(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        message_id: MessageIdParam,
+        thread: StartThreadFromMessage,
+        reason: Option<&str>,
+    ) -> Result<Channel, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/messages/{message.id}/threads".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::POST)
+            .uri(format!(
+                "{}/channels/{}/messages/{}/threads",
+                BASE_URL, channel_id, message_id
+            ))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&thread)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Creates a thread that isn't connected to a message
+    /// @param channel_id The id of the channel to create the thread in
+    /// @param thread The name, type and settings of the thread
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/channel#start-thread-without-message>
+    pub async fn start_thread_without_message(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        thread: StartThreadWithoutMessage,
+        reason: Option<&str>,
+    ) -> Result<Channel, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/threads".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::POST)
+            .uri(format!("{}/channels/{}/threads", BASE_URL, channel_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&thread)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Archives a thread. Requires the MANAGE_THREADS permission to lock it, or if the bot doesn't own the thread
+    /// @param thread_id The id of the thread
+    /// @param locked Whether only members with MANAGE_THREADS can unarchive the thread
+    /// @return The updated thread
+    pub async fn archive_thread(
+        ctx: Context,
+        thread_id: ChannelIdParam,
+        locked: bool,
+    ) -> Result<Channel, Error> {
+        Channel::modify(
+            ctx,
+            thread_id,
+            ModifyThread {
+                archived: Some(true),
+                locked: Some(locked),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Unarchives a thread. Sending a message in an unlocked thread also unarchives it
+    /// @param thread_id The id of the thread
+    /// @return The updated thread
+    pub async fn unarchive_thread(
+        ctx: Context,
+        thread_id: ChannelIdParam,
+    ) -> Result<Channel, Error> {
+        Channel::modify(
+            ctx,
+            thread_id,
+            ModifyThread {
+                archived: Some(false),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Adds the bot to a thread. The thread can't be archived
+    /// @param thread_id The id of the thread
+    /// @docs <https://discord.com/developers/docs/resources/channel#join-thread>
+    pub async fn join_thread(ctx: Context, thread_id: ChannelIdParam) -> Result<(), Error> {
+        thread_member_request(ctx, Method::PUT, thread_id, "@me").await
+    }
+
+    /// Removes the bot from a thread. The thread can't be archived
+    /// @param thread_id The id of the thread
+    /// @docs <https://discord.com/developers/docs/resources/channel#leave-thread>
+    pub async fn leave_thread(ctx: Context, thread_id: ChannelIdParam) -> Result<(), Error> {
+        thread_member_request(ctx, Method::DELETE, thread_id, "@me").await
+    }
+
+    /// Adds another member to a thread. Requires being able to send messages in the thread, and the thread can't be archived
+    /// @param thread_id The id of the thread
+    /// @param user_id The id of the user to add
+    /// @docs <https://discord.com/developers/docs/resources/channel#add-thread-member>
+    pub async fn add_thread_member(
+        ctx: Context,
+        thread_id: ChannelIdParam,
+        user_id: UserIdParam,
+    ) -> Result<(), Error> {
+        thread_member_request(ctx, Method::PUT, thread_id, &user_id.to_string()).await
+    }
+
+    /// Removes another member from a thread. Requires the MANAGE_THREADS permission, or being the creator of a private thread
+    /// @param thread_id The id of the thread
+    /// @param user_id The id of the user to remove
+    /// @docs <https://discord.com/developers/docs/resources/channel#remove-thread-member>
+    pub async fn remove_thread_member(
+        ctx: Context,
+        thread_id: ChannelIdParam,
+        user_id: UserIdParam,
+    ) -> Result<(), Error> {
+        thread_member_request(ctx, Method::DELETE, thread_id, &user_id.to_string()).await
+    }
+
+    /// Gets a member of a thread
+    /// @param thread_id The id of the thread
+    /// @param user_id The id of the user
+    /// @docs <https://discord.com/developers/docs/resources/channel#get-thread-member>
+    pub async fn get_thread_member(
+        ctx: Context,
+        thread_id: ChannelIdParam,
+        user_id: UserIdParam,
+    ) -> Result<ThreadMember, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/thread-members/{user.id}".to_string(),
+            major_param: thread_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/channels/{}/thread-members/{}",
+                BASE_URL, thread_id, user_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Gets a page of the members of a thread, sorted by their user ids. Requires the GUILD_MEMBERS intent
+    /// @param thread_id The id of the thread
+    /// @param query The user id to start after, and the max number of members to return (1-100). `before` is ignored
+    /// @docs <https://discord.com/developers/docs/resources/channel#list-thread-members>
+    pub async fn get_thread_members(
+        ctx: Context,
+        thread_id: ChannelIdParam,
+        query: PageQuery,
+    ) -> Result<Vec<ThreadMember>, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/thread-members".to_string(),
+            major_param: thread_id.to_string(),
+        };
+        let query = PageQuery {
+            before: None,
+            ..query
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/channels/{}/thread-members?{}",
+                BASE_URL,
+                thread_id,
+                query.to_query_string()
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Walks through every member of a thread.
+     * Pages are fetched as the stream is polled.
+     *
+     * @param thread_id The id of the thread
+     */
+    pub fn thread_members(ctx: Context, thread_id: ChannelIdParam) -> Paginated<ThreadMember> {
+        Paginated::new(
+            ctx,
+            Direction::Forward,
+            PageQuery::new().limit(MAX_THREAD_MEMBERS_PER_PAGE),
+            move |ctx, query| async move {
+                Channel::get_thread_members(ctx, thread_id, query)
+                    .await
+                    .map(|members| Page::from_list(members, MAX_THREAD_MEMBERS_PER_PAGE))
+            },
+            |member| Some(member.user_id),
+        )
+    }
+}
+
+/// Sends a request without a body to `/channels/{thread.id}/thread-members/{user}`
+async fn thread_member_request(
+    ctx: Context,
+    method: Method,
+    thread_id: ChannelIdParam,
+    user: &str,
+) -> Result<(), Error> {
+    let route = RequestRoute {
+        base_route: if user == "@me" {
+            "/channels/{channel.id}/thread-members/@me".to_string()
+        } else {
+            "/channels/{channel.id}/thread-members/{user.id}".to_string()
+        },
+        major_param: thread_id.to_string(),
+    };
+    let request_builder = Request::builder()
+        .method(method)
+        .uri(format!(
+            "{}/channels/{}/thread-members/{}",
+            BASE_URL, thread_id, user
+        ))
+        .body(Body::empty())
+        .unwrap();
+
+    send_request_noparse(ctx, route, request_builder).await
+}
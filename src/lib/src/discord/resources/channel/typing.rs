@@ -1,4 +1,4 @@
-use crate::discord::snowflake::Snowflake;
+use crate::discord::{permissions::Permissions, snowflake::Snowflake};
 use bitflags::bitflags;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
@@ -122,6 +122,9 @@ bitflags! {
         const HAS_THREAD = 1 << 5;
         const EPHEMERAL = 1 << 6;
         const LOADING = 1 << 7;
+        const FAILED_TO_MENTION_SOME_ROLES_IN_THREAD = 1 << 8;
+        const SUPPRESS_NOTIFICATIONS = 1 << 12;
+        const IS_VOICE_MESSAGE = 1 << 13;
     }
 }
 
@@ -146,15 +149,41 @@ impl<'de> Deserialize<'de> for MessageFlags {
 pub struct PermissionsOverwriteObject {
     /// The id of the role or user
     pub id: Snowflake,
-    /// The type of the role or user
+    /// The type of the role or user (0 for a role, 1 for a member)
     #[serde(rename = "type")]
     pub type_: u8,
     /// The permissions that the role or user has
+    #[serde(with = "crate::util::stringified")]
     pub allow: u64,
     /// The permissions that the role or user does not have
+    #[serde(with = "crate::util::stringified")]
     pub deny: u64,
 }
 
+impl PermissionsOverwriteObject {
+    /// Creates an overwrite for a role
+    /// @param id The id of the role (the guild id for @everyone)
+    pub fn role(id: Snowflake, allow: Permissions, deny: Permissions) -> Self {
+        Self {
+            id,
+            type_: 0,
+            allow: allow.bits(),
+            deny: deny.bits(),
+        }
+    }
+
+    /// Creates an overwrite for a guild member
+    /// @param id The id of the user
+    pub fn member(id: Snowflake, allow: Permissions, deny: Permissions) -> Self {
+        Self {
+            id,
+            type_: 1,
+            allow: allow.bits(),
+            deny: deny.bits(),
+        }
+    }
+}
+
 /**
 * Thread Metadata Object
 * The thread metadata object contains a number of thread-specific channel fields that are not needed by other channel types.
@@ -1,26 +1,299 @@
+#[cfg(feature = "rest")]
+use std::collections::HashSet;
+
+#[cfg(feature = "rest")]
+use hyper::{Body, Client, Method, Request};
+#[cfg(feature = "rest")]
+use hyper_tls::HttpsConnector;
 use serde::{Deserialize, Serialize};
 
-use crate::discord::snowflake::Snowflake;
+use std::fmt::Display;
+
+use crate::discord::{resources::guild::guild_member::GuildMember, snowflake::Snowflake};
+#[cfg(feature = "rest")]
+use crate::{
+    core::http::rate_limit_client::{send_request, RequestRoute},
+    discord::{
+        ids::params::{self, GuildIdParam},
+        image_formats::ImageData,
+        resources::guild::guild_object::Guild,
+    },
+    util::{
+        error::{Error, ErrorTypes},
+        requests::with_audit_log_reason,
+    },
+    Context, BASE_URL,
+};
 /**
  * Emoji Object
+ * Reactions and other places that use partial emojis only include the id, name, and animated fields.
  * @docs <https://discord.com/developers/docs/resources/emoji#emoji-object>
  */
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Emoji {
-    /// Emoji id
-    pub id: Snowflake,
+    /// Emoji id, null for unicode emojis
+    pub id: Option<Snowflake>,
     /// Emoji name
     pub name: Option<String>,
     /// Roles allowed to use this emoji
+    #[serde(default)]
     pub roles: Vec<Snowflake>,
     /// User that created this emoji
     pub user: Option<Snowflake>,
     /// Whether this emoji must be wrapped in colons
+    #[serde(default)]
     pub require_colons: bool,
     /// Whether this emoji is managed
+    #[serde(default)]
     pub managed: bool,
     /// Whether this emoji is animated
+    #[serde(default)]
     pub animated: bool,
     /// Whether this emoji can be used, may be false due to loss of Server Boosts
+    #[serde(default)]
     pub available: bool,
 }
+
+impl Emoji {
+    /// Whether this is the given emoji
+    /// @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+    pub fn matches(&self, emoji: &str) -> bool {
+        match emoji.rsplit_once(':') {
+            Some((_, id)) => self.id.map(|own_id| own_id.to_string()).as_deref() == Some(id),
+            None => self.name.as_deref() == Some(emoji),
+        }
+    }
+
+    /// Whether a member (usually the bot) can use this guild emoji. The emoji has to be available (guilds lose
+    /// emojis when they lose boosts), and if it is limited to some roles, the member needs one of them.
+    /// Bots can use animated emojis, and emojis from every guild they are in.
+    /// @param member The member, in the guild the emoji belongs to
+    pub fn usable_by(&self, member: &GuildMember) -> bool {
+        self.available
+            && (self.roles.is_empty() || self.roles.iter().any(|role| member.roles.contains(role)))
+    }
+
+    /// The emoji in the form used for reactions: the unicode emoji, or `name:id` for custom emojis
+    pub fn reaction_format(&self) -> String {
+        let name = self.name.clone().unwrap_or_default();
+        match self.id {
+            Some(id) => format!("{}:{}", name, id),
+            None => name,
+        }
+    }
+
+    /// The url of a custom emoji's image on the CDN, a GIF for animated emojis and a PNG otherwise. None for unicode emojis
+    pub fn url(&self) -> Option<String> {
+        let extension = if self.animated { "gif" } else { "png" };
+        self.id
+            .map(|id| format!("https://cdn.discordapp.com/emojis/{}.{}", id, extension))
+    }
+}
+
+/**
+ * Create Guild Emoji Payload
+ * @docs <https://discord.com/developers/docs/resources/emoji#create-guild-emoji-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Clone)]
+pub struct CreateEmoji {
+    /// name of the emoji
+    pub name: String,
+    /// the 128x128 emoji image, at most 256 KiB
+    pub image: ImageData,
+    /// roles allowed to use this emoji, everyone when empty
+    pub roles: Vec<Snowflake>,
+}
+
+/// What `Guild::copy_emojis_from` did with each emoji
+#[cfg(feature = "rest")]
+#[derive(Default)]
+pub struct EmojiCopyReport {
+    /// The emojis that were created in the guild
+    pub copied: Vec<Emoji>,
+    /// The emojis of the source guild that weren't copied, because the guild already has an emoji with the same name
+    pub skipped: Vec<Emoji>,
+    /// The emojis that couldn't be copied (e.g. the guild ran out of emoji slots), and why
+    pub failed: Vec<(Emoji, Error)>,
+}
+
+#[cfg(feature = "rest")]
+impl Guild {
+    /// Gets the emojis of a guild
+    /// @param guild_id The id of the guild
+    /// @docs <https://discord.com/developers/docs/resources/emoji#list-guild-emojis>
+    pub async fn list_emojis(ctx: Context, guild_id: GuildIdParam) -> Result<Vec<Emoji>, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/emojis".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!("{}/guilds/{}/emojis", BASE_URL, guild_id))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Creates an emoji in a guild. Requires the MANAGE_EMOJIS_AND_STICKERS permission
+    /// @param guild_id The id of the guild
+    /// @param emoji The emoji to create
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/emoji#create-guild-emoji>
+    pub async fn create_emoji(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        emoji: CreateEmoji,
+        reason: Option<&str>,
+    ) -> Result<Emoji, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/emojis".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::POST)
+            .uri(format!("{}/guilds/{}/emojis", BASE_URL, guild_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&emoji)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Copies the custom emojis of another guild into this one, e.g. when moving a community to a new server.
+     * Each image is downloaded from the CDN and uploaded one at a time through the rate limiter, since discord only allows
+     * a few emoji uploads per guild every minute, so copying many emojis takes a while. The emojis keep their names,
+     * but aren't limited to any roles, since the roles of the other guild don't exist here.
+     * Emojis with the name of one this guild already has are skipped, so the copy can be run again after it fails partway.
+     *
+     * ```rust,ignore
+     * let report = new_guild.copy_emojis_from(ctx, old_guild_id, |emoji| emoji.available, |done, total, emoji| {
+     *     println!("{}/{} {}", done, total, emoji.name.as_deref().unwrap_or_default());
+     * }).await?;
+     * println!("Copied {}, failed {}", report.copied.len(), report.failed.len());
+     * ```
+     * @param source_guild The guild to copy the emojis from
+     * @param filter Which emojis of the source guild to copy
+     * @param progress Called after each emoji is handled, with how many are done, how many there are in total, and the emoji
+     * @return What happened to each emoji. Failing to copy one emoji doesn't stop the others
+     */
+    pub async fn copy_emojis_from(
+        &self,
+        ctx: Context,
+        source_guild: GuildIdParam,
+        filter: impl Fn(&Emoji) -> bool,
+        mut progress: impl FnMut(usize, usize, &Emoji),
+    ) -> Result<EmojiCopyReport, Error> {
+        let existing: HashSet<String> = Guild::list_emojis(ctx.clone(), params::guild(self.id))
+            .await?
+            .into_iter()
+            .filter_map(|emoji| emoji.name)
+            .collect();
+        let to_copy: Vec<Emoji> = Guild::list_emojis(ctx.clone(), source_guild)
+            .await?
+            .into_iter()
+            // Managed emojis belong to an integration (like Twitch), and only it can upload them
+            .filter(|emoji| emoji.id.is_some() && !emoji.managed && filter(emoji))
+            .collect();
+
+        let client = Client::builder().build::<_, Body>(HttpsConnector::new());
+        let total = to_copy.len();
+        let mut report = EmojiCopyReport::default();
+        for (i, emoji) in to_copy.into_iter().enumerate() {
+            let name = emoji.name.clone().unwrap_or_default();
+            if existing.contains(&name) {
+                progress(i + 1, total, &emoji);
+                report.skipped.push(emoji);
+                continue;
+            }
+            let copied = match download_emoji(&client, &emoji).await {
+                Ok(image) => {
+                    let payload = CreateEmoji {
+                        name,
+                        image,
+                        roles: Vec::new(),
+                    };
+                    Guild::create_emoji(ctx.clone(), params::guild(self.id), payload, None).await
+                }
+                Err(e) => Err(e),
+            };
+            progress(i + 1, total, &emoji);
+            match copied {
+                Ok(created) => report.copied.push(created),
+                Err(e) => report.failed.push((emoji, e)),
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Downloads the image of a custom emoji from the CDN
+#[cfg(feature = "rest")]
+async fn download_emoji(
+    client: &Client<HttpsConnector<hyper::client::HttpConnector>>,
+    emoji: &Emoji,
+) -> Result<ImageData, Error> {
+    let url = emoji.url().ok_or_else(|| {
+        Error::new(
+            "Unicode emojis don't have an image".to_string(),
+            ErrorTypes::PARSE,
+        )
+    })?;
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(&url)
+        .body(Body::empty())?;
+    let response = client.request(request).await?;
+    if !response.status().is_success() {
+        return Err(Error::new(
+            format!("Unable to download {}: {}", url, response.status()),
+            ErrorTypes::REQUEST,
+        ));
+    }
+    let bytes = hyper::body::to_bytes(response.into_body()).await?;
+    ImageData::new(bytes.to_vec())
+}
+
+/**
+ * An emoji that was checked to be usable, see `Guild::usable_emoji`.
+ * Displaying it gives the form used in message content (`<:name:id>`, or `<a:name:id>` for animated emojis).
+ */
+#[derive(Clone)]
+pub enum UsableEmoji {
+    /// A custom emoji the bot can use
+    Custom(Emoji),
+    /// A unicode emoji (either the requested one, or the fallback)
+    Unicode(String),
+}
+
+impl UsableEmoji {
+    /// The emoji in the form used for reactions, for `Message::add_reaction`
+    pub fn reaction_format(&self) -> String {
+        match self {
+            UsableEmoji::Custom(emoji) => emoji.reaction_format(),
+            UsableEmoji::Unicode(emoji) => emoji.clone(),
+        }
+    }
+
+    /// Whether the requested emoji couldn't be used, and this is the fallback
+    pub fn is_fallback(&self, requested: &str) -> bool {
+        self.reaction_format() != requested
+    }
+}
+
+impl Display for UsableEmoji {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UsableEmoji::Custom(emoji) => write!(
+                f,
+                "<{}:{}:{}>",
+                if emoji.animated { "a" } else { "" },
+                emoji.name.as_deref().unwrap_or_default(),
+                emoji.id.map(|id| id.to_string()).unwrap_or_default()
+            ),
+            UsableEmoji::Unicode(emoji) => write!(f, "{}", emoji),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use crate::discord::snowflake::Snowflake;
+
+/**
+ * Entitlements represent that a user or guild has access to a premium offering in your application.
+ * @docs <https://discord.com/developers/docs/monetization/entitlements#entitlement-object-entitlement-structure>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Entitlement {
+    /// The id of the entitlement
+    pub id: Snowflake,
+    /// The id of the SKU
+    pub sku_id: Snowflake,
+    /// The id of the parent application
+    pub application_id: Snowflake,
+    /// The id of the user that is granted access to the entitlement's sku
+    pub user_id: Option<Snowflake>,
+    /// The type of entitlement
+    #[serde(rename = "type")]
+    pub type_: EntitlementType,
+    /// Whether the entitlement was deleted
+    pub deleted: bool,
+    /// Start date at which the entitlement is valid
+    pub starts_at: Option<String>,
+    /// Date at which the entitlement is no longer valid
+    pub ends_at: Option<String>,
+    /// The id of the guild that is granted access to the entitlement's sku
+    pub guild_id: Option<Snowflake>,
+    /// For consumable items, whether or not the entitlement has been consumed
+    pub consumed: Option<bool>,
+}
+
+/**
+ * Entitlement Types
+ * @docs <https://discord.com/developers/docs/monetization/entitlements#entitlement-object-entitlement-types>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum EntitlementType {
+    /// Entitlement was purchased by user
+    Purchase = 1,
+    /// Entitlement for Discord Nitro subscription
+    PremiumSubscription = 2,
+    /// Entitlement was gifted by developer
+    DeveloperGift = 3,
+    /// Entitlement was purchased by a dev in application test mode
+    TestModePurchase = 4,
+    /// Entitlement was granted when the SKU was free
+    FreePurchase = 5,
+    /// Entitlement was gifted by another user
+    UserGift = 6,
+    /// Entitlement was claimed by user for free as a Nitro Subscriber
+    PremiumPurchase = 7,
+    /// Entitlement was purchased as an app subscription
+    ApplicationSubscription = 8,
+}
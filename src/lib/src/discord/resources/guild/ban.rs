@@ -0,0 +1,244 @@
+#[cfg(feature = "rest")]
+use hyper::{Body, Method, Request};
+use serde::{Deserialize, Serialize};
+
+use crate::discord::resources::user::User;
+#[cfg(feature = "rest")]
+use crate::{
+    core::http::rate_limit_client::{send_request, send_request_noparse, RequestRoute},
+    discord::snowflake::Snowflake,
+    util::{
+        error::Error,
+        pagination::{Direction, Page, PageQuery, Paginated},
+        requests::with_audit_log_reason,
+    },
+    Context, BASE_URL,
+};
+
+#[cfg(feature = "rest")]
+use super::guild_object::Guild;
+#[cfg(feature = "rest")]
+use crate::discord::ids::params::{GuildIdParam, UserIdParam};
+
+/**
+ * Ban Object
+ * @docs <https://discord.com/developers/docs/resources/guild#ban-object>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Ban {
+    /// the reason for the ban
+    pub reason: Option<String>,
+    /// the banned user
+    pub user: User,
+}
+
+/**
+ * Bulk Guild Ban Payload
+ * @docs <https://discord.com/developers/docs/resources/guild#bulk-guild-ban-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BulkBan {
+    /// list of user ids to ban (max 200)
+    pub user_ids: Vec<Snowflake>,
+    /// number of seconds to delete messages for, between 0 and 604800 (7 days)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete_message_seconds: Option<u64>,
+}
+
+/**
+ * Bulk Guild Ban Response
+ * If none of the users could be banned, discord returns an error instead.
+ * @docs <https://discord.com/developers/docs/resources/guild#bulk-guild-ban-bulk-ban-response>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BulkBanResponse {
+    /// list of user ids, that were successfully banned
+    pub banned_users: Vec<Snowflake>,
+    /// list of user ids, that were not banned
+    pub failed_users: Vec<Snowflake>,
+}
+
+/**
+ * Create Guild Ban Payload
+ * @docs <https://discord.com/developers/docs/resources/guild#create-guild-ban-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone)]
+struct CreateBan {
+    /// number of seconds to delete messages for, between 0 and 604800 (7 days)
+    delete_message_seconds: u64,
+}
+
+/// The most message history that can be deleted when banning someone: 7 days
+#[cfg(feature = "rest")]
+const MAX_DELETE_MESSAGE_DAYS: u64 = 7;
+
+/// The most bans that discord returns in one page
+#[cfg(feature = "rest")]
+const MAX_BANS_PER_PAGE: u64 = 1000;
+
+#[cfg(feature = "rest")]
+impl Guild {
+    /// Gets a single page of the bans in a guild. Requires the BAN_MEMBERS permission.
+    /// Bans are sorted by user id.
+    /// @param guild_id The id of the guild
+    /// @param query The user id to start before or after, and the max number of bans to return (1-1000, default 1000)
+    /// @docs <https://discord.com/developers/docs/resources/guild#get-guild-bans>
+    pub async fn get_bans(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        query: PageQuery,
+    ) -> Result<Vec<Ban>, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/bans".to_string(),
+            major_param: guild_id.to_string(),
+        };
+
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/guilds/{}/bans?{}",
+                BASE_URL,
+                guild_id,
+                query.to_query_string()
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Bans up to 200 users from a guild at once. Requires both the BAN_MEMBERS and MANAGE_GUILD permissions.
+    /// Users that couldn't be banned (e.g. because they were already banned) are returned in `failed_users`.
+    /// @param guild_id The id of the guild
+    /// @param payload The users to ban
+    /// @param reason The reason to show in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/guild#bulk-guild-ban>
+    pub async fn bulk_ban(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        payload: BulkBan,
+        reason: Option<&str>,
+    ) -> Result<BulkBanResponse, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/bulk-ban".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::POST)
+            .uri(format!("{}/guilds/{}/bulk-ban", BASE_URL, guild_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Walks through every ban in a guild, in order of user id. Requires the BAN_MEMBERS permission.
+     * Pages of up to 1000 bans are fetched as the stream is polled.
+     *
+     * @param guild_id The id of the guild
+     */
+    pub fn bans(ctx: Context, guild_id: GuildIdParam) -> Paginated<Ban> {
+        Paginated::new(
+            ctx,
+            Direction::Forward,
+            // Bans are only sorted by user id when a cursor is passed, so start from the lowest possible id
+            PageQuery::new()
+                .after(Snowflake::from(0))
+                .limit(MAX_BANS_PER_PAGE),
+            move |ctx, query| async move {
+                Guild::get_bans(ctx, guild_id, query)
+                    .await
+                    .map(|bans| Page::from_list(bans, MAX_BANS_PER_PAGE))
+            },
+            |ban| Some(ban.user.id),
+        )
+    }
+
+    /// Removes a member from a guild. They can join again with a new invite. Requires the KICK_MEMBERS permission.
+    /// @param guild_id The id of the guild
+    /// @param user_id The id of the member to kick
+    /// @param reason The reason to show in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/guild#remove-guild-member>
+    pub async fn kick(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/members/{user.id}".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::DELETE)
+            .uri(format!(
+                "{}/guilds/{}/members/{}",
+                BASE_URL, guild_id, user_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+
+    /// Bans a user from a guild, removing them if they are a member. Users don't have to be in the guild to be banned.
+    /// Requires the BAN_MEMBERS permission.
+    /// @param guild_id The id of the guild
+    /// @param user_id The id of the user to ban
+    /// @param delete_message_days How many days of the user's messages to delete, between 0 and 7
+    /// @param reason The reason to show in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/guild#create-guild-ban>
+    pub async fn ban(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+        delete_message_days: u64,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/bans/{user.id}".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        // Discord replaced delete_message_days with seconds
+        let payload = CreateBan {
+            delete_message_seconds: delete_message_days.min(MAX_DELETE_MESSAGE_DAYS) * 24 * 60 * 60,
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::PUT)
+            .uri(format!("{}/guilds/{}/bans/{}", BASE_URL, guild_id, user_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+
+    /// Lifts a user's ban from a guild. Requires the BAN_MEMBERS permission.
+    /// @param guild_id The id of the guild
+    /// @param user_id The id of the banned user
+    /// @param reason The reason to show in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/guild#remove-guild-ban>
+    pub async fn unban(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/bans/{user.id}".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::DELETE)
+            .uri(format!("{}/guilds/{}/bans/{}", BASE_URL, guild_id, user_id))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+}
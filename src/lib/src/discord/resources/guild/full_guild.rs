@@ -0,0 +1,192 @@
+use bitflags::bitflags;
+use futures_util::future::try_join4;
+use hyper::{Body, Method, Request};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    core::http::rate_limit_client::{send_request, RequestRoute},
+    discord::{
+        ids::{params::GuildIdParam, RawId},
+        resources::{
+            channel::{typing::ThreadMember, Channel},
+            emoji::Emoji,
+        },
+        snowflake::Snowflake,
+    },
+    util::error::Error,
+    Context, BASE_URL,
+};
+
+use super::{guild_object::Guild, role::Role};
+
+bitflags! {
+    /// The sub-resources of a guild that `Guild::hydrate` can fetch
+    pub struct GuildParts: u8 {
+        const CHANNELS = 1 << 0;
+        const ROLES = 1 << 1;
+        const EMOJIS = 1 << 2;
+        /// The active threads of the guild
+        const THREADS = 1 << 3;
+    }
+}
+
+/**
+ * A guild with up to date sub-resources, made by `Guild::hydrate`.
+ * Each part is `None` when it wasn't asked for, so an empty list always means that the guild has none.
+ */
+#[derive(Clone)]
+pub struct FullGuild {
+    /// The guild that was hydrated, as it was passed in
+    pub guild: Guild,
+    /// The channels of the guild
+    pub channels: Option<Vec<Channel>>,
+    /// The roles of the guild
+    pub roles: Option<Vec<Role>>,
+    /// The custom emojis of the guild
+    pub emojis: Option<Vec<Emoji>>,
+    /// The active threads of the guild
+    pub threads: Option<Vec<Channel>>,
+}
+
+impl FullGuild {
+    /// The fetched channels, or the ones that came with the guild if they weren't fetched
+    pub fn channels(&self) -> &[Channel] {
+        self.channels
+            .as_deref()
+            .or(self.guild.channels.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The fetched roles, or the ones that came with the guild if they weren't fetched
+    pub fn roles(&self) -> &[Role] {
+        self.roles.as_deref().unwrap_or(&self.guild.roles)
+    }
+
+    /// The fetched emojis, or the ones that came with the guild if they weren't fetched
+    pub fn emojis(&self) -> &[Emoji] {
+        self.emojis.as_deref().unwrap_or(&self.guild.emojis)
+    }
+
+    /// The fetched threads, or the ones that came with the guild if they weren't fetched
+    pub fn threads(&self) -> &[Channel] {
+        self.threads
+            .as_deref()
+            .or(self.guild.threads.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The guild with the fetched parts written into it
+    pub fn into_guild(self) -> Guild {
+        let mut guild = self.guild;
+        if let Some(channels) = self.channels {
+            guild.channels = Some(channels);
+        }
+        if let Some(roles) = self.roles {
+            guild.roles = roles;
+        }
+        if let Some(emojis) = self.emojis {
+            guild.emojis = emojis;
+        }
+        if let Some(threads) = self.threads {
+            guild.threads = Some(threads);
+        }
+        guild
+    }
+}
+
+/**
+ * Active Guild Threads Response
+ * @docs <https://discord.com/developers/docs/resources/guild#list-active-guild-threads-response-body>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ActiveThreads {
+    /// The active threads
+    pub threads: Vec<Channel>,
+    /// A thread member object for each returned thread the current user has joined
+    pub members: Vec<ThreadMember>,
+}
+
+impl Guild {
+    /**
+     * Fetches only the parts of the guild that are needed, instead of everything that comes with it.
+     * The parts are fetched at the same time, and the first error is returned if any of them fail.
+     *
+     * ```rust,ignore
+     * let full = guild.hydrate(ctx, GuildParts::CHANNELS | GuildParts::ROLES).await?;
+     * for channel in full.channels() { /* ... */ }
+     * ```
+     * @param parts The sub-resources to fetch
+     */
+    pub async fn hydrate(self, ctx: Context, parts: GuildParts) -> Result<FullGuild, Error> {
+        let guild_id = self.id;
+        let (channels, roles, emojis, threads) = try_join4(
+            fetch_part(
+                ctx.clone(),
+                guild_id,
+                parts,
+                GuildParts::CHANNELS,
+                "channels",
+            ),
+            fetch_part(ctx.clone(), guild_id, parts, GuildParts::ROLES, "roles"),
+            fetch_part(ctx.clone(), guild_id, parts, GuildParts::EMOJIS, "emojis"),
+            fetch_part::<ActiveThreads>(
+                ctx,
+                guild_id,
+                parts,
+                GuildParts::THREADS,
+                "threads/active",
+            ),
+        )
+        .await?;
+
+        Ok(FullGuild {
+            guild: self,
+            channels,
+            roles,
+            emojis,
+            threads: threads.map(|active| active.threads),
+        })
+    }
+
+    /// Gets the active threads of a guild, which includes public and private threads
+    /// @param guild_id The id of the guild
+    /// @docs <https://discord.com/developers/docs/resources/guild#list-active-guild-threads>
+    pub async fn list_active_threads(
+        ctx: Context,
+        guild_id: GuildIdParam,
+    ) -> Result<ActiveThreads, Error> {
+        get_part(ctx, guild_id.raw(), "threads/active").await
+    }
+}
+
+/// Gets `/guilds/{guild.id}/{path}` if `part` is one of the requested parts
+async fn fetch_part<T: DeserializeOwned>(
+    ctx: Context,
+    guild_id: Snowflake,
+    parts: GuildParts,
+    part: GuildParts,
+    path: &str,
+) -> Result<Option<T>, Error> {
+    if !parts.contains(part) {
+        return Ok(None);
+    }
+    get_part(ctx, guild_id, path).await.map(Some)
+}
+
+async fn get_part<T: DeserializeOwned>(
+    ctx: Context,
+    guild_id: Snowflake,
+    path: &str,
+) -> Result<T, Error> {
+    let route = RequestRoute {
+        base_route: format!("/guilds/{{guild.id}}/{}", path),
+        major_param: guild_id.to_string(),
+    };
+    let request_builder = Request::builder()
+        .method(Method::GET)
+        .uri(format!("{}/guilds/{}/{}", BASE_URL, guild_id, path))
+        .body(Body::empty())
+        .unwrap();
+
+    send_request(ctx, route, request_builder).await
+}
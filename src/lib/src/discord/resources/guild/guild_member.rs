@@ -1,6 +1,43 @@
+#[cfg(feature = "rest")]
+use std::{collections::HashSet, time::Duration};
+
+#[cfg(feature = "rest")]
+use futures_util::{future, stream, StreamExt};
+#[cfg(feature = "rest")]
+use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
 
-use crate::discord::{resources::user::User, snowflake::Snowflake};
+use chrono::{DateTime, Utc};
+
+use crate::discord::{
+    image_formats,
+    permissions::{compute_permissions, Permissions},
+    resources::{channel::Channel, user::User},
+    snowflake::Snowflake,
+};
+#[cfg(feature = "rest")]
+use crate::{
+    core::{
+        abstraction::cache::{
+            cache_or_http, cached_member, cached_members, CacheOrHttp,
+        },
+        http::rate_limit_client::{send_request, send_request_idempotent, RequestRoute},
+    },
+    util::{
+        error::{Error, ErrorTypes},
+        pagination::{Direction, Page, PageQuery, Paginated},
+        requests::with_audit_log_reason,
+    },
+    Context, BASE_URL,
+};
+
+use super::guild_object::Guild;
+use crate::discord::ids::params::GuildIdParam;
+#[cfg(feature = "rest")]
+use crate::discord::ids::{
+    params::{self, RoleIdParam, UserIdParam},
+    RawId,
+};
 
 /**
  * guild member object
@@ -14,18 +51,493 @@ pub struct GuildMember {
     pub nick: Option<String>,
     /// the member's guild avatar hash
     pub avatar: Option<String>,
+    /// the member's guild banner hash
+    pub banner: Option<String>,
     /// array of role object ids
     pub roles: Vec<Snowflake>,
     /// when the user joined the guild
     pub joined_at: String,
     /// when the user started boosting the guild
     pub premium_since: Option<String>,
-    /// whether the user is deafened in voice channels
+    /// whether the user is deafened in voice channels. Not sent for the members in interaction data, where it defaults to false
+    #[serde(default)]
     pub deaf: bool,
-    /// whether the user is muted in voice channels
+    /// whether the user is muted in voice channels. Not sent for the members in interaction data, where it defaults to false
+    #[serde(default)]
     pub mute: bool,
     /// whether the user has not yet passed the guild's Membership Screening requirements
     pub pending: Option<bool>,
     /// total permissions of the member in the channel, including overwrites, returned when in the interaction object
     pub permissions: Option<String>,
+    /// when the user's timeout will expire and the user will be able to communicate in the guild again, null or a time in the past if the user is not timed out
+    pub communication_disabled_until: Option<String>,
+}
+
+impl GuildMember {
+    /// The name that is shown for the member in the guild: their nickname if they have one, otherwise their username.
+    /// Returns None if the member object doesn't include the user (like in MESSAGE_CREATE events) and there is no nickname.
+    pub fn display_name(&self) -> Option<&str> {
+        self.nick
+            .as_deref()
+            .or_else(|| self.user.as_ref().map(|u| u.username.as_str()))
+    }
+
+    /**
+     * Gets the url of the member's guild avatar, which is shown instead of their user avatar in the guild
+     *
+     * @param guild_id The id of the guild the member is in
+     *
+     * @param fmt Image format of the avatar
+     *
+     * @param size Size of the avatar. If none specified, the largest size will be used.
+     * The size must be a power of 2 between `16` and `4096`
+     */
+    pub fn get_avatar_url(
+        &self,
+        guild_id: GuildIdParam,
+        fmt: image_formats::Animated,
+        size: Option<u32>,
+    ) -> Option<String> {
+        self.guild_cdn_url("avatars", self.avatar.as_deref()?, guild_id, fmt, size)
+    }
+
+    /**
+     * Gets the url of the member's guild banner, which is shown instead of their user banner in the guild
+     *
+     * @param guild_id The id of the guild the member is in
+     *
+     * @param fmt Image format of the banner
+     *
+     * @param size Size of the banner. If none specified, the largest size will be used.
+     * The size must be a power of 2 between `16` and `4096`
+     */
+    pub fn get_banner_url(
+        &self,
+        guild_id: GuildIdParam,
+        fmt: image_formats::Animated,
+        size: Option<u32>,
+    ) -> Option<String> {
+        self.guild_cdn_url("banners", self.banner.as_deref()?, guild_id, fmt, size)
+    }
+
+    /// Gets the url of the avatar that is shown for the member in the guild: their guild avatar if they have one, otherwise their user avatar.
+    /// Takes the same parameters as `get_avatar_url`
+    pub fn get_display_avatar_url(
+        &self,
+        guild_id: GuildIdParam,
+        fmt: image_formats::Animated,
+        size: Option<u32>,
+    ) -> Option<String> {
+        match &self.avatar {
+            Some(_) => self.get_avatar_url(guild_id, fmt, size),
+            None => self.user.as_ref()?.get_avatar_url(fmt, size),
+        }
+    }
+
+    /// The CDN url of a per-guild image of the member
+    fn guild_cdn_url(
+        &self,
+        kind: &str,
+        hash: &str,
+        guild_id: GuildIdParam,
+        fmt: image_formats::Animated,
+        size: Option<u32>,
+    ) -> Option<String> {
+        let size_str = match size {
+            Some(s) => format!("?size={}", s),
+            None => "".to_string(),
+        };
+        Some(format!(
+            "https://cdn.discordapp.com/guilds/{}/users/{}/{}/{}.{}{}",
+            guild_id,
+            self.user.as_ref()?.id,
+            kind,
+            hash,
+            fmt,
+            size_str
+        ))
+    }
+
+    /// Whether the member is timed out right now
+    pub fn is_timed_out(&self) -> bool {
+        self.communication_disabled_until
+            .as_deref()
+            .and_then(|until| DateTime::parse_from_rfc3339(until).ok())
+            .is_some_and(|until| until > Utc::now())
+    }
+
+    /**
+     * Computes the member's permissions in a channel, the same way discord does: the permissions of their roles, then the
+     * channel's overwrites. Owners and administrators have every permission, and timed out members can only read.
+     * Threads don't have their own overwrites, so pass the channel that the thread is in.
+     *
+     * ```rust,ignore
+     * let permissions = member.permissions_in(&channel, &guild);
+     * if !permissions.is_some_and(|p| p.has(Permissions::MANAGE_MESSAGES)) {
+     *     return ictx.respond("You can't do that here").await;
+     * }
+     * ```
+     * @param channel A channel of the guild, with its overwrites
+     * @param guild The member's guild, with its roles
+     * @return None if the member object doesn't include the user (like in MESSAGE_CREATE events)
+     */
+    pub fn permissions_in(&self, channel: &Channel, guild: &Guild) -> Option<Permissions> {
+        let user_id = self.user.as_ref()?.id;
+        Some(compute_permissions(
+            guild,
+            user_id,
+            &self.roles,
+            channel.permission_overwrites.as_deref().unwrap_or_default(),
+            self.is_timed_out(),
+        ))
+    }
+}
+
+/**
+ * Modify Guild Member Payload
+ * Only the fields that are set are changed.
+ * @docs <https://discord.com/developers/docs/resources/guild#modify-guild-member-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ModifyGuildMember {
+    /// value to set the user's nickname to. `Some(None)` removes the nickname. Requires MANAGE_NICKNAMES
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nick: Option<Option<String>>,
+    /// array of role ids the member is assigned, replacing the roles they have. Requires MANAGE_ROLES
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roles: Option<Vec<Snowflake>>,
+    /// whether the user is muted in voice channels. Requires MUTE_MEMBERS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mute: Option<bool>,
+    /// whether the user is deafened in voice channels. Requires DEAFEN_MEMBERS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deaf: Option<bool>,
+    /// id of the voice channel to move the user to, if they are connected to voice. `Some(None)` disconnects them. Requires MOVE_MEMBERS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<Option<Snowflake>>,
+    /// when the user's timeout will expire (up to 28 days in the future), as an ISO8601 timestamp. `Some(None)` removes the timeout. Requires MODERATE_MEMBERS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub communication_disabled_until: Option<Option<String>>,
+}
+
+#[cfg(feature = "rest")]
+impl ModifyGuildMember {
+    /// Times the member out for a duration from now, up to 28 days
+    #[must_use]
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        let until = chrono::Utc::now()
+            + chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::days(28));
+        self.communication_disabled_until = Some(Some(until.to_rfc3339()));
+        self
+    }
+
+    /// Ends the member's timeout
+    #[must_use]
+    pub fn remove_timeout(mut self) -> Self {
+        self.communication_disabled_until = Some(None);
+        self
+    }
+}
+
+/// The most members that discord returns in one page
+#[cfg(feature = "rest")]
+const MAX_MEMBERS_PER_PAGE: u64 = 1000;
+
+#[cfg(feature = "rest")]
+impl Guild {
+    /// Gets a single page of the members of a guild, sorted by user id. Requires the GUILD_MEMBERS intent.
+    /// Discord only supports `after` for this endpoint, `before` is ignored.
+    /// @param guild_id The id of the guild
+    /// @param query The user id to start after, and the max number of members to return (1-1000, default 1)
+    /// @docs <https://discord.com/developers/docs/resources/guild#list-guild-members>
+    pub async fn list_members(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        query: PageQuery,
+    ) -> Result<Vec<GuildMember>, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/members".to_string(),
+            major_param: guild_id.to_string(),
+        };
+
+        let query = PageQuery {
+            before: None,
+            ..query
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/guilds/{}/members?{}",
+                BASE_URL,
+                guild_id,
+                query.to_query_string()
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Walks through every member of a guild. Requires the GUILD_MEMBERS intent.
+     * The members in the cache come first. If the cache doesn't have all of them, the rest are fetched
+     * in pages of up to 1000 as the stream is polled, skipping the ones that were already yielded.
+     * If a request fails, the error is yielded and the stream ends.
+     *
+     * ```rust,ignore
+     * let mut members = Guild::members_iter(ctx, guild_id);
+     * while let Some(member) = members.next().await {
+     *     let member = member?;
+     *     // ...
+     * }
+     * ```
+     * @param guild_id The id of the guild
+     */
+    pub fn members_iter(ctx: Context, guild_id: GuildIdParam) -> Paginated<GuildMember> {
+        let cached = cached_members(&ctx, guild_id.raw());
+        let member_count = ctx.cache.guild(guild_id.raw()).and_then(|guild| guild.member_count);
+        if member_count.is_some_and(|count| cached.len() as i64 >= count) {
+            return Paginated::from_stream(stream::iter(cached.into_iter().map(Ok)));
+        }
+
+        // The users that came from the cache, so they aren't yielded again by the requests
+        let seen: HashSet<Snowflake> = cached
+            .iter()
+            .filter_map(|member| member.user.as_ref().map(|user| user.id))
+            .collect();
+        let fetched = Paginated::new(
+            ctx,
+            Direction::Forward,
+            PageQuery::new()
+                .after(Snowflake::from(0))
+                .limit(MAX_MEMBERS_PER_PAGE),
+            move |ctx, query| async move {
+                Guild::list_members(ctx, guild_id, query)
+                    .await
+                    .map(|members| Page::from_list(members, MAX_MEMBERS_PER_PAGE))
+            },
+            |member: &GuildMember| member.user.as_ref().map(|user| user.id),
+        )
+        .filter(move |member| {
+            let seen = match member {
+                Ok(GuildMember {
+                    user: Some(user), ..
+                }) => seen.contains(&user.id),
+                _ => false,
+            };
+            future::ready(!seen)
+        });
+
+        Paginated::from_stream(stream::iter(cached.into_iter().map(Ok)).chain(fetched))
+    }
+
+    /// Walks through every member of the guild, see `Guild::members_iter`
+    pub fn members(&self, ctx: Context) -> Paginated<GuildMember> {
+        Guild::members_iter(ctx, params::guild(self.id))
+    }
+}
+
+#[cfg(feature = "rest")]
+impl GuildMember {
+    /// Gets a member of a guild from discord
+    /// @param guild_id The id of the guild
+    /// @param user_id The id of the user
+    /// @docs <https://discord.com/developers/docs/resources/guild#get-guild-member>
+    pub async fn get(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+    ) -> Result<GuildMember, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/members/{user.id}".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/guilds/{}/members/{}",
+                BASE_URL, guild_id, user_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Changes a member of a guild. Each field needs its own permission, see `ModifyGuildMember`.
+     *
+     * ```rust,ignore
+     * GuildMember::modify(ctx, guild_id, user_id, ModifyGuildMember {
+     *     nick: Some(None),
+     *     ..Default::default()
+     * }.timeout(Duration::from_secs(600)), Some("Spamming")).await?;
+     * ```
+     * @param guild_id The id of the guild
+     * @param user_id The id of the user
+     * @param payload The fields to change
+     * @param reason The reason that shows up in the audit log
+     * @return The updated member
+     * @docs <https://discord.com/developers/docs/resources/guild#modify-guild-member>
+     */
+    pub async fn modify(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+        payload: ModifyGuildMember,
+        reason: Option<&str>,
+    ) -> Result<GuildMember, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/members/{user.id}".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::PATCH)
+            .uri(format!(
+                "{}/guilds/{}/members/{}",
+                BASE_URL, guild_id, user_id
+            ))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Gets a member of a guild from the cache, or from discord if it isn't cached
+    /// @param guild_id The id of the guild
+    /// @param user_id The id of the user
+    pub async fn fetch(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+    ) -> Result<CacheOrHttp<GuildMember>, Error> {
+        let cached = cached_member(&ctx, guild_id.raw(), user_id.raw()).await;
+        cache_or_http(cached, GuildMember::get(ctx, guild_id, user_id)).await
+    }
+
+    /// Gets the display name of a member (see `display_name`), from the cache if possible
+    /// @param guild_id The id of the guild
+    /// @param user_id The id of the user
+    pub async fn fetch_display_name(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+    ) -> Result<CacheOrHttp<String>, Error> {
+        let member = GuildMember::fetch(ctx, guild_id, user_id).await?;
+        // Fetched members always include the user
+        Ok(member.map(|m| m.display_name().unwrap_or_default().to_string()))
+    }
+
+    /// Adds a role to a member. Adding a role the member already has does nothing, so this is safe to call repeatedly.
+    /// @param guild_id The id of the guild
+    /// @param user_id The id of the member
+    /// @param role_id The id of the role to add
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/guild#add-guild-member-role>
+    pub async fn add_role(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+        role_id: RoleIdParam,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        set_role(ctx, Method::PUT, guild_id, user_id, role_id, reason).await
+    }
+
+    /// Removes a role from a member. Removing a role the member doesn't have does nothing, so this is safe to call repeatedly.
+    /// @param guild_id The id of the guild
+    /// @param user_id The id of the member
+    /// @param role_id The id of the role to remove
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/guild#remove-guild-member-role>
+    pub async fn remove_role(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+        role_id: RoleIdParam,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        set_role(ctx, Method::DELETE, guild_id, user_id, role_id, reason).await
+    }
+
+    /// Makes sure the member has a role. No request is made if the member object already has it,
+    /// and the member object is updated once the role is added, so calling this again is free.
+    /// @param guild_id The id of the guild the member is in
+    /// @param role_id The id of the role
+    /// @param reason The reason that shows up in the audit log
+    pub async fn ensure_role(
+        &mut self,
+        ctx: Context,
+        guild_id: GuildIdParam,
+        role_id: RoleIdParam,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        if self.roles.contains(&role_id.raw()) {
+            return Ok(());
+        }
+        GuildMember::add_role(ctx, guild_id, params::user(self.user_id()?), role_id, reason).await?;
+        self.roles.push(role_id.raw());
+        Ok(())
+    }
+
+    /// Makes sure the member doesn't have a role. No request is made if the member object doesn't have it,
+    /// and the member object is updated once the role is removed.
+    /// @param guild_id The id of the guild the member is in
+    /// @param role_id The id of the role
+    /// @param reason The reason that shows up in the audit log
+    pub async fn ensure_no_role(
+        &mut self,
+        ctx: Context,
+        guild_id: GuildIdParam,
+        role_id: RoleIdParam,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        if !self.roles.contains(&role_id.raw()) {
+            return Ok(());
+        }
+        GuildMember::remove_role(ctx, guild_id, params::user(self.user_id()?), role_id, reason)
+            .await?;
+        self.roles.retain(|id| *id != role_id.raw());
+        Ok(())
+    }
+
+    /// The id of the member's user, which is needed to modify them
+    fn user_id(&self) -> Result<Snowflake, Error> {
+        self.user.as_ref().map(|u| u.id).ok_or_else(|| {
+            Error::new(
+                "The member object doesn't include the user".to_string(),
+                ErrorTypes::PARSE,
+            )
+        })
+    }
+}
+
+#[cfg(feature = "rest")]
+async fn set_role(
+    ctx: Context,
+    method: Method,
+    guild_id: GuildIdParam,
+    user_id: UserIdParam,
+    role_id: RoleIdParam,
+    reason: Option<&str>,
+) -> Result<(), Error> {
+    let route = RequestRoute {
+        base_route: "/guilds/{guild.id}/members/{user.id}/roles/{role.id}".to_string(),
+        major_param: guild_id.to_string(),
+    };
+    let uri = format!(
+        "{}/guilds/{}/members/{}/roles/{}",
+        BASE_URL, guild_id, user_id, role_id
+    );
+
+    send_request_idempotent(ctx, route, || {
+        with_audit_log_reason(Request::builder(), reason)
+            .method(method.clone())
+            .uri(&uri)
+            .body(Body::empty())
+            .unwrap()
+    })
+    .await
 }
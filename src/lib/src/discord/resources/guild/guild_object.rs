@@ -1,19 +1,47 @@
 use discrab_codegen::CommandArg;
+#[cfg(feature = "rest")]
+use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     core::abstraction::traits::CommandArg,
     discord::{
         gateway::presence::PresenceUpdate,
-        resources::{channel::Channel, emoji::Emoji, sticker::Sticker, voice::VoiceState},
+        resources::{
+            channel::Channel,
+            emoji::{Emoji, UsableEmoji},
+            sticker::Sticker,
+            voice::VoiceState,
+        },
         snowflake::Snowflake,
     },
 };
+#[cfg(feature = "rest")]
+use crate::{
+    core::{
+        abstraction::{
+            cache::{cache_or_http, cached_guild, CacheOrHttp},
+            context::Context,
+        },
+        http::rate_limit_client::{send_request, RequestRoute},
+    },
+    discord::{
+        permissions::Permissions,
+        resources::channel::typing::{ChannelType, PermissionsOverwriteObject},
+    },
+    util::error::Error,
+    BASE_URL,
+};
 
 use super::{
     guild_member::GuildMember, role::Role, stage_instance::StageInstance,
     welcome_screen::WelcomeScreen,
 };
+#[cfg(feature = "rest")]
+use crate::discord::ids::{
+    params::{GuildIdParam, RoleIdParam, UserIdParam},
+    RawId,
+};
 
 /**
  * Guild Structure
@@ -33,7 +61,8 @@ pub struct Guild {
     pub splash: Option<String>,
     /// discovery splash hash; only present for guilds with the "DISCOVERABLE" feature
     pub discovery_splash: Option<String>,
-    /// true if the user is the owner of the guild
+    /// true if the user is the owner of the guild. Only sent when listing the current user's guilds, and false otherwise
+    #[serde(default)]
     pub owner: bool,
     /// id of owner
     pub owner_id: Snowflake,
@@ -45,7 +74,8 @@ pub struct Guild {
     pub afk_channel_id: Option<Snowflake>,
     /// afk timeout in seconds
     pub afk_timeout: i64,
-    /// true if the server widget is enabled
+    /// true if the server widget is enabled. Not sent in gateway events, where it defaults to false
+    #[serde(default)]
     pub widget_enabled: bool,
     /// the channel id that the widget will generate an invite to, or null if set to no invite
     pub widget_channel_id: Option<Snowflake>,
@@ -73,9 +103,11 @@ pub struct Guild {
     pub rules_channel_id: Option<Snowflake>,
     /// when this guild was joined at
     pub joined_at: Option<String>,
-    /// true if this is considered a large guild
+    /// true if this is considered a large guild. Only sent in GUILD_CREATE, and false otherwise
+    #[serde(default)]
     pub large: bool,
-    /// true if this guild is unavailable due to an outage
+    /// true if this guild is unavailable due to an outage. Only sent in GUILD_CREATE, and false otherwise
+    #[serde(default)]
     pub unavailable: bool,
     /// total number of members in this guild
     pub member_count: Option<i64>,
@@ -91,7 +123,8 @@ pub struct Guild {
     pub presences: Option<Vec<PresenceUpdate>>,
     /// the maximum number of presences for the guild (null is always returned, apart from the largest of guilds)
     pub max_presences: Option<i64>,
-    /// the maximum number of members for the guild
+    /// the maximum number of members for the guild. Not sent in gateway events, where it defaults to 0
+    #[serde(default)]
     pub max_members: i64,
     /// the vanity url code for the guild
     pub vanity_url_code: Option<String>,
@@ -121,6 +154,109 @@ pub struct Guild {
     pub stage_instances: Option<Vec<StageInstance>>,
     /// custom guild stickers
     pub stickers: Option<Vec<Sticker>>,
+    /// the incidents data for this guild
+    pub incidents_data: Option<IncidentsData>,
+}
+
+impl Guild {
+    /// Whether the guild has a feature, like `ROLE_ICONS` or `COMMUNITY`
+    /// @docs <https://discord.com/developers/docs/resources/guild#guild-object-guild-features>
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    /// The highest of the member's roles, or the @everyone role if the member has no other roles
+    /// @param member A member of this guild
+    pub fn highest_role(&self, member: &GuildMember) -> Option<&Role> {
+        self.roles
+            .iter()
+            .filter(|role| role.is_everyone(self.id) || member.roles.contains(&role.id))
+            .max()
+    }
+
+    /**
+     * Whether `actor` is allowed to moderate (kick, ban, timeout, change the nickname or roles of) `target`,
+     * going by the role hierarchy: the owner can moderate everyone, nobody can moderate the owner,
+     * and otherwise the actor's highest role has to be above the target's highest role.
+     * This doesn't check that the actor has the permission for the action itself.
+     * @param actor The member doing the moderation (usually the bot)
+     * @param target The member being moderated
+     */
+    pub fn can_moderate(&self, actor: &GuildMember, target: &GuildMember) -> bool {
+        let actor_id = actor.user.as_ref().map(|u| u.id);
+        let target_id = target.user.as_ref().map(|u| u.id);
+        if actor_id.is_some() && actor_id == target_id {
+            return false;
+        }
+        if target_id == Some(self.owner_id) {
+            return false;
+        }
+        if actor_id == Some(self.owner_id) {
+            return true;
+        }
+        match (self.highest_role(actor), self.highest_role(target)) {
+            (Some(actor_role), Some(target_role)) => actor_role > target_role,
+            _ => false,
+        }
+    }
+
+    /**
+     * Checks that the bot can use an emoji of this guild before reacting or sending it, and falls back to a unicode emoji if it can't
+     * (e.g. the emoji was deleted, is limited to roles the bot doesn't have, or became unavailable when the guild lost its boosts).
+     * Unicode emojis can always be used, so they are returned as they are.
+     *
+     * ```rust,ignore
+     * let emoji = guild.usable_emoji(&bot_member, "party_blob:861708478423252992", "🎉");
+     * Message::add_reaction(ctx, channel_id, message_id, &emoji.reaction_format()).await?;
+     * ```
+     * @param bot_member The bot's member in this guild
+     * @param emoji A unicode emoji, or a custom emoji in the form `name:id`
+     * @param fallback The unicode emoji to use when the custom emoji can't be used
+     */
+    pub fn usable_emoji(
+        &self,
+        bot_member: &GuildMember,
+        emoji: &str,
+        fallback: &str,
+    ) -> UsableEmoji {
+        if !emoji.contains(':') {
+            return UsableEmoji::Unicode(emoji.to_string());
+        }
+        self.emojis
+            .iter()
+            .find(|guild_emoji| guild_emoji.matches(emoji) && guild_emoji.usable_by(bot_member))
+            .map(|guild_emoji| UsableEmoji::Custom(guild_emoji.clone()))
+            .unwrap_or_else(|| UsableEmoji::Unicode(fallback.to_string()))
+    }
+
+    /// Whether `actor` is allowed to manage (edit, delete, give to members) a role, which must be below the actor's highest role
+    /// @param actor The member managing the role (usually the bot)
+    /// @param role A role of this guild
+    pub fn can_manage_role(&self, actor: &GuildMember, role: &Role) -> bool {
+        if actor.user.as_ref().map(|u| u.id) == Some(self.owner_id) {
+            return true;
+        }
+        self.highest_role(actor)
+            .map(|highest| highest > role)
+            .unwrap_or(false)
+    }
+}
+
+/**
+ * Incidents Data
+ * The security actions that are currently active in a guild, and when raids or dm spam were last detected.
+ * @docs <https://discord.com/developers/docs/resources/guild#incidents-data-object>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IncidentsData {
+    /// when invites get enabled again (ISO8601 timestamp)
+    pub invites_disabled_until: Option<String>,
+    /// when direct messages get enabled again (ISO8601 timestamp)
+    pub dms_disabled_until: Option<String>,
+    /// when the dm spam was detected (ISO8601 timestamp)
+    pub dm_spam_detected_at: Option<String>,
+    /// when the raid was detected (ISO8601 timestamp)
+    pub raid_detected_at: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, CommandArg)]
@@ -128,3 +264,185 @@ pub struct UnavailableGuild {
     pub id: Snowflake,
     pub unavailable: bool,
 }
+
+/**
+ * Create Guild Channel Payload
+ * All fields other than `name` are optional.
+ * @docs <https://discord.com/developers/docs/resources/guild#create-guild-channel-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct CreateGuildChannel {
+    /// channel name (1-100 characters)
+    pub name: String,
+    /// the type of channel
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<ChannelType>,
+    /// channel topic (0-1024 characters)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// the bitrate (in bits) of the voice channel (voice only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u64>,
+    /// the user limit of the voice channel (voice only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_limit: Option<u64>,
+    /// amount of seconds a user has to wait before sending another message (0-21600)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<u64>,
+    /// sorting position of the channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<u64>,
+    /// the channel's permission overwrites
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_overwrites: Option<Vec<PermissionsOverwriteObject>>,
+    /// id of the parent category for a channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<Snowflake>,
+    /// whether the channel is nsfw
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nsfw: Option<bool>,
+}
+
+/**
+ * Modify Guild Incident Actions Payload
+ * Both fields are always sent, so leaving one as `None` turns that action off again.
+ * The timestamps can be at most 24 hours in the future.
+ * @docs <https://discord.com/developers/docs/resources/guild#modify-guild-incident-actions-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ModifyIncidentActions {
+    /// when invites will be enabled again (ISO8601 timestamp)
+    pub invites_disabled_until: Option<String>,
+    /// when direct messages will be enabled again (ISO8601 timestamp)
+    pub dms_disabled_until: Option<String>,
+}
+
+#[cfg(feature = "rest")]
+impl Guild {
+    /// The permissions that the targets of `Guild::create_private_channel_for` are given in the channel
+    pub const PRIVATE_CHANNEL_PERMISSIONS: Permissions = Permissions::from_bits_truncate(
+        Permissions::VIEW_CHANNEL.bits()
+            | Permissions::SEND_MESSAGES.bits()
+            | Permissions::READ_MESSAGE_HISTORY.bits()
+            | Permissions::ATTACH_FILES.bits()
+            | Permissions::EMBED_LINKS.bits()
+            | Permissions::ADD_REACTIONS.bits(),
+    );
+
+    /// Gets a guild from discord
+    /// @param guild_id The id of the guild
+    /// @docs <https://discord.com/developers/docs/resources/guild#get-guild>
+    pub async fn get(ctx: Context, guild_id: GuildIdParam) -> Result<Guild, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!("{}/guilds/{}", BASE_URL, guild_id))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Gets a guild from the cache, or from discord if it isn't cached
+    /// @param guild_id The id of the guild
+    pub async fn fetch(ctx: Context, guild_id: GuildIdParam) -> Result<CacheOrHttp<Guild>, Error> {
+        let cached = cached_guild(&ctx, guild_id.raw()).await;
+        cache_or_http(cached, Guild::get(ctx, guild_id)).await
+    }
+
+    /// Creates a new channel in a guild. Requires the MANAGE_CHANNELS permission.
+    /// @param guild_id The id of the guild to create the channel in
+    /// @param payload The channel to create
+    /// @docs <https://discord.com/developers/docs/resources/guild#create-guild-channel>
+    pub async fn create_channel(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        payload: CreateGuildChannel,
+    ) -> Result<Channel, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/channels".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{}/guilds/{}/channels", BASE_URL, guild_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Temporarily disables invites and/or direct messages in a guild, e.g. while a raid is going on. Requires the MANAGE_GUILD permission.
+    /// @param guild_id The id of the guild
+    /// @param payload Until when invites and direct messages should be disabled
+    /// @return The incidents data of the guild after the change
+    /// @docs <https://discord.com/developers/docs/resources/guild#modify-guild-incident-actions>
+    pub async fn modify_incident_actions(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        payload: ModifyIncidentActions,
+    ) -> Result<IncidentsData, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/incident-actions".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::PUT)
+            .uri(format!("{}/guilds/{}/incident-actions", BASE_URL, guild_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Creates a channel that is hidden from @everyone, and only visible to the given users and roles (e.g. a support ticket).
+     *
+     * The @everyone role is denied VIEW_CHANNEL, and every user and role passed in is allowed `Guild::PRIVATE_CHANNEL_PERMISSIONS`.
+     * Any overwrites that are already in `options` are kept. If the bot doesn't have the ADMINISTRATOR permission,
+     * make sure that it is included in `users` (or one of its roles is in `roles`), otherwise it won't be able to see the channel either.
+     *
+     * @param guild_id The id of the guild to create the channel in
+     * @param users The ids of the users that should be able to see the channel
+     * @param roles The ids of the roles that should be able to see the channel
+     * @param options The rest of the channel information (name, type, parent category, etc.)
+     */
+    pub async fn create_private_channel_for(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        users: Vec<UserIdParam>,
+        roles: Vec<RoleIdParam>,
+        mut options: CreateGuildChannel,
+    ) -> Result<Channel, Error> {
+        let overwrites = options.permission_overwrites.get_or_insert_with(Vec::new);
+        // The @everyone role shares its id with the guild
+        overwrites.push(PermissionsOverwriteObject::role(
+            guild_id.raw(),
+            Permissions::empty(),
+            Permissions::VIEW_CHANNEL,
+        ));
+        overwrites.extend(roles.into_iter().map(|id| {
+            PermissionsOverwriteObject::role(
+                id.raw(),
+                Self::PRIVATE_CHANNEL_PERMISSIONS,
+                Permissions::empty(),
+            )
+        }));
+        overwrites.extend(users.into_iter().map(|id| {
+            PermissionsOverwriteObject::member(
+                id.raw(),
+                Self::PRIVATE_CHANNEL_PERMISSIONS,
+                Permissions::empty(),
+            )
+        }));
+
+        Self::create_channel(ctx, guild_id, options).await
+    }
+}
@@ -1,3 +1,6 @@
+pub mod ban;
+#[cfg(feature = "rest")]
+pub mod full_guild;
 pub mod guild_member;
 pub mod guild_object;
 pub mod integration;
@@ -1,6 +1,26 @@
+use std::cmp::Ordering;
+
+#[cfg(feature = "rest")]
+use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
 
-use crate::discord::snowflake::Snowflake;
+use crate::discord::{image_formats::ImageData, permissions::Permissions, snowflake::Snowflake};
+#[cfg(feature = "rest")]
+use crate::{
+    core::http::rate_limit_client::{send_request, send_request_noparse, RequestRoute},
+    discord::ids::params::{self, GuildIdParam, RoleIdParam},
+    util::{
+        error::{Error, ErrorTypes},
+        requests::with_audit_log_reason,
+    },
+    Context, BASE_URL,
+};
+
+#[cfg(feature = "rest")]
+use super::guild_object::Guild;
+
+/// The guild feature that roles need for icons and unicode emojis
+pub const ROLE_ICONS: &str = "ROLE_ICONS";
 
 /**
  * Role Structure
@@ -20,6 +40,9 @@ pub struct Role {
     pub icon: Option<String>,
     /// role icon hash
     pub icon_hash: Option<String>,
+    /// role unicode emoji, shown instead of an icon
+    #[serde(default)]
+    pub unicode_emoji: Option<String>,
     /// position of this role
     pub position: i64,
     /// permission bit set
@@ -32,6 +55,320 @@ pub struct Role {
     pub tags: Option<RoleTags>,
 }
 
+impl Role {
+    /// Whether this is the @everyone role, which has the same id as the guild
+    /// @param guild_id The id of the guild the role belongs to
+    pub fn is_everyone(&self, guild_id: Snowflake) -> bool {
+        self.id == guild_id
+    }
+}
+
+/**
+ * Used to create a role, or to change one with `Role::edit`. Only the fields that are set are sent.
+ * Icons and unicode emojis need the guild to have the ROLE_ICONS feature (level 2 boosts).
+ * @docs <https://discord.com/developers/docs/resources/guild#create-guild-role>
+ */
+#[derive(Serialize, Clone, Default)]
+pub struct CreateRole {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// The permission bit set, as a string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    permissions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hoist: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mentionable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<ImageData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unicode_emoji: Option<String>,
+}
+
+impl CreateRole {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the role
+    #[must_use]
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Sets the permissions of the role
+    #[must_use]
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = Some(permissions.bits().to_string());
+        self
+    }
+
+    /// Sets the color of the role, as an RGB integer like `0xE74C3C`. 0 means no color
+    #[must_use]
+    pub fn color(mut self, color: u32) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Sets whether the role is shown separately in the member list
+    #[must_use]
+    pub fn hoist(mut self, hoist: bool) -> Self {
+        self.hoist = Some(hoist);
+        self
+    }
+
+    /// Sets whether everyone can mention the role
+    #[must_use]
+    pub fn mentionable(mut self, mentionable: bool) -> Self {
+        self.mentionable = Some(mentionable);
+        self
+    }
+
+    /// Sets the icon of the role
+    #[must_use]
+    pub fn icon(mut self, icon: ImageData) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Sets a unicode emoji to show next to the role instead of an icon
+    #[must_use]
+    pub fn unicode_emoji(mut self, emoji: &str) -> Self {
+        self.unicode_emoji = Some(emoji.to_string());
+        self
+    }
+
+    /// Whether the role needs the ROLE_ICONS guild feature
+    #[cfg(feature = "rest")]
+    fn uses_role_icons(&self) -> bool {
+        self.icon.is_some() || self.unicode_emoji.is_some()
+    }
+}
+
+/**
+ * A role and where to move it to, for `Guild::modify_role_positions`
+ * @docs <https://discord.com/developers/docs/resources/guild#modify-guild-role-positions-json-params>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RolePosition {
+    /// role
+    pub id: Snowflake,
+    /// sorting position of the role
+    pub position: Option<i64>,
+}
+
+#[cfg(feature = "rest")]
+impl Guild {
+    /**
+     * Creates a role in a guild. Requires the MANAGE_ROLES permission.
+     * @param guild_id The id of the guild
+     * @param role The role to create
+     * @param reason The reason that shows up in the audit log
+     * @return An error with `ErrorTypes::MISSING_FEATURE` if the role has an icon or emoji, and the guild doesn't have ROLE_ICONS
+     * @docs <https://discord.com/developers/docs/resources/guild#create-guild-role>
+     */
+    pub async fn create_role(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        role: CreateRole,
+        reason: Option<&str>,
+    ) -> Result<Role, Error> {
+        if role.uses_role_icons() {
+            require_role_icons(&ctx, guild_id).await?;
+        }
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/roles".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::POST)
+            .uri(format!("{}/guilds/{}/roles", BASE_URL, guild_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&role)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Moves roles up or down the role list. Only the roles that are passed are moved, and the others are shifted around them.
+     * Requires the MANAGE_ROLES permission, and the bot can only move roles below its highest role.
+     * @param guild_id The id of the guild
+     * @param positions The roles to move and their new positions
+     * @param reason The reason that shows up in the audit log
+     * @return Every role in the guild
+     * @docs <https://discord.com/developers/docs/resources/guild#modify-guild-role-positions>
+     */
+    pub async fn modify_role_positions(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        positions: &[RolePosition],
+        reason: Option<&str>,
+    ) -> Result<Vec<Role>, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/roles".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::PATCH)
+            .uri(format!("{}/guilds/{}/roles", BASE_URL, guild_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(positions)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+}
+
+#[cfg(feature = "rest")]
+impl Role {
+    /**
+     * Changes a role. Only the fields that are set on the builder are changed. Requires the MANAGE_ROLES permission.
+     * @param guild_id The id of the guild the role is in
+     * @param role_id The id of the role
+     * @param role The fields to change
+     * @param reason The reason that shows up in the audit log
+     * @return An error with `ErrorTypes::MISSING_FEATURE` if an icon or emoji is set, and the guild doesn't have ROLE_ICONS
+     * @docs <https://discord.com/developers/docs/resources/guild#modify-guild-role>
+     */
+    pub async fn edit(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        role_id: RoleIdParam,
+        role: CreateRole,
+        reason: Option<&str>,
+    ) -> Result<Role, Error> {
+        if role.uses_role_icons() {
+            require_role_icons(&ctx, guild_id).await?;
+        }
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/roles/{role.id}".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::PATCH)
+            .uri(format!(
+                "{}/guilds/{}/roles/{}",
+                BASE_URL, guild_id, role_id
+            ))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&role)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Deletes a role. Requires the MANAGE_ROLES permission
+    /// @param guild_id The id of the guild the role is in
+    /// @param role_id The id of the role
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/guild#delete-guild-role>
+    pub async fn delete(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        role_id: RoleIdParam,
+        reason: Option<&str>,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/roles/{role.id}".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::DELETE)
+            .uri(format!(
+                "{}/guilds/{}/roles/{}",
+                BASE_URL, guild_id, role_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+
+    /// Sets the icon of the role. The guild needs the ROLE_ICONS feature
+    /// @param guild_id The id of the guild the role is in
+    /// @param icon The new icon
+    /// @return The updated role
+    pub async fn set_icon(
+        &self,
+        ctx: Context,
+        guild_id: GuildIdParam,
+        icon: ImageData,
+    ) -> Result<Role, Error> {
+        Role::edit(
+            ctx,
+            guild_id,
+            params::role(self.id),
+            CreateRole::new().icon(icon),
+            None,
+        )
+        .await
+    }
+
+    /// Sets a unicode emoji to show next to the role. The guild needs the ROLE_ICONS feature
+    /// @param guild_id The id of the guild the role is in
+    /// @param emoji The emoji, e.g. "🔥"
+    /// @return The updated role
+    pub async fn set_unicode_emoji(
+        &self,
+        ctx: Context,
+        guild_id: GuildIdParam,
+        emoji: &str,
+    ) -> Result<Role, Error> {
+        Role::edit(
+            ctx,
+            guild_id,
+            params::role(self.id),
+            CreateRole::new().unicode_emoji(emoji),
+            None,
+        )
+        .await
+    }
+}
+
+/// Fails with `ErrorTypes::MISSING_FEATURE` if the guild can't have role icons, so that the request isn't wasted
+#[cfg(feature = "rest")]
+async fn require_role_icons(ctx: &Context, guild_id: GuildIdParam) -> Result<(), Error> {
+    let guild = Guild::fetch(ctx.clone(), guild_id).await?;
+    if guild.has_feature(ROLE_ICONS) {
+        return Ok(());
+    }
+    Err(Error::new(
+        format!(
+            "Guild {} doesn't have the {} feature, so its roles can't have icons or emojis",
+            guild_id, ROLE_ICONS
+        ),
+        ErrorTypes::MISSING_FEATURE,
+    ))
+}
+
+// Roles are ordered the way discord shows them: a role is "higher" when it has a higher position.
+// Roles with the same position are ordered by their ids, where the older role (lower id) is the higher one.
+impl PartialEq for Role {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Role {}
+
+impl PartialOrd for Role {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Role {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.position
+            .cmp(&other.position)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
 /**
  * Role Tags Structure
  * @docs <https://discord.com/developers/docs/topics/permissions#role-object-role-tags-structure>
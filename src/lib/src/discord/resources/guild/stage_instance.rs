@@ -1,8 +1,22 @@
 use discrab_codegen::CommandArg;
+#[cfg(feature = "rest")]
+use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::{core::abstraction::traits::CommandArg, discord::snowflake::Snowflake};
+#[cfg(feature = "rest")]
+use crate::{
+    core::{
+        abstraction::context::Context,
+        http::rate_limit_client::{send_request, RequestRoute},
+    },
+    discord::resources::voice::{ModifyUserVoiceState, VoiceState},
+    util::error::Error,
+    BASE_URL,
+};
+#[cfg(feature = "rest")]
+use crate::discord::ids::params::{self, UserIdParam};
 
 /**
  * Stage Instance Structure
@@ -24,6 +38,83 @@ pub struct StageInstance {
     pub discoverable_disabled: bool,
 }
 
+/**
+ * Create Stage Instance Payload
+ * @docs <https://discord.com/developers/docs/resources/stage-instance#create-stage-instance-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CreateStageInstance {
+    /// The id of the Stage channel
+    pub channel_id: Snowflake,
+    /// The topic of the Stage instance (1-120 characters)
+    pub topic: String,
+    /// The privacy level of the Stage instance (default GUILD_ONLY)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privacy_level: Option<PrivacyLevel>,
+    /// Notify @everyone that a Stage instance has started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_start_notification: Option<bool>,
+    /// The guild scheduled event associated with this Stage instance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guild_scheduled_event_id: Option<Snowflake>,
+}
+
+#[cfg(feature = "rest")]
+impl StageInstance {
+    /// Creates a new Stage instance associated to a Stage channel.
+    /// Requires the user to be a moderator of the Stage channel (MANAGE_CHANNELS, MUTE_MEMBERS and MOVE_MEMBERS).
+    /// @param payload The Stage instance to create
+    /// @docs <https://discord.com/developers/docs/resources/stage-instance#create-stage-instance>
+    pub async fn create(
+        ctx: Context,
+        payload: CreateStageInstance,
+    ) -> Result<StageInstance, Error> {
+        let route = RequestRoute {
+            base_route: "/stage-instances".to_string(),
+            major_param: "".to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{}/stage-instances", BASE_URL))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Moves a member of the audience up to the speakers. Requires the MUTE_MEMBERS permission.
+    /// @param user_id The id of the user to invite. They must already be in the stage channel.
+    pub async fn invite_to_speak(&self, ctx: Context, user_id: UserIdParam) -> Result<(), Error> {
+        self.set_suppressed(ctx, user_id, false).await
+    }
+
+    /// Moves a speaker back down to the audience. Requires the MUTE_MEMBERS permission.
+    /// @param user_id The id of the user to move. They must already be in the stage channel.
+    pub async fn move_to_audience(&self, ctx: Context, user_id: UserIdParam) -> Result<(), Error> {
+        self.set_suppressed(ctx, user_id, true).await
+    }
+
+    async fn set_suppressed(
+        &self,
+        ctx: Context,
+        user_id: UserIdParam,
+        suppress: bool,
+    ) -> Result<(), Error> {
+        VoiceState::modify_user(
+            ctx,
+            params::guild(self.guild_id),
+            user_id,
+            ModifyUserVoiceState {
+                channel_id: self.channel_id,
+                suppress: Some(suppress),
+            },
+        )
+        .await
+    }
+}
+
 /**
  * Privacy Level
  * @docs <https://discord.com/developers/docs/resources/stage-instance#stage-instance-object-privacy-level>
@@ -1,9 +1,31 @@
 use discrab_codegen::CommandArg;
+#[cfg(feature = "rest")]
+use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::{api::Snowflake, core::abstraction::traits::CommandArg};
+#[cfg(feature = "rest")]
+use crate::{
+    core::{
+        abstraction::context::Context,
+        http::rate_limit_client::{send_request, send_request_noparse, RequestRoute},
+    },
+    util::{error::Error, logger::print_debug},
+    BASE_URL,
+};
 
 use super::user::User;
+#[cfg(feature = "rest")]
+use super::{
+    channel::{typing::ChannelType, Channel},
+    guild::{
+        guild_object::{CreateGuildChannel, Guild},
+        stage_instance::{CreateStageInstance, StageInstance},
+    },
+};
+#[cfg(feature = "rest")]
+use crate::discord::ids::params::{self, GuildIdParam};
 
 /**
  * Guild Scheduled Event Structure
@@ -16,9 +38,9 @@ pub struct GuildScheduledEvent {
     /// The guild id which the scheduled event belongs to
     pub guild_id: Snowflake,
     /// The channel id in which the scheduled event will be hosted, or null if scheduled entity type is EXTERNAL
-    pub channel_id: Option<u64>,
+    pub channel_id: Option<Snowflake>,
     /// The id of the user that created the scheduled event *
-    pub creator_id: Option<u64>,
+    pub creator_id: Option<Snowflake>,
     /// The name of the scheduled event (1-100 characters)
     pub name: String,
     /// The description of the scheduled event (1-1000 characters)
@@ -28,17 +50,418 @@ pub struct GuildScheduledEvent {
     /// The time the scheduled event will end, required if entity_type is EXTERNAL
     pub scheduled_end_time: Option<String>,
     /// The privacy level of the scheduled event
-    pub privacy_level: String,
+    pub privacy_level: GuildScheduledEventPrivacyLevel,
     /// The status of the scheduled event
-    pub status: String,
+    pub status: GuildScheduledEventStatus,
     /// The type of the scheduled event
-    pub entity_type: String,
+    pub entity_type: GuildScheduledEventEntityType,
     /// The id of an entity associated with a guild scheduled event
-    pub entity_id: Option<u64>,
+    pub entity_id: Option<Snowflake>,
     /// Additional metadata for the guild scheduled event
-    pub entity_metadata: Option<String>,
+    pub entity_metadata: Option<GuildScheduledEventEntityMetadata>,
     /// The user that created the scheduled event
     pub creator: Option<User>,
     /// The number of users subscribed to the scheduled event
     pub user_count: Option<u64>,
+    /// How often the event repeats, if it does
+    pub recurrence_rule: Option<RecurrenceRule>,
+}
+
+/**
+ * Guild Scheduled Event Privacy Level
+ * @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-object-guild-scheduled-event-privacy-level>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum GuildScheduledEventPrivacyLevel {
+    /// The scheduled event is only accessible to guild members
+    GuildOnly = 2,
+}
+
+/**
+ * Guild Scheduled Event Status
+ * Once status is set to COMPLETED or CANCELED, the status can no longer be updated
+ * @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-object-guild-scheduled-event-status>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum GuildScheduledEventStatus {
+    Scheduled = 1,
+    Active = 2,
+    Completed = 3,
+    Canceled = 4,
+}
+
+/**
+ * Guild Scheduled Event Entity Types
+ * @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-object-guild-scheduled-event-entity-types>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum GuildScheduledEventEntityType {
+    StageInstance = 1,
+    Voice = 2,
+    External = 3,
+}
+
+/**
+ * Recurrence Rule
+ * Discord's subset of the iCalendar RRULE, see `to_rrule` to convert it.
+ * @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-recurrence-rule-object>
+ */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecurrenceRule {
+    /// Starting time of the recurrence interval (ISO8601 timestamp)
+    pub start: String,
+    /// Ending time of the recurrence interval (ISO8601 timestamp)
+    pub end: Option<String>,
+    /// How often the event occurs
+    pub frequency: RecurrenceRuleFrequency,
+    /// The spacing between the events, defined by `frequency`. For example, WEEKLY with an interval of 2 is every other week
+    pub interval: u32,
+    /// Specific days within a week for the event to recur on
+    pub by_weekday: Option<Vec<RecurrenceRuleWeekday>>,
+    /// Specific days within a specific week (1-5) to recur on
+    pub by_n_weekday: Option<Vec<RecurrenceRuleNWeekday>>,
+    /// Specific months to recur on (1-12)
+    pub by_month: Option<Vec<u8>>,
+    /// Specific dates within a month to recur on (1-31)
+    pub by_month_day: Option<Vec<u8>>,
+    /// Specific days within a year to recur on (1-364)
+    pub by_year_day: Option<Vec<u16>>,
+    /// The total amount of times that the event is allowed to recur before stopping
+    pub count: Option<u32>,
+}
+
+/**
+ * Recurrence Rule Frequency
+ * @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-recurrence-rule-object-guild-scheduled-event-recurrence-rule-frequency>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum RecurrenceRuleFrequency {
+    Yearly = 0,
+    Monthly = 1,
+    Weekly = 2,
+    Daily = 3,
+}
+
+/**
+ * Recurrence Rule Weekday
+ * @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-recurrence-rule-object-guild-scheduled-event-recurrence-rule-weekday>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum RecurrenceRuleWeekday {
+    Monday = 0,
+    Tuesday = 1,
+    Wednesday = 2,
+    Thursday = 3,
+    Friday = 4,
+    Saturday = 5,
+    Sunday = 6,
+}
+
+/**
+ * Recurrence Rule N_Weekday Structure
+ * @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-recurrence-rule-object-guild-scheduled-event-recurrence-rule-nweekday-structure>
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct RecurrenceRuleNWeekday {
+    /// The week to reoccur on (1-5)
+    pub n: u8,
+    /// The day within the week to reoccur on
+    pub day: RecurrenceRuleWeekday,
+}
+
+impl RecurrenceRuleWeekday {
+    /// The two letter code that iCalendar uses for the day
+    pub fn ical_code(&self) -> &'static str {
+        match self {
+            RecurrenceRuleWeekday::Monday => "MO",
+            RecurrenceRuleWeekday::Tuesday => "TU",
+            RecurrenceRuleWeekday::Wednesday => "WE",
+            RecurrenceRuleWeekday::Thursday => "TH",
+            RecurrenceRuleWeekday::Friday => "FR",
+            RecurrenceRuleWeekday::Saturday => "SA",
+            RecurrenceRuleWeekday::Sunday => "SU",
+        }
+    }
+}
+
+impl RecurrenceRule {
+    /**
+     * Converts the rule into an iCalendar RRULE value, like `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE`.
+     * The `start` of the rule isn't included, since it is the DTSTART of the event in iCalendar.
+     * @docs <https://datatracker.ietf.org/doc/html/rfc5545#section-3.3.10>
+     */
+    pub fn to_rrule(&self) -> String {
+        let frequency = match self.frequency {
+            RecurrenceRuleFrequency::Yearly => "YEARLY",
+            RecurrenceRuleFrequency::Monthly => "MONTHLY",
+            RecurrenceRuleFrequency::Weekly => "WEEKLY",
+            RecurrenceRuleFrequency::Daily => "DAILY",
+        };
+        let mut parts = vec![
+            format!("FREQ={}", frequency),
+            format!("INTERVAL={}", self.interval.max(1)),
+        ];
+
+        let mut days: Vec<String> = Vec::new();
+        for day in self.by_weekday.iter().flatten() {
+            days.push(day.ical_code().to_string());
+        }
+        for day in self.by_n_weekday.iter().flatten() {
+            days.push(format!("{}{}", day.n, day.day.ical_code()));
+        }
+        if !days.is_empty() {
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        if let Some(months) = &self.by_month {
+            parts.push(format!("BYMONTH={}", join_numbers(months)));
+        }
+        if let Some(days) = &self.by_month_day {
+            parts.push(format!("BYMONTHDAY={}", join_numbers(days)));
+        }
+        if let Some(days) = &self.by_year_day {
+            parts.push(format!("BYYEARDAY={}", join_numbers(days)));
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        }
+        if let Some(until) = self
+            .end
+            .as_deref()
+            .and_then(super::calendar::ical_timestamp)
+        {
+            parts.push(format!("UNTIL={}", until));
+        }
+        parts.join(";")
+    }
+}
+
+fn join_numbers<T: ToString>(numbers: &[T]) -> String {
+    numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/**
+ * Guild Scheduled Event Entity Metadata
+ * @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-object-guild-scheduled-event-entity-metadata>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GuildScheduledEventEntityMetadata {
+    /// location of the event (1-100 characters), required for EXTERNAL events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/**
+ * Create Guild Scheduled Event Payload
+ * @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#create-guild-scheduled-event-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CreateGuildScheduledEvent {
+    /// the channel id of the scheduled event, required unless entity_type is EXTERNAL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<Snowflake>,
+    /// the entity metadata of the scheduled event, required for EXTERNAL events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_metadata: Option<GuildScheduledEventEntityMetadata>,
+    /// the name of the scheduled event
+    pub name: String,
+    /// the privacy level of the scheduled event
+    pub privacy_level: GuildScheduledEventPrivacyLevel,
+    /// the time to schedule the scheduled event (ISO8601 timestamp)
+    pub scheduled_start_time: String,
+    /// the time when the scheduled event is scheduled to end (ISO8601 timestamp), required for EXTERNAL events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_end_time: Option<String>,
+    /// the description of the scheduled event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// the entity type of the scheduled event
+    pub entity_type: GuildScheduledEventEntityType,
+}
+
+/**
+ * Create Stage Event Options
+ * Everything needed by `GuildScheduledEvent::create_stage_event`.
+ * The channel type, and the event's channel id and entity type are filled in automatically.
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CreateStageEvent {
+    /// The stage channel to create
+    pub channel: CreateGuildChannel,
+    /// The topic of the stage instance (1-120 characters)
+    pub topic: String,
+    /// The scheduled event to create
+    pub event: CreateGuildScheduledEvent,
+}
+
+#[cfg(feature = "rest")]
+/// The objects created by `GuildScheduledEvent::create_stage_event`
+#[derive(Clone)]
+pub struct StageEvent {
+    /// The stage channel that the event is hosted in
+    pub channel: Channel,
+    /// The stage instance, linked to the scheduled event
+    pub stage_instance: StageInstance,
+    /// The scheduled event
+    pub scheduled_event: GuildScheduledEvent,
+}
+
+#[cfg(feature = "rest")]
+impl GuildScheduledEvent {
+    /// Lists the scheduled events of a guild
+    /// @param guild_id The id of the guild
+    /// @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#list-scheduled-events-for-guild>
+    pub async fn list(
+        ctx: Context,
+        guild_id: GuildIdParam,
+    ) -> Result<Vec<GuildScheduledEvent>, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/scheduled-events".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!("{}/guilds/{}/scheduled-events", BASE_URL, guild_id))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Creates a scheduled event in a guild. Requires the MANAGE_EVENTS permission.
+    /// @param guild_id The id of the guild to create the event in
+    /// @param payload The event to create
+    /// @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#create-guild-scheduled-event>
+    pub async fn create(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        payload: CreateGuildScheduledEvent,
+    ) -> Result<GuildScheduledEvent, Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/scheduled-events".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{}/guilds/{}/scheduled-events", BASE_URL, guild_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Deletes a scheduled event. Requires the MANAGE_EVENTS permission.
+    /// @param guild_id The id of the guild that the event is in
+    /// @param event_id The id of the event to delete
+    /// @docs <https://discord.com/developers/docs/resources/guild-scheduled-event#delete-guild-scheduled-event>
+    pub async fn delete(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        event_id: Snowflake,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/scheduled-events/{guild_scheduled_event.id}"
+                .to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::DELETE)
+            .uri(format!(
+                "{}/guilds/{}/scheduled-events/{}",
+                BASE_URL, guild_id, event_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+
+    /**
+     * Creates a stage channel, a scheduled event for it, and a stage instance that is linked to the event.
+     * Creating the linked stage instance starts the event, so this is meant for events that are starting now.
+     *
+     * If any step fails, everything that was already created is deleted again, and the original error is returned.
+     * Requires the MANAGE_CHANNELS, MANAGE_EVENTS, MUTE_MEMBERS and MOVE_MEMBERS permissions.
+     *
+     * @param guild_id The id of the guild to create the event in
+     * @param options The channel, stage topic, and event to create
+     */
+    pub async fn create_stage_event(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        options: CreateStageEvent,
+    ) -> Result<StageEvent, Error> {
+        let CreateStageEvent {
+            channel,
+            topic,
+            mut event,
+        } = options;
+
+        let channel = Guild::create_channel(
+            ctx.clone(),
+            guild_id,
+            CreateGuildChannel {
+                type_: Some(ChannelType::GuildStageVoice),
+                ..channel
+            },
+        )
+        .await?;
+
+        event.channel_id = Some(channel.id);
+        event.entity_type = GuildScheduledEventEntityType::StageInstance;
+        let scheduled_event = match GuildScheduledEvent::create(ctx.clone(), guild_id, event).await
+        {
+            Ok(scheduled_event) => scheduled_event,
+            Err(e) => {
+                rollback(&ctx, Channel::delete(ctx.clone(), params::channel(channel.id)).await);
+                return Err(e);
+            }
+        };
+
+        let stage_instance = CreateStageInstance {
+            channel_id: channel.id,
+            topic,
+            privacy_level: None,
+            send_start_notification: None,
+            guild_scheduled_event_id: Some(scheduled_event.id),
+        };
+        let stage_instance = match StageInstance::create(ctx.clone(), stage_instance).await {
+            Ok(stage_instance) => stage_instance,
+            Err(e) => {
+                rollback(
+                    &ctx,
+                    GuildScheduledEvent::delete(ctx.clone(), guild_id, scheduled_event.id).await,
+                );
+                rollback(&ctx, Channel::delete(ctx.clone(), params::channel(channel.id)).await);
+                return Err(e);
+            }
+        };
+
+        Ok(StageEvent {
+            channel,
+            stage_instance,
+            scheduled_event,
+        })
+    }
+}
+
+#[cfg(feature = "rest")]
+/// Rollback failures can't be returned (the error that caused the rollback is), so they are only logged
+fn rollback<T>(ctx: &Context, result: Result<T, Error>) {
+    if let Err(e) = result {
+        if ctx.settings.debug {
+            print_debug("STAGE_EVENT", format!("Rollback failed: {}", e));
+        }
+    }
 }
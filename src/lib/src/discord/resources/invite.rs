@@ -0,0 +1,182 @@
+#[cfg(feature = "rest")]
+use hyper::{Body, Method, Request};
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use crate::discord::snowflake::Snowflake;
+#[cfg(feature = "rest")]
+use crate::{
+    core::http::rate_limit_client::{send_request, RequestRoute},
+    discord::ids::params::ChannelIdParam,
+    util::{error::Error, requests::with_audit_log_reason},
+    Context, BASE_URL,
+};
+
+#[cfg(feature = "rest")]
+use super::channel::Channel;
+use super::user::User;
+
+/**
+ * The ids of some of discord's own voice channel activities, for `Channel::create_activity_invite`.
+ * Most of them need the guild to be boosted to level 1. Discord can change which activities are available at any time.
+ * @docs <https://discord.com/developers/docs/activities/overview>
+ */
+pub mod activities {
+    use crate::discord::snowflake::Snowflake;
+
+    /// Watch youtube videos together
+    pub const WATCH_TOGETHER: Snowflake = Snowflake::new(880218394199220334);
+    pub const POKER_NIGHT: Snowflake = Snowflake::new(755827207812677713);
+    pub const CHESS_IN_THE_PARK: Snowflake = Snowflake::new(832012774040141894);
+    pub const CHECKERS_IN_THE_PARK: Snowflake = Snowflake::new(832013003968348200);
+    pub const SKETCH_HEADS: Snowflake = Snowflake::new(902271654783242291);
+    pub const LETTER_LEAGUE: Snowflake = Snowflake::new(879863686565621790);
+    pub const SPELLCAST: Snowflake = Snowflake::new(852509694341283871);
+    pub const BLAZING_8S: Snowflake = Snowflake::new(832025144389533716);
+    pub const PUTT_PARTY: Snowflake = Snowflake::new(945737671223947305);
+    pub const LAND_IO: Snowflake = Snowflake::new(903769130790969345);
+    pub const BOBBLE_LEAGUE: Snowflake = Snowflake::new(947957217959759964);
+}
+
+/**
+ * Invite Object
+ * @docs <https://discord.com/developers/docs/resources/invite#invite-object>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Invite {
+    /// the invite code, which the invite url ends with
+    pub code: String,
+    /// the user who created the invite
+    pub inviter: Option<User>,
+    /// the type of target for this voice channel invite
+    pub target_type: Option<InviteTargetType>,
+    /// the user whose stream to display for this voice channel stream invite
+    pub target_user: Option<User>,
+    /// the embedded application to open for this voice channel embedded application invite
+    pub target_application: Option<InviteApplication>,
+    /// the expiration date of this invite (ISO8601 timestamp)
+    pub expires_at: Option<String>,
+    /// how long the invite is valid for (in seconds), 0 if it never expires
+    pub max_age: Option<u64>,
+    /// the maximum number of times the invite can be used, 0 if there is no limit
+    pub max_uses: Option<u64>,
+    /// how many times the invite has been used
+    pub uses: Option<u64>,
+    /// whether the invite only grants temporary membership
+    pub temporary: Option<bool>,
+}
+
+impl Invite {
+    /// The link that opens the invite, e.g. `https://discord.gg/abc123`
+    pub fn url(&self) -> String {
+        format!("https://discord.gg/{}", self.code)
+    }
+}
+
+/**
+ * The parts of an application that are sent with an invite
+ * @docs <https://discord.com/developers/docs/resources/application#application-object>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InviteApplication {
+    /// the id of the app
+    pub id: Snowflake,
+    /// the name of the app
+    pub name: String,
+    /// the icon hash of the app
+    pub icon: Option<String>,
+    /// the description of the app
+    #[serde(default)]
+    pub description: String,
+}
+
+/**
+ * Invite Target
+ * @docs <https://discord.com/developers/docs/resources/invite#invite-object-invite-target-types>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum InviteTargetType {
+    Stream = 1,
+    EmbeddedApplication = 2,
+}
+
+/**
+ * The settings of a new invite
+ * @docs <https://discord.com/developers/docs/resources/channel#create-channel-invite-json-params>
+ */
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct CreateInvite {
+    /// how long the invite is valid for in seconds, between 0 (never expires) and 604800 (7 days). 86400 (24 hours) by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>,
+    /// how many times the invite can be used, between 0 (no limit) and 100. 0 by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u64>,
+    /// whether the invite only grants temporary membership
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temporary: Option<bool>,
+    /// if true, a new invite is made instead of reusing a similar one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unique: Option<bool>,
+    /// what the voice channel invite opens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_type: Option<InviteTargetType>,
+    /// the user whose stream to show, for a `Stream` invite. The user has to be streaming in the channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_user_id: Option<Snowflake>,
+    /// the activity to open, for an `EmbeddedApplication` invite. The application has to have the EMBEDDED flag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_application_id: Option<Snowflake>,
+}
+
+#[cfg(feature = "rest")]
+impl Channel {
+    /// Creates an invite to a channel. Requires the CREATE_INSTANT_INVITE permission
+    /// @param channel_id The id of the channel
+    /// @param invite The settings of the invite
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/channel#create-channel-invite>
+    pub async fn create_invite(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        invite: CreateInvite,
+        reason: Option<&str>,
+    ) -> Result<Invite, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/invites".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::POST)
+            .uri(format!("{}/channels/{}/invites", BASE_URL, channel_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&invite)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /**
+     * Creates an invite that starts an activity in a voice channel. Users that open the invite join the channel and the activity.
+     * The invite lasts 24 hours, and is reused if there is already one for the activity.
+     *
+     * ```rust,ignore
+     * let invite = Channel::create_activity_invite(ctx, voice_channel_id, activities::WATCH_TOGETHER).await?;
+     * ```
+     * @param channel_id The id of the voice channel
+     * @param application_id The id of the activity's application, e.g. one of the `activities` constants
+     */
+    pub async fn create_activity_invite(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        application_id: Snowflake,
+    ) -> Result<Invite, Error> {
+        let invite = CreateInvite {
+            target_type: Some(InviteTargetType::EmbeddedApplication),
+            target_application_id: Some(application_id),
+            ..CreateInvite::default()
+        };
+        Channel::create_invite(ctx, channel_id, invite, None).await
+    }
+}
@@ -1,8 +1,12 @@
 pub mod application;
+pub mod calendar;
 pub mod channel;
 pub mod emoji;
+pub mod entitlement;
 pub mod guild;
 pub mod guild_scheduled_event;
+pub mod invite;
 pub mod sticker;
 pub mod user;
 pub mod voice;
+pub mod webhook;
@@ -0,0 +1,135 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::{
+    discord::{
+        resources::channel::{message::MessageBuilder, Channel},
+        snowflake::Snowflake,
+    },
+    util::logger::print_debug,
+    Context,
+};
+
+use super::User;
+
+/**
+ * Spaces out DMs (e.g. onboarding messages for newly joined members) so that a burst of joins doesn't get the bot rate limited
+ * or flagged for spam. Messages are sent one at a time from a background task, waiting `interval` plus a random jitter between each one.
+ *
+ * Users that can't be DMed (they have DMs closed, or blocked the bot) are remembered, and messages to them are dropped instead of being retried.
+ *
+ * ```rust,ignore
+ * let dms = DmQueue::new(ctx.clone());
+ * // In a GuildMemberAdd handler
+ * dms.push(member.user.unwrap().id, MessageBuilder::new().set_content("Welcome!"));
+ * ```
+ */
+pub struct DmQueue {
+    sender: UnboundedSender<(Snowflake, MessageBuilder)>,
+    undeliverable: Arc<Mutex<HashSet<Snowflake>>>,
+}
+
+impl DmQueue {
+    /// Creates a queue that sends a DM every 2 seconds, with up to 1 second of jitter. Must be called from within a tokio runtime.
+    pub fn new(ctx: Context) -> Self {
+        Self::with_spacing(ctx, Duration::from_secs(2), Duration::from_secs(1))
+    }
+
+    /// Creates a queue with custom spacing between messages. Must be called from within a tokio runtime.
+    /// @param interval The minimum time to wait between two DMs
+    /// @param jitter The maximum random time added on top of the interval
+    pub fn with_spacing(ctx: Context, interval: Duration, jitter: Duration) -> Self {
+        let (sender, receiver) = unbounded_channel();
+        let undeliverable = Arc::new(Mutex::new(HashSet::new()));
+        tokio::spawn(send_loop(
+            ctx,
+            receiver,
+            undeliverable.clone(),
+            interval,
+            jitter,
+        ));
+
+        Self {
+            sender,
+            undeliverable,
+        }
+    }
+
+    /// Queues a DM to a user
+    /// @return false if the message was dropped because the user is known to not accept DMs
+    pub fn push(&self, user_id: Snowflake, message: MessageBuilder) -> bool {
+        if self.is_undeliverable(user_id) {
+            return false;
+        }
+        self.sender.send((user_id, message)).is_ok()
+    }
+
+    /// Whether a previous DM to the user failed
+    pub fn is_undeliverable(&self, user_id: Snowflake) -> bool {
+        self.undeliverable.lock().unwrap().contains(&user_id)
+    }
+
+    /// Forgets that DMs to a user failed, so that they are tried again (e.g. after the user says they opened their DMs)
+    pub fn reset_undeliverable(&self, user_id: Snowflake) {
+        self.undeliverable.lock().unwrap().remove(&user_id);
+    }
+}
+
+async fn send_loop(
+    ctx: Context,
+    mut receiver: UnboundedReceiver<(Snowflake, MessageBuilder)>,
+    undeliverable: Arc<Mutex<HashSet<Snowflake>>>,
+    interval: Duration,
+    jitter: Duration,
+) {
+    let mut rng = seed();
+    while let Some((user_id, message)) = receiver.recv().await {
+        // The user may have failed while this message was waiting in the queue
+        if undeliverable.lock().unwrap().contains(&user_id) {
+            continue;
+        }
+
+        let sent = match User::create_dm(ctx.clone(), user_id).await {
+            Ok(channel) => Channel::send_message(ctx.clone(), channel.id.to_string(), message)
+                .await
+                .map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = sent {
+            if ctx.settings.debug {
+                print_debug("DM_QUEUE", format!("Unable to DM {}: {}", user_id, e));
+            }
+            undeliverable.lock().unwrap().insert(user_id);
+        }
+
+        let jitter_ms = jitter.as_millis() as u64;
+        let extra = if jitter_ms == 0 {
+            0
+        } else {
+            next_random(&mut rng) % jitter_ms
+        };
+        tokio::time::sleep(interval + Duration::from_millis(extra)).await;
+    }
+}
+
+fn seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    // xorshift can't start from 0
+    nanos | 1
+}
+
+/// xorshift64, the jitter doesn't need to be cryptographically random
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
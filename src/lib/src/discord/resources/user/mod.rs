@@ -1,5 +1,9 @@
+#[cfg(feature = "rest")]
+mod dm_queue;
 mod user_flags;
 mod user_object;
 
+#[cfg(feature = "rest")]
+pub use dm_queue::DmQueue;
 pub use user_flags::UserFlags;
 pub use user_object::User;
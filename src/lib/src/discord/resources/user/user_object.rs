@@ -1,16 +1,27 @@
 use discrab_codegen::CommandArg;
+#[cfg(feature = "rest")]
 use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    core::abstraction::traits::CommandArg,
+    discord::{image_formats, snowflake::Snowflake},
+};
+#[cfg(feature = "rest")]
 use crate::{
     core::{
-        abstraction::{traits::CommandArg, context::Context},
+        abstraction::{
+            cache::{cache_or_http, cached_user, CacheOrHttp},
+            context::Context,
+        },
         http::rate_limit_client::{send_request, RequestRoute},
     },
-    discord::{image_formats, snowflake::Snowflake},
+    discord::resources::channel::Channel,
     util::error::Error,
     BASE_URL,
 };
+#[cfg(feature = "rest")]
+use serde_json::json;
 
 use super::UserFlags;
 
@@ -172,7 +183,7 @@ impl User {
 
         self.banner.as_ref().map(|banner| {
             format!(
-                "https://cdn.discordapp.com/avatars/{}/{}.{}{}",
+                "https://cdn.discordapp.com/banners/{}/{}.{}{}",
                 self.id, banner, fmt, size_str
             )
         })
@@ -180,6 +191,7 @@ impl User {
 
     /// Gets the user with a given id
     /// @param id The id of the user
+    #[cfg(feature = "rest")]
     pub async fn get(ctx: Context, id: String) -> Result<User, Error> {
         let route = RequestRoute {
             base_route: "/users".to_string(),
@@ -195,8 +207,38 @@ impl User {
         send_request::<User>(ctx, route, request_builder).await
     }
 
+    /// Gets a user from the cache, or from discord if it isn't cached
+    /// @param id The id of the user
+    #[cfg(feature = "rest")]
+    pub async fn fetch(ctx: Context, id: Snowflake) -> Result<CacheOrHttp<User>, Error> {
+        let cached = cached_user(&ctx, id).await;
+        cache_or_http(cached, User::get(ctx, id.to_string())).await
+    }
+
     /// Gets the bot's user object
+    #[cfg(feature = "rest")]
     pub async fn get_self(ctx: Context) -> Result<User, Error> {
         User::get(ctx, "@me".to_string()).await
     }
+
+    /// Opens a DM channel with a user. Returns the existing channel if there already is one.
+    /// @param recipient_id The id of the user to DM
+    /// @docs <https://discord.com/developers/docs/resources/user#create-dm>
+    #[cfg(feature = "rest")]
+    pub async fn create_dm(ctx: Context, recipient_id: Snowflake) -> Result<Channel, Error> {
+        let route = RequestRoute {
+            base_route: "/users/@me/channels".to_string(),
+            major_param: "".to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{}/users/@me/channels", BASE_URL))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::to_string(&json!({ "recipient_id": recipient_id })).unwrap(),
+            ))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
 }
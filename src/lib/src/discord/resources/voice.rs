@@ -1,9 +1,22 @@
 use discrab_codegen::CommandArg;
+#[cfg(feature = "rest")]
+use hyper::{Body, Method, Request};
 use serde::{Deserialize, Serialize};
 
 use crate::{core::abstraction::traits::CommandArg, discord::snowflake::Snowflake};
+#[cfg(feature = "rest")]
+use crate::{
+    core::{
+        abstraction::context::Context,
+        http::rate_limit_client::{send_request_noparse, RequestRoute},
+    },
+    util::error::Error,
+    BASE_URL,
+};
 
 use super::guild::guild_member::GuildMember;
+#[cfg(feature = "rest")]
+use crate::discord::ids::params::{GuildIdParam, UserIdParam};
 /**
  * Voice State
  * @docs <https://discord.com/developers/docs/resources/voice#voice-state-object>
@@ -37,3 +50,49 @@ pub struct VoiceState {
     /// the time at which the user requested to speak
     pub request_to_speak_timestamp: Option<String>,
 }
+
+/**
+ * Modify User Voice State Payload
+ * @docs <https://discord.com/developers/docs/resources/guild#modify-user-voice-state-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ModifyUserVoiceState {
+    /// the id of the channel the user is currently in
+    pub channel_id: Snowflake,
+    /// toggles the user's suppress state
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suppress: Option<bool>,
+}
+
+#[cfg(feature = "rest")]
+impl VoiceState {
+    /// Updates another user's voice state. Currently only works in stage channels, and the user must already be in the channel.
+    /// Requires the MUTE_MEMBERS permission to (un)suppress the user.
+    /// @param guild_id The id of the guild the user is in
+    /// @param user_id The id of the user to modify
+    /// @param payload The changes to make to the voice state
+    /// @docs <https://discord.com/developers/docs/resources/guild#modify-user-voice-state>
+    pub async fn modify_user(
+        ctx: Context,
+        guild_id: GuildIdParam,
+        user_id: UserIdParam,
+        payload: ModifyUserVoiceState,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/guilds/{guild.id}/voice-states/{user.id}".to_string(),
+            major_param: guild_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::PATCH)
+            .uri(format!(
+                "{}/guilds/{}/voice-states/{}",
+                BASE_URL, guild_id, user_id
+            ))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+}
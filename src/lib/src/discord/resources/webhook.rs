@@ -0,0 +1,495 @@
+#[cfg(any(feature = "rest", feature = "webhook-client"))]
+use hyper::{Body, Method, Request};
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use crate::discord::snowflake::Snowflake;
+#[cfg(any(feature = "rest", feature = "webhook-client"))]
+use crate::{
+    core::http::rate_limit_client::{send_request, send_request_noparse, RequestRoute},
+    discord::ids::params::MessageIdParam,
+    util::{
+        error::{Error, ErrorTypes},
+        multipart::message_request,
+    },
+    Context, BASE_URL,
+};
+#[cfg(feature = "rest")]
+use crate::{
+    discord::{ids::params::ChannelIdParam, image_formats::ImageData},
+    util::requests::with_audit_log_reason,
+};
+
+#[cfg(any(feature = "rest", feature = "webhook-client"))]
+use super::channel::message::{Message, MessageBuilder};
+use super::user::User;
+
+/**
+ * Webhook Object
+ * Webhooks are a low-effort way to post messages to channels in Discord. They do not require a bot user or authentication to use.
+ * @docs <https://discord.com/developers/docs/resources/webhook#webhook-object>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Webhook {
+    /// the id of the webhook
+    pub id: Snowflake,
+    /// the type of the webhook
+    #[serde(rename = "type")]
+    pub type_: WebhookType,
+    /// the guild id this webhook is for, if any
+    pub guild_id: Option<Snowflake>,
+    /// the channel id this webhook is for, if any
+    pub channel_id: Option<Snowflake>,
+    /// the user this webhook was created by (not returned when getting a webhook with its token)
+    pub user: Option<User>,
+    /// the default name of the webhook
+    pub name: Option<String>,
+    /// the default user avatar hash of the webhook
+    pub avatar: Option<String>,
+    /// the secure token of the webhook (returned for Incoming Webhooks)
+    pub token: Option<String>,
+    /// the bot/OAuth2 application that created this webhook
+    pub application_id: Option<Snowflake>,
+    /// the url used for executing the webhook (returned by the webhooks OAuth2 flow)
+    pub url: Option<String>,
+}
+
+/**
+ * Webhook Types
+ * @docs <https://discord.com/developers/docs/resources/webhook#webhook-object-webhook-types>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum WebhookType {
+    /// Incoming Webhooks can post messages to channels with a generated token
+    Incoming = 1,
+    /// Channel Follower Webhooks are internal webhooks used with Channel Following to post new messages into channels
+    ChannelFollower = 2,
+    /// Application webhooks are webhooks used with Interactions
+    Application = 3,
+}
+
+/**
+ * A message to post through a webhook, with the fields that only webhooks have.
+ * A `MessageBuilder` can be passed wherever this is taken, when none of the extra fields are needed.
+ *
+ * ```rust,ignore
+ * let message = ExecuteWebhook::new(MessageBuilder::new().set_content("Build passed"))
+ *     .username("CI")
+ *     .avatar_url("https://example.com/ci.png");
+ * Webhook::execute(ctx, webhook_id, &webhook_token, message).await?;
+ * ```
+ * @docs <https://discord.com/developers/docs/resources/webhook#execute-webhook-jsonform-params>
+ */
+#[cfg(any(feature = "rest", feature = "webhook-client"))]
+#[derive(Serialize)]
+pub struct ExecuteWebhook {
+    #[serde(flatten)]
+    message: MessageBuilder,
+    /// overrides the default username of the webhook
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    /// overrides the default avatar of the webhook
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avatar_url: Option<String>,
+    /// name of the thread to create (for webhooks in forum and media channels)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thread_name: Option<String>,
+    /// ids of the tags to apply to the thread (for webhooks in forum and media channels)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    applied_tags: Option<Vec<Snowflake>>,
+    /// the thread in the webhook's channel to post in, sent in the query string
+    #[serde(skip)]
+    thread_id: Option<Snowflake>,
+}
+
+#[cfg(any(feature = "rest", feature = "webhook-client"))]
+impl ExecuteWebhook {
+    pub fn new(message: MessageBuilder) -> Self {
+        Self {
+            message,
+            username: None,
+            avatar_url: None,
+            thread_name: None,
+            applied_tags: None,
+            thread_id: None,
+        }
+    }
+
+    /// Posts the message under a different name than the webhook's
+    #[must_use]
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    /// Posts the message with a different avatar than the webhook's
+    #[must_use]
+    pub fn avatar_url(mut self, avatar_url: &str) -> Self {
+        self.avatar_url = Some(avatar_url.to_string());
+        self
+    }
+
+    /// Creates a post with this name, when the webhook is in a forum or media channel
+    #[must_use]
+    pub fn thread_name(mut self, thread_name: &str) -> Self {
+        self.thread_name = Some(thread_name.to_string());
+        self
+    }
+
+    /// Adds a tag to the created post, when the webhook is in a forum or media channel
+    #[must_use]
+    pub fn applied_tag(mut self, tag_id: Snowflake) -> Self {
+        self.applied_tags.get_or_insert_with(Vec::new).push(tag_id);
+        self
+    }
+
+    /// Posts the message in a thread of the webhook's channel. The thread is unarchived
+    #[must_use]
+    pub fn in_thread(mut self, thread_id: Snowflake) -> Self {
+        self.thread_id = Some(thread_id);
+        self
+    }
+}
+
+#[cfg(any(feature = "rest", feature = "webhook-client"))]
+impl From<MessageBuilder> for ExecuteWebhook {
+    fn from(message: MessageBuilder) -> Self {
+        Self::new(message)
+    }
+}
+
+/**
+ * Create Webhook Payload
+ * @docs <https://discord.com/developers/docs/resources/webhook#create-webhook-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Clone)]
+pub struct CreateWebhook {
+    /// name of the webhook (1-80 characters, can't contain "clyde" or "discord")
+    pub name: String,
+    /// image for the default webhook avatar
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<ImageData>,
+}
+
+#[cfg(feature = "rest")]
+impl CreateWebhook {
+    /// @param name The name of the webhook
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            avatar: None,
+        }
+    }
+
+    /// Sets the default avatar of the webhook
+    #[must_use]
+    pub fn avatar(mut self, avatar: ImageData) -> Self {
+        self.avatar = Some(avatar);
+        self
+    }
+}
+
+/**
+ * Modify Webhook Payload
+ * Only the fields that are set are changed.
+ * @docs <https://discord.com/developers/docs/resources/webhook#modify-webhook-json-params>
+ */
+#[cfg(feature = "rest")]
+#[derive(Serialize, Clone, Default)]
+pub struct ModifyWebhook {
+    /// the default name of the webhook
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// image for the default webhook avatar. `Some(None)` removes the avatar
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<Option<ImageData>>,
+    /// the new channel id this webhook should be moved to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<Snowflake>,
+}
+
+#[cfg(feature = "rest")]
+impl Webhook {
+    /// Creates a webhook in a channel. Requires the MANAGE_WEBHOOKS permission
+    /// @param channel_id The id of the channel
+    /// @param webhook The name and avatar of the webhook
+    /// @param reason The reason that shows up in the audit log
+    /// @return The webhook, including its token
+    /// @docs <https://discord.com/developers/docs/resources/webhook#create-webhook>
+    pub async fn create(
+        ctx: Context,
+        channel_id: ChannelIdParam,
+        webhook: CreateWebhook,
+        reason: Option<&str>,
+    ) -> Result<Webhook, Error> {
+        let route = RequestRoute {
+            base_route: "/channels/{channel.id}/webhooks".to_string(),
+            major_param: channel_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::POST)
+            .uri(format!("{}/channels/{}/webhooks", BASE_URL, channel_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&webhook)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Gets a webhook. Requires the MANAGE_WEBHOOKS permission
+    /// @param webhook_id The id of the webhook
+    /// @docs <https://discord.com/developers/docs/resources/webhook#get-webhook>
+    pub async fn get(ctx: Context, webhook_id: Snowflake) -> Result<Webhook, Error> {
+        let route = RequestRoute {
+            base_route: "/webhooks/{webhook.id}".to_string(),
+            major_param: webhook_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!("{}/webhooks/{}", BASE_URL, webhook_id))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Changes the name, avatar or channel of a webhook. Requires the MANAGE_WEBHOOKS permission
+    /// @param webhook_id The id of the webhook
+    /// @param webhook The fields to change
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/webhook#modify-webhook>
+    pub async fn modify(
+        ctx: Context,
+        webhook_id: Snowflake,
+        webhook: ModifyWebhook,
+        reason: Option<&str>,
+    ) -> Result<Webhook, Error> {
+        let route = RequestRoute {
+            base_route: "/webhooks/{webhook.id}".to_string(),
+            major_param: webhook_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::PATCH)
+            .uri(format!("{}/webhooks/{}", BASE_URL, webhook_id))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&webhook)?))
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Deletes a webhook. Requires the MANAGE_WEBHOOKS permission
+    /// @param webhook_id The id of the webhook
+    /// @param reason The reason that shows up in the audit log
+    /// @docs <https://discord.com/developers/docs/resources/webhook#delete-webhook>
+    pub async fn delete(ctx: Context, webhook_id: Snowflake, reason: Option<&str>) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/webhooks/{webhook.id}".to_string(),
+            major_param: webhook_id.to_string(),
+        };
+        let request_builder = with_audit_log_reason(Request::builder(), reason)
+            .method(Method::DELETE)
+            .uri(format!("{}/webhooks/{}", BASE_URL, webhook_id))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+}
+
+#[cfg(any(feature = "rest", feature = "webhook-client"))]
+impl Webhook {
+    /// Gets a webhook with its token. No bot token is needed, and the returned webhook has no user
+    /// @param webhook_id The id of the webhook
+    /// @param webhook_token The token of the webhook
+    /// @docs <https://discord.com/developers/docs/resources/webhook#get-webhook-with-token>
+    pub async fn get_with_token(
+        ctx: Context,
+        webhook_id: Snowflake,
+        webhook_token: &str,
+    ) -> Result<Webhook, Error> {
+        let route = RequestRoute {
+            base_route: "/webhooks/{webhook.id}/{webhook.token}".to_string(),
+            major_param: webhook_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::GET)
+            .uri(format!("{}/webhooks/{}/{}", BASE_URL, webhook_id, webhook_token))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Edits a message that was posted through the webhook. Only the fields that are set on the builder are changed
+    /// @param webhook_id The id of the webhook
+    /// @param webhook_token The token of the webhook
+    /// @param message_id The id of the message
+    /// @param message The new content of the message
+    /// @param thread_id The thread the message is in, if it isn't in the webhook's channel
+    /// @docs <https://discord.com/developers/docs/resources/webhook#edit-webhook-message>
+    pub async fn edit_message(
+        ctx: Context,
+        webhook_id: Snowflake,
+        webhook_token: &str,
+        message_id: MessageIdParam,
+        message: MessageBuilder,
+        thread_id: Option<Snowflake>,
+    ) -> Result<Message, Error> {
+        message.validate()?;
+        let route = RequestRoute {
+            base_route: "/webhooks/{webhook.id}/{webhook.token}/messages/{message.id}".to_string(),
+            major_param: webhook_id.to_string(),
+        };
+        let mut payload = serde_json::to_value(&message)?;
+        // Unset fields would clear the message's content/embeds/etc, so only send the ones that are set
+        if let serde_json::Value::Object(fields) = &mut payload {
+            fields.retain(|_, value| !value.is_null());
+        }
+        let request_builder = message_request(
+            Request::builder().method(Method::PATCH).uri(format!(
+                "{}/webhooks/{}/{}/messages/{}{}",
+                BASE_URL,
+                webhook_id,
+                webhook_token,
+                message_id,
+                thread_query(thread_id)
+            )),
+            payload,
+            "",
+            message.files(),
+        );
+
+        send_request(ctx, route, request_builder).await
+    }
+
+    /// Deletes a message that was posted through the webhook
+    /// @param webhook_id The id of the webhook
+    /// @param webhook_token The token of the webhook
+    /// @param message_id The id of the message
+    /// @param thread_id The thread the message is in, if it isn't in the webhook's channel
+    /// @docs <https://discord.com/developers/docs/resources/webhook#delete-webhook-message>
+    pub async fn delete_message(
+        ctx: Context,
+        webhook_id: Snowflake,
+        webhook_token: &str,
+        message_id: MessageIdParam,
+        thread_id: Option<Snowflake>,
+    ) -> Result<(), Error> {
+        let route = RequestRoute {
+            base_route: "/webhooks/{webhook.id}/{webhook.token}/messages/{message.id}".to_string(),
+            major_param: webhook_id.to_string(),
+        };
+        let request_builder = Request::builder()
+            .method(Method::DELETE)
+            .uri(format!(
+                "{}/webhooks/{}/{}/messages/{}{}",
+                BASE_URL,
+                webhook_id,
+                webhook_token,
+                message_id,
+                thread_query(thread_id)
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        send_request_noparse(ctx, route, request_builder).await
+    }
+
+    /// Posts a message through a webhook, and waits for the created message to be returned.
+    /// No bot token is needed, so the context can be created with `Context::new(String::new())`.
+    /// @param webhook_id The id of the webhook
+    /// @param webhook_token The token of the webhook
+    /// @param message The message to send, either a `MessageBuilder` or an `ExecuteWebhook`
+    /// @docs <https://discord.com/developers/docs/resources/webhook#execute-webhook>
+    pub async fn execute(
+        ctx: Context,
+        webhook_id: Snowflake,
+        webhook_token: &str,
+        message: impl Into<ExecuteWebhook>,
+    ) -> Result<Message, Error> {
+        Self::execute_uri(
+            ctx,
+            webhook_id.to_string(),
+            format!("{}/webhooks/{}/{}", BASE_URL, webhook_id, webhook_token),
+            message.into(),
+        )
+        .await
+    }
+
+    /// Same as `Webhook::execute`, but takes the webhook's url (as copied from the discord client) instead of its id and token.
+    /// @param url The url of the webhook (https://discord.com/api/webhooks/{id}/{token})
+    /// @param message The message to send, either a `MessageBuilder` or an `ExecuteWebhook`
+    pub async fn execute_url(
+        ctx: Context,
+        url: &str,
+        message: impl Into<ExecuteWebhook>,
+    ) -> Result<Message, Error> {
+        let invalid_url = || Error::new(format!("Invalid webhook url: {}", url), ErrorTypes::PARSE);
+
+        let parsed = url::Url::parse(url).map_err(|e| {
+            Error::with_source(
+                format!("Invalid webhook url: {}", url),
+                ErrorTypes::PARSE,
+                e,
+            )
+        })?;
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        let webhook_id = match segments.as_slice() {
+            [.., "webhooks", id, _token] => id.to_string(),
+            _ => return Err(invalid_url()),
+        };
+
+        Self::execute_uri(
+            ctx,
+            webhook_id,
+            url.trim_end_matches('/').to_string(),
+            message.into(),
+        )
+        .await
+    }
+
+    async fn execute_uri(
+        ctx: Context,
+        webhook_id: String,
+        uri: String,
+        message: ExecuteWebhook,
+    ) -> Result<Message, Error> {
+        message.message.validate()?;
+        let route = RequestRoute {
+            base_route: "/webhooks/{webhook.id}/{webhook.token}".to_string(),
+            major_param: webhook_id,
+        };
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("wait", "true");
+        if let Some(thread_id) = message.thread_id {
+            query.append_pair("thread_id", &thread_id.to_string());
+        }
+        let request_builder = message_request(
+            Request::builder().method(Method::POST).uri(format!(
+                "{}{}{}",
+                uri,
+                if uri.contains('?') { '&' } else { '?' },
+                query.finish()
+            )),
+            serde_json::to_value(&message)?,
+            "",
+            message.message.files(),
+        );
+
+        send_request(ctx, route, request_builder).await
+    }
+}
+
+/// The query string that points a webhook message request at a thread
+#[cfg(any(feature = "rest", feature = "webhook-client"))]
+fn thread_query(thread_id: Option<Snowflake>) -> String {
+    thread_id
+        .map(|thread_id| format!("?thread_id={}", thread_id))
+        .unwrap_or_default()
+}
@@ -4,7 +4,7 @@ use bitfield::bitfield;
 use serde::{de, Deserialize, Deserializer, Serialize};
 
 bitfield! {
-    #[derive(Serialize, Clone, PartialEq, Eq, Hash, Copy)]
+    #[derive(Serialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Default)]
     pub struct Snowflake(u64);
     pub timestamp, _: 63, 22;
     pub worker_id, _: 21, 17;
@@ -12,6 +12,21 @@ bitfield! {
     pub increment, _: 11, 0;
 }
 
+/// The first millisecond of 2015, which the timestamps of snowflakes count from
+pub const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+
+impl Snowflake {
+    /// A snowflake from its numeric value, for ids that are known ahead of time
+    pub const fn new(id: u64) -> Self {
+        Snowflake(id)
+    }
+
+    /// When the resource with this id was created, as a unix timestamp in milliseconds
+    pub fn created_at_ms(&self) -> u64 {
+        self.timestamp() + DISCORD_EPOCH
+    }
+}
+
 impl Display for Snowflake {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -29,11 +44,39 @@ impl<'de> Deserialize<'de> for Snowflake {
     where
         D: Deserializer<'de>,
     {
-        let s: String = Deserialize::deserialize(deserializer)?;
+        struct SnowflakeVisitor;
+
+        impl<'de> de::Visitor<'de> for SnowflakeVisitor {
+            type Value = Snowflake;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a snowflake")
+            }
 
-        match s.parse() {
-            Ok(sf) => Ok(Snowflake(sf)),
-            Err(_) => Err(de::Error::custom("invalid snowflake")),
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Snowflake, E> {
+                s.parse()
+                    .map(Snowflake)
+                    .map_err(|_| de::Error::custom("invalid snowflake"))
+            }
+
+            // Snowflakes are serialized as numbers, so accept those too to read them back
+            fn visit_u64<E: de::Error>(self, id: u64) -> Result<Snowflake, E> {
+                Ok(Snowflake(id))
+            }
         }
+
+        deserializer.deserialize_any(SnowflakeVisitor)
+    }
+}
+
+impl From<u64> for Snowflake {
+    fn from(id: u64) -> Self {
+        Snowflake(id)
+    }
+}
+
+impl From<Snowflake> for u64 {
+    fn from(id: Snowflake) -> Self {
+        id.0
     }
 }
@@ -0,0 +1,252 @@
+use ed25519_dalek::{Signature, VerifyingKey};
+use hyper::{Body, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use crate::{
+    discord::{
+        resources::{entitlement::Entitlement, guild::guild_object::Guild, user::User},
+        snowflake::Snowflake,
+    },
+    util::error::{Error, ErrorTypes},
+};
+
+/// The header containing the hex encoded ed25519 signature of a request
+pub const SIGNATURE_HEADER: &str = "X-Signature-Ed25519";
+/// The header containing the timestamp that was signed along with the body
+pub const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+
+/**
+ * Checks that a request was sent by discord. This is used by both webhook events and interactions that are received over http.
+ * @param public_key The hex encoded public key of the application, found in the developer portal
+ * @param signature The hex encoded value of the `X-Signature-Ed25519` header
+ * @param timestamp The value of the `X-Signature-Timestamp` header
+ * @param body The raw request body
+ * @return true if the signature is valid
+ * @docs <https://discord.com/developers/docs/events/webhook-events#setting-up-an-endpoint-validating-security-request-headers>
+ */
+pub fn verify_signature(public_key: &str, signature: &str, timestamp: &str, body: &[u8]) -> bool {
+    match parse_public_key(public_key) {
+        Ok(key) => verify_with_key(&key, signature, timestamp, body),
+        Err(_) => false,
+    }
+}
+
+fn parse_public_key(public_key: &str) -> Result<VerifyingKey, Error> {
+    let bytes: [u8; 32] = decode_hex(public_key)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| {
+            Error::new(
+                "Public key must be 32 hex encoded bytes".to_string(),
+                ErrorTypes::PARSE,
+            )
+        })?;
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|e| Error::new(format!("Invalid public key: {}", e), ErrorTypes::PARSE))
+}
+
+fn verify_with_key(key: &VerifyingKey, signature: &str, timestamp: &str, body: &[u8]) -> bool {
+    let signature: [u8; 64] = match decode_hex(signature).and_then(|b| b.try_into().ok()) {
+        Some(signature) => signature,
+        None => return false,
+    };
+    let mut message = Vec::with_capacity(timestamp.len() + body.len());
+    message.extend_from_slice(timestamp.as_bytes());
+    message.extend_from_slice(body);
+
+    key.verify_strict(&message, &Signature::from_bytes(&signature))
+        .is_ok()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/**
+ * Webhook Event Payload
+ * @docs <https://discord.com/developers/docs/events/webhook-events#payload-structure>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WebhookEventPayload {
+    /// Version scheme for the webhook event. Currently always 1
+    pub version: u8,
+    /// The id of your app
+    pub application_id: Snowflake,
+    /// The type of webhook, either a ping or an event
+    #[serde(rename = "type")]
+    pub type_: WebhookEventPayloadType,
+    /// The event data, only present for events
+    pub event: Option<WebhookEventBody>,
+}
+
+/**
+ * Webhook Types
+ * @docs <https://discord.com/developers/docs/events/webhook-events#webhook-types>
+ */
+#[derive(Serialize_repr, Deserialize_repr, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum WebhookEventPayloadType {
+    /// A ping that discord sends to check the endpoint is active
+    Ping = 0,
+    /// An event, with the data in the `event` field
+    Event = 1,
+}
+
+/**
+ * Event Body Object
+ * @docs <https://discord.com/developers/docs/events/webhook-events#event-body-object>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WebhookEventBody {
+    /// The event type, like `APPLICATION_AUTHORIZED`
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Timestamp of when the event occurred in ISO8601 format
+    pub timestamp: String,
+    /// The data for the event, the structure depends on the event type
+    pub data: Option<Value>,
+}
+
+/**
+ * Sent when an app was authorized by a user to a server or their account
+ * @docs <https://discord.com/developers/docs/events/webhook-events#application-authorized-application-authorized-structure>
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApplicationAuthorized {
+    /// The installation context for the authorization. 0 when installed to a server, 1 when installed to a user's account
+    pub integration_type: Option<u8>,
+    /// The user who authorized the app
+    pub user: User,
+    /// List of scopes the user authorized
+    pub scopes: Vec<String>,
+    /// The server that the app was authorized for (only when integration_type is 0)
+    pub guild: Option<Guild>,
+}
+
+type WebhookEventCallback<T> = Box<dyn Fn(T) + Send + Sync>;
+
+/**
+ * Verifies and dispatches the events that discord sends to an application's webhook events url, so that they can be consumed without a gateway connection.
+ *
+ * ```rust,ignore
+ * let mut dispatcher = WebhookEventDispatcher::new(PUBLIC_KEY)?;
+ * dispatcher.on_application_authorized(|event| println!("Authorized by {}", event.user.username));
+ *
+ * // Inside of your http server
+ * let response = dispatcher.handle_request(request).await;
+ * ```
+ * @docs <https://discord.com/developers/docs/events/webhook-events>
+ */
+pub struct WebhookEventDispatcher {
+    public_key: VerifyingKey,
+    application_authorized: Vec<WebhookEventCallback<ApplicationAuthorized>>,
+    entitlement_create: Vec<WebhookEventCallback<Entitlement>>,
+}
+
+impl WebhookEventDispatcher {
+    /// Creates a dispatcher without any handlers
+    /// @param public_key The hex encoded public key of the application, found in the developer portal
+    pub fn new(public_key: &str) -> Result<Self, Error> {
+        Ok(Self {
+            public_key: parse_public_key(public_key)?,
+            application_authorized: Vec::new(),
+            entitlement_create: Vec::new(),
+        })
+    }
+
+    /// Adds a handler for the `APPLICATION_AUTHORIZED` event
+    pub fn on_application_authorized(
+        &mut self,
+        handler: impl Fn(ApplicationAuthorized) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.application_authorized.push(Box::new(handler));
+        self
+    }
+
+    /// Adds a handler for the `ENTITLEMENT_CREATE` event
+    pub fn on_entitlement_create(
+        &mut self,
+        handler: impl Fn(Entitlement) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.entitlement_create.push(Box::new(handler));
+        self
+    }
+
+    /**
+     * Verifies the request and then calls the handlers for the event.
+     * @param signature The value of the `X-Signature-Ed25519` header
+     * @param timestamp The value of the `X-Signature-Timestamp` header
+     * @param body The raw request body
+     * @return The status code to respond to discord with. Discord expects a 204 for valid requests and a 401 when the signature is invalid.
+     */
+    pub fn handle(&self, signature: &str, timestamp: &str, body: &[u8]) -> StatusCode {
+        if !verify_with_key(&self.public_key, signature, timestamp, body) {
+            return StatusCode::UNAUTHORIZED;
+        }
+
+        let payload: WebhookEventPayload = match serde_json::from_slice(body) {
+            Ok(payload) => payload,
+            Err(_) => return StatusCode::BAD_REQUEST,
+        };
+        let event = match payload.event {
+            Some(event) if payload.type_ == WebhookEventPayloadType::Event => event,
+            // Pings only need to be acknowledged
+            _ => return StatusCode::NO_CONTENT,
+        };
+        let data = event.data.unwrap_or(Value::Null);
+
+        match event.type_.as_str() {
+            "APPLICATION_AUTHORIZED" => dispatch(&self.application_authorized, data),
+            "ENTITLEMENT_CREATE" => dispatch(&self.entitlement_create, data),
+            // Unknown events still have to be acknowledged, or discord will eventually disable the endpoint
+            _ => StatusCode::NO_CONTENT,
+        }
+    }
+
+    /// Same as `handle`, but reads the headers and the body from a hyper request
+    pub async fn handle_request(&self, request: Request<Body>) -> Response<Body> {
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let signature = header(SIGNATURE_HEADER);
+        let timestamp = header(TIMESTAMP_HEADER);
+
+        let status = match hyper::body::to_bytes(request.into_body()).await {
+            Ok(body) => self.handle(&signature, &timestamp, &body),
+            Err(_) => StatusCode::BAD_REQUEST,
+        };
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = status;
+        response
+    }
+}
+
+fn dispatch<T: serde::de::DeserializeOwned + Clone>(
+    handlers: &[WebhookEventCallback<T>],
+    data: Value,
+) -> StatusCode {
+    if handlers.is_empty() {
+        return StatusCode::NO_CONTENT;
+    }
+    match serde_json::from_value::<T>(data) {
+        Ok(data) => {
+            for handler in handlers {
+                handler(data.clone());
+            }
+            StatusCode::NO_CONTENT
+        }
+        Err(_) => StatusCode::BAD_REQUEST,
+    }
+}
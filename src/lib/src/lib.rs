@@ -3,13 +3,45 @@ pub mod core;
 mod discord;
 mod util;
 
+pub use crate::core::abstraction::context::Context;
+pub use crate::core::abstraction::memory_cache::{Cache, CacheConfig, CacheSizes};
+pub use crate::core::abstraction::cache_backend::{CacheBackend, CacheKey, CacheResource};
+#[cfg(feature = "cache-sqlite")]
+pub use crate::core::abstraction::sqlite_cache::SqliteCache;
+#[cfg(feature = "announcements")]
+pub use crate::core::abstraction::announcements::{
+    AnnouncementSchedule, AnnouncementScheduler, AnnouncementStore, FileAnnouncementStore,
+    ScheduledAnnouncement,
+};
+pub use crate::core::abstraction::traits::CommandChoice;
+pub use crate::core::settings::GatewayEncoding;
+#[cfg(feature = "rest")]
+pub use crate::core::abstraction::outbox::{FileOutboxStore, Outbox, OutboxEntry, OutboxStore};
+#[cfg(feature = "gateway")]
 pub use crate::core::abstraction::{
-    traits::{CommandHandler, EventHandler, Registerable, RegFns, SubRegisterable, SubsVector, CommonHandler},
+    traits::{AutocompleteHandler, CommandHandler, ComponentHandler, EventHandler, ModalHandler, Registerable, RegFns, SubRegisterable, SubsVector, CommonHandler},
     bot::Bot,
-    context::Context,
     event_dispatcher::{EventDispatcher, Events},
+    middleware::{EventMiddleware, Flow, GatewayEvent},
     interaction_router::InteractionRouter,
+    command_group::CommandGroup,
+    handler_registry::HandlerRegistry,
+    observer::handler_ptr,
+    command_registry::CommandRegistry,
+    diagnostics::{DebugReport, QueueDepths, ShardReport},
+    shutdown::ShutdownHandle,
+    user_throttle::UserThrottle,
+};
+#[cfg(all(feature = "gateway", feature = "rest"))]
+pub use crate::core::abstraction::prefix_commands::{
+    ArgError, PrefixArg, PrefixArgs, PrefixCommand, PrefixCommands, Rest,
 };
+#[cfg(feature = "gateway")]
+pub use crate::core::interactions::handler::events::Intents;
+#[cfg(feature = "event-forwarder")]
+pub use crate::core::abstraction::event_forwarder::EventForwarder;
+#[cfg(feature = "wasm-plugins")]
+pub use crate::core::abstraction::wasm_plugin::{WasmLimits, WasmPlugin};
 
 pub mod macros {
     pub use discrab_codegen::*;
@@ -18,11 +50,18 @@ pub mod macros {
 pub mod builders {
     pub use crate::core::abstraction::option_builder::*;
     pub use crate::api::channel::message::MessageBuilder;
+    pub use crate::core::interactions::typing::{InteractionMessageBuilder, ModalBuilder};
+    pub use crate::core::interactions::message::{
+        ActionRow, Button, MessageSelectOption, SelectMenu, TextInput, TextInputStyle,
+    };
+    #[cfg(feature = "gateway")]
+    pub use crate::core::abstraction::option_rules::OptionRule;
 }
 
 /**
  * For internal use only. Is public because it is used by the proc macros
  */
+#[cfg(feature = "gateway")]
 #[doc(hidden)]
 pub mod __internal__ {
     pub use crate::core::abstraction::traits::__InternalEventHandler;
@@ -37,17 +76,44 @@ pub mod api {
         ApplicationCommandOption, ApplicationCommandOptionChoice,
         ApplicationCommandOptionValue, ApplicationCommandOptionType,
     };
+    pub use crate::discord::color::Color;
+    pub use crate::discord::image_formats::{self, ImageData};
     pub use crate::discord::permissions::Permissions;
     pub use crate::discord::resources::channel::embed;
     pub use crate::discord::resources::channel::message::Message;
+    pub use crate::discord::resources::channel::reaction::ReactionType;
+    #[cfg(feature = "rest")]
+    pub use crate::discord::resources::channel::message::Delivery;
+    #[cfg(feature = "rest")]
+    pub use crate::util::pagination::{PageQuery, Paginated};
+    #[cfg(feature = "rest")]
+    pub use crate::discord::resources::channel::history::MessageHistory;
+    #[cfg(feature = "rest")]
+    pub use crate::discord::resources::channel::search::MessageFilter;
     pub use crate::discord::resources::channel::Channel;
     pub use crate::discord::resources::*;
     pub use crate::discord::snowflake::Snowflake;
+    pub use crate::discord::ids::{ChannelId, GuildId, MessageId, RoleId, UserId};
+    pub use crate::discord::gateway::activity::{
+        Activity, ActivityAssets, ActivityButton, ActivityParty, ActivityTimestamps, ActivityType,
+    };
+    pub use crate::discord::gateway::presence::{Status, UpdatePresence};
+}
+
+/**
+ * The error type returned by the library, and discord's json error codes
+ */
+pub mod error {
+    pub use crate::util::error::{
+        Error, ErrorTypes, FieldError, CANNOT_DM_USER, INVALID_FORM_BODY, MISSING_ACCESS,
+        MISSING_PERMISSIONS, UNKNOWN_CHANNEL, UNKNOWN_MEMBER, UNKNOWN_MESSAGE,
+    };
 }
 
 /**
  * Objects associated with different events
  */
+#[cfg(feature = "gateway")]
 pub mod events {
     pub use crate::api::guild::guild_object::{Guild, UnavailableGuild};
     pub use crate::api::guild::stage_instance::StageInstance;
@@ -57,10 +123,42 @@ pub mod events {
     pub use crate::api::{Channel, Message};
     pub use crate::core::interactions::handler::events::dispatch_payloads::*;
     pub use crate::core::interactions::{
-        interaction_event::InteractionCtx, typing::Interaction
+        interaction_event::InteractionCtx, long_task::LongTask, typing::Interaction
     };
     pub use crate::discord::gateway::presence::PresenceUpdate;
 }
 
+/**
+ * Conversions between emoji shortcodes and unicode emojis
+ */
+#[cfg(feature = "emoji-data")]
+pub mod emoji_data {
+    pub use crate::util::shortcodes::*;
+}
+
+/**
+ * Posting log records to a discord channel
+ */
+#[cfg(feature = "discord-logger")]
+pub mod logging {
+    pub use crate::util::discord_logger::DiscordLogger;
+}
+
+/**
+ * Events that discord delivers to an application's webhook events url
+ */
+#[cfg(feature = "webhook-events")]
+pub mod webhook_events {
+    pub use crate::discord::webhook_events::*;
+}
+
+/**
+ * Utilities for testing handlers without connecting to discord
+ */
+#[cfg(feature = "gateway")]
+pub mod testing {
+    pub use crate::core::interactions::testing::{CapturedRequest, TestInteraction};
+}
+
 #[doc(hidden)]
 pub(crate) static BASE_URL: &str = "https://discord.com/api/v9";
@@ -0,0 +1,173 @@
+use std::{collections::VecDeque, time::Duration};
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::{
+    discord::{
+        ids::params,
+        resources::channel::{
+            embed::chunk_into_embeds,
+            message::{Message, MessageBuilder},
+        },
+        snowflake::Snowflake,
+    },
+    util::logger::print_debug,
+    Context,
+};
+
+/// The most characters posted at once. The embeds of a message can't have more than 6000 characters together
+const MAX_BATCH_LENGTH: usize = 5500;
+/// Longer records are cut off, so that one record can't fill a whole batch
+const MAX_RECORD_LENGTH: usize = 1000;
+
+/**
+ * A `log` logger that posts the log records to a discord channel, so that small bots can see their own warnings in discord.
+ * Records are batched and posted as embeds at most once every flush interval (5 seconds by default), which keeps the logger
+ * well under the channel's rate limit no matter how much is logged. If discord can't keep up, the oldest records are dropped.
+ *
+ * ```rust,ignore
+ * DiscordLogger::new(ctx, log_channel_id)
+ *     .level(LevelFilter::Warn)
+ *     .init()
+ *     .unwrap();
+ * log::warn!("Couldn't load the config, using the defaults");
+ * ```
+ * Must be initialized inside a tokio runtime.
+ */
+pub struct DiscordLogger {
+    ctx: Context,
+    channel_id: Snowflake,
+    level: LevelFilter,
+    flush_interval: Duration,
+    max_buffered: usize,
+}
+
+impl DiscordLogger {
+    /// Creates a logger that posts warnings and errors to a channel
+    /// @param channel_id The channel to post the records to
+    pub fn new(ctx: Context, channel_id: Snowflake) -> Self {
+        Self {
+            ctx,
+            channel_id,
+            level: LevelFilter::Warn,
+            flush_interval: Duration::from_secs(5),
+            max_buffered: 500,
+        }
+    }
+
+    /// Sets the most verbose level that is posted
+    #[must_use]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets how often the buffered records are posted
+    #[must_use]
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Sets how many records are kept while waiting to be posted, older records are dropped after that
+    #[must_use]
+    pub fn max_buffered(mut self, max_buffered: usize) -> Self {
+        self.max_buffered = max_buffered.max(1);
+        self
+    }
+
+    /// Installs the logger as the global `log` logger and starts posting
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        let (sender, receiver) = unbounded_channel();
+        let level = self.level;
+        log::set_boxed_logger(Box::new(ChannelLog { level, sender }))?;
+        log::set_max_level(level);
+        tokio::spawn(self.post_loop(receiver));
+        Ok(())
+    }
+
+    /// Collects the records, and posts them every flush interval
+    async fn post_loop(self, mut receiver: UnboundedReceiver<String>) {
+        let mut buffer: VecDeque<String> = VecDeque::new();
+        let mut dropped = 0;
+
+        loop {
+            tokio::time::sleep(self.flush_interval).await;
+            while let Ok(line) = receiver.try_recv() {
+                if buffer.len() == self.max_buffered {
+                    buffer.pop_front();
+                    dropped += 1;
+                }
+                buffer.push_back(line);
+            }
+            if buffer.is_empty() {
+                continue;
+            }
+
+            // Post the oldest records that fit in one message, the rest wait for the next flush
+            let mut batch = Vec::new();
+            let mut batch_length = 0;
+            if dropped > 0 {
+                batch.push(format!("*{} older records were dropped*", dropped));
+            }
+            for line in &buffer {
+                let length = line.chars().count() + 1;
+                if batch_length + length > MAX_BATCH_LENGTH && batch_length > 0 {
+                    break;
+                }
+                batch.push(line.clone());
+                batch_length += length;
+            }
+            let posted = batch.len() - if dropped > 0 { 1 } else { 0 };
+
+            let mut message = MessageBuilder::new();
+            for embed in chunk_into_embeds("Logs", &batch.join("\n")) {
+                message = message.embed(embed);
+            }
+            match Message::send(self.ctx.clone(), params::channel(self.channel_id), message).await {
+                Ok(_) => {
+                    buffer.drain(..posted);
+                    dropped = 0;
+                }
+                Err(e) => {
+                    if self.ctx.settings.debug {
+                        print_debug("LOGGER", format!("Unable to post the logs: {}", e));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The part of the logger that is installed globally, which only hands the records to the posting task
+struct ChannelLog {
+    level: LevelFilter,
+    sender: UnboundedSender<String>,
+}
+
+impl Log for ChannelLog {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Requests made while posting the logs must never log to discord again
+        metadata.level() <= self.level && !metadata.target().starts_with("discrab")
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let icon = match record.level() {
+            Level::Error => "🔴",
+            Level::Warn => "🟡",
+            Level::Info => "🔵",
+            Level::Debug | Level::Trace => "⚪",
+        };
+        let mut line = format!("{} `{}` {}", icon, record.target(), record.args());
+        if line.chars().count() > MAX_RECORD_LENGTH {
+            line = line.chars().take(MAX_RECORD_LENGTH - 1).collect::<String>() + "…";
+        }
+        let _ = self.sender.send(line);
+    }
+
+    fn flush(&self) {}
+}
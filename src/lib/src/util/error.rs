@@ -1,25 +1,390 @@
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
+use serde_json::Value;
+
+use crate::discord::{permissions::Permissions, resources::channel::embed::EmbedLimitError};
+
+/**
+ * Everything that can go wrong in discrab. Match on the variant to handle the errors discord sends differently from the rest:
+ *
+ * ```rust,ignore
+ * match Message::send(ctx, channel_id, message).await {
+ *     Err(Error::Http { discord_code: Some(UNKNOWN_CHANNEL), .. }) => forget_channel(channel_id),
+ *     Err(Error::RateLimited { retry_after, .. }) => retry_in(retry_after),
+ *     Err(e) => return Err(e),
+ *     Ok(_) => {}
+ * }
+ * ```
+ */
 #[derive(Debug)]
-pub struct Error {
+#[non_exhaustive]
+pub enum Error {
+    /// Discord kept rate limiting the request until it ran out of attempts.
+    /// Only happens when the local rate limiter is turned off, e.g. when going through a proxy
+    RateLimited {
+        /// How long to wait before making another request
+        retry_after: Duration,
+        /// If true, every request is rate limited, not only the ones to this route
+        global: bool,
+    },
+    /// Discord rejected the request
+    /// @docs <https://discord.com/developers/docs/topics/opcodes-and-status-codes#json>
+    Http {
+        /// The http status of the response
+        status: u16,
+        /// Discord's json error code, e.g. `MISSING_PERMISSIONS`. None if the body wasn't discord's error json
+        discord_code: Option<u64>,
+        message: String,
+        /// The fields of the request that were invalid, if discord rejected the body (`INVALID_FORM_BODY`)
+        errors: Vec<FieldError>,
+    },
+    /// The gateway connection failed
+    #[cfg(feature = "gateway")]
+    Gateway(Box<tokio_tungstenite::tungstenite::Error>),
+    /// Something couldn't be serialized, or something discord sent couldn't be parsed
+    Serde(serde_json::Error),
+    /// A permission check (like `ctx.assert_permissions`) found that the bot is missing these permissions
+    MissingPermissions(Permissions),
+    /// A message's embeds are over one of discord's limits, so it wasn't sent
+    EmbedLimit(EmbedLimitError),
+    /// Every other error, like a check that failed before making a request
+    Other {
+        message: String,
+        kind: ErrorTypes,
+        /// The underlying error that caused this one, if there is one
+        source: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    },
+}
+
+/**
+ * A field of the request body that discord found invalid
+ * @docs <https://discord.com/developers/docs/reference#error-messages>
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    /// Where the field is in the body, e.g. `embeds.0.title`
+    pub path: String,
+    /// Discord's code for what is wrong with it, e.g. `BASE_TYPE_MAX_LENGTH`
+    pub code: String,
     pub message: String,
-    pub code: ErrorTypes,
+}
+
+/// Discord's json error code for when the channel doesn't exist (anymore)
+/// @docs <https://discord.com/developers/docs/topics/opcodes-and-status-codes#json-json-error-codes>
+pub const UNKNOWN_CHANNEL: u64 = 10003;
+/// Discord's json error code for when the user isn't a member of the guild
+pub const UNKNOWN_MEMBER: u64 = 10007;
+/// Discord's json error code for when the message doesn't exist (anymore)
+pub const UNKNOWN_MESSAGE: u64 = 10008;
+/// Discord's json error code for when the bot can't see the channel (or guild)
+pub const MISSING_ACCESS: u64 = 50001;
+/// Discord's json error code for when the user has their DMs closed, or blocked the bot
+pub const CANNOT_DM_USER: u64 = 50007;
+/// Discord's json error code for when the bot is missing a permission it needs for the request
+pub const MISSING_PERMISSIONS: u64 = 50013;
+/// Discord's json error code for when the request body is invalid, see `Error::field_errors`
+pub const INVALID_FORM_BODY: u64 = 50035;
+
+/// The body of an error response from discord
+#[derive(serde::Deserialize)]
+struct ApiErrorBody {
+    code: u64,
+    message: String,
+    #[serde(default)]
+    errors: Option<Value>,
+}
+
+/// The body of a 429 response
+#[derive(serde::Deserialize)]
+struct RateLimitedBody {
+    retry_after: f64,
+    #[serde(default)]
+    global: bool,
 }
 
 impl Error {
-    pub fn new(message: String, code: ErrorTypes) -> Error {
-        Error { message, code }
+    pub fn new(message: String, kind: ErrorTypes) -> Error {
+        Error::Other {
+            message,
+            kind,
+            source: None,
+        }
+    }
+
+    /// Creates an error that was caused by another error, so that it can be retrieved with `source()`
+    pub fn with_source(
+        message: String,
+        kind: ErrorTypes,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Error {
+        Error::Other {
+            message,
+            kind,
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /**
+     * Creates an error from a response that discord rejected
+     * @param status The http status of the response
+     * @param body The body of the response, which usually has discord's error code and message
+     */
+    pub fn from_response(status: u16, body: &[u8]) -> Error {
+        if status == 429 {
+            // Some proxies answer with an empty body, in which case there is nothing better than a guess
+            let (retry_after, global) = serde_json::from_slice::<RateLimitedBody>(body)
+                .map_or((1f64, false), |body| (body.retry_after, body.global));
+            return Error::RateLimited {
+                retry_after: Duration::from_secs_f64(retry_after.max(0f64)),
+                global,
+            };
+        }
+        match serde_json::from_slice::<ApiErrorBody>(body) {
+            Ok(api_error) => {
+                let mut errors = Vec::new();
+                if let Some(fields) = &api_error.errors {
+                    flatten_field_errors(fields, String::new(), &mut errors);
+                }
+                Error::Http {
+                    status,
+                    discord_code: Some(api_error.code),
+                    message: api_error.message,
+                    errors,
+                }
+            }
+            Err(_) => Error::Http {
+                status,
+                discord_code: None,
+                message: String::from_utf8_lossy(body).into_owned(),
+                errors: Vec::new(),
+            },
+        }
+    }
+
+    /// Creates an error for when a permission check (like `ctx.assert_permissions`) found that the bot is missing permissions
+    /// @param missing The permissions that the bot doesn't have
+    pub fn from_missing_permissions(missing: Permissions) -> Error {
+        Error::MissingPermissions(missing)
+    }
+
+    /// What kind of error this is
+    pub fn kind(&self) -> ErrorTypes {
+        match self {
+            Error::RateLimited { .. } => ErrorTypes::RATE_LIMITED,
+            Error::Http { .. } => ErrorTypes::REQUEST,
+            #[cfg(feature = "gateway")]
+            Error::Gateway(_) => ErrorTypes::GATEWAY,
+            Error::Serde(_) => ErrorTypes::PARSE,
+            Error::MissingPermissions(_) => ErrorTypes::MISSING_PERMISSIONS,
+            Error::EmbedLimit(_) => ErrorTypes::EMBED_LIMIT,
+            Error::Other { kind, .. } => *kind,
+        }
+    }
+
+    /// The http status of the response, if the error came from discord rejecting the request
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Http { status, .. } => Some(*status),
+            Error::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+
+    /// Discord's json error code, e.g. `MISSING_PERMISSIONS`
+    /// @docs <https://discord.com/developers/docs/topics/opcodes-and-status-codes#json-json-error-codes>
+    pub fn api_code(&self) -> Option<u64> {
+        match self {
+            Error::Http { discord_code, .. } => *discord_code,
+            _ => None,
+        }
+    }
+
+    /// The fields of the request body that discord found invalid. Empty for every other error
+    pub fn field_errors(&self) -> &[FieldError] {
+        match self {
+            Error::Http { errors, .. } => errors,
+            _ => &[],
+        }
+    }
+
+    /// How long to wait before trying again, if the request was rate limited
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::RateLimited { retry_after, .. } => Some(*retry_after),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the request failed because the bot can't see the channel, or is missing a permission in it
+    pub fn is_missing_permissions(&self) -> bool {
+        match self {
+            Error::MissingPermissions(_) => true,
+            Error::Http {
+                status: 403,
+                discord_code,
+                ..
+            } => matches!(discord_code, Some(MISSING_ACCESS | MISSING_PERMISSIONS) | None),
+            _ => false,
+        }
+    }
+
+    /// The permissions the bot is missing, if the error came from a permission check like `ctx.assert_permissions`.
+    /// Discord doesn't say which permissions were missing when it rejects a request, so this is None for those
+    pub fn missing_permissions(&self) -> Option<Permissions> {
+        match self {
+            Error::MissingPermissions(missing) => Some(*missing),
+            _ => None,
+        }
+    }
+
+    /// Returns true if a DM couldn't be sent because the user has their DMs closed, or blocked the bot
+    pub fn is_cannot_dm(&self) -> bool {
+        self.api_code() == Some(CANNOT_DM_USER)
+    }
+
+    /// The embed limit that a message went over, if the message was rejected before it was sent
+    pub fn embed_limit(&self) -> Option<&EmbedLimitError> {
+        match self {
+            Error::EmbedLimit(limit) => Some(limit),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Turns discord's nested `errors` object into a list of fields.
+ * The invalid fields are the objects with an `_errors` array, and the keys on the way to them make up the path
+ */
+fn flatten_field_errors(value: &Value, path: String, errors: &mut Vec<FieldError>) {
+    let Some(object) = value.as_object() else {
+        return;
+    };
+    for (key, value) in object {
+        if key == "_errors" {
+            for error in value.as_array().into_iter().flatten() {
+                errors.push(FieldError {
+                    path: path.clone(),
+                    code: error["code"].as_str().unwrap_or_default().to_string(),
+                    message: error["message"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+        } else if path.is_empty() {
+            flatten_field_errors(value, key.clone(), errors);
+        } else {
+            flatten_field_errors(value, format!("{}.{}", path, key), errors);
+        }
+    }
+}
+
+impl From<EmbedLimitError> for Error {
+    fn from(e: EmbedLimitError) -> Self {
+        Error::EmbedLimit(e)
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Error ({:?}) - {}", self.code, self.message)
+        write!(f, "Error ({:?}) - ", self.kind())?;
+        match self {
+            Error::RateLimited {
+                retry_after,
+                global,
+            } => write!(
+                f,
+                "Rate limited{}, retry after {:.3}s",
+                if *global { " globally" } else { "" },
+                retry_after.as_secs_f64()
+            ),
+            Error::Http {
+                status,
+                discord_code: Some(code),
+                message,
+                errors,
+            } => {
+                write!(
+                    f,
+                    "Request failed with status {}: {} (code {})",
+                    status, message, code
+                )?;
+                for error in errors {
+                    write!(f, "; {}: {}", error.path, error.message)?;
+                }
+                Ok(())
+            }
+            Error::Http { status, .. } => write!(f, "Request failed with status {}", status),
+            #[cfg(feature = "gateway")]
+            Error::Gateway(e) => write!(f, "{}", e),
+            Error::Serde(e) => write!(f, "{}", e),
+            Error::MissingPermissions(missing) => write!(f, "Missing permissions: {:?}", missing),
+            Error::EmbedLimit(limit) => write!(f, "{}", limit),
+            Error::Other { message, .. } => write!(f, "{}", message),
+        }
     }
 }
 
-#[derive(Debug)]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "gateway")]
+            Error::Gateway(e) => Some(e.as_ref()),
+            Error::Serde(e) => Some(e),
+            Error::Other {
+                source: Some(source),
+                ..
+            } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<hyper::Error> for Error {
+    fn from(e: hyper::Error) -> Self {
+        Error::with_source(e.to_string(), ErrorTypes::REQUEST, e)
+    }
+}
+
+impl From<hyper::http::Error> for Error {
+    fn from(e: hyper::http::Error) -> Self {
+        Error::with_source(e.to_string(), ErrorTypes::REQUEST, e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Serde(e)
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(e: url::ParseError) -> Self {
+        Error::with_source(e.to_string(), ErrorTypes::PARSE, e)
+    }
+}
+
+#[cfg(feature = "gateway")]
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::Gateway(Box::new(e))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
 pub enum ErrorTypes {
     PARSE,
     REQUEST,
+    /// Discord kept rate limiting the request, see `Error::RateLimited`
+    RATE_LIMITED,
+    /// The gateway connection failed, see `Error::Gateway`
+    GATEWAY,
+    /// The guild doesn't have the feature (like `ROLE_ICONS`) that the request needs
+    MISSING_FEATURE,
+    /// The bot doesn't have the permissions that were checked for, see `Error::missing_permissions`
+    MISSING_PERMISSIONS,
+    /// The interaction was responded to with an ephemeral message, which can't be used like a normal one (reacted to, made public, etc.)
+    EPHEMERAL_RESPONSE,
+    /// A WASM plugin couldn't be loaded, or failed while it was running
+    PLUGIN,
+    /// A message's embeds are over one of discord's limits, see `Error::embed_limit`
+    EMBED_LIMIT,
 }
@@ -1,4 +1,12 @@
 pub mod error;
 pub mod logger;
 pub mod requests;
-pub mod common;
\ No newline at end of file
+pub mod common;
+#[cfg(feature = "discord-logger")]
+pub mod discord_logger;
+pub mod multipart;
+#[cfg(feature = "rest")]
+pub mod pagination;
+pub mod stringified;
+#[cfg(feature = "emoji-data")]
+pub mod shortcodes;
@@ -0,0 +1,133 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hyper::{http::request::Builder, Body, Request};
+use serde_json::{json, Value};
+
+use crate::discord::resources::channel::attachment::AttachmentFile;
+
+/// A multipart/form-data body
+/// @docs <https://discord.com/developers/docs/reference#uploading-files>
+pub struct Multipart {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl Multipart {
+    pub fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        Self {
+            boundary: format!("discrab-boundary-{:x}", nanos),
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds a json field to the form
+    pub fn add_json(&mut self, name: &str, json: &str) {
+        self.add_part(name, None, "application/json", json.as_bytes());
+    }
+
+    /// Adds a file to the form
+    pub fn add_file(&mut self, name: &str, filename: &str, content_type: &str, data: &[u8]) {
+        self.add_part(name, Some(filename), content_type, data);
+    }
+
+    fn add_part(&mut self, name: &str, filename: Option<&str>, content_type: &str, data: &[u8]) {
+        self.body
+            .extend_from_slice(format!("--{}\r\n", self.boundary).as_bytes());
+        let disposition = match filename {
+            Some(filename) => format!(
+                "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
+                name,
+                filename.replace('"', "")
+            ),
+            None => format!("Content-Disposition: form-data; name=\"{}\"\r\n", name),
+        };
+        self.body.extend_from_slice(disposition.as_bytes());
+        self.body
+            .extend_from_slice(format!("Content-Type: {}\r\n\r\n", content_type).as_bytes());
+        self.body.extend_from_slice(data);
+        self.body.extend_from_slice(b"\r\n");
+    }
+
+    /// The value of the content-type header for this body
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Finishes the form, and returns the body
+    pub fn finish(mut self) -> Vec<u8> {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        self.body
+    }
+}
+
+impl Default for Multipart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+ * Finishes a request that sends a message payload, uploading the files with it if there are any.
+ * Without files, the payload is sent as plain json. With files, the request becomes multipart, and an attachment
+ * entry for each file is added to the object at `attachments_at` (a json pointer, "" for the root of the payload).
+ *
+ * @param builder The request, with everything but the content-type and body set
+ * @param payload The json payload of the message
+ * @param attachments_at Where in the payload the message's attachments array lives
+ * @param files The files to upload
+ */
+pub fn message_request(
+    builder: Builder,
+    mut payload: Value,
+    attachments_at: &str,
+    files: &[AttachmentFile],
+) -> Request<Body> {
+    if files.is_empty() {
+        return builder
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .unwrap();
+    }
+
+    let attachments: Vec<Value> = files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| {
+            let mut attachment = json!({
+                "id": i,
+                "filename": file.filename,
+                "description": file.description,
+            });
+            if let (Some(duration_secs), Some(waveform)) = (file.duration_secs, &file.waveform) {
+                attachment["duration_secs"] = json!(duration_secs);
+                attachment["waveform"] = json!(waveform);
+            }
+            attachment
+        })
+        .collect();
+    if let Some(Value::Object(target)) = payload.pointer_mut(attachments_at) {
+        target.insert("attachments".to_string(), Value::Array(attachments));
+    }
+
+    let mut form = Multipart::new();
+    form.add_json("payload_json", &payload.to_string());
+    for (i, file) in files.iter().enumerate() {
+        form.add_file(
+            &format!("files[{}]", i),
+            &file.filename,
+            file.content_type(),
+            &file.data,
+        );
+    }
+
+    builder
+        .header("content-type", form.content_type())
+        .body(Body::from(form.finish()))
+        .unwrap()
+}
@@ -0,0 +1,209 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    task::{Context as TaskContext, Poll},
+};
+
+use futures_util::{stream, Stream, StreamExt};
+
+use crate::{discord::snowflake::Snowflake, util::error::Error, Context};
+
+/**
+ * The query parameters of one page of a list endpoint.
+ * Every list endpoint takes one of these, so fetching a single page looks the same everywhere.
+ * Only one of `before` or `after` should be set.
+ *
+ * ```rust,ignore
+ * let bans = Guild::get_bans(ctx, guild_id, PageQuery::new().after(last_seen).limit(100)).await?;
+ * ```
+ */
+#[derive(Clone, Debug)]
+pub struct PageQuery<C = Snowflake> {
+    /// Only return entries before this cursor
+    pub before: Option<C>,
+    /// Only return entries after this cursor
+    pub after: Option<C>,
+    /// The max number of entries to return. Each endpoint has its own default and maximum
+    pub limit: Option<u64>,
+}
+
+impl<C> Default for PageQuery<C> {
+    fn default() -> Self {
+        Self {
+            before: None,
+            after: None,
+            limit: None,
+        }
+    }
+}
+
+impl<C: Display> PageQuery<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn before(mut self, cursor: C) -> Self {
+        self.before = Some(cursor);
+        self
+    }
+
+    #[must_use]
+    pub fn after(mut self, cursor: C) -> Self {
+        self.after = Some(cursor);
+        self
+    }
+
+    #[must_use]
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The query string for the request, without the leading '?'
+    pub(crate) fn to_query_string(&self) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(before) = &self.before {
+            query.append_pair("before", &before.to_string());
+        }
+        if let Some(after) = &self.after {
+            query.append_pair("after", &after.to_string());
+        }
+        if let Some(limit) = self.limit {
+            query.append_pair("limit", &limit.to_string());
+        }
+        query.finish()
+    }
+}
+
+/// Which way a list is walked
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Direction {
+    /// Each page continues from before the last entry of the previous one (newest first)
+    Backward,
+    /// Each page continues from after the last entry of the previous one (oldest first)
+    Forward,
+}
+
+/// One page of a list, as returned by the fetch function of a `Paginated`
+pub(crate) struct Page<T> {
+    pub items: Vec<T>,
+    /// Whether there could be another page after this one
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    /// For the endpoints that only return a list. A page that isn't full is the last one
+    pub fn from_list(items: Vec<T>, per_page: u64) -> Self {
+        Self {
+            has_more: items.len() as u64 >= per_page,
+            items,
+        }
+    }
+}
+
+/**
+ * Every entry of a list endpoint, fetched page by page as the stream is polled.
+ * Stopping early won't make any extra requests. If a request fails, the error is yielded and the stream ends.
+ *
+ * ```rust,ignore
+ * let mut bans = Guild::bans(ctx, guild_id);
+ * while let Some(ban) = bans.next().await {
+ *     let ban = ban?;
+ *     // ...
+ * }
+ * ```
+ */
+pub struct Paginated<T> {
+    inner: Pin<Box<dyn Stream<Item = Result<T, Error>> + Send>>,
+}
+
+impl<T: Send + 'static> Paginated<T> {
+    /// Walks a list endpoint.
+    /// @param direction Which cursor each request after the first one sets
+    /// @param start The query of the first request. Its limit is used for every page
+    /// @param fetch Gets one page
+    /// @param cursor_of The cursor to continue from after an entry
+    pub(crate) fn new<C, F, Fut>(
+        ctx: Context,
+        direction: Direction,
+        start: PageQuery<C>,
+        fetch: F,
+        cursor_of: fn(&T) -> Option<C>,
+    ) -> Self
+    where
+        C: Send + 'static,
+        F: Fn(Context, PageQuery<C>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Page<T>, Error>> + Send,
+    {
+        struct State<T, C, F> {
+            ctx: Context,
+            buffer: VecDeque<T>,
+            next: Option<PageQuery<C>>,
+            fetch: F,
+        }
+
+        let state = State {
+            ctx,
+            buffer: VecDeque::new(),
+            next: Some(start),
+            fetch,
+        };
+
+        Self::from_stream(stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(item) = state.buffer.pop_front() {
+                    return Some((Ok(item), state));
+                }
+                let query = state.next.take()?;
+                let limit = query.limit;
+
+                match (state.fetch)(state.ctx.clone(), query).await {
+                    Ok(page) => {
+                        let cursor = page.items.last().and_then(cursor_of);
+                        if let (true, Some(cursor)) = (page.has_more, cursor) {
+                            let (before, after) = match direction {
+                                Direction::Backward => (Some(cursor), None),
+                                Direction::Forward => (None, Some(cursor)),
+                            };
+                            state.next = Some(PageQuery {
+                                before,
+                                after,
+                                limit,
+                            });
+                        }
+                        state.buffer.extend(page.items);
+                    }
+                    Err(e) => return Some((Err(e), state)),
+                }
+            }
+        }))
+    }
+
+    pub(crate) fn from_stream(
+        stream: impl Stream<Item = Result<T, Error>> + Send + 'static,
+    ) -> Self {
+        Self {
+            inner: Box::pin(stream),
+        }
+    }
+
+    /// Fetches every remaining page and returns all of the entries, or the first error
+    pub async fn collect_all(mut self) -> Result<Vec<T>, Error> {
+        let mut items = Vec::new();
+        while let Some(item) = self.next().await {
+            items.push(item?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T> Stream for Paginated<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
@@ -11,3 +11,23 @@ where
         .and_then(|header| header.to_str().ok())
         .and_then(|header_str| header_str.parse().ok())
 }
+
+/**
+ * Adds the X-Audit-Log-Reason header to a request, if there is a reason.
+ * The reason shows up in the guild's audit log next to the action.
+ * @docs <https://discord.com/developers/docs/resources/audit-log#audit-log-entry-object>
+ */
+#[cfg(feature = "rest")]
+pub fn with_audit_log_reason(
+    builder: hyper::http::request::Builder,
+    reason: Option<&str>,
+) -> hyper::http::request::Builder {
+    match reason {
+        Some(reason) => builder.header(
+            "X-Audit-Log-Reason",
+            percent_encoding::utf8_percent_encode(reason, percent_encoding::NON_ALPHANUMERIC)
+                .to_string(),
+        ),
+        None => builder,
+    }
+}
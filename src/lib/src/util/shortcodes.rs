@@ -0,0 +1,76 @@
+use crate::discord::snowflake::Snowflake;
+
+/**
+ * Looks up the unicode emoji for a shortcode.
+ * @param shortcode The shortcode, with or without the surrounding colons (e.g. `:thumbsup:` or `thumbsup`)
+ * @return The unicode emoji, or None if the shortcode is unknown
+ */
+pub fn shortcode_to_unicode(shortcode: &str) -> Option<&'static str> {
+    let shortcode = shortcode.trim();
+    let shortcode = shortcode
+        .strip_prefix(':')
+        .and_then(|s| s.strip_suffix(':'))
+        .unwrap_or(shortcode);
+    emojis::get_by_shortcode(shortcode).map(|e| e.as_str())
+}
+
+/**
+ * Looks up the shortcode for a unicode emoji.
+ * @param emoji The unicode emoji
+ * @return The shortcode without colons (e.g. `thumbsup`), or None if the emoji is unknown or doesn't have a shortcode
+ */
+pub fn unicode_to_shortcode(emoji: &str) -> Option<&'static str> {
+    let emoji = emojis::get(emoji.trim())
+        // Some clients send emojis without the variation selector
+        .or_else(|| emojis::get(&format!("{}\u{fe0f}", emoji.trim())))?;
+    emoji.shortcode()
+}
+
+/// An emoji that a user entered as an argument
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParsedEmoji {
+    /// A standard unicode emoji
+    Unicode(String),
+    /// A custom guild emoji
+    Custom {
+        name: String,
+        id: Snowflake,
+        animated: bool,
+    },
+}
+
+impl ParsedEmoji {
+    /**
+     * Parses an emoji the way users type them into discord: a shortcode (`:thumbsup:`), a unicode emoji (👍),
+     * or a custom emoji mention (`<:name:id>` or `<a:name:id>`).
+     * @return None if the input isn't a known emoji
+     */
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if let Some(inner) = input.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            let (animated, inner) = match inner.strip_prefix("a:") {
+                Some(rest) => (true, rest),
+                None => (false, inner.strip_prefix(':')?),
+            };
+            let (name, id) = inner.split_once(':')?;
+            return Some(ParsedEmoji::Custom {
+                name: name.to_string(),
+                id: Snowflake::from(id.parse::<u64>().ok()?),
+                animated,
+            });
+        }
+        if let Some(unicode) = shortcode_to_unicode(input) {
+            return Some(ParsedEmoji::Unicode(unicode.to_string()));
+        }
+        emojis::get(input).map(|e| ParsedEmoji::Unicode(e.as_str().to_string()))
+    }
+
+    /// The emoji in the format that the reaction endpoints expect (`name:id` for custom emojis).
+    /// It still has to be url encoded before it is put in a url.
+    pub fn reaction_format(&self) -> String {
+        match self {
+            ParsedEmoji::Unicode(emoji) => emoji.clone(),
+            ParsedEmoji::Custom { name, id, .. } => format!("{}:{}", name, id),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+/**
+ * Discord sends large integers (like permission bit sets) as strings so that they don't lose precision in javascript.
+ * This module can be used with `#[serde(with = "crate::util::stringified")]` to (de)serialize a `u64` field as a string.
+ * Plain numbers are also accepted when deserializing.
+ */
+pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_string())
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNum {
+        String(String),
+        Num(u64),
+    }
+
+    match StringOrNum::deserialize(deserializer)? {
+        StringOrNum::String(s) => s
+            .parse()
+            .map_err(|_| de::Error::custom(format!("invalid stringified integer {}", s))),
+        StringOrNum::Num(n) => Ok(n),
+    }
+}